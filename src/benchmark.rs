@@ -0,0 +1,204 @@
+/// 积分器基准测试模块
+/// 在固定初始条件下测量各积分器的吞吐量和能量漂移
+use crate::pendulum::{consts, real_to_f64, PendulumParams, PendulumState, Real};
+use crate::physics::PhysicsEngine;
+use std::time::{Duration, Instant};
+
+/// 单步积分函数
+type IntegratorFn = fn(&PhysicsEngine, &PendulumState, &PendulumParams) -> PendulumState;
+
+/// 单个积分器的基准测试结果
+#[derive(Clone, Debug)]
+pub struct IntegratorBenchmark {
+    /// 积分器名称
+    pub name: &'static str,
+    /// 执行的步数
+    pub steps: usize,
+    /// 耗时
+    pub elapsed: Duration,
+    /// 固定模拟时长后的相对能量漂移
+    pub energy_drift: Real,
+}
+
+impl IntegratorBenchmark {
+    /// 每秒执行的步数
+    pub fn steps_per_second(&self) -> f64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds > 0.0 {
+            self.steps as f64 / seconds
+        } else {
+            f64::INFINITY
+        }
+    }
+}
+
+/// 基准测试使用的固定初始状态（与 Classic Chaos 预设相同）
+pub fn benchmark_initial_state() -> PendulumState {
+    PendulumState::at_rest(-consts::PI / 2.0, -consts::PI / 3.0)
+}
+
+/// 以固定步长运行所有积分器的基准测试
+pub fn run_integrator_benchmarks(steps: usize, dt: Real) -> Vec<IntegratorBenchmark> {
+    let engine = PhysicsEngine::new(dt);
+    let params = PendulumParams::default();
+    let initial = benchmark_initial_state();
+
+    let integrators: [(&'static str, IntegratorFn); 5] = [
+        ("Euler", PhysicsEngine::integrate_euler),
+        ("RK4", PhysicsEngine::integrate_rk4),
+        ("RK4 (robust)", PhysicsEngine::integrate_rk4_robust),
+        ("RK4 + correction", |engine, state, params| engine.step(state, params).0),
+        ("Adaptive", |engine, state, params| engine.step_adaptive(state, params).0.state),
+    ];
+
+    integrators
+        .iter()
+        .map(|(name, integrate)| {
+            let mut state = initial;
+            let start = Instant::now();
+            for _ in 0..steps {
+                state = integrate(&engine, &state, &params);
+            }
+            let elapsed = start.elapsed();
+
+            IntegratorBenchmark {
+                name,
+                steps,
+                elapsed,
+                energy_drift: relative_energy_drift(&initial, &state, &params),
+            }
+        })
+        .collect()
+}
+
+/// 计算两个状态之间的相对能量漂移
+fn relative_energy_drift(
+    initial: &PendulumState,
+    current: &PendulumState,
+    params: &PendulumParams,
+) -> Real {
+    let initial_energy = initial.total_energy(params);
+    let drift = (current.total_energy(params) - initial_energy).abs();
+    if initial_energy.abs() > 1e-12 {
+        drift / initial_energy.abs()
+    } else {
+        drift
+    }
+}
+
+/// 确定性检查的步数
+pub const CHECKSUM_STEPS: usize = 10_000;
+
+/// 确定性检查的时间步长
+pub const CHECKSUM_DT: Real = 0.001;
+
+/// 对状态各分量的二进制位做 FNV-1a 哈希，数值的任何改变都会改变结果
+pub fn state_checksum(state: &PendulumState) -> u64 {
+    const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    [state.theta1, state.theta2, state.omega1, state.omega2]
+        .iter()
+        .flat_map(|&value| real_to_f64(value).to_bits().to_le_bytes())
+        .fold(FNV_OFFSET, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+        })
+}
+
+/// 从固定初始状态用默认步进函数运行固定步数，返回最终状态及其校验和
+pub fn run_determinism_check(steps: usize, dt: Real) -> (PendulumState, u64) {
+    let engine = PhysicsEngine::new(dt);
+    let params = PendulumParams::default();
+    let final_state = engine.advance(&benchmark_initial_state(), &params, steps);
+    (final_state, state_checksum(&final_state))
+}
+
+/// 将基准测试结果格式化为文本表格
+pub fn format_report(results: &[IntegratorBenchmark], dt: Real) -> String {
+    let mut report = String::new();
+    if let Some(first) = results.first() {
+        report.push_str(&format!(
+            "Integrator benchmark: {} steps, dt = {}, simulated {:.3}s\n",
+            first.steps,
+            dt,
+            real_to_f64(dt) * first.steps as f64
+        ));
+    }
+    report.push_str(&format!(
+        "{:<16} {:>14} {:>12} {:>14}\n",
+        "integrator", "steps/s", "time (ms)", "energy drift"
+    ));
+    for result in results {
+        report.push_str(&format!(
+            "{:<16} {:>14.0} {:>12.2} {:>14.3e}\n",
+            result.name,
+            result.steps_per_second(),
+            result.elapsed.as_secs_f64() * 1000.0,
+            result.energy_drift
+        ));
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_benchmark_runs_all_integrators() {
+        let results = run_integrator_benchmarks(200, 0.001);
+        let names: Vec<&str> = results.iter().map(|r| r.name).collect();
+        assert_eq!(
+            names,
+            vec!["Euler", "RK4", "RK4 (robust)", "RK4 + correction", "Adaptive"]
+        );
+
+        for result in &results {
+            assert_eq!(result.steps, 200);
+            assert!(result.energy_drift.is_finite());
+            assert!(result.steps_per_second() > 0.0);
+        }
+
+        // RK4 的能量漂移应远小于欧拉法
+        assert!(results[1].energy_drift < results[0].energy_drift);
+    }
+
+    #[test]
+    fn test_benchmark_accuracy_is_deterministic() {
+        let first = run_integrator_benchmarks(100, 0.002);
+        let second = run_integrator_benchmarks(100, 0.002);
+        for (a, b) in first.iter().zip(second.iter()) {
+            assert_eq!(a.energy_drift, b.energy_drift);
+        }
+    }
+
+    #[test]
+    fn test_state_checksum_sensitivity() {
+        let state = benchmark_initial_state();
+        let mut nudged = state;
+        nudged.omega2 = Real::from_bits(nudged.omega2.to_bits() + 1);
+
+        assert_eq!(state_checksum(&state), state_checksum(&benchmark_initial_state()));
+        assert_ne!(state_checksum(&state), state_checksum(&nudged));
+    }
+
+    #[test]
+    fn test_determinism_checksum_is_pinned() {
+        // 若此测试失败，说明物理数值结果发生了变化（例如浮点运算顺序被调整）
+        #[cfg(not(feature = "f32-physics"))]
+        const EXPECTED: u64 = 0x40db_ef04_6a31_a1a2;
+        #[cfg(feature = "f32-physics")]
+        const EXPECTED: u64 = 0x3827_b2c0_5465_50b5;
+
+        let (state, checksum) = run_determinism_check(CHECKSUM_STEPS, CHECKSUM_DT);
+        assert_eq!(checksum, EXPECTED, "final state: {:?}", state);
+    }
+
+    #[test]
+    fn test_format_report() {
+        let results = run_integrator_benchmarks(10, 0.001);
+        let report = format_report(&results, 0.001);
+        assert!(report.starts_with("Integrator benchmark: 10 steps"));
+        assert_eq!(report.lines().count(), 2 + results.len());
+    }
+}
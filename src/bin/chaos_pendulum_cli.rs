@@ -0,0 +1,36 @@
+//! 无界面命令行工具
+//! 积分器基准测试和物理确定性校验和，结果输出到控制台
+
+use chaos_pendulum::benchmark;
+use std::process::ExitCode;
+
+/// 命令行用法
+const USAGE: &str = "usage: chaos_pendulum_cli (--bench-integrators | --checksum)";
+
+fn main() -> ExitCode {
+    match std::env::args().nth(1).as_deref() {
+        // 积分器基准测试
+        Some("--bench-integrators") => {
+            let dt = 0.001;
+            let results = benchmark::run_integrator_benchmarks(100_000, dt);
+            print!("{}", benchmark::format_report(&results, dt));
+        }
+        // 物理确定性校验和
+        Some("--checksum") => {
+            let steps = benchmark::CHECKSUM_STEPS;
+            let dt = benchmark::CHECKSUM_DT;
+            let (state, checksum) = benchmark::run_determinism_check(steps, dt);
+            println!("Determinism check: Classic Chaos, {} steps, dt = {}", steps, dt);
+            println!(
+                "final state: theta1 = {:e}, theta2 = {:e}, omega1 = {:e}, omega2 = {:e}",
+                state.theta1, state.theta2, state.omega1, state.omega2
+            );
+            println!("checksum: {:016x}", checksum);
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        }
+    }
+    ExitCode::SUCCESS
+}
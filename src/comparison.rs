@@ -0,0 +1,238 @@
+/// 参考运行对比模块
+/// 保存一次运行的完整初始条件，并与当前运行同步重新模拟，进行 A/B 对比
+use crate::events::flip_direction;
+use crate::pendulum::{
+    normalize_angle, real_to_f64, PendulumParams, PendulumState, Real, StepClock,
+};
+use crate::physics::{IntegratorKind, PhysicsEngine};
+
+/// 相空间距离超过该值时视为两次运行已经分离
+pub const DIVERGENCE_DISTANCE: f64 = 0.1;
+
+/// 命名的参考运行，保存重新模拟所需的全部初始条件
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReferenceRun {
+    pub name: String,
+    pub initial_state: PendulumState,
+    pub params: PendulumParams,
+    /// 积分步长
+    pub dt: Real,
+    /// 引擎的误差修正设置
+    pub error_correction: bool,
+    /// 引擎的角度标准化设置
+    pub normalize_after_step: bool,
+    /// 引擎的积分方式
+    pub integrator: IntegratorKind,
+    /// 引擎的自适应积分容差
+    pub adaptive_tolerance: Real,
+}
+
+impl ReferenceRun {
+    /// 以当前状态、参数和引擎设置创建参考运行
+    pub fn capture(
+        name: String,
+        state: PendulumState,
+        params: PendulumParams,
+        engine: &PhysicsEngine,
+    ) -> Self {
+        Self {
+            name,
+            initial_state: state,
+            params,
+            dt: engine.dt(),
+            error_correction: engine.error_correction(),
+            normalize_after_step: engine.normalize_after_step(),
+            integrator: engine.integrator(),
+            adaptive_tolerance: engine.adaptive_tolerance(),
+        }
+    }
+
+    /// 按保存的设置重建物理引擎
+    fn engine(&self) -> PhysicsEngine {
+        let mut engine = PhysicsEngine::new(self.dt);
+        engine.set_error_correction(self.error_correction);
+        engine.set_normalize_after_step(self.normalize_after_step);
+        engine.set_integrator(self.integrator);
+        engine.set_adaptive_tolerance(self.adaptive_tolerance);
+        engine
+    }
+}
+
+/// 当前运行与参考运行的对比结果
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ComparisonMetrics {
+    /// 当前运行与参考运行的总能量差（J）
+    pub energy_difference: f64,
+    /// 当前的相空间距离
+    pub distance: f64,
+    /// 首次分离的时间（相对运行开始）
+    pub divergence_time: Option<f64>,
+    /// 首次翻转时间差（当前减参考），两者都已翻转时才有值
+    pub flip_time_difference: Option<f64>,
+}
+
+/// 运行对比器，按步数推进参考运行，使其与当前运行在同一时刻比较
+pub struct RunComparison {
+    reference: ReferenceRun,
+    engine: PhysicsEngine,
+    /// 参考运行的当前状态
+    state: PendulumState,
+    /// 参考运行的时钟（相对运行开始）
+    clock: StepClock,
+    /// 运行开始时的模拟时间，使两次运行的驱动力矩相位一致
+    start_time: Real,
+    /// 上一次观测时当前运行的状态
+    previous_current: Option<PendulumState>,
+    /// 当前运行的首次翻转时间
+    current_flip: Option<f64>,
+    /// 参考运行的首次翻转时间
+    reference_flip: Option<f64>,
+    metrics: ComparisonMetrics,
+}
+
+impl RunComparison {
+    /// 从参考运行的初始条件开始对比，start_time 为当前运行开始时的模拟时间
+    pub fn new(reference: ReferenceRun, start_time: Real) -> Self {
+        Self {
+            engine: reference.engine(),
+            state: reference.initial_state,
+            clock: StepClock::default(),
+            start_time,
+            previous_current: None,
+            current_flip: None,
+            reference_flip: None,
+            metrics: ComparisonMetrics::default(),
+            reference,
+        }
+    }
+
+    /// 参考运行
+    pub fn reference(&self) -> &ReferenceRun {
+        &self.reference
+    }
+
+    /// 参考运行的当前状态，用于绘制叠影
+    pub fn reference_state(&self) -> PendulumState {
+        self.state
+    }
+
+    /// 最近一次观测的对比结果
+    pub fn metrics(&self) -> ComparisonMetrics {
+        self.metrics
+    }
+
+    /// 观测当前运行在 elapsed 时刻（相对运行开始）的状态
+    /// 参考运行按自己的步长推进到同一时刻后再比较
+    pub fn observe(
+        &mut self,
+        elapsed: f64,
+        current: &PendulumState,
+        params: &PendulumParams,
+    ) -> ComparisonMetrics {
+        let dt = real_to_f64(self.reference.dt);
+        // 允许半个步长的误差，避免步长相同时因舍入多走或少走一步
+        while self.clock.time() + dt * 0.5 <= elapsed {
+            let before = self.state;
+            self.engine.set_time(self.start_time + self.clock.time() as Real);
+            self.state = self.engine.step(&self.state, &self.reference.params).0;
+            self.clock.advance(dt);
+            if self.reference_flip.is_none() && lower_arm_flipped(&before, &self.state) {
+                self.reference_flip = Some(self.clock.time());
+            }
+        }
+
+        if let Some(previous) = self.previous_current {
+            if self.current_flip.is_none() && lower_arm_flipped(&previous, current) {
+                self.current_flip = Some(elapsed);
+            }
+        }
+        self.previous_current = Some(*current);
+
+        let distance = phase_distance(current, &self.state);
+        if self.metrics.divergence_time.is_none() && distance > DIVERGENCE_DISTANCE {
+            self.metrics.divergence_time = Some(elapsed);
+        }
+        self.metrics.distance = distance;
+        self.metrics.energy_difference = real_to_f64(
+            current.total_energy(params) - self.state.total_energy(&self.reference.params),
+        );
+        self.metrics.flip_time_difference = self
+            .current_flip
+            .zip(self.reference_flip)
+            .map(|(current, reference)| current - reference);
+        self.metrics
+    }
+}
+
+/// 下摆是否在两步之间越过顶部
+fn lower_arm_flipped(before: &PendulumState, after: &PendulumState) -> bool {
+    flip_direction(before.theta2, after.theta2).is_some()
+}
+
+/// 两个状态在相空间中的欧氏距离，角度差取最短方向
+pub fn phase_distance(a: &PendulumState, b: &PendulumState) -> f64 {
+    let d_theta1 = real_to_f64(normalize_angle(a.theta1 - b.theta1));
+    let d_theta2 = real_to_f64(normalize_angle(a.theta2 - b.theta2));
+    let d_omega1 = real_to_f64(a.omega1 - b.omega1);
+    let d_omega2 = real_to_f64(a.omega2 - b.omega2);
+    (d_theta1 * d_theta1 + d_theta2 * d_theta2 + d_omega1 * d_omega1 + d_omega2 * d_omega2).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chaotic_reference() -> ReferenceRun {
+        let engine = PhysicsEngine::new(0.01);
+        ReferenceRun::capture(
+            "A".to_string(),
+            PendulumState::new(2.5, 2.0, 0.0, 0.0),
+            PendulumParams::default(),
+            &engine,
+        )
+    }
+
+    #[test]
+    fn test_identical_configuration_never_diverges() {
+        let reference = chaotic_reference();
+        let mut comparison = RunComparison::new(reference.clone(), 0.0);
+        let engine = reference.engine();
+        let mut state = reference.initial_state;
+
+        comparison.observe(0.0, &state, &reference.params);
+        for step in 1..=2000 {
+            state = engine.step(&state, &reference.params).0;
+            let elapsed = step as f64 * real_to_f64(reference.dt);
+            let metrics = comparison.observe(elapsed, &state, &reference.params);
+            assert_eq!(metrics.distance, 0.0);
+            assert_eq!(metrics.energy_difference, 0.0);
+            assert_eq!(metrics.divergence_time, None);
+            assert!(metrics.flip_time_difference.is_none_or(|diff| diff == 0.0));
+        }
+    }
+
+    #[test]
+    fn test_perturbed_configuration_diverges() {
+        let reference = chaotic_reference();
+        let mut comparison = RunComparison::new(reference.clone(), 0.0);
+        let engine = reference.engine();
+        let mut state = reference.initial_state;
+        state.theta2 += 1e-3;
+
+        let mut metrics = comparison.observe(0.0, &state, &reference.params);
+        for step in 1..=3000 {
+            state = engine.step(&state, &reference.params).0;
+            let elapsed = step as f64 * real_to_f64(reference.dt);
+            metrics = comparison.observe(elapsed, &state, &reference.params);
+        }
+        let divergence = metrics.divergence_time.expect("chaotic runs should separate");
+        assert!(divergence > 0.0 && divergence <= 30.0);
+    }
+
+    #[test]
+    fn test_phase_distance_wraps_angles() {
+        let a = PendulumState::new(3.1, 0.0, 0.0, 0.0);
+        let b = PendulumState::new(-3.1, 0.0, 0.0, 0.0);
+        assert!(phase_distance(&a, &b) < 0.1);
+    }
+}
@@ -0,0 +1,137 @@
+/// 系综模块
+/// 从当前状态出发、初始角度带微小随机扰动的一组摆，与主摆同步积分，直观展示混沌的敏感依赖
+use std::collections::VecDeque;
+
+use crate::noise::NoiseRng;
+use crate::pendulum::{normalize_angle, real_to_f64, DoublePendulum, PendulumParams, Real};
+use crate::physics::PhysicsEngine;
+
+/// 每个成员保留的轨迹点数
+pub const ENSEMBLE_TRAIL_LENGTH: usize = 300;
+/// 扰动使用的固定随机种子，相同设置下系综可复现
+const ENSEMBLE_SEED: u64 = 7;
+
+/// 扰动摆系综，主摆算作成员0，这里只保存其余的扰动成员
+#[derive(Clone, Debug)]
+pub struct Ensemble {
+    members: Vec<DoublePendulum>,
+    /// 各成员下摆球的最近位置（世界坐标）
+    trails: Vec<VecDeque<(Real, Real)>>,
+}
+
+impl Ensemble {
+    /// 以主摆为基准生成 size - 1 个成员，两个角度各加 [-magnitude, magnitude] 内的均匀扰动
+    pub fn seeded(base: &DoublePendulum, size: usize, magnitude: Real) -> Self {
+        let mut rng = NoiseRng::new(ENSEMBLE_SEED);
+        let mut offset = || ((2.0 * rng.next_unit() - 1.0) as Real) * magnitude;
+        let members: Vec<DoublePendulum> = (1..size)
+            .map(|_| {
+                let mut member = base.clone();
+                member.state.theta1 += offset();
+                member.state.theta2 += offset();
+                member
+            })
+            .collect();
+        let trails = vec![VecDeque::with_capacity(ENSEMBLE_TRAIL_LENGTH); members.len()];
+        Self { members, trails }
+    }
+
+    /// 扰动成员（不含主摆）
+    pub fn members(&self) -> &[DoublePendulum] {
+        &self.members
+    }
+
+    /// 各成员下摆球的轨迹
+    pub fn trails(&self) -> &[VecDeque<(Real, Real)>] {
+        &self.trails
+    }
+
+    /// 用主摆的引擎和参数把所有成员推进一步
+    pub fn step(&mut self, engine: &PhysicsEngine, params: &PendulumParams, dt: Real) {
+        for member in &mut self.members {
+            member.params = *params;
+            member.state = if engine.adaptive() {
+                engine.step_adaptive(&member.state, params).0.state
+            } else {
+                engine.step(&member.state, params).0
+            };
+            member.advance_time(dt);
+        }
+    }
+
+    /// 记录各成员下摆球的当前位置，超出长度时丢弃最旧的点
+    pub fn record_trails(&mut self) {
+        for (member, trail) in self.members.iter().zip(&mut self.trails) {
+            if trail.len() >= ENSEMBLE_TRAIL_LENGTH {
+                trail.pop_front();
+            }
+            trail.push_back(member.get_positions().1);
+        }
+    }
+
+    /// 成员与主摆下摆角度的最大偏离（rad），衡量系综的散开程度
+    pub fn spread(&self, primary: &DoublePendulum) -> f64 {
+        self.members
+            .iter()
+            .map(|member| {
+                real_to_f64(normalize_angle(member.state.theta2 - primary.state.theta2).abs())
+            })
+            .fold(0.0, f64::max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pendulum::PendulumState;
+
+    fn base_pendulum() -> DoublePendulum {
+        DoublePendulum::new(
+            PendulumState::new(2.0, 2.5, 0.0, 0.0),
+            PendulumParams::new(1.0, 1.0, 1.0, 1.0, 9.81, 0.0),
+        )
+    }
+
+    #[test]
+    fn test_seeded_members_are_small_perturbations() {
+        let base = base_pendulum();
+        let ensemble = Ensemble::seeded(&base, 10, 1e-3);
+        assert_eq!(ensemble.members().len(), 9);
+        assert_eq!(ensemble.trails().len(), 9);
+        for member in ensemble.members() {
+            assert!((member.state.theta1 - base.state.theta1).abs() <= 1e-3);
+            assert!((member.state.theta2 - base.state.theta2).abs() <= 1e-3);
+            assert_ne!(member.state, base.state);
+            assert_eq!(member.state.omega1, base.state.omega1);
+        }
+        // 固定种子，相同设置生成相同的系综
+        let again = Ensemble::seeded(&base, 10, 1e-3);
+        assert_eq!(again.members()[3].state, ensemble.members()[3].state);
+        assert!(Ensemble::seeded(&base, 1, 1e-3).members().is_empty());
+    }
+
+    #[test]
+    fn test_step_follows_params_and_spreads() {
+        let base = base_pendulum();
+        let mut ensemble = Ensemble::seeded(&base, 5, 1e-6);
+        let engine = PhysicsEngine::new(0.001);
+        let mut params = base.params;
+        params.g = 5.0;
+        assert!(ensemble.spread(&base) <= 1e-6 * 2.0);
+
+        let mut primary = base.clone();
+        primary.params = params;
+        for _ in 0..20_000 {
+            ensemble.step(&engine, &params, 0.001);
+            ensemble.record_trails();
+            primary.state = engine.step(&primary.state, &params).0;
+        }
+        for (member, trail) in ensemble.members().iter().zip(ensemble.trails()) {
+            assert_eq!(member.params, params);
+            assert!((real_to_f64(member.time) - 20.0).abs() < 1e-6);
+            assert_eq!(trail.len(), ENSEMBLE_TRAIL_LENGTH);
+        }
+        // 混沌运动下微小扰动在 20 秒内被明显放大
+        assert!(ensemble.spread(&primary) > 1e-3);
+    }
+}
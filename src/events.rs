@@ -0,0 +1,233 @@
+/// 物理事件模块
+/// 检测翻转、近距离碰撞、自动暂停和数值发散等事件，并以 JSONL 格式记录
+use crate::pendulum::{real_to_f64, PendulumParams, PendulumState, Real};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+
+/// 下摆球到悬挂点的距离小于总摆长的该比例时视为近距离碰撞
+const NEAR_COLLISION_RATIO: Real = 0.1;
+
+/// 相对能量误差超过该值时视为数值发散
+const DIVERGENCE_THRESHOLD: Real = 1e-2;
+
+/// 物理事件，每个事件都带有发生时的模拟时间
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum PhysicsEvent {
+    /// 摆臂越过竖直向上的位置
+    Flip {
+        time: f64,
+        /// 摆臂编号（1 为上摆，2 为下摆）
+        arm: u8,
+        /// 翻转方向（1 为逆时针，-1 为顺时针）
+        direction: i8,
+    },
+    /// 下摆球接近悬挂点
+    NearCollision { time: f64, distance: f64 },
+    /// 到达设定时间后自动暂停
+    AutoPause { time: f64 },
+    /// 状态无效或能量误差过大
+    Divergence { time: f64, energy_error: f64 },
+}
+
+/// 事件检测器，记录上一步的状态以检测跨越
+#[derive(Clone, Debug, Default)]
+pub struct EventDetector {
+    /// 上一步的状态
+    previous: Option<PendulumState>,
+    /// 上一步是否处于近距离碰撞范围内
+    was_near_collision: bool,
+    /// 上一步是否已处于发散状态
+    was_diverged: bool,
+}
+
+impl EventDetector {
+    /// 创建新的事件检测器
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 清除历史，下一次观测不会与之前的状态比较
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// 观测一步后的状态，返回本步发生的事件
+    pub fn observe(
+        &mut self,
+        time: Real,
+        state: &PendulumState,
+        params: &PendulumParams,
+        energy_error: Real,
+    ) -> Vec<PhysicsEvent> {
+        let mut events = Vec::new();
+        let time = real_to_f64(time);
+
+        if let Some(previous) = self.previous {
+            for (arm, before, after) in [
+                (1, previous.theta1, state.theta1),
+                (2, previous.theta2, state.theta2),
+            ] {
+                if let Some(direction) = flip_direction(before, after) {
+                    events.push(PhysicsEvent::Flip {
+                        time,
+                        arm,
+                        direction,
+                    });
+                }
+            }
+        }
+
+        // 只在进入范围时记录一次
+        let (x2, y2) = state.get_mass2_position(params.l1, params.l2);
+        let distance = (x2 * x2 + y2 * y2).sqrt();
+        let near = distance < NEAR_COLLISION_RATIO * (params.l1 + params.l2);
+        if near && !self.was_near_collision {
+            events.push(PhysicsEvent::NearCollision {
+                time,
+                distance: real_to_f64(distance),
+            });
+        }
+        self.was_near_collision = near;
+
+        let diverged = !energy_error.is_finite() || energy_error > DIVERGENCE_THRESHOLD;
+        if diverged && !self.was_diverged {
+            events.push(PhysicsEvent::Divergence {
+                time,
+                energy_error: real_to_f64(energy_error),
+            });
+        }
+        self.was_diverged = diverged;
+
+        self.previous = Some(*state);
+        events
+    }
+}
+
+/// 判断角度从 before 变化到 after 时是否越过竖直向上的位置（θ = ±π）
+/// 对未标准化和已标准化到 [-π, π] 的角度都适用，返回翻转方向
+pub(crate) fn flip_direction(before: Real, after: Real) -> Option<i8> {
+    let crossed_vertical = before.sin() * after.sin() < 0.0;
+    let near_top = before.cos() + after.cos() < 0.0;
+    if !(crossed_vertical && near_top) {
+        return None;
+    }
+    // 越过顶部时 sin 从正变负为逆时针
+    if before.sin() > 0.0 {
+        Some(1)
+    } else {
+        Some(-1)
+    }
+}
+
+/// 事件记录器，每行写出一个 JSON 事件
+pub struct EventLogger<W: Write> {
+    /// 输出目标
+    writer: W,
+    /// 已写出的事件数
+    count: usize,
+}
+
+impl<W: Write> EventLogger<W> {
+    /// 创建新的事件记录器
+    pub fn new(writer: W) -> Self {
+        Self { writer, count: 0 }
+    }
+
+    /// 写出一个事件
+    pub fn log(&mut self, event: &PhysicsEvent) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, event)?;
+        writeln!(self.writer)?;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// 刷新缓冲区
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// 已写出的事件数
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// 取回输出目标，只在测试中检查写出的内容
+    #[cfg(test)]
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::PhysicsEngine;
+
+    #[test]
+    fn test_flip_direction() {
+        use crate::pendulum::consts::PI;
+
+        // 逆时针越过顶部（未标准化和标准化两种表示）
+        assert_eq!(flip_direction(PI - 0.1, PI + 0.1), Some(1));
+        assert_eq!(flip_direction(PI - 0.1, -PI + 0.1), Some(1));
+        // 顺时针越过顶部
+        assert_eq!(flip_direction(-PI + 0.1, -PI - 0.1), Some(-1));
+        // 越过底部不算翻转
+        assert_eq!(flip_direction(-0.1, 0.1), None);
+        assert_eq!(flip_direction(0.5, 0.6), None);
+    }
+
+    #[test]
+    fn test_flip_event_is_logged() {
+        let engine = PhysicsEngine::new(0.001);
+        let params = PendulumParams::default();
+        // 下摆从接近顶部处以正角速度越过顶部
+        let mut state = PendulumState::new(0.0, crate::pendulum::consts::PI - 0.2, 0.0, 3.0);
+        let mut detector = EventDetector::new();
+        let mut logger = EventLogger::new(Vec::new());
+        let mut time = 0.0;
+
+        detector.observe(time, &state, &params, 0.0);
+        for _ in 0..1000 {
+            let (next, error) = engine.step(&state, &params);
+            state = next;
+            time += 0.001;
+            for event in detector.observe(time, &state, &params, error) {
+                logger.log(&event).unwrap();
+            }
+        }
+        logger.flush().unwrap();
+
+        let output = String::from_utf8(logger.into_inner()).unwrap();
+        let flip_line = output
+            .lines()
+            .find(|line| line.contains("\"event\":\"flip\""))
+            .expect("no flip event logged");
+
+        match serde_json::from_str::<PhysicsEvent>(flip_line).unwrap() {
+            PhysicsEvent::Flip {
+                time,
+                arm,
+                direction,
+            } => {
+                assert_eq!(arm, 2);
+                assert_eq!(direction, 1);
+                assert!(time > 0.0 && time < 1.0);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_near_collision_is_edge_triggered() {
+        let params = PendulumParams::default();
+        let folded = PendulumState::at_rest(0.0, crate::pendulum::consts::PI);
+        let mut detector = EventDetector::new();
+
+        let first = detector.observe(0.0, &folded, &params, 0.0);
+        let second = detector.observe(0.001, &folded, &params, 0.0);
+        assert!(matches!(first[..], [PhysicsEvent::NearCollision { .. }]));
+        assert!(second.is_empty());
+    }
+}
@@ -0,0 +1,106 @@
+//! 物理读数说明模块
+//! 集中存放鼠标悬停在读数上时显示的解释文字
+
+/// 动能
+pub const KINETIC_ENERGY: &str = "Kinetic energy T of both bobs.\n\
+T = ½m₁l₁²ω₁² + ½m₂|v₂|², where v₂ includes the upper arm's motion.";
+
+/// 势能
+pub const POTENTIAL_ENERGY: &str = "Gravitational potential energy V, zero at the pivot height.\n\
+V = m₁g·y₁ + m₂g·y₂ (negative while the bobs hang below the pivot).";
+
+/// 拉格朗日量
+pub const LAGRANGIAN: &str = "Lagrangian L = T − V.\n\
+The equations of motion follow from the Euler–Lagrange equations of L.";
+
+/// 哈密顿量
+pub const HAMILTONIAN: &str = "Hamiltonian H = T + V, the total lab-frame energy.\n\
+Conserved by the true motion only with zero damping, zero Coulomb friction, \
+zero drive amplitude and a fixed pivot.";
+
+/// 悬挂点参考系能量
+pub const PIVOT_FRAME_ENERGY: &str = "Energy measured in the frame that moves with the pivot.\n\
+Adds the potential of the inertial force −m·a from the pivot's acceleration. \
+Lab-frame energy is not conserved when the pivot or a drive pumps energy in; \
+this view shows how much of the change comes from the pivot's motion.";
+
+/// 阻尼耗散功率
+pub const DISSIPATED_POWER: &str = "Power removed by damping and Coulomb friction.\n\
+P = b(ω₁² + ω₂²) + μ(|ω₁| + |ω₂|) with linear drag, or c(|ω₁|³ + |ω₂|³) + μ(|ω₁| + |ω₂|) \
+with quadratic drag, where b, c are the damping coefficient and μ the friction torque.";
+
+/// 单步能量误差
+pub const ENERGY_ERROR: &str = "Relative energy change over the last integration step.\n\
+|E_after − E_before| / |E_before|. Below 1e-8 is excellent, above 1e-6 suggests a smaller dt.";
+
+/// 最大能量误差
+pub const MAX_ENERGY_ERROR: &str =
+    "Largest single-step relative energy error since the last reset or preset load.";
+
+/// 能量漂移率
+pub const ENERGY_DRIFT_RATE: &str = "Average rate of change of total energy.\n\
+Least-squares slope of E(t) over the recent samples; negative with damping, \
+otherwise a measure of systematic integrator drift.";
+
+/// 最近窗口内的能量标准差
+pub const ENERGY_SPREAD: &str = "Standard deviation of total energy over the recent samples.\n\
+σ(E) = √(Σ(Eᵢ − Ē)² / n). Ignores any jump that happened before the window.";
+
+/// 翻转能量阈值
+pub const FLIP_THRESHOLD: &str = "Minimum total energy that lets the lower arm pass over the top.\n\
+E_flip = m₂gl₂ − (m₁ + m₂)gl₁ (upper arm hanging, lower arm vertical, at rest).";
+
+/// 李雅普诺夫指数
+pub const LYAPUNOV: &str = "Estimated largest Lyapunov exponent, per recorded sample.\n\
+λ ≈ ⟨ln(d_later / d_now)⟩ / window over pairs of nearby phase-space points.\n\
+Positive values mean nearby trajectories separate exponentially (chaos).";
+
+/// Benettin 算法的李雅普诺夫指数
+pub const LYAPUNOV_BENETTIN: &str = "Largest Lyapunov exponent via Benettin's method, per second.\n\
+A shadow run starts a tiny distance away and is rescaled back every few steps;\n\
+λ = Σ ln(dᵢ / d₀) / t. Independent of the recorded history.";
+
+/// θ₂ 极大值的回归映射
+pub const RETURN_MAP: &str = "Each point pairs one maximum of θ₂ with the next: (θ₂[n], θ₂[n+1]).\n\
+Periodic motion collapses onto a few points; points on the dashed diagonal are fixed points.\n\
+Chaotic motion spreads into a curve or cloud.";
+
+/// θ₂ 频谱
+pub const SPECTRUM: &str = "FFT of the recorded θ₂ series (mean removed, zero-padded to 2ⁿ).\n\
+Samples are spaced record interval × time step apart, which sets the frequency axis.\n\
+Sharp peaks mean regular oscillation; a broad spectrum indicates chaos.";
+
+/// 周期
+pub const PERIOD: &str = "Shortest repeat length of the phase-space trajectory, in samples.\n\
+Found by checking that points one period apart stay within a small distance.";
+
+/// 系综模式
+pub const ENSEMBLE: &str = "Extra pendulums whose starting angles differ by tiny random offsets.\n\
+They share the parameters and integrator of the main pendulum, coloured blue to red.\n\
+In chaotic motion the cloud fans out within seconds; statistics follow the main pendulum.";
+
+/// 总能量的解释，附带当前的动能、势能分解
+pub fn total_energy(kinetic: f64, potential: f64) -> String {
+    format!(
+        "Total mechanical energy E = T + V = {:.3} J\n\
+         T (kinetic) = {:.3} J\n\
+         V (potential) = {:.3} J\n\
+         Constant without damping; any drift comes from the integrator.",
+        kinetic + potential,
+        kinetic,
+        potential
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_total_energy_breakdown() {
+        let text = total_energy(2.5, -10.0);
+        assert!(text.contains("= -7.500 J"));
+        assert!(text.contains("T (kinetic) = 2.500 J"));
+        assert!(text.contains("V (potential) = -10.000 J"));
+    }
+}
@@ -0,0 +1,617 @@
+/// 数据导出模块
+/// 负责把统计历史写成外部工具可读取的文本格式
+use crate::pendulum::{real_to_f64, DoublePendulum, PendulumParams, PendulumState, Real};
+use crate::physics::PhysicsEngine;
+use crate::presets::PendulumPreset;
+use crate::statistics::{PhysicsStatistics, LYAPUNOV_WINDOW, MIN_PERIOD, PERIOD_TOLERANCE};
+use crate::visualization::{render_frame_image, FrameStyle};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// 批量导出时没有推荐步长的预设使用的时间步长
+const BATCH_DEFAULT_DT: Real = 0.001;
+
+/// 批量导出时每隔多少物理步记录一个轨迹点
+pub const BATCH_RECORD_INTERVAL: usize = 10;
+
+/// GIF 录制的最大帧数，避免生成过大的文件
+pub const MAX_GIF_FRAMES: usize = 300;
+
+/// GIF 录制时每帧记录的轨迹点数
+const GIF_TRAIL_SAMPLES_PER_FRAME: usize = 4;
+
+/// GIF 中轨迹保留的最大点数，更早的点被丢弃
+const GIF_TRAIL_LENGTH: usize = 400;
+
+/// GIF 调色板量化的速度（1 最慢质量最好，30 最快）
+const GIF_QUANTIZE_SPEED: i32 = 10;
+
+/// 可选的导出格式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// 下摆球轨迹 (t, x, y)，用于外部动画
+    TrajectoryPoints,
+    /// 以最低点为势能零点的能量构成，可直接画堆叠面积图
+    EnergyBreakdown,
+}
+
+impl ExportFormat {
+    /// 所有导出格式
+    pub const ALL: [ExportFormat; 2] = [
+        ExportFormat::TrajectoryPoints,
+        ExportFormat::EnergyBreakdown,
+    ];
+
+    /// 界面显示名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            ExportFormat::TrajectoryPoints => "Lower Bob Path (t, x, y)",
+            ExportFormat::EnergyBreakdown => "Energy Breakdown (stacked)",
+        }
+    }
+
+    /// 默认文件名
+    pub fn default_file_name(&self) -> &'static str {
+        match self {
+            ExportFormat::TrajectoryPoints => "trajectory_points.csv",
+            ExportFormat::EnergyBreakdown => "energy_breakdown.csv",
+        }
+    }
+
+    /// 按该格式写出统计数据
+    pub fn write<W: Write>(
+        &self,
+        writer: &mut W,
+        statistics: &PhysicsStatistics,
+        params: &PendulumParams,
+    ) -> io::Result<()> {
+        match self {
+            ExportFormat::TrajectoryPoints => write_trajectory_points(writer, statistics),
+            ExportFormat::EnergyBreakdown => write_energy_breakdown(
+                writer,
+                statistics,
+                real_to_f64(params.minimum_potential_energy()),
+            ),
+        }
+    }
+}
+
+/// 写出下摆球的带时间戳世界坐标，每行为 time,x2,y2
+pub fn write_trajectory_points<W: Write>(
+    writer: &mut W,
+    statistics: &PhysicsStatistics,
+) -> io::Result<()> {
+    writeln!(writer, "time,x2,y2")?;
+    for (time, (_, _, x2, y2)) in statistics
+        .get_trajectory_time_history()
+        .iter()
+        .zip(statistics.get_trajectory_history())
+    {
+        writeln!(writer, "{},{},{}", time, x2, y2)?;
+    }
+    Ok(())
+}
+
+/// GIF 录制设置
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GifSettings {
+    /// 录制的模拟时长（s）
+    pub duration: f64,
+    /// 每秒帧数
+    pub fps: u32,
+    /// 正方形画面的边长（像素）
+    pub size: usize,
+}
+
+impl Default for GifSettings {
+    fn default() -> Self {
+        Self {
+            duration: 5.0,
+            fps: 20,
+            size: 320,
+        }
+    }
+}
+
+impl GifSettings {
+    /// 请求的帧数，未截断
+    fn requested_frames(&self) -> usize {
+        (self.duration * self.fps as f64).round().max(1.0) as usize
+    }
+
+    /// 实际录制的帧数，不超过 MAX_GIF_FRAMES
+    pub fn frame_count(&self) -> usize {
+        self.requested_frames().min(MAX_GIF_FRAMES)
+    }
+
+    /// 请求的帧数是否超过上限而被截断
+    pub fn is_capped(&self) -> bool {
+        self.requested_frames() > MAX_GIF_FRAMES
+    }
+
+    /// 帧间隔（GIF 以 1/100 秒为单位）
+    pub fn frame_delay(&self) -> u16 {
+        (100.0 / self.fps.max(1) as f64).round().max(1.0) as u16
+    }
+
+    /// 两帧之间推进的物理步数，使播放速度与模拟时间一致
+    pub fn steps_per_frame(&self, dt: Real) -> usize {
+        let frame_seconds = self.frame_delay() as f64 / 100.0;
+        (frame_seconds / real_to_f64(dt)).round().max(1.0) as usize
+    }
+}
+
+/// 后台 GIF 录制线程发回界面的消息
+pub enum GifExportMessage {
+    /// 已渲染 done / total 帧
+    Progress { done: usize, total: usize },
+    /// 录制结束，返回写出的文件路径
+    Finished(io::Result<PathBuf>),
+}
+
+/// GIF 画面尺寸以 16 位存储，超出范围或为 0 时报错
+fn gif_dimension(value: usize) -> io::Result<u16> {
+    match u16::try_from(value) {
+        Ok(value) if value > 0 => Ok(value),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("GIF dimension {} out of range", value),
+        )),
+    }
+}
+
+/// 把 GIF 编码错误转换为 io::Error
+fn gif_error(err: gif::EncodingError) -> io::Error {
+    match err {
+        gif::EncodingError::Io(err) => err,
+        gif::EncodingError::Format(err) => io::Error::new(io::ErrorKind::InvalidData, err),
+    }
+}
+
+/// 记录一个 GIF 轨迹点，超过 GIF_TRAIL_LENGTH 时丢弃最早的点
+fn push_gif_trail_sample(
+    trail: &mut VecDeque<(f64, f64, f64, f64)>,
+    times: &mut VecDeque<f64>,
+    pendulum: &DoublePendulum,
+) {
+    let params = &pendulum.params;
+    let (x1, y1) = pendulum.state.get_mass1_position(params.l1);
+    let (x2, y2) = pendulum.state.get_mass2_position(params.l1, params.l2);
+    let [x1, y1, x2, y2] = [x1, y1, x2, y2].map(real_to_f64);
+    trail.push_back((x1, y1, x2, y2));
+    times.push_back(real_to_f64(pendulum.time));
+    if trail.len() > GIF_TRAIL_LENGTH {
+        trail.pop_front();
+        times.pop_front();
+    }
+}
+
+/// 从当前状态起按 engine 的设置无界面地模拟，逐帧离屏渲染并写成无限循环的 GIF
+/// 每帧单独量化调色板；每写出一帧调用一次 progress(已完成帧数, 总帧数)
+pub fn record_gif<W: Write>(
+    writer: W,
+    engine: &PhysicsEngine,
+    pendulum: &DoublePendulum,
+    settings: &GifSettings,
+    style: &FrameStyle,
+    mut progress: impl FnMut(usize, usize),
+) -> io::Result<W> {
+    let total = settings.frame_count();
+    let dt = engine.dt();
+    let steps_per_frame = settings.steps_per_frame(dt);
+    let steps_per_sample = (steps_per_frame / GIF_TRAIL_SAMPLES_PER_FRAME).max(1);
+    let mut engine = engine.clone();
+    let mut pendulum = pendulum.clone();
+    let mut trail = VecDeque::with_capacity(GIF_TRAIL_LENGTH + 1);
+    let mut trail_times = VecDeque::with_capacity(GIF_TRAIL_LENGTH + 1);
+    let side = gif_dimension(settings.size)?;
+    let mut encoder = gif::Encoder::new(writer, side, side, &[]).map_err(gif_error)?;
+    encoder.set_repeat(gif::Repeat::Infinite).map_err(gif_error)?;
+
+    push_gif_trail_sample(&mut trail, &mut trail_times, &pendulum);
+    for frame_index in 0..total {
+        if frame_index > 0 {
+            for step in 1..=steps_per_frame {
+                engine.set_time(pendulum.time);
+                pendulum.state = if engine.adaptive() {
+                    engine.step_adaptive(&pendulum.state, &pendulum.params).0.state
+                } else {
+                    engine.step(&pendulum.state, &pendulum.params).0
+                };
+                pendulum.advance_time(dt);
+                if step.is_multiple_of(steps_per_sample) {
+                    push_gif_trail_sample(&mut trail, &mut trail_times, &pendulum);
+                }
+            }
+        }
+        let image = render_frame_image(
+            [settings.size; 2],
+            &pendulum,
+            trail.make_contiguous(),
+            trail_times.make_contiguous(),
+            style,
+        );
+        let mut rgba: Vec<u8> = image.pixels.iter().flat_map(|pixel| pixel.to_array()).collect();
+        let mut frame = gif::Frame::from_rgba_speed(side, side, &mut rgba, GIF_QUANTIZE_SPEED);
+        frame.delay = settings.frame_delay();
+        encoder.write_frame(&frame).map_err(gif_error)?;
+        progress(frame_index + 1, total);
+    }
+    encoder.into_inner()
+}
+
+/// 以二进制 PPM（P6）格式写出图像，忽略透明度
+pub fn write_ppm<W: Write>(writer: &mut W, image: &egui::ColorImage) -> io::Result<()> {
+    let [width, height] = image.size;
+    write!(writer, "P6\n{} {}\n255\n", width, height)?;
+    let bytes: Vec<u8> = image
+        .pixels
+        .iter()
+        .flat_map(|pixel| [pixel.r(), pixel.g(), pixel.b()])
+        .collect();
+    writer.write_all(&bytes)
+}
+
+/// 写出能量构成，每行为 time,kinetic,potential_above_floor,total_above_floor
+/// 势能和总能量减去 floor（势能最小值），使势能非负且 kinetic + potential = total
+pub fn write_energy_breakdown<W: Write>(
+    writer: &mut W,
+    statistics: &PhysicsStatistics,
+    floor: f64,
+) -> io::Result<()> {
+    writeln!(writer, "time,kinetic,potential_above_floor,total_above_floor")?;
+    for (time, (_, kinetic, potential)) in statistics
+        .get_energy_time_history()
+        .iter()
+        .zip(statistics.get_energy_history())
+    {
+        let potential = potential - floor;
+        writeln!(writer, "{},{},{},{}", time, kinetic, potential, kinetic + potential)?;
+    }
+    Ok(())
+}
+
+/// 一次运行的指标汇总，导出为单个 JSON 文件供批量研究使用
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RunSummary {
+    /// 运行时长（s）
+    pub duration: f64,
+    /// 积分步长（s）
+    pub dt: f64,
+    /// 积分方式
+    pub integrator: String,
+    /// 运行开始时的总能量（J）
+    pub initial_energy: Option<f64>,
+    /// 最新记录的总能量（J）
+    pub final_energy: Option<f64>,
+    /// 单步最大相对能量误差
+    pub max_energy_error: Option<f64>,
+    /// 下摆首次翻转的时间（相对运行开始）
+    pub first_flip_time: Option<f64>,
+    /// 估计的最大李雅普诺夫指数（每个样本）
+    pub lyapunov_exponent: Option<f64>,
+    /// 检测到的周期（样本数）
+    pub period_samples: Option<usize>,
+    /// 记录中最大的 |ω|（rad/s）
+    pub max_angular_velocity: Option<f64>,
+    /// 记录的下摆球路径长度（m）
+    pub path_length: f64,
+}
+
+impl RunSummary {
+    /// 由统计历史、积分设置和运行中跟踪的指标汇总
+    /// initial_energy 为运行开始时记下的能量，历史被裁剪后最早的样本已不是起点
+    pub fn assemble(
+        statistics: &PhysicsStatistics,
+        engine: &PhysicsEngine,
+        duration: f64,
+        initial_energy: f64,
+        max_energy_error: Option<f64>,
+        first_flip_time: Option<f64>,
+    ) -> Self {
+        let energy = statistics.get_energy_history();
+        let max_angular_velocity = statistics
+            .get_phase_space_history()
+            .iter()
+            .map(|&(_, omega1, _, omega2)| omega1.abs().max(omega2.abs()))
+            .reduce(f64::max);
+        let path_length = statistics
+            .get_trajectory_history()
+            .windows(2)
+            .map(|pair| (pair[1].2 - pair[0].2).hypot(pair[1].3 - pair[0].3))
+            .sum();
+        Self {
+            duration,
+            dt: real_to_f64(engine.dt()),
+            integrator: engine.integrator_label().to_string(),
+            initial_energy: Some(initial_energy),
+            final_energy: energy.last().map(|&(total, _, _)| total),
+            max_energy_error,
+            first_flip_time,
+            lyapunov_exponent: statistics.estimate_lyapunov_exponent(LYAPUNOV_WINDOW),
+            period_samples: statistics.detect_periodicity(PERIOD_TOLERANCE, MIN_PERIOD),
+            max_angular_velocity,
+            path_length,
+        }
+    }
+
+    /// 序列化为格式化的 JSON
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+/// 后台批量导出线程发回界面的消息
+pub enum BatchExportMessage {
+    /// 已完成 done / total 个预设
+    Progress { done: usize, total: usize },
+    /// 全部完成，返回写出的文件路径
+    Finished(io::Result<Vec<PathBuf>>),
+}
+
+/// 无界面地模拟一个预设 duration 秒，按 BATCH_RECORD_INTERVAL 记录轨迹
+pub fn simulate_preset(preset: &PendulumPreset, duration: Real) -> PhysicsStatistics {
+    let dt = preset.recommended_dt.unwrap_or(BATCH_DEFAULT_DT);
+    let steps = (duration / dt).round() as usize;
+    let mut engine = PhysicsEngine::new(dt);
+    let mut statistics = PhysicsStatistics::new(steps / BATCH_RECORD_INTERVAL + 1);
+    let mut state = preset.initial_state;
+
+    let record = |statistics: &mut PhysicsStatistics, step: usize, state: &PendulumState| {
+        let time = real_to_f64(step as Real * dt);
+        let (x1, y1) = state.get_mass1_position(preset.params.l1);
+        let (x2, y2) = state.get_mass2_position(preset.params.l1, preset.params.l2);
+        statistics.add_trajectory_point(
+            time,
+            real_to_f64(x1),
+            real_to_f64(y1),
+            real_to_f64(x2),
+            real_to_f64(y2),
+        );
+    };
+
+    record(&mut statistics, 0, &state);
+    for step in 1..=steps {
+        engine.set_time((step - 1) as Real * dt);
+        state = engine.step(&state, &preset.params).0;
+        if step % BATCH_RECORD_INTERVAL == 0 {
+            record(&mut statistics, step, &state);
+        }
+    }
+    statistics
+}
+
+/// 由预设名称生成文件名，非字母数字字符替换为下划线
+pub fn preset_file_name(name: &str) -> String {
+    let stem: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("{}.csv", stem)
+}
+
+/// 依次模拟每个预设，并把轨迹写入目录下的 `<预设名>.csv`
+/// 每完成一个预设调用一次 progress(已完成数, 总数)
+pub fn export_preset_runs(
+    presets: &[PendulumPreset],
+    directory: &Path,
+    duration: Real,
+    mut progress: impl FnMut(usize, usize),
+) -> io::Result<Vec<PathBuf>> {
+    let mut paths = Vec::with_capacity(presets.len());
+    for (index, preset) in presets.iter().enumerate() {
+        let statistics = simulate_preset(preset, duration);
+        let path = directory.join(preset_file_name(&preset.name));
+        let mut writer = io::BufWriter::new(std::fs::File::create(&path)?);
+        write_trajectory_points(&mut writer, &statistics)?;
+        writer.flush()?;
+        paths.push(path);
+        progress(index + 1, presets.len());
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::presets::get_preset_by_name;
+
+    #[test]
+    fn test_run_summary_from_short_run() {
+        let mut engine = PhysicsEngine::new(0.01);
+        engine.set_error_correction(false);
+        let params = PendulumParams::default();
+        let mut statistics = PhysicsStatistics::new(10_000);
+        let mut state = PendulumState::new(2.5, 2.0, 0.0, 0.0);
+        let initial_energy = real_to_f64(state.total_energy(&params));
+        let mut first_flip = None;
+
+        for step in 0..=3000 {
+            if step > 0 {
+                let previous = state;
+                state = engine.step(&state, &params).0;
+                if first_flip.is_none()
+                    && crate::events::flip_direction(previous.theta2, state.theta2).is_some()
+                {
+                    first_flip = Some(step as f64 * 0.01);
+                }
+            }
+            if step % 10 == 0 {
+                let time = step as f64 * 0.01;
+                let (x1, y1) = state.get_mass1_position(params.l1);
+                let (x2, y2) = state.get_mass2_position(params.l1, params.l2);
+                let [x1, y1, x2, y2] = [x1, y1, x2, y2].map(real_to_f64);
+                let energy = real_to_f64(state.total_energy(&params));
+                statistics.add_energy_data(time, energy, 0.0, 0.0);
+                statistics.add_trajectory_point(time, x1, y1, x2, y2);
+                let [theta1, omega1, theta2, omega2] =
+                    [state.theta1, state.omega1, state.theta2, state.omega2].map(real_to_f64);
+                statistics.add_phase_space_point(theta1, omega1, theta2, omega2);
+            }
+        }
+
+        let summary = RunSummary::assemble(
+            &statistics,
+            &engine,
+            30.0,
+            initial_energy,
+            Some(1e-9),
+            first_flip,
+        );
+        assert_eq!(summary.duration, 30.0);
+        assert_eq!(summary.dt, real_to_f64(engine.dt()));
+        assert_eq!(summary.integrator, "RK4");
+        assert_eq!(summary.initial_energy, Some(initial_energy));
+        let (initial, last) = (summary.initial_energy.unwrap(), summary.final_energy.unwrap());
+        assert!((initial - last).abs() < 1e-2 * initial.abs());
+        assert_eq!(summary.max_energy_error, Some(1e-9));
+        assert!(summary.first_flip_time.is_some_and(|time| time > 0.0 && time < 30.0));
+        assert!(summary.lyapunov_exponent.is_some());
+        assert!(summary.max_angular_velocity.is_some_and(|omega| omega > 1.0));
+        assert!(summary.path_length > 1.0);
+
+        let json = summary.to_json().unwrap();
+        for key in ["\"duration\"", "\"lyapunov_exponent\"", "\"path_length\""] {
+            assert!(json.contains(key), "{} missing from {}", key, json);
+        }
+    }
+
+    #[test]
+    fn test_write_ppm() {
+        let mut image = egui::ColorImage::new([2, 1], egui::Color32::BLACK);
+        image.pixels[1] = egui::Color32::from_rgb(255, 128, 1);
+
+        let mut buffer = Vec::new();
+        write_ppm(&mut buffer, &image).unwrap();
+        let header = b"P6\n2 1\n255\n";
+        assert_eq!(&buffer[..header.len()], header);
+        assert_eq!(&buffer[header.len()..], &[0, 0, 0, 255, 128, 1]);
+    }
+
+    #[test]
+    fn test_gif_settings_cap_frames() {
+        let settings = GifSettings { duration: 2.0, fps: 20, size: 64 };
+        assert_eq!(settings.frame_count(), 40);
+        assert!(!settings.is_capped());
+        assert_eq!(settings.frame_delay(), 5);
+        assert_eq!(settings.steps_per_frame(0.01), 5);
+
+        let long = GifSettings { duration: 60.0, ..settings };
+        assert!(long.is_capped());
+        assert_eq!(long.frame_count(), MAX_GIF_FRAMES);
+    }
+
+    #[test]
+    fn test_record_gif_reports_progress() {
+        let settings = GifSettings { duration: 0.5, fps: 10, size: 48 };
+        let pendulum = DoublePendulum::new(
+            PendulumState::new(2.0, 1.0, 0.0, 0.0),
+            PendulumParams::default(),
+        );
+        let style = FrameStyle {
+            background: egui::Color32::BLACK,
+            rod_color: egui::Color32::WHITE,
+            mass_color: egui::Color32::RED,
+            trajectory_color: egui::Color32::BLUE,
+            trail_paths: Some(crate::visualization::TrailSelection::Both.paths()),
+            trail_color_mode: crate::visualization::TrailColorMode::Speed,
+            trajectory_alpha: 1.0,
+        };
+        let mut reported = Vec::new();
+        let bytes = record_gif(
+            Vec::new(),
+            &PhysicsEngine::new(0.01),
+            &pendulum,
+            &settings,
+            &style,
+            |done, total| reported.push((done, total)),
+        )
+        .unwrap();
+        assert_eq!(reported, (1..=5).map(|done| (done, 5)).collect::<Vec<_>>());
+
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::RGBA);
+        let mut decoder = options.read_info(bytes.as_slice()).unwrap();
+        assert_eq!((decoder.width(), decoder.height()), (48, 48));
+        assert_eq!(decoder.repeat(), gif::Repeat::Infinite);
+        let mut frames = 0;
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!(frame.delay, settings.frame_delay());
+            frames += 1;
+        }
+        assert_eq!(frames, 5);
+
+        // 超出 GIF 16 位尺寸范围时在渲染前报错
+        let too_large = GifSettings { size: 70_000, ..settings };
+        let engine = PhysicsEngine::new(0.01);
+        assert!(record_gif(Vec::new(), &engine, &pendulum, &too_large, &style, |_, _| {}).is_err());
+    }
+
+    #[test]
+    fn test_write_trajectory_points() {
+        let mut statistics = PhysicsStatistics::new(100);
+        for i in 0..5 {
+            let t = i as f64 * 0.05;
+            statistics.add_trajectory_point(t, 0.0, -1.0, t, -2.0);
+        }
+
+        let mut buffer = Vec::new();
+        write_trajectory_points(&mut buffer, &statistics).unwrap();
+        let text = String::from_utf8(buffer).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+
+        assert_eq!(lines[0], "time,x2,y2");
+        assert_eq!(lines.len(), 6);
+        assert_eq!(lines[2], "0.05,0.05,-2");
+    }
+
+    #[test]
+    fn test_write_energy_breakdown() {
+        let mut statistics = PhysicsStatistics::new(100);
+        statistics.add_energy_data(0.0, -20.0, 0.0, -20.0);
+        statistics.add_energy_data(0.5, -20.0, 4.0, -24.0);
+
+        let mut buffer = Vec::new();
+        write_energy_breakdown(&mut buffer, &statistics, -30.0).unwrap();
+        let text = String::from_utf8(buffer).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+
+        assert_eq!(lines[0], "time,kinetic,potential_above_floor,total_above_floor");
+        assert_eq!(lines[1], "0,0,10,10");
+        assert_eq!(lines[2], "0.5,4,6,10");
+    }
+
+    #[test]
+    fn test_preset_file_name() {
+        assert_eq!(preset_file_name("Classic Chaos"), "Classic_Chaos.csv");
+        assert_eq!(preset_file_name("Upper Up, Lower Down"), "Upper_Up__Lower_Down.csv");
+    }
+
+    #[test]
+    fn test_export_preset_runs_writes_one_file_per_preset() {
+        let directory = tempfile::tempdir().unwrap();
+        let presets = vec![
+            get_preset_by_name("Small Angle").unwrap(),
+            get_preset_by_name("Horizontal Release").unwrap(),
+        ];
+        let mut reported = Vec::new();
+
+        let paths = export_preset_runs(&presets, directory.path(), 0.5, |done, total| {
+            reported.push((done, total));
+        })
+        .unwrap();
+
+        assert_eq!(paths.len(), 2);
+        assert_eq!(reported, vec![(1, 2), (2, 2)]);
+        for (path, preset) in paths.iter().zip(&presets) {
+            let dt = preset.recommended_dt.unwrap_or(BATCH_DEFAULT_DT);
+            let samples = (0.5 / dt).round() as usize / BATCH_RECORD_INTERVAL + 1;
+            let text = std::fs::read_to_string(path).unwrap();
+            assert_eq!(path.file_name().unwrap(), preset_file_name(&preset.name).as_str());
+            // 表头加每个采样点一行
+            assert_eq!(text.lines().count(), samples + 1);
+        }
+    }
+}
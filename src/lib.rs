@@ -0,0 +1,49 @@
+/// 双摆物理库
+/// 不依赖界面的物理模型、数值积分、统计分析和预设，供图形界面和脚本化实验共用
+use pendulum::{PendulumParams, PendulumState, Real};
+use physics::PhysicsEngine;
+
+pub mod benchmark;
+pub mod pendulum;
+pub mod physics;
+pub mod presets;
+pub mod statistics;
+
+/// 无界面地从 initial 开始以步长 dt 积分 steps 步，返回包括初始状态在内的 steps + 1 个状态
+/// 使用引擎的默认设置（带能量误差修正的 RK4，每步标准化角度）
+pub fn simulate(
+    initial: PendulumState,
+    params: PendulumParams,
+    dt: Real,
+    steps: usize,
+) -> Vec<PendulumState> {
+    let mut engine = PhysicsEngine::new(dt);
+    let mut states = Vec::with_capacity(steps + 1);
+    let mut state = initial;
+    states.push(state);
+    for step in 0..steps {
+        engine.set_time(step as Real * dt);
+        state = engine.step(&state, &params).0;
+        states.push(state);
+    }
+    states
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simulate_matches_engine_advance() {
+        let preset = presets::get_preset_by_name("Classic Chaos").unwrap();
+        let states = simulate(preset.initial_state, preset.params, 0.01, 300);
+
+        assert_eq!(states.len(), 301);
+        assert_eq!(states[0], preset.initial_state);
+        let engine = PhysicsEngine::new(0.01);
+        assert_eq!(
+            states[300],
+            engine.advance(&preset.initial_state, &preset.params, 300)
+        );
+    }
+}
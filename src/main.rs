@@ -1,23 +1,84 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 // 导入模块
-mod pendulum;
-mod physics;
-mod presets;
-mod statistics;
+mod comparison;
+mod ensemble;
+mod events;
+mod explain;
+mod export;
+mod noise;
+mod readout;
+mod recording;
+mod scenario;
 mod theme;
 mod ui_state;
 mod visualization;
+mod window;
+
+// 物理部分来自库，界面模块仍通过 crate::pendulum 等路径使用
+use chaos_pendulum::{pendulum, physics, presets, statistics};
 
 // 导入所需的外部crate
+use comparison::{ReferenceRun, RunComparison};
+use ensemble::Ensemble;
 use eframe::egui;
-use pendulum::{DoublePendulum, PendulumParams, PendulumState};
-use physics::PhysicsEngine;
-use presets::get_all_presets;
-use statistics::PhysicsStatistics;
+use events::{EventDetector, EventLogger, PhysicsEvent};
+use export::{BatchExportMessage, ExportFormat, GifExportMessage, GifSettings, RunSummary};
+use noise::NoiseInjector;
+use pendulum::{
+    consts, real_to_f64, DoublePendulum, DragModel, NormalModeSolution, PendulumParams,
+    PendulumState, Real, SMALL_ANGLE_LIMIT,
+};
+use physics::{AdaptiveStep, IntegratorKind, PhysicsEngine};
+use presets::{get_all_presets, get_preset_by_name, CustomPresets, PendulumPreset};
+use readout::{fmt_value, ValueKind};
+use recording::Recording;
+use scenario::{Scenario, ScenarioAction, ScenarioRunner};
+use statistics::{
+    extremum_index, phase_space_rates, poincare_crossing, MaxErrorTracker, PhysicsStatistics,
+    TrajectorySampling, LYAPUNOV_WINDOW, MIN_PERIOD, PERIOD_TOLERANCE,
+};
 use theme::{ColorTheme, ThemeManager};
 use ui_state::UiStateManager;
-use visualization::PendulumRenderer;
+use visualization::{
+    draw_thumbnail, fade_chunks, smoothing_weight, sparkline_points, AspectRatio, CameraMode,
+    CanvasAction, FrameStyle, PendulumRenderer, PlotDecimation, RodWidthMode, TrailColorMode,
+    TrailSelection,
+};
+use window::WindowGeometry;
+
+/// 估计能量漂移率时使用的最近样本数
+const ENERGY_DRIFT_WINDOW: usize = 200;
+
+/// 单帧计入累加器的最长墙钟时间（秒），避免卡顿后一次补算过多
+const MAX_FRAME_TIME: f64 = 0.25;
+
+/// 单帧最多执行的物理步数，超出部分的模拟时间直接丢弃
+const MAX_STEPS_PER_FRAME: u32 = 20_000;
+
+/// 李雅普诺夫指数估计超过该值（每样本）时视为相邻轨迹发散
+const CHAOS_LYAPUNOV_THRESHOLD: f64 = 1e-3;
+
+/// 近期能量守恒度使用的最近样本数
+const CONSERVATION_WINDOW: usize = 200;
+
+/// 能量误差迷你折线图显示的最近样本数
+const SPARKLINE_LENGTH: usize = 100;
+
+/// Benettin 李雅普诺夫指数的积分时长（秒）
+const BENETTIN_DURATION: f64 = 20.0;
+
+/// Benettin 算法每隔多少步重新缩放影子轨迹
+const BENETTIN_RENORM_INTERVAL: usize = 10;
+
+/// 激发简正模时较大摆角的振幅（弧度），远小于 SMALL_ANGLE_LIMIT
+const NORMAL_MODE_AMPLITUDE: f64 = 0.05;
+
+/// 默认物理时间步长（秒）
+const DEFAULT_TIME_STEP: Real = 0.001;
+
+/// 请求截图后等待截图事件的最长墙钟时间，超时视为截图失败
+const SCREENSHOT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
 
 /// 混沌双摆应用程序的主结构体
 /// 包含物理系统、UI设置和控制参数
@@ -32,10 +93,28 @@ struct ChaosPendulumApp {
     last_update: std::time::Instant,
     /// 更新间隔时间
     update_interval: std::time::Duration,
-    /// 模拟速度倍率
+    /// 本次运行累计的墙钟时间（仅在运行时累加）
+    wall_time: std::time::Duration,
+    /// 上一帧的时间戳，用于累加墙钟时间
+    last_frame: std::time::Instant,
+    /// 本次运行开始时的模拟时间
+    run_start_time: Real,
+    /// 本次运行开始时的总能量，不受历史裁剪影响
+    run_start_energy: Real,
+    /// 模拟速度倍率（每墙钟秒推进的模拟秒数）
     simulation_speed: f32,
+    /// 最近测得的物理步速率（步/墙钟秒，指数平滑）
+    steps_per_second: f64,
+    /// 是否记录新的轨迹点（关闭时冻结显示的轨迹，摆仍继续运动）
+    record_trajectory: bool,
+    /// 尚未推进的模拟时间（秒），不足一步的部分留到下一帧
+    time_accumulator: f64,
     /// 时间步长设置
-    time_step: f64,
+    time_step: Real,
+    /// 时间步长精确输入框的值
+    time_step_input: Real,
+    /// 应用预设推荐步长之前生效的步长，加载没有推荐步长的预设时恢复
+    time_step_before_preset: Option<Real>,
 
     /// 物理统计管理器
     statistics: PhysicsStatistics,
@@ -50,17 +129,133 @@ struct ChaosPendulumApp {
     trajectory_record_interval: u32,
     /// 轨迹记录计数器
     trajectory_counter: u32,
+    /// 轨迹采样方式
+    trajectory_sampling: TrajectorySampling,
+    /// 按距离采样时的弧长阈值（米）
+    trajectory_arc_length: Real,
+    /// 上次记录时下摆球的位置
+    last_recorded_position: (Real, Real),
 
     /// 参数调节的临时值
     temp_params: PendulumParams,
     /// 是否显示相空间图
     show_phase_space: bool,
+    /// 相空间轨迹是否随时间淡出
+    fade_phase_space: bool,
+    /// 庞加莱截面按新旧程度着色
+    poincare_recency_color: bool,
+    /// 是否显示位形空间图 (θ₁, θ₂)
+    show_configuration_space: bool,
+    /// 位形空间图是否展开角度
+    unwrap_configuration_space: bool,
     /// 是否显示能量图
     show_energy_plot: bool,
+    /// 是否在能量图中绘制拉格朗日量
+    show_lagrangian_plot: bool,
+    /// 能量面板是否显示随悬挂点运动参考系中的能量
+    show_pivot_frame_energy: bool,
+    /// 是否绘制阻尼耗散功率曲线
+    show_power_plot: bool,
+    /// 是否绘制绕悬挂点的角动量曲线
+    show_angular_momentum_plot: bool,
     /// 是否显示能量误差图
     show_energy_error_plot: bool,
     /// 当前能量误差
-    energy_error: f64,
+    energy_error: Real,
+    /// "跳转到时间"输入框的值
+    jump_time_input: f64,
+    /// 每步积分后注入的状态噪声
+    noise: NoiseInjector,
+    /// 等待中的画布截图请求的发出时刻
+    screenshot_requested: Option<std::time::Instant>,
+    /// 自适应步长模式下最近一步的积分诊断
+    last_adaptive_step: Option<AdaptiveStep>,
+    /// 线性化解析解及其起始模拟时间（启用参考叠加时）
+    analytic_reference: Option<(NormalModeSolution, Real)>,
+    /// 无重力守恒量检查的初始状态
+    invariant_baseline: PendulumState,
+    /// 本次运行中的最大能量误差
+    max_energy_error: MaxErrorTracker,
+    /// 能量误差的显示颜色等级（带滞后，避免在阈值附近闪烁）
+    energy_error_level: ErrorLevel,
+    /// 本次运行中下摆首次翻转的时间（相对运行开始）
+    first_flip_time: Option<f64>,
+    /// 最近一次 Benettin 算法的结果（起始模拟时间, 指数）
+    benettin_exponent: Option<(f64, f64)>,
+    /// 已保存的参考运行
+    reference_runs: Vec<ReferenceRun>,
+    /// 参考运行名称输入框的值
+    reference_name_input: String,
+    /// 当前运行与所选参考运行的对比
+    comparison: Option<RunComparison>,
+    /// 系综模式下与主摆同步积分的扰动成员（None 为关闭）
+    ensemble: Option<Ensemble>,
+    /// 系综的摆数，包括主摆
+    ensemble_size: usize,
+    /// 系综初始角度扰动的幅度（rad）
+    ensemble_perturbation: Real,
+    /// 时间步长精度扫描结果 (dt, 能量漂移)
+    dt_sweep_results: Vec<(Real, Real)>,
+    /// 到达该模拟时间后自动暂停
+    stop_after: Option<Real>,
+    /// "运行N秒"输入框的值
+    run_duration_input: Real,
+    /// 是否启用积分器混合显示（仅用于可视化对比）
+    integrator_blend: bool,
+    /// 混合系数：0 为欧拉法，1 为 RK4
+    integrator_blend_factor: f32,
+    /// 与主模拟同步推进的欧拉法影子状态
+    euler_shadow: PendulumState,
+    /// 当前选择的导出格式
+    export_format: ExportFormat,
+    /// 批量导出中各预设是否被选中（与 get_all_presets 顺序一致）
+    batch_selection: Vec<bool>,
+    /// 批量导出时每个预设的模拟时长（秒）
+    batch_duration: Real,
+    /// 后台批量导出线程的消息通道（导出进行中时存在）
+    batch_export: Option<std::sync::mpsc::Receiver<BatchExportMessage>>,
+    /// 批量导出进度 (已完成, 总数)
+    batch_progress: (usize, usize),
+    /// GIF 录制设置
+    gif_settings: GifSettings,
+    /// 后台 GIF 录制线程的消息通道（录制进行中时存在）
+    gif_export: Option<std::sync::mpsc::Receiver<GifExportMessage>>,
+    /// GIF 录制进度 (已渲染帧数, 总帧数)
+    gif_progress: (usize, usize),
+    /// 最近一次会话录制（录制中或已停止）
+    recording: Option<Recording>,
+    /// 是否正在把每步的状态追加到录制中
+    recording_active: bool,
+    /// 回放模式下显示的录制帧，Some 时冻结物理推进
+    playback_index: Option<usize>,
+    /// 物理事件检测器
+    event_detector: EventDetector,
+    /// 物理事件记录器（启用事件日志时存在）
+    event_logger: Option<EventLogger<std::io::BufWriter<std::fs::File>>>,
+    /// 正在执行的场景脚本
+    scenario: Option<ScenarioRunner>,
+    /// 锁定参数：加载预设时只应用初始状态
+    lock_parameters: bool,
+    /// 用户保存的自定义预设
+    custom_presets: CustomPresets,
+    /// 自定义预设名称输入框的值
+    custom_preset_name: String,
+    /// 自定义预设描述输入框的值
+    custom_preset_description: String,
+    /// 键盘微调的摆臂（1 为上摆，2 为下摆）
+    selected_joint: u8,
+    /// 键盘微调一次的角度（弧度）
+    nudge_step: Real,
+    /// 初始条件输入框中的摆球坐标 (x1, y1, x2, y2)
+    position_input: [Real; 4],
+    /// 最近一次记录的窗口几何（退出时保存）
+    window_geometry: Option<WindowGeometry>,
+    /// 启动时等待恢复的窗口几何
+    pending_window_restore: Option<WindowGeometry>,
+    /// 顶部读数的平滑系数（0 为原始值，越大越平滑）
+    readout_smoothing: f64,
+    /// 顶部显示的平滑读数 (θ₁, θ₂, ω₁, ω₂)，不影响模拟和图表
+    smoothed_readout: [f64; 4],
 }
 
 impl Default for ChaosPendulumApp {
@@ -70,60 +265,132 @@ impl Default for ChaosPendulumApp {
 
         // 创建初始状态（向上偏移以获得足够势能）
         let initial_state = PendulumState::new(
-            -std::f64::consts::PI * 2.0 / 3.0, // 上摆向上120度（接近倒立）
-            -std::f64::consts::PI / 2.0,       // 下摆向上90度（水平）
+            -consts::PI * 2.0 / 3.0, // 上摆向上120度（接近倒立）
+            -consts::PI / 2.0,       // 下摆向上90度（水平）
             0.0,                               // 初始角速度为0
             0.0,
         );
 
         let pendulum = DoublePendulum::new(initial_state, params);
-        let physics_engine = PhysicsEngine::new(0.001); // 1ms时间步长
-
-        // 初始化统计数据
-        let mut statistics = PhysicsStatistics::new(2000);
-        let energy = pendulum.total_energy();
-        statistics.add_energy_data(
-            energy,
-            pendulum.kinetic_energy(),
-            pendulum.potential_energy(),
-        );
+        let physics_engine = PhysicsEngine::new(DEFAULT_TIME_STEP); // 1ms时间步长
 
-        let (pos1, pos2) = pendulum.get_positions();
-        statistics.add_trajectory_point(pos1.0, pos1.1, pos2.0, pos2.1);
-        statistics.add_phase_space_point(
-            pendulum.state.theta1,
-            pendulum.state.omega1,
-            pendulum.state.theta2,
-            pendulum.state.omega2,
-        );
-
-        Self {
+        let mut app = Self {
             pendulum,
             physics_engine,
             is_running: false,
             last_update: std::time::Instant::now(),
+            wall_time: std::time::Duration::ZERO,
+            last_frame: std::time::Instant::now(),
+            run_start_time: 0.0,
+            run_start_energy: initial_state.total_energy(&params),
             update_interval: std::time::Duration::from_millis(16), // ~60 FPS
             simulation_speed: 1.0,
-            time_step: 0.001,
+            time_accumulator: 0.0,
+            record_trajectory: true,
+            steps_per_second: 0.0,
+            time_step: DEFAULT_TIME_STEP,
+            time_step_input: DEFAULT_TIME_STEP,
+            time_step_before_preset: None,
 
-            statistics,
+            statistics: PhysicsStatistics::new(2000),
             theme_manager: ThemeManager::new(ColorTheme::Dark),
             ui_state: UiStateManager::new(),
             renderer: PendulumRenderer::new(),
 
             trajectory_record_interval: 5, // 每5步记录一次轨迹点
             trajectory_counter: 0,
+            trajectory_sampling: TrajectorySampling::StepInterval,
+            trajectory_arc_length: 0.05,
+            last_recorded_position: (0.0, 0.0),
 
             temp_params: params,
             show_phase_space: false,
+            fade_phase_space: true,
+            show_configuration_space: false,
+            unwrap_configuration_space: true,
+            poincare_recency_color: true,
             show_energy_plot: true,
+            show_lagrangian_plot: false,
+            show_pivot_frame_energy: false,
+            show_power_plot: false,
+            show_angular_momentum_plot: false,
             show_energy_error_plot: true,
             energy_error: 0.0,
-        }
+            last_adaptive_step: None,
+            jump_time_input: 0.0,
+            noise: NoiseInjector::default(),
+            screenshot_requested: None,
+            max_energy_error: MaxErrorTracker::default(),
+            energy_error_level: ErrorLevel::default(),
+            first_flip_time: None,
+            benettin_exponent: None,
+            analytic_reference: None,
+            invariant_baseline: initial_state,
+            reference_runs: Vec::new(),
+            reference_name_input: "Reference 1".to_string(),
+            comparison: None,
+            ensemble: None,
+            ensemble_size: 12,
+            ensemble_perturbation: 1e-4,
+            dt_sweep_results: Vec::new(),
+            stop_after: None,
+            run_duration_input: 10.0,
+            export_format: ExportFormat::TrajectoryPoints,
+            batch_selection: vec![false; get_all_presets().len()],
+            batch_duration: 10.0,
+            batch_export: None,
+            batch_progress: (0, 0),
+            gif_settings: GifSettings::default(),
+            gif_export: None,
+            gif_progress: (0, 0),
+            recording: None,
+            recording_active: false,
+            playback_index: None,
+            event_detector: EventDetector::new(),
+            event_logger: None,
+            scenario: None,
+            lock_parameters: false,
+            custom_presets: CustomPresets::default(),
+            custom_preset_name: String::new(),
+            custom_preset_description: String::new(),
+            selected_joint: 1,
+            nudge_step: 0.001,
+            position_input: [0.0, -1.0, 0.0, -2.0],
+            window_geometry: None,
+            pending_window_restore: None,
+            readout_smoothing: 0.0,
+            smoothed_readout: [0.0; 4],
+            integrator_blend: false,
+            integrator_blend_factor: 1.0,
+            euler_shadow: initial_state,
+        };
+
+        // 初始化统计数据
+        app.record_statistics();
+        app
     }
 }
 
 impl ChaosPendulumApp {
+    /// 恢复上次保存的窗口几何，并记录当前几何以便退出时保存
+    fn track_window_geometry(&mut self, ctx: &egui::Context) {
+        let (geometry, monitor_size) =
+            ctx.input(|i| (WindowGeometry::from_viewport(i.viewport()), i.viewport().monitor_size));
+
+        // 显示器大小可用后再恢复，窗口大小不超过当前显示器
+        if let (Some(saved), Some(monitor_size)) = (self.pending_window_restore, monitor_size) {
+            for command in saved.restore_commands(monitor_size) {
+                ctx.send_viewport_cmd(command);
+            }
+            self.pending_window_restore = None;
+            return;
+        }
+
+        if self.pending_window_restore.is_none() && geometry.is_some() {
+            self.window_geometry = geometry;
+        }
+    }
+
     /// 设置状态信息
     fn set_status(&mut self, message: String) {
         self.ui_state.set_status(message);
@@ -139,86 +406,1146 @@ impl ChaosPendulumApp {
         self.theme_manager.apply_ui_theme(ctx);
     }
 
-    /// 更新物理模拟
-    fn update_physics(&mut self) {
-        if !self.is_running {
+    /// 按经过的墙钟时间（秒）更新物理模拟
+    fn update_physics(&mut self, elapsed: f64) {
+        if !self.is_running || self.playback_index.is_some() {
             return;
         }
 
-        let steps_per_frame = (self.simulation_speed * 10.0) as u32;
+        let (steps, remainder) = accumulate_steps(
+            self.time_accumulator,
+            elapsed,
+            self.simulation_speed,
+            real_to_f64(self.time_step),
+        );
+        self.time_accumulator = remainder;
+        let mut executed = 0;
+        for _ in 0..steps {
+            self.step_once();
+            executed += 1;
+
+            // 到达设定时间或被场景脚本暂停时在当前子步停止
+            if self.check_stop_after() || !self.is_running {
+                self.time_accumulator = 0.0;
+                break;
+            }
+        }
+        if elapsed > 0.0 {
+            let rate = executed as f64 / elapsed;
+            self.steps_per_second = egui::lerp(self.steps_per_second..=rate, 0.1);
+        }
+
+        // 每帧刷新一次事件日志
+        if let Some(logger) = self.event_logger.as_mut() {
+            if let Err(err) = logger.flush() {
+                self.event_logger = None;
+                self.set_status(format!("Event log disabled: {}", err));
+            }
+        }
+    }
 
-        for _ in 0..steps_per_frame {
-            // 使用新的step函数更新物理状态并获取能量误差
-            let (new_state, energy_err) = self
+    /// 暂停时手动推进 steps 个物理步，用于逐步调试轨迹
+    fn step_paused(&mut self, steps: usize) {
+        if self.is_running || self.playback_index.is_some() {
+            return;
+        }
+        for _ in 0..steps {
+            self.step_once();
+            if self.check_stop_after() {
+                break;
+            }
+        }
+    }
+
+    /// 执行单个物理步并记录统计数据
+    fn step_once(&mut self) {
+        let previous = self.pendulum.state;
+        // 驱动力矩的相位取自当前模拟时间
+        self.physics_engine.set_time(self.pendulum.time);
+        // 使用新的step函数更新物理状态并获取能量误差
+        let (mut new_state, energy_err) = if self.physics_engine.adaptive() {
+            let (result, energy_err) = self
+                .physics_engine
+                .step_adaptive(&self.pendulum.state, &self.pendulum.params);
+            self.last_adaptive_step = Some(result);
+            (result.state, energy_err)
+        } else {
+            self.last_adaptive_step = None;
+            self.physics_engine
+                .step(&self.pendulum.state, &self.pendulum.params)
+        };
+        self.noise.apply(&mut new_state);
+        if self.integrator_blend {
+            self.euler_shadow = self
                 .physics_engine
-                .step(&self.pendulum.state, &self.pendulum.params);
-            self.pendulum.state = new_state;
-            self.energy_error = energy_err;
-            self.pendulum.advance_time(self.time_step);
+                .integrate_euler(&self.euler_shadow, &self.pendulum.params);
+        }
+        self.pendulum.state = new_state;
+        if let Some(ensemble) = self.ensemble.as_mut() {
+            ensemble.step(&self.physics_engine, &self.pendulum.params, self.time_step);
+        }
+        self.energy_error = energy_err;
+        self.energy_error_level = self.energy_error_level.next(real_to_f64(energy_err));
+        self.pendulum.advance_time(self.time_step);
+        if let Some((theta2, omega2)) = poincare_crossing(&previous, &new_state) {
+            self.statistics.add_poincare_crossing(theta2, omega2);
+        }
+        self.max_energy_error
+            .observe(real_to_f64(self.pendulum.time), real_to_f64(self.energy_error));
+        if self.first_flip_time.is_none()
+            && events::flip_direction(previous.theta2, new_state.theta2).is_some()
+        {
+            self.first_flip_time = Some(real_to_f64(self.pendulum.time - self.run_start_time));
+        }
+        if let Some(comparison) = self.comparison.as_mut() {
+            let elapsed = real_to_f64(self.pendulum.time - self.run_start_time);
+            comparison.observe(elapsed, &self.pendulum.state, &self.pendulum.params);
+        }
+        if self.recording_active {
+            self.record_frame();
+        }
 
-            // 记录统计数据
-            self.trajectory_counter += 1;
-            if self.trajectory_counter >= self.trajectory_record_interval {
-                self.trajectory_counter = 0;
+        if self.event_logger.is_some() {
+            let events = self.event_detector.observe(
+                self.pendulum.time,
+                &self.pendulum.state,
+                &self.pendulum.params,
+                self.energy_error,
+            );
+            self.log_events(&events);
+        }
 
-                let energy = self.pendulum.total_energy();
-                self.statistics.add_energy_data(
-                    energy,
-                    self.pendulum.kinetic_energy(),
-                    self.pendulum.potential_energy(),
-                );
-                self.statistics.add_energy_error(self.energy_error);
+        if let Some(runner) = self.scenario.as_mut() {
+            let actions = runner.advance(real_to_f64(self.time_step));
+            self.run_scenario_actions(actions);
+        }
 
-                let (pos1, pos2) = self.pendulum.get_positions();
-                self.statistics
-                    .add_trajectory_point(pos1.0, pos1.1, pos2.0, pos2.1);
-                self.statistics.add_phase_space_point(
-                    self.pendulum.state.theta1,
-                    self.pendulum.state.omega1,
-                    self.pendulum.state.theta2,
-                    self.pendulum.state.omega2,
-                );
+        // 记录统计数据：能量、相空间等始终按步数间隔记录，保证时间间隔均匀；
+        // 按弧长采样只决定轨迹点
+        let by_distance = self.trajectory_sampling == TrajectorySampling::ArcLength;
+        if self.sample_due() {
+            self.record_state_statistics();
+            if !by_distance {
+                self.record_trajectory_point();
+            }
+            self.statistics.add_energy_error(real_to_f64(self.energy_error));
+            if let Some(ensemble) = self.ensemble.as_mut() {
+                ensemble.record_trails();
             }
         }
+        if by_distance && self.moved_beyond_arc_length() {
+            self.record_trajectory_point();
+        }
     }
 
-    /// 重置模拟
-    fn reset_simulation(&mut self) {
-        self.pendulum.reset(PendulumState::new(
-            -std::f64::consts::PI * 2.0 / 3.0,
-            -std::f64::consts::PI / 2.0,
-            0.0,
-            0.0,
-        ));
-        self.statistics.clear_history();
-        self.trajectory_counter = 0;
+    /// 按步数间隔判断本步是否记录统计数据
+    fn sample_due(&mut self) -> bool {
+        self.trajectory_counter += 1;
+        if self.trajectory_counter >= self.trajectory_record_interval {
+            self.trajectory_counter = 0;
+            true
+        } else {
+            false
+        }
+    }
 
-        // 记录初始数据
+    /// 下摆球自上次记录轨迹点以来是否移动了超过设定的弧长
+    fn moved_beyond_arc_length(&self) -> bool {
+        let (last_x, last_y) = self.last_recorded_position;
+        let (x, y) = self.pendulum.get_positions().1;
+        statistics::moved_beyond_threshold(
+            (real_to_f64(last_x), real_to_f64(last_y)),
+            (real_to_f64(x), real_to_f64(y)),
+            real_to_f64(self.trajectory_arc_length),
+        )
+    }
+
+    /// 检查是否到达自动暂停时间，到达时暂停并返回true
+    fn check_stop_after(&mut self) -> bool {
+        match self.stop_after {
+            // 允许半个步长的浮点累积误差
+            Some(limit) if self.pendulum.time >= limit - self.time_step * 0.5 => {
+                self.is_running = false;
+                self.stop_after = None;
+                self.set_status(format!("Auto-paused at t = {:.3}s", self.pendulum.time));
+                self.log_events(&[PhysicsEvent::AutoPause {
+                    time: real_to_f64(self.pendulum.time),
+                }]);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// 写出事件到事件日志（未启用时忽略），写入失败时关闭日志
+    fn log_events(&mut self, events: &[PhysicsEvent]) {
+        let Some(logger) = self.event_logger.as_mut() else {
+            return;
+        };
+        if let Err(err) = events.iter().try_for_each(|event| logger.log(event)) {
+            self.event_logger = None;
+            self.set_status(format!("Event log disabled: {}", err));
+        }
+    }
+
+    /// 开始把物理事件记录到指定文件（JSONL）
+    fn start_event_log(&mut self, path: &std::path::Path) -> std::io::Result<()> {
+        let file = std::fs::File::create(path)?;
+        self.event_logger = Some(EventLogger::new(std::io::BufWriter::new(file)));
+        self.event_detector.reset();
+        self.set_status(format!("Logging events to {}", path.display()));
+        Ok(())
+    }
+
+    /// 停止记录物理事件
+    fn stop_event_log(&mut self) {
+        if let Some(mut logger) = self.event_logger.take() {
+            let count = logger.count();
+            match logger.flush() {
+                Ok(()) => self.set_status(format!("Event log closed ({} events)", count)),
+                Err(err) => self.set_status(format!("Event log flush failed: {}", err)),
+            }
+        }
+    }
+
+    /// 从头开始执行场景脚本，并立即执行 t=0 的操作
+    fn start_scenario(&mut self, scenario: Scenario) {
+        let mut runner = ScenarioRunner::new(scenario);
+        let actions = runner.due();
+        self.scenario = Some(runner);
+        self.is_running = true;
+        self.last_update = std::time::Instant::now();
+        self.set_status("Scenario started".to_string());
+        self.run_scenario_actions(actions);
+    }
+
+    /// 依次执行场景操作，全部执行完后结束场景
+    fn run_scenario_actions(&mut self, actions: Vec<ScenarioAction>) {
+        for action in actions {
+            match action {
+                ScenarioAction::LoadPreset { name } => match get_preset_by_name(&name) {
+                    Some(preset) => self.load_preset(&preset),
+                    None => self.set_status(format!("Scenario: unknown preset '{}'", name)),
+                },
+                ScenarioAction::SetDamping { value } => {
+                    // 与应用参数时一样先验证，无效的阻尼不写入
+                    let mut params = self.pendulum.params;
+                    params.damping = value as Real;
+                    match params.validate() {
+                        Ok(_) => {
+                            self.temp_params.damping = params.damping;
+                            self.pendulum.params = params;
+                        }
+                        Err(err) => self.set_status(format!("Scenario: invalid damping: {}", err)),
+                    }
+                }
+                ScenarioAction::SetSpeed { value } => {
+                    self.simulation_speed = (value as f32).clamp(0.1, 5.0);
+                }
+                ScenarioAction::Pause => self.is_running = false,
+                ScenarioAction::Reset => self.reset_simulation(),
+            }
+        }
+        if self.scenario.as_ref().is_some_and(ScenarioRunner::is_finished) {
+            self.scenario = None;
+            self.set_status("Scenario finished".to_string());
+        }
+    }
+
+    /// 从当前时刻开始运行指定的模拟时长
+    fn run_for(&mut self, duration: Real) {
+        self.stop_after = Some(self.pendulum.time + duration);
+        self.is_running = true;
+        self.last_update = std::time::Instant::now();
+    }
+
+    /// 记录当前状态的能量、轨迹和相空间数据
+    fn record_statistics(&mut self) {
+        self.record_state_statistics();
+        self.record_trajectory_point();
+    }
+
+    /// 记录能量、角动量、相空间和频闪样本（不含轨迹点）
+    fn record_state_statistics(&mut self) {
         let energy = self.pendulum.total_energy();
         self.statistics.add_energy_data(
-            energy,
-            self.pendulum.kinetic_energy(),
-            self.pendulum.potential_energy(),
+            real_to_f64(self.pendulum.time),
+            real_to_f64(energy),
+            real_to_f64(self.pendulum.kinetic_energy()),
+            real_to_f64(self.pendulum.potential_energy()),
         );
-
-        let (pos1, pos2) = self.pendulum.get_positions();
+        let state = self.pendulum.state;
         self.statistics
-            .add_trajectory_point(pos1.0, pos1.1, pos2.0, pos2.1);
+            .add_angular_momentum(real_to_f64(state.angular_momentum(&self.pendulum.params)));
+
         self.statistics.add_phase_space_point(
-            self.pendulum.state.theta1,
-            self.pendulum.state.omega1,
-            self.pendulum.state.theta2,
-            self.pendulum.state.omega2,
+            real_to_f64(state.theta1),
+            real_to_f64(state.omega1),
+            real_to_f64(state.theta2),
+            real_to_f64(state.omega2),
         );
+        self.statistics.add_strobe_sample(self.pendulum.state);
+    }
+
+    /// 记录当前两个摆球的位置作为轨迹点（冻结轨迹时跳过）
+    fn record_trajectory_point(&mut self) {
+        let (pos1, pos2) = self.pendulum.get_positions();
+        self.last_recorded_position = pos2;
+        if self.record_trajectory {
+            self.statistics.add_trajectory_point(
+                real_to_f64(self.pendulum.time),
+                real_to_f64(pos1.0),
+                real_to_f64(pos1.1),
+                real_to_f64(pos2.0),
+                real_to_f64(pos2.1),
+            );
+        }
+    }
+
+    /// 相邻能量样本之间的模拟时间
+    fn energy_sample_dt(&self) -> f64 {
+        real_to_f64(self.time_step) * self.trajectory_record_interval as f64
+    }
+
+    /// 弹出保存对话框，并按所选格式导出统计数据
+    fn export_data(&mut self) {
+        let format = self.export_format;
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("CSV", &["csv"])
+            .set_file_name(format.default_file_name())
+            .save_file()
+        else {
+            return;
+        };
+
+        let result = std::fs::File::create(&path).and_then(|file| {
+            let mut writer = std::io::BufWriter::new(file);
+            format.write(&mut writer, &self.statistics, &self.pendulum.params)?;
+            std::io::Write::flush(&mut writer)
+        });
+
+        match result {
+            Ok(()) => self.set_status(format!("Exported to {}", path.display())),
+            Err(err) => self.set_status(format!("Export failed: {}", err)),
+        }
+    }
+
+    /// 弹出保存对话框，把轨迹、相空间和能量历史导出为一个 CSV 表格
+    fn export_history_csv(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("CSV", &["csv"])
+            .set_file_name("history.csv")
+            .save_file()
+        else {
+            return;
+        };
+
+        let (rows, longest) = self.statistics.history_row_counts();
+        match self.statistics.export_csv(&path, &self.pendulum.params) {
+            Ok(()) if rows < longest => self.set_status(format!(
+                "Exported {} rows to {} (histories differ in length; {} samples dropped)",
+                rows,
+                path.display(),
+                longest - rows
+            )),
+            Ok(()) => self.set_status(format!("Exported {} rows to {}", rows, path.display())),
+            Err(err) => self.set_status(format!("Export failed: {}", err)),
+        }
+    }
+
+    /// 把本次运行的指标汇总导出为 JSON 文件
+    fn export_summary(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("JSON", &["json"])
+            .set_file_name("run_summary.json")
+            .save_file()
+        else {
+            return;
+        };
+
+        let summary = RunSummary::assemble(
+            &self.statistics,
+            &self.physics_engine,
+            real_to_f64(self.pendulum.time - self.run_start_time),
+            real_to_f64(self.run_start_energy),
+            self.max_energy_error.peak().map(|(error, _, _)| error),
+            self.first_flip_time,
+        );
+        let result = summary
+            .to_json()
+            .map_err(std::io::Error::from)
+            .and_then(|text| std::fs::write(&path, text));
+        match result {
+            Ok(()) => self.set_status(format!("Summary exported to {}", path.display())),
+            Err(err) => self.set_status(format!("Summary export failed: {}", err)),
+        }
+    }
+
+    /// 把当前状态和参数保存为预设文件
+    fn save_preset_file(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("JSON", &["json"])
+            .set_file_name("preset.json")
+            .save_file()
+        else {
+            return;
+        };
+
+        let preset = PendulumPreset::new(
+            "Custom".to_string(),
+            "Saved from the simulator".to_string(),
+            self.pendulum.state,
+            self.pendulum.params,
+        )
+        .with_recommended_dt(self.time_step);
+        let result = preset
+            .to_json()
+            .map_err(std::io::Error::from)
+            .and_then(|text| std::fs::write(&path, text));
+        match result {
+            Ok(()) => self.set_status(format!("Preset saved to {}", path.display())),
+            Err(err) => self.set_status(format!("Preset save failed: {}", err)),
+        }
+    }
+
+    /// 从文件加载预设，角度按文件注明的约定转换
+    fn load_preset_file(&mut self) {
+        let Some(path) = rfd::FileDialog::new().add_filter("JSON", &["json"]).pick_file() else {
+            return;
+        };
+
+        match std::fs::read_to_string(&path) {
+            Ok(text) => self.load_preset_json(&text),
+            Err(err) => self.set_status(format!("Preset load failed: {}", err)),
+        }
+    }
+
+    /// 解析并加载预设 JSON，参数或推荐步长无效时保持当前状态
+    fn load_preset_json(&mut self, text: &str) {
+        let preset = match PendulumPreset::from_json(text) {
+            Ok(preset) => preset,
+            Err(err) => {
+                self.set_status(format!("Preset load failed: {}", err));
+                return;
+            }
+        };
+        if let Err(err) = preset.validate() {
+            self.set_status(format!("Preset load failed: invalid preset: {}", err));
+            return;
+        }
+
+        self.load_preset(&preset);
+        self.set_status(format!("Loaded preset {}", preset.name));
+    }
+
+    /// 把当前状态和参数保存为自定义预设，显示在预设列表中并在下次启动时恢复
+    fn save_custom_preset(&mut self) {
+        let name = self.custom_preset_name.trim().to_string();
+        let description = match self.custom_preset_description.trim() {
+            "" => "Custom preset".to_string(),
+            text => text.to_string(),
+        };
+        let preset = PendulumPreset::new(
+            name.clone(),
+            description,
+            self.pendulum.state,
+            self.pendulum.params,
+        )
+        .with_recommended_dt(self.time_step);
+        match self.custom_presets.add(preset) {
+            Ok(()) => {
+                self.custom_preset_name.clear();
+                self.custom_preset_description.clear();
+                self.set_status(format!("Saved custom preset '{}'", name));
+            }
+            Err(err) => self.set_status(format!("Preset not saved: {}", err)),
+        }
+    }
+
+    /// 删除自定义预设
+    fn delete_custom_preset(&mut self, name: &str) {
+        if self.custom_presets.remove(name) {
+            self.set_status(format!("Deleted custom preset '{}'", name));
+        }
+    }
+
+    /// 把当前状态、参数和模拟时间保存为 JSON 文件
+    fn save_state_file(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("JSON", &["json"])
+            .set_file_name("state.json")
+            .save_file()
+        else {
+            return;
+        };
+
+        let result = self
+            .pendulum
+            .to_json()
+            .map_err(std::io::Error::from)
+            .and_then(|text| std::fs::write(&path, text));
+        match result {
+            Ok(()) => self.set_status(format!("State saved to {}", path.display())),
+            Err(err) => self.set_status(format!("State save failed: {}", err)),
+        }
+    }
+
+    /// 从 JSON 文件恢复模拟，参数无效时不应用
+    fn load_state_file(&mut self) {
+        let Some(path) = rfd::FileDialog::new().add_filter("JSON", &["json"]).pick_file() else {
+            return;
+        };
+
+        let result = std::fs::read_to_string(&path)
+            .and_then(|text| DoublePendulum::from_json(&text).map_err(std::io::Error::from));
+        let pendulum = match result {
+            Ok(pendulum) => pendulum,
+            Err(err) => {
+                self.set_status(format!("State load failed: {}", err));
+                return;
+            }
+        };
+        if let Err(err) = pendulum.params.validate() {
+            self.set_status(format!("State load failed: invalid parameters: {}", err));
+            return;
+        }
+
+        self.temp_params = pendulum.params;
+        self.pendulum = pendulum;
+        self.stop_after = None;
+        self.reseed_statistics();
+        self.restart_run_clock();
+        self.set_status(format!("State loaded from {}", path.display()));
+    }
+
+    /// 开始或停止会话录制，开始时丢弃之前的录制
+    fn toggle_recording(&mut self) {
+        if self.recording_active {
+            self.recording_active = false;
+            if let Some(recording) = &self.recording {
+                let message = format!("Recorded {} frames", recording.len());
+                self.set_status(message);
+            }
+        } else {
+            self.playback_index = None;
+            self.recording = Some(Recording::start(
+                real_to_f64(self.pendulum.time),
+                self.pendulum.state,
+                self.pendulum.params,
+            ));
+            self.recording_active = true;
+            self.set_status("Recording started".to_string());
+        }
+    }
+
+    /// 把当前状态记入录制；参数与开始录制时不同则停止录制，回放时摆形只按一组参数绘制
+    fn record_frame(&mut self) {
+        let Some(recording) = self.recording.as_mut() else {
+            return;
+        };
+        if recording.params != self.pendulum.params {
+            let message = format!(
+                "Recording stopped after {} frames: parameters changed",
+                recording.len()
+            );
+            self.recording_active = false;
+            self.set_status(message);
+            return;
+        }
+        recording.observe(real_to_f64(self.pendulum.time), self.pendulum.state);
+    }
+
+    /// 进入回放模式：暂停模拟，从第一帧开始显示录制
+    fn start_playback(&mut self) {
+        if self.recording.as_ref().is_some_and(|recording| !recording.is_empty()) {
+            self.recording_active = false;
+            self.is_running = false;
+            self.playback_index = Some(0);
+        }
+    }
+
+    /// 把录制保存为 JSON 文件
+    fn save_recording_file(&mut self) {
+        let Some(recording) = &self.recording else {
+            return;
+        };
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("JSON", &["json"])
+            .set_file_name("recording.json")
+            .save_file()
+        else {
+            return;
+        };
+
+        let result = recording
+            .to_json()
+            .map_err(std::io::Error::from)
+            .and_then(|text| std::fs::write(&path, text));
+        match result {
+            Ok(()) => self.set_status(format!("Recording saved to {}", path.display())),
+            Err(err) => self.set_status(format!("Recording save failed: {}", err)),
+        }
+    }
+
+    /// 从 JSON 文件加载录制并进入回放模式，参数无效或没有帧时不应用
+    fn load_recording_file(&mut self) {
+        let Some(path) = rfd::FileDialog::new().add_filter("JSON", &["json"]).pick_file() else {
+            return;
+        };
+
+        let result = std::fs::read_to_string(&path)
+            .and_then(|text| Recording::from_json(&text).map_err(std::io::Error::from));
+        let recording = match result {
+            Ok(recording) => recording,
+            Err(err) => {
+                self.set_status(format!("Recording load failed: {}", err));
+                return;
+            }
+        };
+        if let Err(err) = recording.params.validate() {
+            self.set_status(format!("Recording load failed: invalid parameters: {}", err));
+            return;
+        }
+        if recording.is_empty() {
+            self.set_status("Recording load failed: no frames".to_string());
+            return;
+        }
+
+        self.recording = Some(recording);
+        self.start_playback();
+        self.set_status(format!("Recording loaded from {}", path.display()));
+    }
+
+    /// 选择目录后在后台线程中模拟并导出所有选中的预设
+    fn start_batch_export(&mut self) {
+        let presets: Vec<_> = get_all_presets()
+            .into_iter()
+            .zip(&self.batch_selection)
+            .filter_map(|(preset, &selected)| selected.then_some(preset))
+            .collect();
+        if presets.is_empty() {
+            self.set_status("No presets selected".to_string());
+            return;
+        }
+        let Some(directory) = rfd::FileDialog::new().pick_folder() else {
+            return;
+        };
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let duration = self.batch_duration;
+        self.batch_progress = (0, presets.len());
+        self.batch_export = Some(receiver);
+        std::thread::spawn(move || {
+            let result = export::export_preset_runs(&presets, &directory, duration, |done, total| {
+                let _ = sender.send(BatchExportMessage::Progress { done, total });
+            });
+            let _ = sender.send(BatchExportMessage::Finished(result));
+        });
+    }
+
+    /// 处理后台批量导出线程发来的消息
+    fn poll_batch_export(&mut self) {
+        let Some(receiver) = self.batch_export.as_ref() else {
+            return;
+        };
+        let mut finished = None;
+        for message in receiver.try_iter() {
+            match message {
+                BatchExportMessage::Progress { done, total } => self.batch_progress = (done, total),
+                BatchExportMessage::Finished(result) => finished = Some(result),
+            }
+        }
+
+        if let Some(result) = finished {
+            self.batch_export = None;
+            match result {
+                Ok(paths) => self.set_status(format!("Exported {} runs", paths.len())),
+                Err(err) => self.set_status(format!("Batch export failed: {}", err)),
+            }
+        }
+    }
+
+    /// 选择文件后在后台线程中从当前状态录制 GIF，background 为画布背景色
+    fn start_gif_recording(&mut self, background: egui::Color32) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("GIF animation", &["gif"])
+            .set_file_name("pendulum.gif")
+            .save_file()
+        else {
+            return;
+        };
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let settings = self.gif_settings;
+        let style = FrameStyle::new(&self.theme_manager, &self.ui_state, background);
+        let engine = self.physics_engine.clone();
+        let pendulum = self.pendulum.clone();
+        self.gif_progress = (0, settings.frame_count());
+        self.gif_export = Some(receiver);
+        std::thread::spawn(move || {
+            let result = std::fs::File::create(&path).and_then(|file| {
+                let writer = std::io::BufWriter::new(file);
+                export::record_gif(writer, &engine, &pendulum, &settings, &style, |done, total| {
+                    let _ = sender.send(GifExportMessage::Progress { done, total });
+                })?;
+                Ok(path)
+            });
+            let _ = sender.send(GifExportMessage::Finished(result));
+        });
+    }
+
+    /// 处理后台 GIF 录制线程发来的消息
+    fn poll_gif_export(&mut self) {
+        let Some(receiver) = self.gif_export.as_ref() else {
+            return;
+        };
+        let mut finished = None;
+        for message in receiver.try_iter() {
+            match message {
+                GifExportMessage::Progress { done, total } => self.gif_progress = (done, total),
+                GifExportMessage::Finished(result) => finished = Some(result),
+            }
+        }
+
+        if let Some(result) = finished {
+            self.gif_export = None;
+            match result {
+                Ok(path) => self.set_status(format!(
+                    "GIF saved to {} ({} frames)",
+                    path.display(),
+                    self.gif_progress.1
+                )),
+                Err(err) => self.set_status(format!("GIF recording failed: {}", err)),
+            }
+        }
+    }
+
+    /// 汇总判断当前配置是否混沌的各项指标
+    fn draw_chaos_explorer(&self, ui: &mut egui::Ui) {
+        let energy = real_to_f64(self.pendulum.total_energy());
+        let threshold = real_to_f64(self.pendulum.params.flip_energy_threshold());
+        let lyapunov = self.statistics.estimate_lyapunov_exponent(LYAPUNOV_WINDOW);
+        let period = self.statistics.detect_periodicity(PERIOD_TOLERANCE, MIN_PERIOD);
+        let precision = self.ui_state.display_precision();
+
+        egui::Grid::new("chaos_explorer").num_columns(2).show(ui, |ui| {
+            ui.label("Total energy").on_hover_text(explain::total_energy(
+                real_to_f64(self.pendulum.kinetic_energy()),
+                real_to_f64(self.pendulum.potential_energy()),
+            ));
+            ui.monospace(fmt_value(energy, ValueKind::Energy, precision));
+            ui.end_row();
+            ui.label("Flip-over threshold").on_hover_text(explain::FLIP_THRESHOLD);
+            ui.monospace(fmt_value(threshold, ValueKind::Energy, precision));
+            ui.end_row();
+            ui.label("Lyapunov estimate").on_hover_text(explain::LYAPUNOV);
+            ui.monospace(match lyapunov {
+                Some(exponent) => {
+                    format!("{} / sample", fmt_value(exponent, ValueKind::Number, precision))
+                }
+                None => "n/a (need more data)".to_string(),
+            });
+            ui.end_row();
+            ui.label("Period").on_hover_text(explain::PERIOD);
+            ui.monospace(match period {
+                Some(period) => format!("{} samples", period),
+                None => "none detected".to_string(),
+            });
+            ui.end_row();
+        });
+        ui.label(chaos_verdict(energy, threshold, lyapunov, period));
+    }
+
+    /// 从当前状态用 Benettin 算法计算最大李雅普诺夫指数，不改变模拟
+    fn compute_benettin_exponent(&mut self) {
+        let dt = real_to_f64(self.physics_engine.dt());
+        let steps = (BENETTIN_DURATION / dt).round() as usize;
+        let mut engine = self.physics_engine.clone();
+        engine.set_time(self.pendulum.time);
+        let exponent = engine.lyapunov_benettin(
+            &self.pendulum.state,
+            &self.pendulum.params,
+            steps,
+            BENETTIN_RENORM_INTERVAL,
+        );
+        self.benettin_exponent = Some((real_to_f64(self.pendulum.time), exponent));
+    }
+
+    /// 暂停并把模拟恢复到第 index 个记录样本的状态和时间
+    fn jump_to_recorded_state(&mut self, index: usize) {
+        let Some((time, state)) = self.statistics.recorded_state(index) else {
+            return;
+        };
+        self.jump_to_state(time, state);
+    }
+
+    /// 暂停并跳转到记录窗口内任意时刻的插值状态
+    fn jump_to_time(&mut self, time: f64) {
+        match self.statistics.state_at_time(time) {
+            Some(state) => self.jump_to_state(time, state),
+            None => self.set_status(format!("t = {:.3}s is outside the recorded window", time)),
+        }
+    }
+
+    /// 暂停并把摆设置为给定时刻的状态
+    fn jump_to_state(&mut self, time: f64, state: PendulumState) {
+        self.is_running = false;
+        self.stop_after = None;
+        self.pendulum.state = state;
+        self.pendulum.time = time as Real;
+        self.euler_shadow = state;
+        self.event_detector.reset();
+        self.restart_ensemble();
+        self.set_status(format!("Jumped to recorded state at t = {:.3}s", time));
+    }
+
+    /// 以等宽网格显示所有状态变量、导数和能量
+    fn draw_state_table(&self, ui: &mut egui::Ui) {
+        let state = self.pendulum.state;
+        let derivative = self
+            .physics_engine
+            .compute_derivatives(&state, &self.pendulum.params);
+        let rows = [
+            ("θ₁", state.theta1, "rad"),
+            ("θ₂", state.theta2, "rad"),
+            ("ω₁", state.omega1, "rad/s"),
+            ("ω₂", state.omega2, "rad/s"),
+            ("dθ₁/dt", derivative.dtheta1, "rad/s"),
+            ("dθ₂/dt", derivative.dtheta2, "rad/s"),
+            ("dω₁/dt", derivative.domega1, "rad/s²"),
+            ("dω₂/dt", derivative.domega2, "rad/s²"),
+            ("Kinetic", self.pendulum.kinetic_energy(), "J"),
+            ("Potential", self.pendulum.potential_energy(), "J"),
+            ("Total", self.pendulum.total_energy(), "J"),
+        ];
+
+        egui::Grid::new("state_table")
+            .num_columns(3)
+            .striped(true)
+            .show(ui, |ui| {
+                for (name, value, unit) in rows {
+                    ui.label(name);
+                    ui.monospace(format_table_value(real_to_f64(value)));
+                    ui.label(unit);
+                    ui.end_row();
+                }
+                ui.label("Energy Error");
+                ui.monospace(format_table_error(real_to_f64(self.energy_error)));
+                ui.label("");
+                ui.end_row();
+            });
+    }
+
+    /// RK4 阶段表：当前状态下一步积分的四个中间状态和导数
+    fn show_rk4_stages(&self, ui: &mut egui::Ui) {
+        let stages = self
+            .physics_engine
+            .rk4_stages(&self.pendulum.state, &self.pendulum.params);
+        ui.small(format!(
+            "One RK4 step of dt = {} s from the current state",
+            self.physics_engine.dt()
+        ));
+        egui::Grid::new("rk4_stages")
+            .num_columns(5)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("");
+                for stage in 1..=4 {
+                    ui.strong(format!("Stage {}", stage));
+                }
+                ui.end_row();
+                let states = stages.states;
+                let derivatives = stages.derivatives;
+                let rows = [
+                    ("θ₁", states.map(|state| state.theta1)),
+                    ("θ₂", states.map(|state| state.theta2)),
+                    ("ω₁", states.map(|state| state.omega1)),
+                    ("ω₂", states.map(|state| state.omega2)),
+                    ("dθ₁/dt", derivatives.map(|k| k.dtheta1)),
+                    ("dθ₂/dt", derivatives.map(|k| k.dtheta2)),
+                    ("dω₁/dt", derivatives.map(|k| k.domega1)),
+                    ("dω₂/dt", derivatives.map(|k| k.domega2)),
+                ];
+                for (name, values) in rows {
+                    ui.label(name);
+                    for value in values {
+                        ui.monospace(format_table_value(real_to_f64(value)));
+                    }
+                    ui.end_row();
+                }
+            });
+    }
+
+    /// 无重力时的守恒量检查：总能量和角动量的漂移，以及可积运动的相对转动
+    fn show_zero_gravity_invariants(&self, ui: &mut egui::Ui, precision: usize) {
+        let state = &self.pendulum.state;
+        let params = &self.pendulum.params;
+        ui.small(format!(
+            "Angular momentum L: {}",
+            fmt_value(
+                real_to_f64(state.angular_momentum(params)),
+                ValueKind::AngularMomentum,
+                precision
+            )
+        ))
+        .on_hover_text(
+            "L = (m₁+m₂)l₁²ω₁ + m₂l₂²ω₂ + m₂l₁l₂(ω₁+ω₂)cos(θ₁−θ₂), \
+             conserved about the pivot when g = 0",
+        );
+        let (energy_drift, momentum_drift) = physics::zero_gravity_invariant_drift(
+            &self.invariant_baseline,
+            state,
+            params,
+        );
+        let error = ValueKind::Error;
+        ui.small(format!(
+            "Energy drift: {}",
+            fmt_value(real_to_f64(energy_drift), error, precision)
+        ));
+        ui.small(format!(
+            "Angular momentum drift: {}",
+            fmt_value(real_to_f64(momentum_drift), error, precision)
+        ));
+        if params.damping > 0.0 || params.coulomb_friction > 0.0 {
+            ui.colored_label(
+                egui::Color32::YELLOW,
+                "Damping and friction remove energy and angular momentum; \
+                 set them to 0 to check drift",
+            );
+        }
+        ui.small(format!(
+            "Relative angle θ₂−θ₁: {} | rate ω₂−ω₁: {}",
+            fmt_value(real_to_f64(state.theta2 - state.theta1), ValueKind::Angle, precision),
+            fmt_value(
+                real_to_f64(state.omega2 - state.omega1),
+                ValueKind::AngularVelocity,
+                precision
+            )
+        ))
+        .on_hover_text(
+            "With two conserved quantities the motion is integrable: \
+             the arms fold and unfold regularly instead of chaotically",
+        );
+    }
+
+    /// 清除统计历史并以当前状态重新记录
+    fn reseed_statistics(&mut self) {
+        self.euler_shadow = self.pendulum.state;
+        self.event_detector.reset();
+        self.statistics.clear_history();
+        self.trajectory_counter = 0;
+        self.record_statistics();
+    }
+
+    /// 让顶部读数向当前状态靠近时长为 dt（秒）的一帧
+    fn update_readouts(&mut self, dt: f64) {
+        let state = &self.pendulum.state;
+        let actual = [state.theta1, state.theta2, state.omega1, state.omega2].map(real_to_f64);
+        for (i, (shown, target)) in self.smoothed_readout.iter_mut().zip(actual).enumerate() {
+            // 角度跨越 ±π 回绕时直接跳转，避免读数扫过中间值
+            if i < 2 && (target - *shown).abs() > std::f64::consts::PI {
+                *shown = target;
+            } else {
+                *shown = smooth_readout(*shown, target, self.readout_smoothing, dt);
+            }
+        }
+    }
+
+    /// 累加自上一帧以来的墙钟时间，暂停期间不计入
+    fn tick_wall_clock(&mut self, now: std::time::Instant) {
+        if self.is_running {
+            self.wall_time += now.saturating_duration_since(self.last_frame);
+        }
+        self.last_frame = now;
+    }
+
+    /// 以当前模拟时间为起点重新开始计时
+    fn restart_run_clock(&mut self) {
+        self.wall_time = std::time::Duration::ZERO;
+        self.time_accumulator = 0.0;
+        self.last_frame = std::time::Instant::now();
+        self.run_start_time = self.pendulum.time;
+        self.run_start_energy = self.pendulum.total_energy();
+        self.max_energy_error.reset();
+        self.first_flip_time = None;
+        self.noise.restart();
+        self.invariant_baseline = self.pendulum.state;
+        self.restart_analytic_reference();
+        self.restart_comparison();
+        self.restart_ensemble();
+        // 新的运行从实时模拟开始显示
+        self.playback_index = None;
+    }
+
+    /// 系综开启时以主摆的当前状态重新生成扰动成员
+    fn restart_ensemble(&mut self) {
+        if self.ensemble.is_some() {
+            self.ensemble = Some(Ensemble::seeded(
+                &self.pendulum,
+                self.ensemble_size,
+                self.ensemble_perturbation,
+            ));
+        }
+    }
+
+    /// 从参考运行的初始条件重新开始对比，与当前运行从同一时刻起步
+    fn restart_comparison(&mut self) {
+        if let Some(comparison) = self.comparison.take() {
+            self.start_comparison(comparison.reference().clone());
+        }
+    }
+
+    /// 开始与指定参考运行对比
+    fn start_comparison(&mut self, reference: ReferenceRun) {
+        let mut comparison = RunComparison::new(reference, self.run_start_time);
+        let elapsed = real_to_f64(self.pendulum.time - self.run_start_time);
+        comparison.observe(elapsed, &self.pendulum.state, &self.pendulum.params);
+        self.comparison = Some(comparison);
+    }
+
+    /// 以当前状态、参数和步长保存命名的参考运行
+    fn save_reference_run(&mut self) {
+        let name = self.reference_name_input.trim().to_string();
+        if name.is_empty() {
+            self.set_status("Reference name is empty".to_string());
+            return;
+        }
+        let reference = ReferenceRun::capture(
+            name.clone(),
+            self.pendulum.state,
+            self.pendulum.params,
+            &self.physics_engine,
+        );
+        // 同名参考运行直接覆盖
+        self.reference_runs.retain(|existing| existing.name != name);
+        self.reference_runs.push(reference);
+        self.reference_name_input = format!("Reference {}", self.reference_runs.len() + 1);
+        self.set_status(format!("Saved reference run '{}'", name));
+    }
+
+    /// 启用参考叠加时，以当前状态和参数重新计算线性化解析解
+    fn restart_analytic_reference(&mut self) {
+        // 无重力时没有回复力，线性化解不存在
+        if self.pendulum.params.g == 0.0 {
+            self.analytic_reference = None;
+        } else if self.analytic_reference.is_some() {
+            self.analytic_reference = Some((
+                NormalModeSolution::new(&self.pendulum.state, &self.pendulum.params),
+                self.pendulum.time,
+            ));
+        }
+    }
+
+    /// 切换运行/暂停
+    fn toggle_running(&mut self) {
+        // 回放中开始运行时回到实时模拟
+        self.playback_index = None;
+        self.is_running = !self.is_running;
+        self.last_update = std::time::Instant::now();
+    }
+
+    /// 清除轨迹
+    fn clear_trails(&mut self) {
+        // 清除后记录当前数据点
+        self.reseed_statistics();
+
+        self.set_status("Trails cleared".to_string());
+    }
+
+    /// 重置视图
+    fn reset_view(&mut self) {
+        self.ui_state.reset_view();
+        self.renderer.reset_view();
+    }
+
+    /// 处理画布右键菜单中选择的操作
+    fn handle_canvas_action(&mut self, ctx: &egui::Context, action: CanvasAction) {
+        match action {
+            CanvasAction::Reset => self.reset_simulation(),
+            CanvasAction::TogglePause => self.toggle_running(),
+            CanvasAction::ResetView => self.reset_view(),
+            CanvasAction::ClearTrail => self.clear_trails(),
+            CanvasAction::SaveImage => {
+                // 截图在之后的帧中以事件形式返回
+                ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot);
+                self.screenshot_requested = Some(std::time::Instant::now());
+            }
+        }
+    }
+
+    /// 截图请求超时仍未收到截图时放弃等待，并在状态栏报告失败
+    fn expire_screenshot_request(&mut self, now: std::time::Instant) {
+        let expired = self
+            .screenshot_requested
+            .is_some_and(|requested| now.duration_since(requested) >= SCREENSHOT_TIMEOUT);
+        if expired {
+            self.screenshot_requested = None;
+            self.set_status("Image save failed: no screenshot received".to_string());
+        }
+    }
+
+    /// 收到截图后裁剪出画布区域并保存为 PPM 图像
+    fn save_canvas_image(&mut self, image: &egui::ColorImage, pixels_per_point: f32) {
+        let canvas = image.region(&self.renderer.canvas_rect(), Some(pixels_per_point));
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("PPM image", &["ppm"])
+            .set_file_name("pendulum.ppm")
+            .save_file()
+        else {
+            return;
+        };
+
+        let result = std::fs::File::create(&path).and_then(|file| {
+            let mut writer = std::io::BufWriter::new(file);
+            export::write_ppm(&mut writer, &canvas)?;
+            std::io::Write::flush(&mut writer)
+        });
+        match result {
+            Ok(()) => self.set_status(format!("Image saved to {}", path.display())),
+            Err(err) => self.set_status(format!("Image save failed: {}", err)),
+        }
+    }
+
+    /// 重置模拟
+    fn reset_simulation(&mut self) {
+        self.pendulum.reset(PendulumState::new(
+            -consts::PI * 2.0 / 3.0,
+            -consts::PI / 2.0,
+            0.0,
+            0.0,
+        ));
+        self.stop_after = None;
+        self.reseed_statistics();
+        self.restart_run_clock();
 
         self.set_status("Simulation reset".to_string());
     }
 
+    /// 以小振幅沿第 mode 个简正模的振型重新开始（0 为慢模，1 为快模）
+    fn excite_normal_mode(&mut self, mode: usize) {
+        let state =
+            PendulumState::from_normal_mode(&self.pendulum.params, mode, NORMAL_MODE_AMPLITUDE);
+        self.pendulum.reset(state);
+        self.stop_after = None;
+        self.reseed_statistics();
+        self.restart_run_clock();
+
+        let name = if mode == 0 { "slow" } else { "fast" };
+        self.set_status(format!("Excited the {} normal mode", name));
+    }
+
     /// 应用参数更改
     fn apply_parameters(&mut self) {
         match self.temp_params.validate() {
             Ok(_) => {
+                // 短暂显示旧参数下的摆形，便于对比几何变化
+                if self.temp_params != self.pendulum.params {
+                    self.renderer
+                        .show_parameter_ghost(self.pendulum.state, self.pendulum.params);
+                }
                 self.pendulum.params = self.temp_params;
+                self.invariant_baseline = self.pendulum.state;
+                self.restart_analytic_reference();
                 self.set_status("Parameters updated".to_string());
             }
             Err(err) => {
@@ -227,14 +1554,365 @@ impl ChaosPendulumApp {
         }
     }
 
-    /// 更新时间步长
+    /// 更新时间步长，视为用户选择的步长
     fn update_time_step(&mut self) {
         self.physics_engine.set_dt(self.time_step);
+        self.time_step_input = self.time_step;
+        self.time_step_before_preset = None;
+    }
+
+    /// 验证并应用精确输入的时间步长
+    fn apply_time_step_input(&mut self) {
+        match physics::validate_time_step(self.time_step_input) {
+            Ok(dt) => {
+                self.time_step = dt;
+                self.update_time_step();
+                self.set_status(format!("Time step set to {}", dt));
+            }
+            Err(err) => {
+                self.time_step_input = self.time_step;
+                self.set_status(format!("Invalid time step: {}", err));
+            }
+        }
+    }
+
+    /// 加载预设，锁定参数时只应用预设的初始状态
+    fn load_preset(&mut self, preset: &PendulumPreset) {
+        self.pendulum.state = preset.initial_state;
+        if !self.lock_parameters {
+            self.temp_params = preset.params;
+            self.pendulum.params = preset.params;
+        }
+        self.stop_after = None;
+        // 未给出推荐步长的预设恢复推荐步长之前的步长，不沿用上一个预设的步长
+        let before_preset = match preset.recommended_dt {
+            Some(dt) => {
+                let before = self.time_step_before_preset.unwrap_or(self.time_step);
+                self.time_step = dt;
+                Some(before)
+            }
+            None => {
+                if let Some(dt) = self.time_step_before_preset {
+                    self.time_step = dt;
+                }
+                None
+            }
+        };
+        self.update_time_step();
+        self.time_step_before_preset = before_preset;
+
+        // 记录初始数据
+        self.reseed_statistics();
+        self.restart_run_clock();
+
+        self.set_status(format!("Loaded preset: {}", preset.name));
+    }
+
+    /// 暂停时把选中摆臂的角度按 nudge_step 微调 direction 次，并重新开始记录
+    fn nudge_selected_joint(&mut self, direction: Real) {
+        if self.is_running {
+            return;
+        }
+        self.pendulum
+            .state
+            .nudge(self.selected_joint, direction * self.nudge_step);
+        self.reseed_statistics();
+        self.restart_run_clock();
+
+        let theta = match self.selected_joint {
+            1 => self.pendulum.state.theta1,
+            _ => self.pendulum.state.theta2,
+        };
+        self.set_status(format!("θ{} = {:.6} rad", self.selected_joint, theta));
+    }
+
+    /// 按输入的摆球坐标设置初始状态，坐标与摆长不符时保持原状态
+    fn apply_position_input(&mut self) {
+        let [x1, y1, x2, y2] = self.position_input;
+        let params = self.pendulum.params;
+        match PendulumState::from_positions((x1, y1), (x2, y2), params.l1, params.l2) {
+            Ok(state) => {
+                self.pendulum.reset(state);
+                self.stop_after = None;
+                self.reseed_statistics();
+                self.restart_run_clock();
+                self.set_status("Initial positions applied".to_string());
+            }
+            Err(err) => {
+                self.set_status(format!("Invalid positions: {}", err));
+            }
+        }
+    }
+
+    /// 用当前摆球坐标填充初始条件输入框
+    fn fill_position_input(&mut self) {
+        let ((x1, y1), (x2, y2)) = self.pendulum.get_positions();
+        self.position_input = [x1, y1, x2, y2];
+    }
+
+    /// 对当前状态运行时间步长精度扫描
+    fn run_dt_sweep(&mut self) {
+        let dts = physics::log_spaced_dts(1e-4, 1e-2, 9);
+        self.dt_sweep_results = physics::sweep_dt_accuracy(
+            &self.pendulum.state,
+            &self.pendulum.params,
+            &dts,
+            2.0,
+        );
+        self.set_status("dt sweep finished".to_string());
+    }
+}
+
+/// 模拟质量预设，一键设置积分方式、时间步长和积分修正
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum QualityPreset {
+    /// 大步长RK4，关闭能量误差修正
+    Fast,
+    /// 默认设置
+    Balanced,
+    /// 小步长，自适应RK4，开启能量误差修正
+    Accurate,
+}
+
+impl QualityPreset {
+    /// 所有质量预设
+    const ALL: [QualityPreset; 3] = [
+        QualityPreset::Fast,
+        QualityPreset::Balanced,
+        QualityPreset::Accurate,
+    ];
+
+    /// 界面显示名称
+    fn label(&self) -> &'static str {
+        match self {
+            QualityPreset::Fast => "Fast",
+            QualityPreset::Balanced => "Balanced",
+            QualityPreset::Accurate => "Accurate",
+        }
+    }
+
+    /// 该预设使用的时间步长
+    fn time_step(&self) -> Real {
+        match self {
+            QualityPreset::Fast => 0.004,
+            QualityPreset::Balanced => 0.001,
+            QualityPreset::Accurate => 0.0002,
+        }
+    }
+
+    /// 该预设使用的积分方式
+    fn integrator(&self) -> IntegratorKind {
+        match self {
+            QualityPreset::Fast | QualityPreset::Balanced => IntegratorKind::Rk4,
+            QualityPreset::Accurate => IntegratorKind::Adaptive,
+        }
+    }
+
+    /// 该预设是否启用能量误差修正
+    fn error_correction(&self) -> bool {
+        !matches!(self, QualityPreset::Fast)
+    }
+
+    /// 把预设应用到应用程序的引擎和相关字段
+    fn apply(&self, app: &mut ChaosPendulumApp) {
+        app.time_step = self.time_step();
+        app.update_time_step();
+        app.physics_engine.set_integrator(self.integrator());
+        app.physics_engine.set_error_correction(self.error_correction());
+        app.set_status(format!("Quality: {}", self.label()));
+    }
+}
+
+/// 状态表数值格式：始终带符号、固定宽度，避免数值变号时表格抖动
+fn format_table_value(value: f64) -> String {
+    format!("{:>+12.5}", value)
+}
+
+/// 状态表误差格式：科学计数法，指数固定两位
+fn format_table_error(value: f64) -> String {
+    let formatted = format!("{:.2e}", value);
+    match formatted.split_once('e') {
+        Some((mantissa, exponent)) if value.is_finite() => {
+            let exponent: i32 = exponent.parse().unwrap_or(0);
+            format!("{}e{:+03}", mantissa, exponent)
+        }
+        _ => formatted,
+    }
+}
+
+/// 根据能量、翻转阈值、李雅普诺夫指数和周期检测结果给出通俗结论
+fn chaos_verdict(
+    energy: f64,
+    flip_threshold: f64,
+    lyapunov: Option<f64>,
+    period: Option<usize>,
+) -> String {
+    let diverging = lyapunov.is_some_and(|exponent| exponent > CHAOS_LYAPUNOV_THRESHOLD);
+    if let Some(period) = period {
+        format!(
+            "This configuration is periodic: its phase-space path repeats every {} samples.",
+            period
+        )
+    } else if energy < flip_threshold {
+        "This configuration is likely regular: its energy is below the threshold for the \
+         lower arm to go over the top, so the motion stays confined."
+            .to_string()
+    } else if diverging {
+        "This configuration is chaotic because its energy exceeds the threshold for the \
+         lower arm to go over the top, and nearby trajectories diverge."
+            .to_string()
+    } else {
+        "This configuration has enough energy for the lower arm to go over the top, \
+         but no divergence has been measured yet. Let it run longer."
+            .to_string()
+    }
+}
+
+/// 显示值的指数平滑：factor 为 0 时直接取真实值，越接近 1 变化越慢
+/// 按帧时长 dt（秒）换算靠近比例，显示的变化速度与帧率无关
+fn smooth_readout(displayed: f64, actual: f64, factor: f64, dt: f64) -> f64 {
+    let weight = f64::from(smoothing_weight(factor as f32, dt as f32));
+    displayed + (actual - displayed) * weight
+}
+
+/// 按相对能量误差的量级选择颜色：绿（<1e-8）、黄（<1e-6）、红
+fn energy_error_color(error: f64) -> egui::Color32 {
+    ErrorLevel::of(error).color()
+}
+
+/// 能量误差等级之间的阈值（绿/黄、黄/红）
+const ERROR_LEVEL_THRESHOLDS: [f64; 2] = [1e-8, 1e-6];
+
+/// 等级切换的滞后倍数：变差须超过阈值的该倍数，变好须低于阈值的该倍数分之一
+const ERROR_LEVEL_HYSTERESIS: f64 = 2.0;
+
+/// 能量误差的颜色等级，按从好到差排序
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+enum ErrorLevel {
+    #[default]
+    Good,
+    Warning,
+    Bad,
+}
+
+impl ErrorLevel {
+    /// 不带滞后的等级（NaN 视为最差）
+    fn of(error: f64) -> Self {
+        if error < ERROR_LEVEL_THRESHOLDS[0] {
+            ErrorLevel::Good
+        } else if error < ERROR_LEVEL_THRESHOLDS[1] {
+            ErrorLevel::Warning
+        } else {
+            ErrorLevel::Bad
+        }
+    }
+
+    /// 带滞后的等级更新：误差在阈值附近波动时保持当前等级
+    fn next(self, error: f64) -> Self {
+        let raw = Self::of(error);
+        if raw > self {
+            self.max(Self::of(error / ERROR_LEVEL_HYSTERESIS))
+        } else if raw < self {
+            self.min(Self::of(error * ERROR_LEVEL_HYSTERESIS))
+        } else {
+            self
+        }
+    }
+
+    /// 等级对应的颜色
+    fn color(self) -> egui::Color32 {
+        match self {
+            ErrorLevel::Good => egui::Color32::GREEN,
+            ErrorLevel::Warning => egui::Color32::YELLOW,
+            ErrorLevel::Bad => egui::Color32::RED,
+        }
+    }
+}
+
+/// 把运行状态、步长、积分器、能量误差、步速率和自动暂停汇总为一行健康状态
+/// 颜色取自能量误差
+fn format_health(app: &ChaosPendulumApp) -> (String, egui::Color32) {
+    let error = real_to_f64(app.energy_error);
+    let run_state = if app.is_running {
+        format!("▶ Running {:.0} steps/s", app.steps_per_second)
+    } else {
+        "⏸ Paused".to_string()
+    };
+    let integrator = app.physics_engine.integrator_label();
+    let auto_pause = match app.stop_after {
+        Some(limit) => format!("auto-pause @ {:.2}s", limit),
+        None => "no auto-pause".to_string(),
+    };
+    let text = format!(
+        "{} | dt {} s | {} | err {:.1e} | {}",
+        run_state,
+        app.physics_engine.dt(),
+        integrator,
+        error,
+        auto_pause
+    );
+    (text, app.energy_error_level.next(error).color())
+}
+
+/// 模拟时间与墙钟时间之比，墙钟时间为0时返回None
+fn realtime_ratio(physics_time: f64, wall_time: f64) -> Option<f64> {
+    if wall_time > 0.0 {
+        Some(physics_time / wall_time)
+    } else {
+        None
     }
 }
 
+/// 把经过的墙钟时间按模拟速度计入累加器，返回 (本帧步数, 剩余累加时间)
+/// 步长 dt 固定，速度只改变每墙钟秒推进的模拟时间，不影响精度
+fn accumulate_steps(accumulator: f64, elapsed: f64, simulation_speed: f32, dt: f64) -> (u32, f64) {
+    if dt <= 0.0 {
+        return (0, 0.0);
+    }
+    let accumulator = accumulator + elapsed.clamp(0.0, MAX_FRAME_TIME) * simulation_speed as f64;
+    let steps = (accumulator / dt).floor();
+    if steps >= MAX_STEPS_PER_FRAME as f64 {
+        return (MAX_STEPS_PER_FRAME, 0.0);
+    }
+    (steps as u32, accumulator - steps * dt)
+}
+
+/// 带缩略图的预设按钮
+fn preset_button(
+    ui: &mut egui::Ui,
+    preset: &PendulumPreset,
+    theme_manager: &ThemeManager,
+) -> egui::Response {
+    ui.horizontal(|ui| {
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(36.0, 36.0), egui::Sense::hover());
+        draw_thumbnail(ui.painter(), rect, &preset.initial_state, &preset.params, theme_manager);
+        ui.button(&preset.name)
+    })
+    .inner
+}
+
 impl eframe::App for ChaosPendulumApp {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        if let Ok(text) = self.custom_presets.to_json() {
+            storage.set_string(presets::CUSTOM_PRESETS_KEY, text);
+        }
+        if let Some(geometry) = self.window_geometry {
+            eframe::set_value(storage, window::WINDOW_GEOMETRY_KEY, &geometry);
+        }
+        eframe::set_value(
+            storage,
+            visualization::GRAB_TOLERANCE_KEY,
+            &self.renderer.grab_tolerance_px(),
+        );
+        eframe::set_value(storage, ui_state::UI_STATE_KEY, &self.ui_state);
+        eframe::set_value(storage, theme::THEME_KEY, &self.theme_manager.theme());
+        eframe::set_value(storage, visualization::RENDER_SCALE_KEY, &self.renderer.scale());
+    }
+
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // 累加运行时的墙钟时间
+        self.tick_wall_clock(std::time::Instant::now());
+
         // 更新主题动画
         self.theme_manager.update_theme_transition();
 
@@ -248,8 +1926,7 @@ impl eframe::App for ChaosPendulumApp {
         ctx.input(|i| {
             // Space - 开始/暂停
             if i.key_pressed(egui::Key::Space) {
-                self.is_running = !self.is_running;
-                self.last_update = std::time::Instant::now();
+                self.toggle_running();
             }
 
             // R - 重置
@@ -259,48 +1936,81 @@ impl eframe::App for ChaosPendulumApp {
 
             // C - 清除轨迹
             if i.key_pressed(egui::Key::C) {
-                self.statistics.clear_history();
-                self.trajectory_counter = 0;
-
-                // 记录当前数据点
-                let energy = self.pendulum.total_energy();
-                self.statistics.add_energy_data(
-                    energy,
-                    self.pendulum.kinetic_energy(),
-                    self.pendulum.potential_energy(),
-                );
-
-                let (pos1, pos2) = self.pendulum.get_positions();
-                self.statistics
-                    .add_trajectory_point(pos1.0, pos1.1, pos2.0, pos2.1);
-                self.statistics.add_phase_space_point(
-                    self.pendulum.state.theta1,
-                    self.pendulum.state.omega1,
-                    self.pendulum.state.theta2,
-                    self.pendulum.state.omega2,
-                );
-
-                self.set_status("Trails cleared".to_string());
-            }
-
-            // T - 切换主题
+                self.clear_trails();
+            }
+
+            // T - 循环切换主题
             if i.key_pressed(egui::Key::T) {
                 self.theme_manager.toggle_theme();
             }
         });
 
+        // Shift+方向键 - 暂停时选择摆臂并微调角度（输入框获得焦点时不处理）
+        if !self.is_running && !ctx.wants_keyboard_input() {
+            let (up, down, left, right) = ctx.input_mut(|i| {
+                (
+                    i.consume_key(egui::Modifiers::SHIFT, egui::Key::ArrowUp),
+                    i.consume_key(egui::Modifiers::SHIFT, egui::Key::ArrowDown),
+                    i.consume_key(egui::Modifiers::SHIFT, egui::Key::ArrowLeft),
+                    i.consume_key(egui::Modifiers::SHIFT, egui::Key::ArrowRight),
+                )
+            });
+            if up {
+                self.selected_joint = 1;
+            }
+            if down {
+                self.selected_joint = 2;
+            }
+            if left {
+                self.nudge_selected_joint(-1.0);
+            }
+            if right {
+                self.nudge_selected_joint(1.0);
+            }
+
+            // → - 暂停时单步推进（Shift+→ 已在上面用于微调）
+            if ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowRight)) {
+                self.step_paused(1);
+                ctx.request_repaint();
+            }
+        }
+
+        // F11 - 切换全屏
+        if ctx.input(|i| i.key_pressed(egui::Key::F11)) {
+            let fullscreen = ctx.input(|i| i.viewport().fullscreen.unwrap_or(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(!fullscreen));
+        }
+
+        self.track_window_geometry(ctx);
+
+        // 批量导出进行中时持续刷新进度
+        self.poll_batch_export();
+        self.poll_gif_export();
+        if self.batch_export.is_some() || self.gif_export.is_some() {
+            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+        }
+
         // 检查是否需要更新物理模拟
-        if self.is_running && self.last_update.elapsed() >= self.update_interval {
-            self.update_physics();
+        let elapsed = self.last_update.elapsed();
+        if self.is_running && elapsed >= self.update_interval {
+            self.update_physics(elapsed.as_secs_f64());
             self.last_update = std::time::Instant::now();
             ctx.request_repaint(); // 请求重绘
         }
+        self.update_readouts(f64::from(ctx.input(|i| i.stable_dt)));
 
         // 创建左侧控制面板
         egui::SidePanel::left("controls")
             .resizable(true)
             .default_width(250.0)
             .show(ctx, |ui| {
+                // 底部的模拟健康状态行
+                egui::TopBottomPanel::bottom("health").show_inside(ui, |ui| {
+                    let (health, color) = format_health(self);
+                    let text = egui::RichText::new(health).small().color(color);
+                    ui.add(egui::Label::new(text).wrap());
+                });
+
                 egui::ScrollArea::vertical()
                     .auto_shrink([false; 2])
                     .show(ui, |ui| {
@@ -316,8 +2026,7 @@ impl eframe::App for ChaosPendulumApp {
                                     "▶ Play"
                                 };
                                 if ui.button(play_text).clicked() {
-                                    self.is_running = !self.is_running;
-                                    self.last_update = std::time::Instant::now();
+                                    self.toggle_running();
                                 }
 
                                 if ui.button("🔄 Reset").clicked() {
@@ -325,84 +2034,308 @@ impl eframe::App for ChaosPendulumApp {
                                 }
                             });
 
+                            ui.horizontal(|ui| {
+                                let paused = !self.is_running;
+                                if ui
+                                    .add_enabled(paused, egui::Button::new("⏭ Step (→)"))
+                                    .on_hover_text("Advance exactly one physics step")
+                                    .clicked()
+                                {
+                                    self.step_paused(1);
+                                }
+                                if ui.add_enabled(paused, egui::Button::new("Step ×10")).clicked() {
+                                    self.step_paused(10);
+                                }
+                            });
+
+                            ui.horizontal(|ui| {
+                                if ui.button("💾 Save State...").clicked() {
+                                    self.save_state_file();
+                                }
+                                if ui.button("📂 Load State...").clicked() {
+                                    self.load_state_file();
+                                }
+                            });
+
                             ui.horizontal(|ui| {
                                 if ui.button("🗑 Clear Trails (C)").clicked() {
-                                    self.statistics.clear_history();
-                                    self.trajectory_counter = 0;
-
-                                    // 记录当前数据点
-                                    let energy = self.pendulum.total_energy();
-                                    self.statistics.add_energy_data(
-                                        energy,
-                                        self.pendulum.kinetic_energy(),
-                                        self.pendulum.potential_energy(),
-                                    );
+                                    self.clear_trails();
+                                }
+                            });
 
-                                    let (pos1, pos2) = self.pendulum.get_positions();
-                                    self.statistics
-                                        .add_trajectory_point(pos1.0, pos1.1, pos2.0, pos2.1);
-                                    self.statistics.add_phase_space_point(
-                                        self.pendulum.state.theta1,
-                                        self.pendulum.state.omega1,
-                                        self.pendulum.state.theta2,
-                                        self.pendulum.state.omega2,
-                                    );
+                            ui.add(
+                                egui::Slider::new(&mut self.simulation_speed, 0.1..=5.0)
+                                    .text("Speed")
+                                    .logarithmic(false),
+                            );
+
+                            let mut integrator = self.physics_engine.integrator();
+                            egui::ComboBox::from_label("Integrator")
+                                .selected_text(integrator.label())
+                                .show_ui(ui, |ui| {
+                                    for kind in IntegratorKind::ALL {
+                                        ui.selectable_value(&mut integrator, kind, kind.label());
+                                    }
+                                });
+                            self.physics_engine.set_integrator(integrator);
+
+                            // 自适应积分由容差决定子步长，时间步长滑块换成容差滑块
+                            if integrator == IntegratorKind::Adaptive {
+                                let mut tolerance = self.physics_engine.adaptive_tolerance();
+                                ui.add(
+                                    egui::Slider::new(
+                                        &mut tolerance,
+                                        physics::MIN_ADAPTIVE_TOLERANCE
+                                            ..=physics::MAX_ADAPTIVE_TOLERANCE,
+                                    )
+                                    .text("Tolerance")
+                                    .logarithmic(true),
+                                )
+                                .on_hover_text(
+                                    "Subdivide each step until the local error estimate \
+                                     is below the tolerance",
+                                );
+                                self.physics_engine.set_adaptive_tolerance(tolerance);
+                            } else {
+                                ui.add(
+                                    egui::Slider::new(&mut self.time_step, 0.0001..=0.01)
+                                        .text("Time Step")
+                                        .logarithmic(true),
+                                );
+                                if ui.button("Apply Time Step").clicked() {
+                                    self.update_time_step();
+                                }
+                            }
+
+                            ui.horizontal(|ui| {
+                                ui.label("Exact dt:");
+                                let response = ui.add(
+                                    egui::DragValue::new(&mut self.time_step_input)
+                                        .speed(0.00001)
+                                        .max_decimals(6)
+                                        .suffix(" s"),
+                                );
+                                let entered = response.lost_focus()
+                                    && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                                if entered || ui.button("Set").clicked() {
+                                    self.apply_time_step_input();
+                                }
+                            });
+                            ui.small(format!(
+                                "Effective dt: {} s",
+                                self.physics_engine.dt()
+                            ));
+                            // 刚性参数下提示可以稳定积分的步长
+                            if let Some(suggested) = self.pendulum.params.suggested_max_dt() {
+                                if real_to_f64(self.physics_engine.dt()) > suggested {
+                                    ui.colored_label(
+                                        egui::Color32::YELLOW,
+                                        format!(
+                                            "Suggested dt ≤ {} for these parameters",
+                                            suggested
+                                        ),
+                                    )
+                                    .on_hover_text(format!(
+                                        "Fastest natural frequency ≈ {:.1} rad/s; \
+                                         larger steps lose energy accuracy",
+                                        self.pendulum.params.stiffness_estimate()
+                                    ));
+                                }
+                            }
+
+                            ui.horizontal(|ui| {
+                                ui.add(
+                                    egui::DragValue::new(&mut self.run_duration_input)
+                                        .speed(0.1)
+                                        .range(0.01..=3600.0)
+                                        .suffix(" s"),
+                                );
+                                if ui.button("⏱ Run for N seconds").clicked() {
+                                    self.run_for(self.run_duration_input);
+                                }
+                            });
+                            if let Some(limit) = self.stop_after {
+                                ui.small(format!("Auto-pause at t = {:.3}s", limit));
+                            }
+
+                            match self.scenario.as_ref() {
+                                Some(runner) => {
+                                    let (done, total) = runner.progress();
+                                    ui.small(format!(
+                                        "Scenario t = {:.1}s: {}/{} actions run",
+                                        runner.elapsed(),
+                                        done,
+                                        total
+                                    ));
+                                    if ui.button("⏹ Stop Scenario").clicked() {
+                                        self.scenario = None;
+                                        self.set_status("Scenario stopped".to_string());
+                                    }
+                                }
+                                None => {
+                                    if ui.button("🎬 Run Scenario...").clicked() {
+                                        if let Some(path) = rfd::FileDialog::new()
+                                            .add_filter("JSON", &["json"])
+                                            .pick_file()
+                                        {
+                                            match Scenario::load(&path) {
+                                                Ok(scenario) => self.start_scenario(scenario),
+                                                Err(err) => self.set_status(format!(
+                                                    "Failed to load scenario: {}",
+                                                    err
+                                                )),
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+
+                            if ui.button("📉 Run dt Sweep").clicked() {
+                                self.run_dt_sweep();
+                            }
 
-                                    self.set_status("Trails cleared".to_string());
+                            ui.horizontal(|ui| {
+                                ui.label("Quality:");
+                                for preset in QualityPreset::ALL {
+                                    if ui.button(preset.label()).clicked() {
+                                        preset.apply(self);
+                                    }
                                 }
                             });
 
-                            ui.add(
-                                egui::Slider::new(&mut self.simulation_speed, 0.1..=5.0)
-                                    .text("Speed")
-                                    .logarithmic(false),
+                            let was_blending = self.integrator_blend;
+                            ui.checkbox(
+                                &mut self.integrator_blend,
+                                "Blend Euler ↔ RK4 (visual aid)",
                             );
+                            if self.integrator_blend {
+                                if !was_blending {
+                                    self.euler_shadow = self.pendulum.state;
+                                }
+                                ui.add(
+                                    egui::Slider::new(&mut self.integrator_blend_factor, 0.0..=1.0)
+                                        .text("0 = Euler, 1 = RK4"),
+                                );
+                                ui.small(
+                                    "Only the displayed bobs are blended; physics is unaffected",
+                                );
+                            }
 
-                            ui.add(
-                                egui::Slider::new(&mut self.time_step, 0.0001..=0.01)
-                                    .text("Time Step")
-                                    .logarithmic(true),
+                            // 误差修正只作用于固定步长RK4
+                            let mut error_correction = self.physics_engine.error_correction();
+                            ui.add_enabled(
+                                self.physics_engine.integrator() == IntegratorKind::Rk4,
+                                egui::Checkbox::new(
+                                    &mut error_correction,
+                                    "Energy Error Correction",
+                                ),
                             );
-                            if ui.button("Apply Time Step").clicked() {
-                                self.update_time_step();
-                            }
+                            self.physics_engine.set_error_correction(error_correction);
+
+                            let mut normalize = self.physics_engine.normalize_after_step();
+                            ui.checkbox(&mut normalize, "Normalize Angles")
+                                .on_hover_text("Off keeps unwrapped angles for rotation counting");
+                            self.physics_engine.set_normalize_after_step(normalize);
+
+                            ui.collapsing("Show RK4 stages", |ui| {
+                                if self.is_running {
+                                    ui.small("Pause the simulation to inspect a single step");
+                                } else {
+                                    self.show_rk4_stages(ui);
+                                }
+                            });
+
+                            ui.collapsing("Noise Injection", |ui| {
+                                let mut angle_std = self.noise.angle_std();
+                                ui.add(
+                                    egui::Slider::new(&mut angle_std, 0.0..=1e-2)
+                                        .logarithmic(true)
+                                        .text("Angle σ (rad)"),
+                                );
+                                self.noise.set_angle_std(angle_std);
+                                let mut velocity_std = self.noise.velocity_std();
+                                ui.add(
+                                    egui::Slider::new(&mut velocity_std, 0.0..=1e-1)
+                                        .logarithmic(true)
+                                        .text("Velocity σ (rad/s)"),
+                                );
+                                self.noise.set_velocity_std(velocity_std);
+                                ui.horizontal(|ui| {
+                                    let mut seed = self.noise.seed();
+                                    ui.label("Seed:");
+                                    if ui.add(egui::DragValue::new(&mut seed)).changed() {
+                                        self.noise.set_seed(seed);
+                                    }
+                                });
+                                ui.small(if self.noise.is_enabled() {
+                                    "Gaussian noise is added after every step; \
+                                     the sequence restarts on reset"
+                                } else {
+                                    "σ = 0: noise is off"
+                                });
+                            });
                         });
 
                         ui.separator();
 
                         // 预设配置
                         ui.collapsing("Presets", |ui| {
+                            ui.checkbox(&mut self.lock_parameters, "Lock Parameters")
+                                .on_hover_text("Apply only the preset's initial state");
                             let presets = get_all_presets();
                             for preset in presets.iter() {
-                                if ui.button(&preset.name).clicked() {
-                                    self.pendulum.state = preset.initial_state;
-                                    self.temp_params = preset.params;
-                                    self.pendulum.params = preset.params;
-                                    self.statistics.clear_history();
-                                    self.trajectory_counter = 0;
-
-                                    // 记录初始数据
-                                    let energy = self.pendulum.total_energy();
-                                    self.statistics.add_energy_data(
-                                        energy,
-                                        self.pendulum.kinetic_energy(),
-                                        self.pendulum.potential_energy(),
-                                    );
-
-                                    let (pos1, pos2) = self.pendulum.get_positions();
-                                    self.statistics
-                                        .add_trajectory_point(pos1.0, pos1.1, pos2.0, pos2.1);
-                                    self.statistics.add_phase_space_point(
-                                        self.pendulum.state.theta1,
-                                        self.pendulum.state.omega1,
-                                        self.pendulum.state.theta2,
-                                        self.pendulum.state.omega2,
-                                    );
-
-                                    self.set_status(format!("Loaded preset: {}", preset.name));
+                                if preset_button(ui, preset, &self.theme_manager).clicked() {
+                                    self.load_preset(preset);
                                 }
                                 ui.small(&preset.description);
                             }
+
+                            // 自定义预设，可删除
+                            if !self.custom_presets.presets().is_empty() {
+                                ui.separator();
+                                ui.label("Custom");
+                            }
+                            let mut deleted = None;
+                            let custom = self.custom_presets.presets().to_vec();
+                            for preset in custom.iter() {
+                                ui.horizontal(|ui| {
+                                    if preset_button(ui, preset, &self.theme_manager).clicked() {
+                                        self.load_preset(preset);
+                                    }
+                                    if ui.small_button("🗑").on_hover_text("Delete").clicked() {
+                                        deleted = Some(preset.name.clone());
+                                    }
+                                });
+                                ui.small(&preset.description);
+                            }
+                            if let Some(name) = deleted {
+                                self.delete_custom_preset(&name);
+                            }
+
+                            ui.horizontal(|ui| {
+                                ui.label("Name:");
+                                ui.text_edit_singleline(&mut self.custom_preset_name);
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label("Description:");
+                                ui.text_edit_singleline(&mut self.custom_preset_description);
+                            });
+                            if ui
+                                .button("⭐ Save as Preset")
+                                .on_hover_text("Keep the current state and parameters in this list")
+                                .clicked()
+                            {
+                                self.save_custom_preset();
+                            }
+
+                            ui.horizontal(|ui| {
+                                if ui.button("📂 Load File...").clicked() {
+                                    self.load_preset_file();
+                                }
+                                if ui.button("💾 Save Current...").clicked() {
+                                    self.save_preset_file();
+                                }
+                            });
                         });
 
                         ui.separator();
@@ -426,13 +2359,80 @@ impl eframe::App for ChaosPendulumApp {
                                     .text("Length 2 (m)"),
                             );
                             ui.add(
-                                egui::Slider::new(&mut self.temp_params.g, 1.0..=20.0)
+                                egui::Slider::new(&mut self.temp_params.g, 0.0..=20.0)
                                     .text("Gravity (m/s²)"),
                             );
+                            ui.checkbox(
+                                &mut self.temp_params.inverted_gravity,
+                                "Anti-Pendulum (inverted stability)",
+                            )
+                            .on_hover_text(
+                                "Gravity points up: straight up becomes the stable, \
+                                 lowest-energy position and hanging down becomes unstable",
+                            );
                             ui.add(
                                 egui::Slider::new(&mut self.temp_params.damping, 0.0..=1.0)
                                     .text("Damping"),
                             );
+                            ui.horizontal(|ui| {
+                                ui.label("Drag:");
+                                for model in DragModel::ALL {
+                                    ui.selectable_value(
+                                        &mut self.temp_params.drag_model,
+                                        model,
+                                        model.label(),
+                                    );
+                                }
+                            })
+                            .response
+                            .on_hover_text(
+                                "Linear: torque −b·ω. Quadratic: torque −c·ω|ω|, \
+                                 stronger at high speed and weaker at low speed",
+                            );
+                            ui.add(
+                                egui::Slider::new(
+                                    &mut self.temp_params.coulomb_friction,
+                                    0.0..=1.0,
+                                )
+                                .text("Coulomb Friction (N·m)"),
+                            )
+                            .on_hover_text(
+                                "Constant friction torque at each joint; \
+                                 the pendulum sticks once it can hold the remaining torque",
+                            );
+                            ui.add(
+                                egui::Slider::new(
+                                    &mut self.temp_params.drive_amplitude,
+                                    0.0..=20.0,
+                                )
+                                .text("Drive Amplitude (N·m)"),
+                            )
+                            .on_hover_text("Sinusoidal torque A·sin(ω_d·t) on the upper joint");
+                            ui.add(
+                                egui::Slider::new(
+                                    &mut self.temp_params.drive_frequency,
+                                    0.0..=10.0,
+                                )
+                                .text("Drive Frequency (rad/s)"),
+                            );
+                            ui.add(
+                                egui::Slider::new(
+                                    &mut self.temp_params.pivot_amplitude,
+                                    0.0..=0.3,
+                                )
+                                .text("Pivot Amplitude (m)"),
+                            )
+                            .on_hover_text(
+                                "Vertical pivot oscillation A·sin(Ω·t); \
+                                 fast shaking can hold the pendulum upside down",
+                            );
+                            ui.add(
+                                egui::Slider::new(
+                                    &mut self.temp_params.pivot_frequency,
+                                    0.0..=200.0,
+                                )
+                                .text("Pivot Frequency (rad/s)"),
+                            );
 
                             if ui.button("Apply Parameters").clicked() {
                                 self.apply_parameters();
@@ -441,28 +2441,336 @@ impl eframe::App for ChaosPendulumApp {
 
                         ui.separator();
 
+                        // 初始条件（以摆球坐标给出）
+                        ui.collapsing("Initial Conditions", |ui| {
+                            ui.small("Bob positions relative to the pivot (m), y up");
+                            egui::Grid::new("position_input").show(ui, |ui| {
+                                for (label, index) in [("Bob 1", 0), ("Bob 2", 2)] {
+                                    ui.label(label);
+                                    ui.add(
+                                        egui::DragValue::new(&mut self.position_input[index])
+                                            .speed(0.01)
+                                            .prefix("x: "),
+                                    );
+                                    ui.add(
+                                        egui::DragValue::new(&mut self.position_input[index + 1])
+                                            .speed(0.01)
+                                            .prefix("y: "),
+                                    );
+                                    ui.end_row();
+                                }
+                            });
+                            ui.horizontal(|ui| {
+                                if ui.button("Set Positions").clicked() {
+                                    self.apply_position_input();
+                                }
+                                if ui.button("Use Current").clicked() {
+                                    self.fill_position_input();
+                                }
+                            });
+
+                            ui.separator();
+                            ui.horizontal(|ui| {
+                                ui.label("Nudge joint:");
+                                ui.radio_value(&mut self.selected_joint, 1, "θ₁");
+                                ui.radio_value(&mut self.selected_joint, 2, "θ₂");
+                            });
+                            ui.add(
+                                egui::DragValue::new(&mut self.nudge_step)
+                                    .speed(0.0001)
+                                    .range(1e-6..=0.1)
+                                    .max_decimals(6)
+                                    .prefix("step: ")
+                                    .suffix(" rad"),
+                            );
+                            ui.small("Paused: Shift+↑/↓ selects joint, Shift+←/→ nudges");
+                        });
+
+                        ui.separator();
+
                         // 显示选项
+                        ui.collapsing("Export", |ui| {
+                            egui::ComboBox::from_label("Format")
+                                .selected_text(self.export_format.label())
+                                .show_ui(ui, |ui| {
+                                    for format in ExportFormat::ALL {
+                                        ui.selectable_value(
+                                            &mut self.export_format,
+                                            format,
+                                            format.label(),
+                                        );
+                                    }
+                                });
+                            if ui.button("💾 Export...").clicked() {
+                                self.export_data();
+                            }
+                            if ui
+                                .button("📄 Export Summary...")
+                                .on_hover_text("All run metrics as a single JSON file")
+                                .clicked()
+                            {
+                                self.export_summary();
+                            }
+
+                            ui.separator();
+                            ui.label("Batch Runs");
+                            for (preset, selected) in
+                                get_all_presets().iter().zip(self.batch_selection.iter_mut())
+                            {
+                                ui.checkbox(selected, &preset.name);
+                            }
+                            ui.add(
+                                egui::Slider::new(&mut self.batch_duration, 1.0..=120.0)
+                                    .text("Duration (s)"),
+                            );
+                            if self.batch_export.is_some() {
+                                let (done, total) = self.batch_progress;
+                                ui.add(
+                                    egui::ProgressBar::new(done as f32 / total.max(1) as f32)
+                                        .text(format!("Exporting {}/{}", done, total)),
+                                );
+                            } else if ui.button("💾 Export Selected Runs...").clicked() {
+                                self.start_batch_export();
+                            }
+
+                            ui.separator();
+                            ui.label("GIF Recording");
+                            let gif = &mut self.gif_settings;
+                            ui.add(
+                                egui::Slider::new(&mut gif.duration, 1.0..=60.0)
+                                    .text("Duration (s)"),
+                            );
+                            ui.add(egui::Slider::new(&mut gif.fps, 5..=30).text("Frame Rate"));
+                            ui.add(egui::Slider::new(&mut gif.size, 120..=640).text("Size (px)"));
+                            if gif.is_capped() {
+                                ui.small(format!(
+                                    "Capped at {} frames ({:.1} s)",
+                                    export::MAX_GIF_FRAMES,
+                                    export::MAX_GIF_FRAMES as f64 / gif.fps as f64
+                                ));
+                            }
+                            if self.gif_export.is_some() {
+                                let (done, total) = self.gif_progress;
+                                ui.add(
+                                    egui::ProgressBar::new(done as f32 / total.max(1) as f32)
+                                        .text(format!("Rendering frame {}/{}", done, total)),
+                                );
+                            } else if ui.button("🎞 Record GIF...").clicked() {
+                                self.start_gif_recording(ui.visuals().panel_fill);
+                            }
+
+                            ui.separator();
+                            ui.label("Session Recording");
+                            ui.horizontal(|ui| {
+                                let record_text = if self.recording_active {
+                                    "⏹ Stop Recording"
+                                } else {
+                                    "⏺ Record"
+                                };
+                                if ui.button(record_text).clicked() {
+                                    self.toggle_recording();
+                                }
+                                if ui.button("📂 Load...").clicked() {
+                                    self.load_recording_file();
+                                }
+                            });
+                            if let Some(recording) = &self.recording {
+                                ui.small(format!(
+                                    "{} frames, {:.2} s (every {} steps)",
+                                    recording.len(),
+                                    recording.duration(),
+                                    recording.stride()
+                                ));
+                            }
+                            let frame_count = self.recording.as_ref().map_or(0, Recording::len);
+                            if !self.recording_active && frame_count > 0 {
+                                ui.horizontal(|ui| {
+                                    if self.playback_index.is_some() {
+                                        if ui.button("Exit Playback").clicked() {
+                                            self.playback_index = None;
+                                        }
+                                    } else if ui.button("▶ Playback").clicked() {
+                                        self.start_playback();
+                                    }
+                                    if ui.button("💾 Save...").clicked() {
+                                        self.save_recording_file();
+                                    }
+                                });
+                            }
+                            if let Some(index) = self.playback_index.as_mut() {
+                                ui.add(
+                                    egui::Slider::new(index, 0..=frame_count.saturating_sub(1))
+                                        .text("Frame"),
+                                );
+                                let time = self
+                                    .recording
+                                    .as_ref()
+                                    .and_then(|recording| recording.frame(*index))
+                                    .map_or(0.0, |(time, _)| time);
+                                ui.small(format!("t = {:.3} s", time));
+                            }
+
+                            ui.separator();
+                            match self.event_logger.as_ref().map(|logger| logger.count()) {
+                                Some(count) => {
+                                    ui.small(format!("Event log active: {} events", count));
+                                    if ui.button("⏹ Stop Event Log").clicked() {
+                                        self.stop_event_log();
+                                    }
+                                }
+                                None => {
+                                    if ui.button("📝 Start Event Log...").clicked() {
+                                        if let Some(path) = rfd::FileDialog::new()
+                                            .add_filter("JSON Lines", &["jsonl"])
+                                            .set_file_name("events.jsonl")
+                                            .save_file()
+                                        {
+                                            if let Err(err) = self.start_event_log(&path) {
+                                                self.set_status(format!(
+                                                    "Failed to open event log: {}",
+                                                    err
+                                                ));
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        });
+
                         ui.collapsing("Display Options", |ui| {
                             let mut show_trajectory = self.ui_state.show_trajectory();
                             ui.checkbox(&mut show_trajectory, "Show Trajectory");
                             self.ui_state.set_show_trajectory(show_trajectory);
+                            if show_trajectory {
+                                ui.horizontal(|ui| {
+                                    ui.label("Trail:");
+                                    let current = self.ui_state.trail_selection();
+                                    for selection in TrailSelection::ALL {
+                                        if ui
+                                            .selectable_label(
+                                                current == Some(selection),
+                                                selection.label(),
+                                            )
+                                            .clicked()
+                                        {
+                                            self.ui_state.set_trail_selection(selection);
+                                        }
+                                    }
+                                });
+                            }
+
+                            let mut precision = self.ui_state.display_precision();
+                            ui.add(
+                                egui::Slider::new(&mut precision, 0..=readout::MAX_PRECISION)
+                                    .text("Display Precision"),
+                            )
+                            .on_hover_text("Decimal places for energies; other readouts follow");
+                            self.ui_state.set_display_precision(precision);
+
+                            let mut plot_limit = self.ui_state.plot_point_limit();
+                            ui.add(
+                                egui::Slider::new(&mut plot_limit, ui_state::PLOT_POINT_LIMIT_RANGE)
+                                    .logarithmic(true)
+                                    .text("Plot Point Limit"),
+                            )
+                            .on_hover_text("Plots with more points than this are decimated");
+                            self.ui_state.set_plot_point_limit(plot_limit);
+
+                            let mut freeze_trail = !self.record_trajectory;
+                            ui.checkbox(&mut freeze_trail, "Freeze Trail")
+                                .on_hover_text("Stop recording new trail points");
+                            self.record_trajectory = !freeze_trail;
+
+                            ui.add(
+                                egui::Slider::new(&mut self.readout_smoothing, 0.0..=0.95)
+                                    .text("Readout Smoothing"),
+                            )
+                            .on_hover_text(
+                                "Smooths the angle readouts above the pendulum only; \
+                                 the simulation and plots use raw values",
+                            );
 
                             ui.checkbox(&mut self.show_energy_plot, "Show Energy Plot");
                             ui.checkbox(&mut self.show_energy_error_plot, "Show Energy Error Plot");
                             ui.checkbox(&mut self.show_phase_space, "Show Phase Space");
+                            ui.checkbox(
+                                &mut self.show_configuration_space,
+                                "Show Configuration Space",
+                            );
+
+                            let mut show_legend = self.ui_state.show_legend();
+                            ui.checkbox(&mut show_legend, "Show Legend");
+                            self.ui_state.set_show_legend(show_legend);
+
+                            let mut show_pivot_force = self.ui_state.show_pivot_force();
+                            ui.checkbox(&mut show_pivot_force, "Show Pivot Force");
+                            self.ui_state.set_show_pivot_force(show_pivot_force);
+
+                            let mut show_state_border = self.ui_state.show_state_border();
+                            ui.checkbox(&mut show_state_border, "Show Run State Border");
+                            self.ui_state.set_show_state_border(show_state_border);
 
                             let mut show_grid = self.ui_state.show_grid_lines();
                             ui.checkbox(&mut show_grid, "Show Grid");
                             self.ui_state.set_show_grid_lines(show_grid);
 
-                            if ui.button("🎨 Toggle Theme").clicked() {
-                                self.theme_manager.toggle_theme();
+                            let mut show_strobe = self.ui_state.show_strobe();
+                            ui.checkbox(&mut show_strobe, "Show Strobe");
+                            self.ui_state.set_show_strobe(show_strobe);
+                            if show_strobe {
+                                let mut interval = self.statistics.strobe_interval();
+                                ui.add(
+                                    egui::Slider::new(&mut interval, 1..=100)
+                                        .text("Strobe Interval"),
+                                );
+                                self.statistics.set_strobe_interval(interval);
+
+                                let mut opacity = self.ui_state.strobe_opacity();
+                                ui.add(
+                                    egui::Slider::new(&mut opacity, 0.05..=1.0)
+                                        .text("Strobe Opacity"),
+                                );
+                                self.ui_state.set_strobe_opacity(opacity);
+                            }
+
+                            let mut theme = self.theme_manager.theme();
+                            egui::ComboBox::from_label("🎨 Theme")
+                                .selected_text(theme.label())
+                                .show_ui(ui, |ui| {
+                                    for option in ColorTheme::ALL {
+                                        ui.selectable_value(&mut theme, option, option.label());
+                                    }
+                                });
+                            if theme != self.theme_manager.theme() {
+                                self.theme_manager.start_theme_transition(theme);
                             }
 
+                            egui::ComboBox::from_label("Trajectory Sampling")
+                                .selected_text(self.trajectory_sampling.label())
+                                .show_ui(ui, |ui| {
+                                    for sampling in TrajectorySampling::ALL {
+                                        ui.selectable_value(
+                                            &mut self.trajectory_sampling,
+                                            sampling,
+                                            sampling.label(),
+                                        );
+                                    }
+                                });
                             ui.add(
                                 egui::Slider::new(&mut self.trajectory_record_interval, 1..=20)
-                                    .text("Trajectory Detail"),
+                                    .text("Sample Interval"),
+                            )
+                            .on_hover_text(
+                                "Steps between recorded energy and phase-space samples; \
+                                 also the trajectory spacing when sampling by step interval",
                             );
+                            if self.trajectory_sampling == TrajectorySampling::ArcLength {
+                                ui.add(
+                                    egui::Slider::new(&mut self.trajectory_arc_length, 0.005..=0.5)
+                                        .logarithmic(true)
+                                        .text("Point Spacing (m)"),
+                                );
+                            }
 
                             let mut alpha = self.ui_state.trajectory_alpha();
                             ui.add(
@@ -470,11 +2778,114 @@ impl eframe::App for ChaosPendulumApp {
                             );
                             self.ui_state.set_trajectory_alpha(alpha);
 
-                            if ui.button("Reset View").clicked() {
-                                self.ui_state.reset_view();
-                                self.renderer.reset_view();
+                            let mut trail_color_mode = self.ui_state.trail_color_mode();
+                            egui::ComboBox::from_label("Trail Color")
+                                .selected_text(trail_color_mode.label())
+                                .show_ui(ui, |ui| {
+                                    for mode in TrailColorMode::ALL {
+                                        ui.selectable_value(
+                                            &mut trail_color_mode,
+                                            mode,
+                                            mode.label(),
+                                        );
+                                    }
+                                })
+                                .response
+                                .on_hover_text("Speed: blue = slow, red = fast");
+                            self.ui_state.set_trail_color_mode(trail_color_mode);
+
+                            let mut camera_mode = self.renderer.camera_mode();
+                            egui::ComboBox::from_label("Camera")
+                                .selected_text(camera_mode.label())
+                                .show_ui(ui, |ui| {
+                                    for mode in CameraMode::ALL {
+                                        ui.selectable_value(&mut camera_mode, mode, mode.label());
+                                    }
+                                });
+                            self.renderer.set_camera_mode(camera_mode);
+                            if camera_mode != CameraMode::FixedPivot {
+                                let mut smoothing = self.renderer.camera_smoothing();
+                                ui.add(
+                                    egui::Slider::new(&mut smoothing, 0.0..=0.95)
+                                        .text("Camera Smoothing"),
+                                );
+                                self.renderer.set_camera_smoothing(smoothing);
+                            }
+
+                            let mut aspect_ratio = self.renderer.aspect_ratio();
+                            egui::ComboBox::from_label("Aspect Ratio")
+                                .selected_text(aspect_ratio.label())
+                                .show_ui(ui, |ui| {
+                                    for ratio in AspectRatio::ALL {
+                                        ui.selectable_value(
+                                            &mut aspect_ratio,
+                                            ratio,
+                                            ratio.label(),
+                                        );
+                                    }
+                                });
+                            self.renderer.set_aspect_ratio(aspect_ratio);
+
+                            let mut rod_width_mode = self.renderer.rod_width_mode();
+                            egui::ComboBox::from_label("Rod Width")
+                                .selected_text(rod_width_mode.label())
+                                .show_ui(ui, |ui| {
+                                    for mode in RodWidthMode::ALL {
+                                        ui.selectable_value(
+                                            &mut rod_width_mode,
+                                            mode,
+                                            mode.label(),
+                                        );
+                                    }
+                                });
+                            self.renderer.set_rod_width_mode(rod_width_mode);
+                            let mut rod_width = self.renderer.rod_width();
+                            ui.add(
+                                egui::Slider::new(&mut rod_width, 0.5..=12.0)
+                                    .text("Rod Width (px)"),
+                            )
+                            .on_hover_text("By load: width at the hanging system's total weight");
+                            self.renderer.set_rod_width(rod_width);
+
+                            let mut motion_blur = self.renderer.bob_motion_blur();
+                            ui.checkbox(&mut motion_blur, "Bob Motion Blur")
+                                .on_hover_text("Faded copies of each bob along its recent path");
+                            self.renderer.set_bob_motion_blur(motion_blur);
+                            if motion_blur {
+                                let mut intensity = self.renderer.motion_blur_intensity();
+                                ui.add(
+                                    egui::Slider::new(&mut intensity, 0.0..=1.0)
+                                        .text("Blur Intensity"),
+                                );
+                                self.renderer.set_motion_blur_intensity(intensity);
                             }
 
+                            let mut grab_tolerance = self.renderer.grab_tolerance_px();
+                            ui.add(
+                                egui::Slider::new(&mut grab_tolerance, 0.0..=50.0)
+                                    .text("Grab Tolerance (px)"),
+                            )
+                            .on_hover_text("Extra distance around a bob that still starts a drag");
+                            self.renderer.set_grab_tolerance_px(grab_tolerance);
+
+                            let mut pan_inertia = self.renderer.pan_inertia();
+                            ui.checkbox(&mut pan_inertia, "Pan Inertia");
+                            self.renderer.set_pan_inertia(pan_inertia);
+
+                            ui.horizontal(|ui| {
+                                if ui.button("Reset View").clicked() {
+                                    self.reset_view();
+                                }
+                                if ui
+                                    .button("Fit")
+                                    .on_hover_text("Zoom so the full reach l₁ + l₂ fits the canvas")
+                                    .clicked()
+                                {
+                                    let rect = self.renderer.canvas_rect();
+                                    self.renderer.fit_reach(rect, &self.pendulum.params);
+                                }
+                            });
+
                             let mut scale = self.renderer.scale();
                             ui.add(
                                 egui::Slider::new(&mut scale, 20.0..=500.0)
@@ -496,65 +2907,477 @@ impl eframe::App for ChaosPendulumApp {
 
                         // 实时信息显示
                         ui.separator();
-                        ui.small(format!("Time: {:.2}s", self.pendulum.time));
+                        let precision = self.ui_state.display_precision();
+                        let fmt =
+                            |value: Real, kind| fmt_value(real_to_f64(value), kind, precision);
+                        ui.small(format!("Time: {}", fmt(self.pendulum.time, ValueKind::Time)));
+                        let physics_time = real_to_f64(self.pendulum.time - self.run_start_time);
+                        let wall_time = self.wall_time.as_secs_f64();
                         ui.small(format!(
-                            "Total Energy: {:.3}J",
-                            self.pendulum.total_energy()
+                            "Physics: {} / Wall: {}",
+                            fmt_value(physics_time, ValueKind::Time, precision),
+                            fmt_value(wall_time, ValueKind::Time, precision)
                         ));
-                        ui.small(format!("Kinetic: {:.3}J", self.pendulum.kinetic_energy()));
+                        match realtime_ratio(physics_time, wall_time) {
+                            Some(ratio) => ui.small(format!(
+                                "{}× real time",
+                                fmt_value(ratio, ValueKind::Number, precision)
+                            )),
+                            None => ui.small("—× real time"),
+                        };
+                        match self.first_flip_time {
+                            Some(time) => ui.small(format!(
+                                "First flip: {}",
+                                fmt_value(time, ValueKind::Time, precision)
+                            )),
+                            None => ui.small("No flip yet"),
+                        }
+                        .on_hover_text(
+                            "Time since the run started until the lower arm \
+                             first passed over the top",
+                        );
+                        let kinetic = self.pendulum.kinetic_energy();
+                        let potential = self.pendulum.potential_energy();
+                        let energy = ValueKind::Energy;
                         ui.small(format!(
-                            "Potential: {:.3}J",
-                            self.pendulum.potential_energy()
+                            "Total Energy: {}",
+                            fmt(self.pendulum.total_energy(), energy)
+                        ))
+                        .on_hover_text(explain::total_energy(
+                            real_to_f64(kinetic),
+                            real_to_f64(potential),
                         ));
+                        let oscillating_pivot = self.pendulum.params.pivot_amplitude > 0.0;
+                        ui.add_enabled(
+                            oscillating_pivot,
+                            egui::Checkbox::new(
+                                &mut self.show_pivot_frame_energy,
+                                "Pivot-frame energy",
+                            ),
+                        )
+                        .on_hover_text(explain::PIVOT_FRAME_ENERGY)
+                        .on_disabled_hover_text("Needs an oscillating pivot");
+                        if oscillating_pivot && self.show_pivot_frame_energy {
+                            ui.small(format!(
+                                "Pivot-frame Energy: {}",
+                                fmt(self.pendulum.pivot_frame_energy(), energy)
+                            ))
+                            .on_hover_text(explain::PIVOT_FRAME_ENERGY);
+                        }
+                        ui.small(format!("Kinetic: {}", fmt(kinetic, energy)))
+                            .on_hover_text(explain::KINETIC_ENERGY);
+                        ui.small(format!("Potential: {}", fmt(potential, energy)))
+                            .on_hover_text(explain::POTENTIAL_ENERGY);
+                        ui.small(format!(
+                            "Lagrangian L = T - V: {}",
+                            fmt(self.pendulum.lagrangian(), energy)
+                        ))
+                        .on_hover_text(explain::LAGRANGIAN);
+                        ui.small(format!(
+                            "Hamiltonian H = T + V: {}",
+                            fmt(self.pendulum.hamiltonian(), energy)
+                        ))
+                        .on_hover_text(explain::HAMILTONIAN);
+                        ui.small(format!(
+                            "Power dissipated: {}",
+                            fmt(self.pendulum.dissipated_power(), ValueKind::Power)
+                        ))
+                        .on_hover_text(explain::DISSIPATED_POWER);
+                        ui.collapsing("Normal Modes (small angle)", |ui| {
+                            let params = &self.pendulum.params;
+                            if params.g == 0.0 {
+                                ui.small("No gravity: there is no restoring force and no modes");
+                                return;
+                            }
+                            let (slow, fast) = params.normal_mode_frequencies();
+                            let (slow_shape, fast_shape) = params.normal_mode_shapes();
+                            for (name, omega, shape) in
+                                [("Slow", slow, slow_shape), ("Fast", fast, fast_shape)]
+                            {
+                                let period = 2.0 * std::f64::consts::PI / omega;
+                                let sign = if shape >= 0.0 { "+" } else { "" };
+                                ui.small(format!(
+                                    "{}: ω = {}, T = {}, θ₂/θ₁ = {}{}",
+                                    name,
+                                    fmt_value(omega, ValueKind::AngularVelocity, precision),
+                                    fmt_value(period, ValueKind::Time, precision),
+                                    sign,
+                                    fmt_value(shape, ValueKind::Number, precision)
+                                ));
+                            }
+
+                            ui.horizontal(|ui| {
+                                let modes = [(0, "Excite Slow Mode"), (1, "Excite Fast Mode")];
+                                for (mode, label) in modes {
+                                    if ui
+                                        .button(label)
+                                        .on_hover_text("Start at rest along this mode's shape")
+                                        .clicked()
+                                    {
+                                        self.excite_normal_mode(mode);
+                                    }
+                                }
+                            });
+
+                            let mut overlay = self.analytic_reference.is_some();
+                            ui.checkbox(&mut overlay, "Overlay Linear Solution")
+                                .on_hover_text("Normal-mode superposition from the current state");
+                            if overlay != self.analytic_reference.is_some() {
+                                self.analytic_reference = overlay.then(|| {
+                                    let state = &self.pendulum.state;
+                                    let solution =
+                                        NormalModeSolution::new(state, &self.pendulum.params);
+                                    (solution, self.pendulum.time)
+                                });
+                            }
+                            if let Some((solution, _)) = &self.analytic_reference {
+                                if solution.max_amplitude() > SMALL_ANGLE_LIMIT {
+                                    ui.colored_label(
+                                        egui::Color32::YELLOW,
+                                        "⚠ Amplitude too large; linear theory will diverge",
+                                    );
+                                }
+                            }
+                        });
+                        if self.pendulum.params.g == 0.0 {
+                            ui.collapsing("Zero-Gravity Invariants", |ui| {
+                                self.show_zero_gravity_invariants(ui, precision);
+                            });
+                        }
+                        ui.collapsing("Reference Comparison", |ui| {
+                            ui.horizontal(|ui| {
+                                ui.add(
+                                    egui::TextEdit::singleline(&mut self.reference_name_input)
+                                        .desired_width(100.0),
+                                );
+                                if ui
+                                    .button("Save Reference")
+                                    .on_hover_text("Store the current state, parameters and dt")
+                                    .clicked()
+                                {
+                                    self.save_reference_run();
+                                }
+                            });
+
+                            let selected = self
+                                .comparison
+                                .as_ref()
+                                .map(|comparison| comparison.reference().name.clone());
+                            let mut choice = selected.clone();
+                            egui::ComboBox::from_label("Compare With")
+                                .selected_text(choice.as_deref().unwrap_or("None"))
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(&mut choice, None, "None");
+                                    for reference in &self.reference_runs {
+                                        let name = Some(reference.name.clone());
+                                        ui.selectable_value(&mut choice, name, &reference.name);
+                                    }
+                                });
+                            if choice != selected {
+                                let reference = choice.and_then(|name| {
+                                    self.reference_runs.iter().find(|r| r.name == name).cloned()
+                                });
+                                self.comparison = None;
+                                if let Some(reference) = reference {
+                                    // 参考运行从初始条件起步，当前运行也从头开始计时
+                                    self.restart_run_clock();
+                                    self.start_comparison(reference);
+                                }
+                            }
+
+                            if let Some(comparison) = &self.comparison {
+                                let metrics = comparison.metrics();
+                                let (time, energy) = (ValueKind::Time, ValueKind::Energy);
+                                ui.small(format!(
+                                    "ΔE (current − reference): {}",
+                                    fmt_value(metrics.energy_difference, energy, precision)
+                                ));
+                                ui.small(format!(
+                                    "Phase distance: {}",
+                                    fmt_value(metrics.distance, ValueKind::Number, precision)
+                                ));
+                                ui.small(match metrics.divergence_time {
+                                    Some(t) => {
+                                        format!("Diverged at: {}", fmt_value(t, time, precision))
+                                    }
+                                    None => "Diverged at: —".to_string(),
+                                })
+                                .on_hover_text(format!(
+                                    "First time the phase-space distance exceeded {}",
+                                    comparison::DIVERGENCE_DISTANCE
+                                ));
+                                ui.small(match metrics.flip_time_difference {
+                                    Some(dt) => {
+                                        format!("First flip Δt: {}", fmt_value(dt, time, precision))
+                                    }
+                                    None => "First flip Δt: — (waiting for both)".to_string(),
+                                });
+                            }
+                        });
+                        ui.collapsing("Ensemble", |ui| {
+                            let mut enabled = self.ensemble.is_some();
+                            let mut changed = ui
+                                .checkbox(&mut enabled, "Ensemble mode")
+                                .on_hover_text(explain::ENSEMBLE)
+                                .changed();
+                            changed |= ui
+                                .add(
+                                    egui::Slider::new(&mut self.ensemble_size, 2..=50)
+                                        .text("Pendulums"),
+                                )
+                                .changed();
+                            changed |= ui
+                                .add(
+                                    egui::Slider::new(&mut self.ensemble_perturbation, 1e-9..=1e-2)
+                                        .logarithmic(true)
+                                        .text("Perturbation (rad)"),
+                                )
+                                .changed();
+                            if changed {
+                                self.ensemble = enabled.then(|| {
+                                    Ensemble::seeded(
+                                        &self.pendulum,
+                                        self.ensemble_size,
+                                        self.ensemble_perturbation,
+                                    )
+                                });
+                            }
+                            if let Some(ensemble) = &self.ensemble {
+                                let spread = ensemble.spread(&self.pendulum);
+                                ui.small(format!(
+                                    "Spread (max |Δθ₂|): {}",
+                                    fmt_value(spread, ValueKind::Angle, precision)
+                                ));
+                            }
+                        });
 
                         // 能量守恒监控
                         ui.separator();
-                        let energy_color = if self.energy_error < 1e-8 {
-                            egui::Color32::GREEN
-                        } else if self.energy_error < 1e-6 {
-                            egui::Color32::YELLOW
-                        } else {
-                            egui::Color32::RED
+                        let energy_color = self
+                            .energy_error_level
+                            .next(real_to_f64(self.energy_error))
+                            .color();
+                        ui.horizontal(|ui| {
+                            ui.colored_label(
+                                energy_color,
+                                format!(
+                                    "Energy Error: {}",
+                                    fmt(self.energy_error, ValueKind::Error)
+                                ),
+                            )
+                            .on_hover_text(explain::ENERGY_ERROR);
+
+                            // 最近能量误差的迷你折线图
+                            let history = self.statistics.get_energy_error_history();
+                            let recent = &history[history.len().saturating_sub(SPARKLINE_LENGTH)..];
+                            let (rect, _) = ui.allocate_exact_size(
+                                egui::vec2(80.0, ui.text_style_height(&egui::TextStyle::Body)),
+                                egui::Sense::hover(),
+                            );
+                            if recent.len() >= 2 {
+                                ui.painter().add(egui::Shape::line(
+                                    sparkline_points(recent, rect),
+                                    egui::Stroke::new(1.0, energy_color),
+                                ));
+                            }
+
+                            // 自适应积分最近一步采用的子步长
+                            let adaptive_step = self
+                                .last_adaptive_step
+                                .filter(|_| self.physics_engine.adaptive());
+                            if let Some(step) = adaptive_step {
+                                let substeps = self.physics_engine.dt() / step.accepted_dt;
+                                ui.small(format!("adaptive dt {:.1e} s", step.accepted_dt))
+                                    .on_hover_text(format!(
+                                        "Sub-step chosen by the adaptive integrator; each \
+                                         frame step still covers the full dt\n\
+                                         {:.0} substeps, {} iterations, error estimate \
+                                         {:.2e} (tolerance {:.0e})",
+                                        substeps,
+                                        step.iterations,
+                                        step.error_estimate,
+                                        self.physics_engine.adaptive_tolerance()
+                                    ));
+                            }
+                        });
+                        match self.max_energy_error.peak() {
+                            Some((error, step, time)) => ui
+                                .label(format!(
+                                    "Max energy error: {} at t={}",
+                                    fmt_value(error, ValueKind::Error, precision),
+                                    fmt_value(time, ValueKind::Time, precision)
+                                ))
+                                .on_hover_text(format!(
+                                    "{}\nStep {} since reset",
+                                    explain::MAX_ENERGY_ERROR,
+                                    step
+                                )),
+                            None => ui
+                                .label("Max energy error: n/a")
+                                .on_hover_text(explain::MAX_ENERGY_ERROR),
                         };
-                        ui.colored_label(
-                            energy_color,
-                            format!("Energy Error: {:.2e}", self.energy_error),
-                        );
+                        match self
+                            .statistics
+                            .energy_drift_rate(ENERGY_DRIFT_WINDOW, self.energy_sample_dt())
+                        {
+                            Some(rate) => ui.label(format!(
+                                "dE/dt: {}",
+                                fmt_value(rate, ValueKind::Power, precision)
+                            )),
+                            None => ui.label("dE/dt: n/a"),
+                        }
+                        .on_hover_text(explain::ENERGY_DRIFT_RATE);
+                        if let Some(sigma) =
+                            self.statistics.energy_conservation_window(CONSERVATION_WINDOW)
+                        {
+                            // 按相对总能量的比例着色，阈值与瞬时误差一致
+                            let energy = real_to_f64(self.pendulum.total_energy()).abs();
+                            let relative = sigma / energy.max(1e-12);
+                            let badge_color = energy_error_color(relative);
+                            ui.colored_label(
+                                badge_color,
+                                format!(
+                                    "Recent σ(E), last {}: {} J",
+                                    CONSERVATION_WINDOW,
+                                    fmt_value(sigma, ValueKind::Error, precision)
+                                ),
+                            )
+                            .on_hover_text(explain::ENERGY_SPREAD);
+                        }
+
+                        ui.separator();
+                        ui.collapsing("State Table", |ui| {
+                            self.draw_state_table(ui);
+                        });
                     }); // 结束 ScrollArea
             });
 
         // 创建右侧统计面板
-        if self.show_energy_plot || self.show_energy_error_plot || self.show_phase_space {
+        if self.show_energy_plot
+            || self.show_energy_error_plot
+            || self.show_phase_space
+            || self.show_configuration_space
+            || !self.dt_sweep_results.is_empty()
+        {
             egui::SidePanel::right("statistics")
                 .default_width(400.0)
                 .min_width(300.0)
                 .show(ctx, |ui| {
                     ui.heading("📊 Analysis");
+                    if ui
+                        .button("💾 Export CSV...")
+                        .on_hover_text(
+                            "Positions, angles, angular velocities and energies \
+                             of every recorded sample",
+                        )
+                        .clicked()
+                    {
+                        self.export_history_csv();
+                    }
+
+                    ui.collapsing("Chaos Explorer", |ui| {
+                        self.draw_chaos_explorer(ui);
+                        ui.horizontal(|ui| {
+                            if ui
+                                .button("Benettin λ")
+                                .on_hover_text(explain::LYAPUNOV_BENETTIN)
+                                .clicked()
+                            {
+                                self.compute_benettin_exponent();
+                            }
+                            let precision = self.ui_state.display_precision();
+                            match self.benettin_exponent {
+                                Some((start, exponent)) => ui.monospace(format!(
+                                    "{} / s ({:.0} s from t={})",
+                                    fmt_value(exponent, ValueKind::Number, precision),
+                                    BENETTIN_DURATION,
+                                    fmt_value(start, ValueKind::Time, precision)
+                                )),
+                                None => ui.small("not computed"),
+                            };
+                        });
+                        ui.horizontal(|ui| {
+                            if ui.button("⏭ Most Chaotic Moment").clicked() {
+                                let rates =
+                                    phase_space_rates(self.statistics.get_phase_space_history());
+                                if let Some(index) = extremum_index(rates) {
+                                    self.jump_to_recorded_state(index);
+                                }
+                            }
+                            if ui.button("⏭ Fastest Bob").clicked() {
+                                let (l1, l2) = (self.pendulum.params.l1, self.pendulum.params.l2);
+                                let history = self.statistics.get_phase_space_history();
+                                let speeds = (0..history.len()).filter_map(|index| {
+                                    let (_, state) = self.statistics.recorded_state(index)?;
+                                    Some(real_to_f64(state.mass2_speed(l1, l2)))
+                                });
+                                if let Some(index) = extremum_index(speeds) {
+                                    self.jump_to_recorded_state(index);
+                                }
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                egui::DragValue::new(&mut self.jump_time_input)
+                                    .speed(0.01)
+                                    .prefix("t = ")
+                                    .suffix(" s"),
+                            );
+                            if ui
+                                .button("⏭ Jump to Time")
+                                .on_hover_text("Interpolated from the recorded samples")
+                                .clicked()
+                            {
+                                self.jump_to_time(self.jump_time_input);
+                            }
+                        });
+                    });
 
                     if self.show_energy_plot && self.statistics.has_data() {
                         ui.collapsing("Energy Plot", |ui| {
                             use egui_plot::{Line, Plot, PlotPoints};
 
+                            ui.checkbox(&mut self.show_lagrangian_plot, "Show Lagrangian");
+                            ui.checkbox(&mut self.show_power_plot, "Show Dissipated Power");
+                            let momentum_toggle = &mut self.show_angular_momentum_plot;
+                            ui.checkbox(momentum_toggle, "Show Angular Momentum")
+                                .on_hover_text(
+                                    "Total angular momentum about the pivot; \
+                                     gravity makes it vary, but it is conserved when g = 0",
+                                );
+
+                            let plot_limit = self.ui_state.plot_point_limit();
                             let energy_history = self.statistics.get_energy_history();
                             if !energy_history.is_empty() {
-                                let total_energy: PlotPoints = energy_history
-                                    .iter()
-                                    .enumerate()
+                                // 数据点过多时自动抽稀，避免每帧构建大量绘图点
+                                let decimation =
+                                    PlotDecimation::for_len(energy_history.len(), plot_limit);
+                                let len = energy_history.len();
+                                let sampled = || {
+                                    energy_history
+                                        .iter()
+                                        .enumerate()
+                                        .filter(move |(i, _)| decimation.keep(*i, len))
+                                };
+                                let total_energy: PlotPoints = sampled()
                                     .map(|(i, (total, _, _))| [i as f64, *total])
                                     .collect();
 
-                                let kinetic_energy: PlotPoints = energy_history
-                                    .iter()
-                                    .enumerate()
+                                let kinetic_energy: PlotPoints = sampled()
                                     .map(|(i, (_, kinetic, _))| [i as f64, *kinetic])
                                     .collect();
 
-                                let potential_energy: PlotPoints = energy_history
-                                    .iter()
-                                    .enumerate()
+                                let potential_energy: PlotPoints = sampled()
                                     .map(|(i, (_, _, potential))| [i as f64, *potential])
                                     .collect();
 
+                                let lagrangian: PlotPoints = sampled()
+                                    .map(|(i, (_, kinetic, potential))| {
+                                        [i as f64, kinetic - potential]
+                                    })
+                                    .collect();
+                                let show_lagrangian = self.show_lagrangian_plot;
+
                                 Plot::new("energy_plot").height(250.0).show(ui, |plot_ui| {
                                     plot_ui.line(
                                         Line::new(total_energy)
@@ -567,11 +3390,70 @@ impl eframe::App for ChaosPendulumApp {
                                             .color(egui::Color32::RED),
                                     );
                                     plot_ui.line(
-                                        Line::new(potential_energy)
-                                            .name("Potential")
-                                            .color(egui::Color32::BLUE),
+                                        Line::new(potential_energy)
+                                            .name("Potential")
+                                            .color(egui::Color32::BLUE),
+                                    );
+                                    if show_lagrangian {
+                                        plot_ui.line(
+                                            Line::new(lagrangian)
+                                                .name("Lagrangian")
+                                                .color(egui::Color32::GREEN),
+                                        );
+                                    }
+                                });
+                                decimation.show_note(ui);
+                            }
+
+                            // 耗散功率由相空间历史中的角速度计算，单位为 W
+                            if self.show_power_plot {
+                                let params = self.pendulum.params;
+                                let history = self.statistics.get_phase_space_history();
+                                let decimation = PlotDecimation::for_len(history.len(), plot_limit);
+                                let power: PlotPoints = history
+                                    .iter()
+                                    .enumerate()
+                                    .filter(|(i, _)| decimation.keep(*i, history.len()))
+                                    .map(|(i, &(_, omega1, _, omega2))| {
+                                        let state = PendulumState::new(
+                                            0.0,
+                                            0.0,
+                                            omega1 as Real,
+                                            omega2 as Real,
+                                        );
+                                        [i as f64, real_to_f64(state.dissipated_power(&params))]
+                                    })
+                                    .collect();
+                                Plot::new("power_plot").height(120.0).show(ui, |plot_ui| {
+                                    plot_ui.line(
+                                        Line::new(power)
+                                            .name("Dissipated Power (W)")
+                                            .color(egui::Color32::GOLD),
                                     );
                                 });
+                                decimation.show_note(ui);
+                            }
+
+                            if self.show_angular_momentum_plot {
+                                let history = self.statistics.get_angular_momentum_history();
+                                let decimation = PlotDecimation::for_len(history.len(), plot_limit);
+                                let momentum: PlotPoints = history
+                                    .iter()
+                                    .enumerate()
+                                    .filter(|(i, _)| decimation.keep(*i, history.len()))
+                                    .map(|(i, &momentum)| [i as f64, momentum])
+                                    .collect();
+                                Plot::new("angular_momentum_plot")
+                                    .height(120.0)
+                                    .y_axis_label("L (kg·m²/s)")
+                                    .show(ui, |plot_ui| {
+                                        plot_ui.line(
+                                            Line::new(momentum)
+                                                .name("Angular Momentum")
+                                                .color(egui::Color32::from_rgb(120, 200, 255)),
+                                        );
+                                    });
+                                decimation.show_note(ui);
                             }
                         });
                     }
@@ -583,30 +3465,31 @@ impl eframe::App for ChaosPendulumApp {
                             use egui_plot::{Line, Plot, PlotPoints};
 
                             let error_history = self.statistics.get_energy_error_history();
+                            let error_level = self.energy_error_level;
                             let theme_manager = &self.theme_manager;
+                            let decimation = PlotDecimation::for_len(
+                                error_history.len(),
+                                self.ui_state.plot_point_limit(),
+                            );
                             if !error_history.is_empty() {
                                 Plot::new("energy_error_plot")
                                     .height(200.0)
                                     .y_axis_label("Log10(Energy Error)")
                                     .show(ui, |plot_ui| {
                                         // 直接显示已经是对数的误差值
+                                        let len = error_history.len();
                                         let log_error_points: PlotPoints = error_history
                                             .iter()
                                             .enumerate()
+                                            .filter(|(i, _)| decimation.keep(*i, len))
                                             .map(|(i, log_error)| [i as f64, *log_error])
                                             .collect();
 
-                                        // 根据当前误差数量级动态选择颜色
-                                        let line_color = if let Some(current_log_error) = error_history.last() {
-                                            if *current_log_error < -8.0 {  // < 1e-8
-                                                egui::Color32::GREEN
-                                            } else if *current_log_error < -6.0 {  // < 1e-6
-                                                egui::Color32::YELLOW
-                                            } else {
-                                                egui::Color32::RED
-                                            }
-                                        } else {
+                                        // 与误差读数使用同一个带滞后的颜色等级
+                                        let line_color = if error_history.is_empty() {
                                             egui::Color32::YELLOW
+                                        } else {
+                                            error_level.color()
                                         };
 
                                         plot_ui.line(
@@ -618,7 +3501,12 @@ impl eframe::App for ChaosPendulumApp {
                                         // 计算并显示平均值线
                                         if error_history.len() > 1 {
                                             let avg_log_error = error_history.iter().sum::<f64>() / error_history.len() as f64;
-                                            let avg_line: PlotPoints = (0..error_history.len())
+                                            let indices = || {
+                                                (0..error_history.len()).filter(|&i| {
+                                                    decimation.keep(i, error_history.len())
+                                                })
+                                            };
+                                            let avg_line: PlotPoints = indices()
                                                 .map(|i| [i as f64, avg_log_error])
                                                 .collect();
                                             
@@ -642,10 +3530,10 @@ impl eframe::App for ChaosPendulumApp {
                                             let upper_bound = avg_log_error + std_dev;
                                             let lower_bound = avg_log_error - std_dev;
 
-                                            let upper_line: PlotPoints = (0..error_history.len())
+                                            let upper_line: PlotPoints = indices()
                                                 .map(|i| [i as f64, upper_bound])
                                                 .collect();
-                                            let lower_line: PlotPoints = (0..error_history.len())
+                                            let lower_line: PlotPoints = indices()
                                                 .map(|i| [i as f64, lower_bound])
                                                 .collect();
 
@@ -710,35 +3598,253 @@ impl eframe::App for ChaosPendulumApp {
 
                     if self.show_phase_space && self.statistics.has_data() {
                         ui.collapsing("Phase Space", |ui| {
-                            use egui_plot::{Line, Plot, PlotPoints};
+                            use egui_plot::{Line, Plot, PlotPoints, Points};
+
+                            ui.checkbox(&mut self.fade_phase_space, "Fade Older Points");
 
                             let phase_history = self.statistics.get_phase_space_history();
                             if !phase_history.is_empty() {
-                                let phase_points1: PlotPoints = phase_history
+                                let decimation = PlotDecimation::for_len(
+                                    phase_history.len(),
+                                    self.ui_state.plot_point_limit(),
+                                );
+                                let phase_history = decimation.apply(phase_history);
+                                let phase_points1: Vec<[f64; 2]> = phase_history
                                     .iter()
                                     .map(|(theta1, omega1, _, _)| [*theta1, *omega1])
                                     .collect();
 
-                                let phase_points2: PlotPoints = phase_history
+                                let phase_points2: Vec<[f64; 2]> = phase_history
                                     .iter()
                                     .map(|(_, _, theta2, omega2)| [*theta2, *omega2])
                                     .collect();
 
+                                // 淡出时把轨迹分成若干段，越旧的段越透明
+                                let chunk_count = if self.fade_phase_space { 24 } else { 1 };
+                                let chunks = fade_chunks(phase_points1.len(), chunk_count);
+                                let series = [
+                                    (&phase_points1, "Pendulum 1", egui::Color32::RED),
+                                    (&phase_points2, "Pendulum 2", egui::Color32::BLUE),
+                                ];
+
                                 Plot::new("phase_space").height(250.0).show(ui, |plot_ui| {
-                                    plot_ui.line(
-                                        Line::new(phase_points1)
-                                            .name("Pendulum 1")
-                                            .color(egui::Color32::RED),
-                                    );
-                                    plot_ui.line(
-                                        Line::new(phase_points2)
-                                            .name("Pendulum 2")
-                                            .color(egui::Color32::BLUE),
-                                    );
+                                    for (points, name, color) in series {
+                                        for &(start, end, alpha) in &chunks {
+                                            plot_ui.line(
+                                                Line::new(PlotPoints::from(
+                                                    points[start..=end].to_vec(),
+                                                ))
+                                                .name(name)
+                                                .color(color.gamma_multiply(alpha)),
+                                            );
+                                        }
+
+                                        // 用亮点标记当前状态
+                                        if let Some(&latest) = points.last() {
+                                            plot_ui.points(
+                                                Points::new(PlotPoints::from(vec![latest]))
+                                                    .radius(4.0)
+                                                    .color(color)
+                                                    .name(name),
+                                            );
+                                        }
+                                    }
                                 });
+                                decimation.show_note(ui);
                             }
+
+                            ui.collapsing("Poincaré Section (θ₁ = 0, ω₁ > 0)", |ui| {
+                                let mut max_crossings = self.statistics.max_poincare_crossings();
+                                ui.add(
+                                    egui::Slider::new(&mut max_crossings, 100..=20_000)
+                                        .logarithmic(true)
+                                        .text("Max Crossings"),
+                                );
+                                self.statistics.set_max_poincare_crossings(max_crossings);
+                                ui.checkbox(&mut self.poincare_recency_color, "Color by Recency");
+
+                                let crossings: Vec<[f64; 2]> = self
+                                    .statistics
+                                    .get_poincare_crossings()
+                                    .iter()
+                                    .map(|&(theta2, omega2)| [theta2, omega2])
+                                    .collect();
+                                ui.small(format!("{} crossings", crossings.len()));
+
+                                // 按新旧分组，越旧的点越透明
+                                let group_count = if self.poincare_recency_color { 16 } else { 1 };
+                                let mut groups = fade_chunks(crossings.len(), group_count);
+                                if groups.is_empty() && !crossings.is_empty() {
+                                    groups.push((0, crossings.len() - 1, 1.0));
+                                }
+                                let color = egui::Color32::from_rgb(255, 140, 0);
+                                Plot::new("poincare_section")
+                                    .height(250.0)
+                                    .x_axis_label("θ₂ (rad)")
+                                    .y_axis_label("ω₂ (rad/s)")
+                                    .show(ui, |plot_ui| {
+                                        for (start, end, alpha) in groups {
+                                            plot_ui.points(
+                                                Points::new(PlotPoints::from(
+                                                    crossings[start..=end].to_vec(),
+                                                ))
+                                                .radius(1.5)
+                                                .color(color.gamma_multiply(alpha.max(0.1))),
+                                            );
+                                        }
+                                    });
+                            });
+
+                            ui.collapsing("Return Map (θ₂ maxima)", |ui| {
+                                let pairs: Vec<[f64; 2]> = self
+                                    .statistics
+                                    .theta2_return_map()
+                                    .into_iter()
+                                    .map(|(current, next)| [current, next])
+                                    .collect();
+                                ui.small(format!("{} pairs", pairs.len()))
+                                    .on_hover_text(explain::RETURN_MAP);
+
+                                // 对角线 θ₂[n+1] = θ₂[n] 上的点对应周期轨道
+                                let (lo, hi) = pairs
+                                    .iter()
+                                    .flatten()
+                                    .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| {
+                                        (lo.min(v), hi.max(v))
+                                    });
+                                let color = egui::Color32::from_rgb(200, 80, 200);
+                                Plot::new("theta2_return_map")
+                                    .height(250.0)
+                                    .data_aspect(1.0)
+                                    .x_axis_label("θ₂[n] (rad)")
+                                    .y_axis_label("θ₂[n+1] (rad)")
+                                    .show(ui, |plot_ui| {
+                                        if lo < hi {
+                                            let diagonal = vec![[lo, lo], [hi, hi]];
+                                            plot_ui.line(
+                                                Line::new(PlotPoints::from(diagonal))
+                                                    .color(egui::Color32::GRAY)
+                                                    .style(egui_plot::LineStyle::dashed_loose()),
+                                            );
+                                        }
+                                        plot_ui.points(
+                                            Points::new(PlotPoints::from(pairs))
+                                                .radius(2.0)
+                                                .color(color),
+                                        );
+                                    });
+                            });
+
+                            ui.collapsing("Spectrum (θ₂)", |ui| {
+                                let precision = self.ui_state.display_precision();
+                                let spectrum = self.statistics.power_spectrum();
+                                let peak = extremum_index(spectrum.iter().skip(1).map(|p| p.1))
+                                    .map(|index| spectrum[index + 1].0);
+                                match peak {
+                                    Some(frequency) => ui.small(format!(
+                                        "Dominant frequency: {} (period {})",
+                                        fmt_value(frequency, ValueKind::Frequency, precision),
+                                        fmt_value(1.0 / frequency, ValueKind::Time, precision)
+                                    )),
+                                    None => ui.small("Not enough samples"),
+                                }
+                                .on_hover_text(explain::SPECTRUM);
+
+                                // 对数幅值坐标，跳过直流分量和零幅值
+                                let points: Vec<[f64; 2]> = spectrum
+                                    .iter()
+                                    .skip(1)
+                                    .filter(|p| p.1 > 0.0)
+                                    .map(|&(frequency, magnitude)| [frequency, magnitude.log10()])
+                                    .collect();
+                                Plot::new("theta2_spectrum")
+                                    .height(200.0)
+                                    .x_axis_label("Frequency (Hz)")
+                                    .y_axis_label("log₁₀ |Θ₂(f)|")
+                                    .show(ui, |plot_ui| {
+                                        plot_ui.line(
+                                            Line::new(PlotPoints::from(points))
+                                                .color(egui::Color32::from_rgb(0, 170, 200)),
+                                        );
+                                    });
+                            });
+                        });
+                    }
+
+                    if self.show_configuration_space && self.statistics.has_data() {
+                        ui.collapsing("Configuration Space", |ui| {
+                            use egui_plot::{Line, Plot, PlotPoints, Points};
+
+                            ui.checkbox(&mut self.unwrap_configuration_space, "Unwrap Angles")
+                                .on_hover_text("Follow full rotations instead of folding at ±π");
+                            let path = statistics::configuration_path(
+                                self.statistics.get_phase_space_history(),
+                                self.unwrap_configuration_space,
+                            );
+                            let limit = self.ui_state.plot_point_limit();
+                            let decimation = PlotDecimation::for_len(path.len(), limit);
+                            let path = decimation.apply(&path);
+                            let color = egui::Color32::from_rgb(0, 160, 120);
+                            Plot::new("configuration_space")
+                                .height(250.0)
+                                .data_aspect(1.0)
+                                .x_axis_label("θ₁ (rad)")
+                                .y_axis_label("θ₂ (rad)")
+                                .show(ui, |plot_ui| {
+                                    if let Some(&latest) = path.last() {
+                                        plot_ui.points(
+                                            Points::new(PlotPoints::from(vec![latest]))
+                                                .radius(4.0)
+                                                .color(color),
+                                        );
+                                    }
+                                    plot_ui.line(Line::new(PlotPoints::from(path)).color(color));
+                                });
+                            decimation.show_note(ui);
                         });
                     }
+
+                    if !self.dt_sweep_results.is_empty() {
+                        egui::CollapsingHeader::new("dt Sweep")
+                            .default_open(true)
+                            .show(ui, |ui| {
+                                use egui_plot::{Line, Plot, PlotPoints, Points};
+
+                                // 双对数坐标：log10(dt) - log10(能量漂移)
+                                let log_points: Vec<[f64; 2]> = self
+                                    .dt_sweep_results
+                                    .iter()
+                                    .map(|&(dt, drift)| {
+                                        [
+                                            real_to_f64(dt).log10(),
+                                            real_to_f64(drift).max(1e-20).log10(),
+                                        ]
+                                    })
+                                    .collect();
+
+                                Plot::new("dt_sweep_plot")
+                                    .height(200.0)
+                                    .x_axis_label("Log10(dt)")
+                                    .y_axis_label("Log10(Energy Drift)")
+                                    .show(ui, |plot_ui| {
+                                        plot_ui.line(
+                                            Line::new(PlotPoints::from(log_points.clone()))
+                                                .name("Drift")
+                                                .color(egui::Color32::LIGHT_BLUE),
+                                        );
+                                        plot_ui.points(
+                                            Points::new(PlotPoints::from(log_points))
+                                                .radius(3.0)
+                                                .color(egui::Color32::LIGHT_BLUE),
+                                        );
+                                    });
+
+                                ui.small("Drift after 2s simulated time; RK4 slope ≈ 4");
+                                if ui.button("Clear").clicked() {
+                                    self.dt_sweep_results.clear();
+                                }
+                            });
+                    }
                 });
         }
 
@@ -748,57 +3854,99 @@ impl eframe::App for ChaosPendulumApp {
 
             // 显示当前状态信息
             ui.horizontal(|ui| {
-                ui.label(format!(
-                    "θ₁: {:.1}°",
-                    self.pendulum.state.theta1.to_degrees()
-                ));
+                let [theta1, theta2, omega1, omega2] = self.smoothed_readout;
+                let precision = self.ui_state.display_precision();
+                ui.label(format!("θ₁: {}", fmt_value(theta1, ValueKind::Angle, precision)));
                 ui.separator();
-                ui.label(format!(
-                    "θ₂: {:.1}°",
-                    self.pendulum.state.theta2.to_degrees()
-                ));
+                ui.label(format!("θ₂: {}", fmt_value(theta2, ValueKind::Angle, precision)));
                 ui.separator();
-                ui.label(format!("ω₁: {:.2}", self.pendulum.state.omega1));
+                let velocity = ValueKind::AngularVelocity;
+                ui.label(format!("ω₁: {}", fmt_value(omega1, velocity, precision)));
                 ui.separator();
-                ui.label(format!("ω₂: {:.2}", self.pendulum.state.omega2));
+                ui.label(format!("ω₂: {}", fmt_value(omega2, velocity, precision)));
             });
 
             ui.separator();
 
+            // 线性化解析解的参考摆形（g = 0 等退化情况下为 NaN，不显示）
+            let reference = self.analytic_reference.map(|(solution, start)| {
+                solution.state_at(real_to_f64(self.pendulum.time - start))
+            });
+            self.renderer.set_reference_overlay(
+                reference.filter(|state| state.theta1.is_finite() && state.theta2.is_finite()),
+            );
+            self.renderer.set_comparison_overlay(self.comparison.as_ref().map(|comparison| {
+                (comparison.reference_state(), comparison.reference().params)
+            }));
+
             // 渲染摆系统，如果在暂停状态下拖动了摆球，则更新状态
-            if let Some(new_state) = self.renderer.render(
-                ui,
-                &self.pendulum,
-                &self.statistics,
-                &self.theme_manager,
-                &self.ui_state,
-                !self.is_running, // 传递暂停状态
-            ) {
+            // 积分器混合模式下显示欧拉法与 RK4 状态之间的插值
+            let mut display_pendulum = self.pendulum.clone();
+            if self.integrator_blend {
+                display_pendulum.state = self
+                    .euler_shadow
+                    .lerp(&self.pendulum.state, self.integrator_blend_factor as Real);
+            }
+            // 回放模式下显示录制的帧，实时模拟保持不变
+            if let Some(recording) = &self.recording {
+                if let Some((time, state)) = self.playback_index.and_then(|i| recording.frame(i)) {
+                    display_pendulum.state = state;
+                    display_pendulum.params = recording.params;
+                    display_pendulum.time = time as Real;
+                }
+            }
+
+            // 回放时不应用拖动，录制的帧不可编辑
+            if let Some(new_state) = self
+                .renderer
+                .render(
+                    ui,
+                    &display_pendulum,
+                    self.ensemble.as_ref(),
+                    &self.statistics,
+                    &self.theme_manager,
+                    &self.ui_state,
+                    !self.is_running, // 传递暂停状态
+                )
+                .filter(|_| self.playback_index.is_none())
+            {
                 // 更新摆的状态
                 self.pendulum.state = new_state;
+                self.euler_shadow = new_state;
+                self.restart_analytic_reference();
+                self.restart_ensemble();
 
                 // 重新记录统计数据
-                let energy = self.pendulum.total_energy();
-                self.statistics.add_energy_data(
-                    energy,
-                    self.pendulum.kinetic_energy(),
-                    self.pendulum.potential_energy(),
-                );
-
-                let (pos1, pos2) = self.pendulum.get_positions();
-                self.statistics
-                    .add_trajectory_point(pos1.0, pos1.1, pos2.0, pos2.1);
-                self.statistics.add_phase_space_point(
-                    self.pendulum.state.theta1,
-                    self.pendulum.state.omega1,
-                    self.pendulum.state.theta2,
-                    self.pendulum.state.omega2,
-                );
+                self.record_statistics();
 
                 self.set_status("Pendulum position updated".to_string());
             }
+
+            if let Some(action) = self.renderer.take_canvas_action() {
+                self.handle_canvas_action(ctx, action);
+            }
         });
 
+        // 收到请求的截图后保存画布区域
+        if self.screenshot_requested.is_some() {
+            let screenshot = ctx.input(|i| {
+                i.raw.events.iter().find_map(|event| match event {
+                    egui::Event::Screenshot { image, .. } => Some(image.clone()),
+                    _ => None,
+                })
+            });
+            if let Some(image) = screenshot {
+                self.screenshot_requested = None;
+                self.save_canvas_image(&image, ctx.pixels_per_point());
+            } else {
+                self.expire_screenshot_request(std::time::Instant::now());
+                // 暂停时也继续重绘，才能收到截图或判定超时
+                if self.screenshot_requested.is_some() {
+                    ctx.request_repaint();
+                }
+            }
+        }
+
         // 如果模拟正在运行，请求持续重绘
         if self.is_running {
             ctx.request_repaint_after(self.update_interval);
@@ -813,11 +3961,31 @@ impl eframe::App for ChaosPendulumApp {
 
 /// 程序主入口函数
 fn main() -> Result<(), eframe::Error> {
+    // 可选：把物理事件记录到文件
+    let event_log_path = std::env::args()
+        .skip_while(|arg| arg != "--event-log")
+        .nth(1)
+        .map(std::path::PathBuf::from);
+
+    // 可选：启动后执行场景脚本
+    let scenario = std::env::args()
+        .skip_while(|arg| arg != "--scenario")
+        .nth(1)
+        .and_then(|path| match Scenario::load(std::path::Path::new(&path)) {
+            Ok(scenario) => Some(scenario),
+            Err(err) => {
+                eprintln!("Failed to load scenario {}: {}", path, err);
+                None
+            }
+        });
+
     // 配置应用程序窗口选项
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
-            .with_inner_size([1200.0, 800.0])
+            .with_inner_size(window::DEFAULT_WINDOW_SIZE)
             .with_title("Chaos Double Pendulum Simulation"),
+        // 窗口位置和大小由应用自行保存，以便检查位置是否仍在屏幕上
+        persist_window: false,
         ..Default::default()
     };
 
@@ -825,6 +3993,699 @@ fn main() -> Result<(), eframe::Error> {
     eframe::run_native(
         "Chaos Double Pendulum",
         options,
-        Box::new(|_cc| Ok(Box::new(ChaosPendulumApp::default()))),
+        Box::new(move |cc| {
+            let mut app = ChaosPendulumApp {
+                pending_window_restore: cc
+                    .storage
+                    .and_then(|storage| eframe::get_value(storage, window::WINDOW_GEOMETRY_KEY)),
+                ..Default::default()
+            };
+            if let Some(tolerance) = cc
+                .storage
+                .and_then(|storage| eframe::get_value(storage, visualization::GRAB_TOLERANCE_KEY))
+            {
+                app.renderer.set_grab_tolerance_px(tolerance);
+            }
+            if let Some(storage) = cc.storage {
+                if let Some(ui_state) = eframe::get_value(storage, ui_state::UI_STATE_KEY) {
+                    app.ui_state = ui_state;
+                    app.ui_state.sanitize();
+                }
+                if let Some(theme) = eframe::get_value(storage, theme::THEME_KEY) {
+                    app.theme_manager = ThemeManager::new(theme);
+                }
+                if let Some(scale) = eframe::get_value(storage, visualization::RENDER_SCALE_KEY) {
+                    app.renderer.set_scale(scale);
+                }
+            }
+            if let Some(text) = cc
+                .storage
+                .and_then(|storage| storage.get_string(presets::CUSTOM_PRESETS_KEY))
+            {
+                match CustomPresets::from_json(&text) {
+                    Ok(custom) => app.custom_presets = custom,
+                    Err(err) => eprintln!("Failed to load custom presets: {}", err),
+                }
+            }
+            if let Some(path) = event_log_path {
+                if let Err(err) = app.start_event_log(&path) {
+                    eprintln!("Failed to open event log {}: {}", path.display(), err);
+                }
+            }
+            if let Some(scenario) = scenario {
+                app.start_scenario(scenario);
+            }
+            Ok(Box::new(app))
+        }),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试中模拟的一帧墙钟时间（60 FPS）
+    const FRAME_TIME: f64 = 1.0 / 60.0;
+
+    #[test]
+    fn test_lock_parameters_keeps_params_on_preset_load() {
+        let preset = presets::get_preset_by_name("Low Gravity").unwrap();
+        let tuned = PendulumParams::new(1.7, 0.4, 1.2, 0.8, 9.81, 0.05);
+        let mut app = ChaosPendulumApp {
+            lock_parameters: true,
+            ..Default::default()
+        };
+        app.pendulum.params = tuned;
+        app.temp_params = tuned;
+
+        app.load_preset(&preset);
+        assert_eq!(app.pendulum.params, tuned);
+        assert_eq!(app.temp_params, tuned);
+        assert_eq!(app.pendulum.state, preset.initial_state);
+
+        app.lock_parameters = false;
+        app.load_preset(&preset);
+        assert_eq!(app.pendulum.params, preset.params);
+    }
+
+    #[test]
+    fn test_load_preset_json_rejects_invalid_preset() {
+        let mut app = ChaosPendulumApp::default();
+        let state = app.pendulum.state;
+        let params = app.pendulum.params;
+        let time_step = app.time_step;
+
+        let zero_length = r#"{
+            "name": "Broken", "description": "",
+            "initial_state": { "theta1": 1.0, "theta2": 0.5, "omega1": 0.0, "omega2": 0.0 },
+            "params": { "m1": 1.0, "m2": 1.0, "l1": 0.0, "l2": 1.0, "g": 9.81, "damping": 0.0 }
+        }"#;
+        let huge_dt = r#"{
+            "name": "Coarse", "description": "",
+            "initial_state": { "theta1": 1.0, "theta2": 0.5, "omega1": 0.0, "omega2": 0.0 },
+            "params": { "m1": 1.0, "m2": 1.0, "l1": 1.0, "l2": 1.0, "g": 9.81, "damping": 0.0 },
+            "recommended_dt": 1.0
+        }"#;
+        for text in [zero_length, huge_dt] {
+            app.load_preset_json(text);
+            assert_eq!(app.pendulum.state, state);
+            assert_eq!(app.pendulum.params, params);
+            assert_eq!(app.time_step, time_step);
+            let status = app.ui_state.status_message().cloned().unwrap_or_default();
+            assert!(status.contains("invalid preset"), "{}", status);
+        }
+    }
+
+    #[test]
+    fn test_screenshot_request_expires() {
+        let mut app = ChaosPendulumApp::default();
+        let requested = std::time::Instant::now();
+        app.screenshot_requested = Some(requested);
+
+        // 超时前继续等待
+        app.expire_screenshot_request(requested + SCREENSHOT_TIMEOUT / 2);
+        assert_eq!(app.screenshot_requested, Some(requested));
+
+        app.expire_screenshot_request(requested + SCREENSHOT_TIMEOUT);
+        assert_eq!(app.screenshot_requested, None);
+        let status = app.ui_state.status_message().cloned().unwrap_or_default();
+        assert!(status.contains("no screenshot"), "{}", status);
+    }
+
+    #[test]
+    fn test_load_preset_resets_time_step() {
+        let mut app = ChaosPendulumApp::default();
+        let fine = presets::get_preset_by_name("Horizontal Release").unwrap();
+        app.load_preset(&fine);
+        assert_eq!(Some(app.time_step), fine.recommended_dt);
+        assert_eq!(app.physics_engine.dt(), app.time_step);
+
+        // 没有推荐步长的预设恢复推荐步长之前的步长
+        let plain = presets::get_preset_by_name("Low Gravity").unwrap();
+        assert_eq!(plain.recommended_dt, None);
+        app.load_preset(&plain);
+        assert_eq!(app.time_step, DEFAULT_TIME_STEP);
+        assert_eq!(app.physics_engine.dt(), DEFAULT_TIME_STEP);
+
+        // 用户选择的步长在加载预设后保留
+        QualityPreset::Accurate.apply(&mut app);
+        let accurate = QualityPreset::Accurate.time_step();
+        app.load_preset(&plain);
+        assert_eq!(app.time_step, accurate);
+        app.load_preset(&fine);
+        app.load_preset(&fine);
+        assert_eq!(Some(app.time_step), fine.recommended_dt);
+        app.load_preset(&plain);
+        assert_eq!(app.time_step, accurate);
+        assert_eq!(app.physics_engine.dt(), accurate);
+
+        app.time_step_input = 0.0025;
+        app.apply_time_step_input();
+        app.load_preset(&fine);
+        app.load_preset(&plain);
+        assert_eq!(app.time_step, 0.0025);
+    }
+
+    #[test]
+    fn test_nudge_selected_joint_only_while_paused() {
+        let mut app = ChaosPendulumApp {
+            selected_joint: 2,
+            nudge_step: 0.001,
+            ..Default::default()
+        };
+        let before = app.pendulum.state;
+
+        app.nudge_selected_joint(1.0);
+        assert!((app.pendulum.state.theta2 - before.theta2 - 0.001).abs() < 1e-6);
+        assert_eq!(app.pendulum.state.theta1, before.theta1);
+
+        app.is_running = true;
+        let running = app.pendulum.state;
+        app.nudge_selected_joint(-1.0);
+        assert_eq!(app.pendulum.state, running);
+    }
+
+    #[test]
+    fn test_apply_position_input() {
+        let mut app = ChaosPendulumApp::default();
+        let before = app.pendulum.state;
+
+        // 与摆长不符的坐标不改变状态
+        app.position_input = [0.0, -1.0, 0.0, -3.0];
+        app.apply_position_input();
+        assert_eq!(app.pendulum.state, before);
+
+        // 上摆水平向右，下摆竖直向下
+        app.position_input = [1.0, 0.0, 1.0, -1.0];
+        app.apply_position_input();
+        assert!((app.pendulum.state.theta1 - consts::PI / 2.0).abs() < 1e-5);
+        assert!(app.pendulum.state.theta2.abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_stop_after_pauses_within_one_step() {
+        let mut app = ChaosPendulumApp::default();
+        app.run_for(1.0);
+        assert_eq!(app.stop_after, Some(1.0));
+
+        let mut frames = 0;
+        while app.is_running && frames < 10_000 {
+            app.update_physics(FRAME_TIME);
+            frames += 1;
+        }
+
+        assert!(!app.is_running);
+        assert!(app.stop_after.is_none());
+        assert!(
+            (app.pendulum.time - 1.0).abs() <= app.time_step,
+            "paused at t = {}",
+            app.pendulum.time
+        );
+    }
+
+    #[test]
+    fn test_apply_time_step_input() {
+        let mut app = ChaosPendulumApp {
+            time_step_input: 0.0025,
+            ..Default::default()
+        };
+        app.apply_time_step_input();
+        assert_eq!(app.time_step, 0.0025);
+        assert_eq!(app.physics_engine.dt(), 0.0025);
+
+        // 无效输入被拒绝并恢复为当前值
+        app.time_step_input = -1.0;
+        app.apply_time_step_input();
+        assert_eq!(app.time_step, 0.0025);
+        assert_eq!(app.time_step_input, 0.0025);
+    }
+
+    #[test]
+    fn test_arc_length_sampling_spacing() {
+        let mut app = ChaosPendulumApp {
+            trajectory_sampling: TrajectorySampling::ArcLength,
+            trajectory_arc_length: 0.1,
+            ..Default::default()
+        };
+        app.run_for(2.0);
+        while app.is_running {
+            app.update_physics(FRAME_TIME);
+        }
+
+        // 相邻记录点之间的距离不小于阈值
+        let trajectory = app.statistics.get_trajectory_history();
+        assert!(trajectory.len() > 2);
+        for pair in trajectory.windows(2) {
+            let (_, _, x0, y0) = pair[0];
+            let (_, _, x1, y1) = pair[1];
+            assert!(((x1 - x0).powi(2) + (y1 - y0).powi(2)).sqrt() >= 0.1 - 1e-6);
+        }
+
+        // 能量样本不受弧长门限影响，时间间隔均匀
+        let times = app.statistics.get_energy_time_history();
+        assert!(times.len() > trajectory.len());
+        for pair in times.windows(2) {
+            assert!((pair[1] - pair[0] - app.energy_sample_dt()).abs() < 1e-6);
+        }
+
+        // 静止的摆不产生轨迹点，但仍记录能量
+        let mut resting = ChaosPendulumApp {
+            trajectory_sampling: TrajectorySampling::ArcLength,
+            ..Default::default()
+        };
+        resting.pendulum.state = PendulumState::at_rest(0.0, 0.0);
+        resting.reseed_statistics();
+        resting.step_paused(50);
+        assert_eq!(resting.statistics.get_trajectory_history().len(), 1);
+        assert_eq!(resting.statistics.get_energy_time_history().len(), 11);
+    }
+
+    #[test]
+    fn test_first_flip_time_recorded_and_reset() {
+        let mut app = ChaosPendulumApp::default();
+        app.load_preset(&get_preset_by_name("High Energy").unwrap());
+        assert_eq!(app.first_flip_time, None);
+
+        let mut steps = 0;
+        while app.first_flip_time.is_none() && steps < 20_000 {
+            app.step_once();
+            steps += 1;
+        }
+        let flip = app.first_flip_time.expect("high energy run should flip");
+        assert!((flip - real_to_f64(app.pendulum.time)).abs() < 1e-9);
+        // 翻转时下摆位于顶部附近
+        assert!(app.pendulum.state.theta2.cos() < -0.9);
+
+        // 之后的翻转不改变首次翻转时间
+        for _ in 0..1000 {
+            app.step_once();
+        }
+        assert_eq!(app.first_flip_time, Some(flip));
+
+        app.reset_simulation();
+        assert_eq!(app.first_flip_time, None);
+    }
+
+    #[test]
+    fn test_step_paused_advances_exactly_while_paused() {
+        let mut app = ChaosPendulumApp::default();
+        let start = app.pendulum.state;
+
+        // 运行中不做任何事
+        app.is_running = true;
+        app.step_paused(1);
+        assert_eq!(app.pendulum.time, 0.0);
+
+        app.is_running = false;
+        app.step_paused(1);
+        assert_eq!(app.pendulum.time, app.time_step);
+        assert_ne!(app.pendulum.state, start);
+        assert!(app.energy_error > 0.0);
+
+        app.step_paused(10);
+        assert!((app.pendulum.time - 11.0 * app.time_step).abs() < app.time_step * 1e-3);
+        assert!(!app.is_running);
+    }
+
+    #[test]
+    fn test_recording_playback_freezes_physics() {
+        let mut app = ChaosPendulumApp::default();
+        app.toggle_recording();
+        app.step_paused(5);
+        app.toggle_recording();
+        assert_eq!(app.recording.as_ref().map(Recording::len), Some(6));
+
+        // 停止录制后的步不再记录
+        app.step_paused(2);
+        assert_eq!(app.recording.as_ref().map(Recording::len), Some(6));
+
+        // 录制中参数改变后停止录制，已录的帧仍按原参数回放
+        app.toggle_recording();
+        app.step_paused(2);
+        app.pendulum.params.l1 = 2.0;
+        app.step_paused(2);
+        assert!(!app.recording_active);
+        assert_eq!(app.recording.as_ref().map(Recording::len), Some(3));
+        assert_eq!(app.recording.as_ref().unwrap().params.l1, 1.0);
+
+        app.start_playback();
+        assert_eq!(app.playback_index, Some(0));
+        let time = app.pendulum.time;
+        app.step_paused(1);
+        app.is_running = true;
+        app.update_physics(FRAME_TIME);
+        assert_eq!(app.pendulum.time, time);
+
+        // 开始运行时退出回放
+        app.is_running = false;
+        app.toggle_running();
+        assert_eq!(app.playback_index, None);
+        app.update_physics(FRAME_TIME);
+        assert!(app.pendulum.time > time);
+    }
+
+    #[test]
+    fn test_jump_to_state_reseeds_ensemble() {
+        let mut app = ChaosPendulumApp::default();
+        app.ensemble = Some(Ensemble::seeded(&app.pendulum, 4, 1e-4));
+        app.step_paused(50);
+
+        let state = PendulumState::new(0.3, -0.2, 0.0, 0.0);
+        app.jump_to_state(1.5, state);
+        let ensemble = app.ensemble.as_ref().expect("ensemble stays enabled");
+        assert_eq!(ensemble.members().len(), app.ensemble_size - 1);
+        for member in ensemble.members() {
+            assert!((member.state.theta1 - state.theta1).abs() <= app.ensemble_perturbation);
+            assert!((member.state.theta2 - state.theta2).abs() <= app.ensemble_perturbation);
+            assert_eq!(member.time, app.pendulum.time);
+        }
+        assert!(ensemble.trails().iter().all(|trail| trail.is_empty()));
+    }
+
+    #[test]
+    fn test_save_and_delete_custom_preset() {
+        let mut app = ChaosPendulumApp::default();
+        app.pendulum.state = PendulumState::new(1.0, -0.5, 0.0, 0.0);
+
+        app.custom_preset_name = "Classic Chaos".to_string();
+        app.save_custom_preset();
+        assert!(app.custom_presets.presets().is_empty());
+
+        app.custom_preset_name = " Mine ".to_string();
+        app.save_custom_preset();
+        let saved = &app.custom_presets.presets()[0];
+        assert_eq!(saved.name, "Mine");
+        assert_eq!(saved.initial_state, app.pendulum.state);
+        assert_eq!(saved.recommended_dt, Some(app.time_step));
+        assert!(app.custom_preset_name.is_empty());
+
+        app.delete_custom_preset("Mine");
+        assert!(app.custom_presets.presets().is_empty());
+    }
+
+    #[test]
+    fn test_wall_clock_counts_only_running_time() {
+        let mut app = ChaosPendulumApp::default();
+        let start = app.last_frame;
+        let second = std::time::Duration::from_secs(1);
+
+        app.is_running = true;
+        app.tick_wall_clock(start + second);
+        assert_eq!(app.wall_time, second);
+
+        // 暂停期间的时间不计入
+        app.is_running = false;
+        app.tick_wall_clock(start + second * 3);
+        assert_eq!(app.wall_time, second);
+
+        app.is_running = true;
+        app.tick_wall_clock(start + second * 4);
+        assert_eq!(app.wall_time, second * 2);
+
+        app.restart_run_clock();
+        assert_eq!(app.wall_time, std::time::Duration::ZERO);
+    }
+
+    #[test]
+    fn test_run_start_energy_survives_history_trim() {
+        let mut app = ChaosPendulumApp {
+            statistics: PhysicsStatistics::new(20),
+            ..Default::default()
+        };
+        app.restart_run_clock();
+        let start = app.pendulum.total_energy();
+        assert_eq!(app.run_start_energy, start);
+
+        // 历史裁剪后最早的能量样本不再是运行起点，起始能量保持不变
+        app.step_paused(500);
+        assert!(app.statistics.get_energy_time_history()[0] > real_to_f64(app.run_start_time));
+        assert_eq!(app.run_start_energy, start);
+    }
+
+    #[test]
+    fn test_quality_presets() {
+        let mut app = ChaosPendulumApp::default();
+
+        app.physics_engine.set_integrator(IntegratorKind::Euler);
+        QualityPreset::Fast.apply(&mut app);
+        assert_eq!(app.physics_engine.integrator(), IntegratorKind::Rk4);
+        assert_eq!(app.time_step, 0.004);
+        assert_eq!(app.physics_engine.dt(), 0.004);
+        assert!(!app.physics_engine.error_correction());
+
+        QualityPreset::Accurate.apply(&mut app);
+        assert_eq!(app.physics_engine.integrator(), IntegratorKind::Adaptive);
+        assert_eq!(app.time_step, 0.0002);
+        assert_eq!(app.physics_engine.dt(), 0.0002);
+        assert!(app.physics_engine.error_correction());
+
+        // Balanced 恢复默认值
+        QualityPreset::Balanced.apply(&mut app);
+        let defaults = ChaosPendulumApp::default();
+        assert_eq!(app.time_step, defaults.time_step);
+        assert_eq!(app.physics_engine.dt(), defaults.time_step);
+        assert_eq!(app.physics_engine.integrator(), defaults.physics_engine.integrator());
+        assert!(app.physics_engine.error_correction());
+    }
+
+    #[test]
+    fn test_state_table_format_width_is_stable() {
+        let widths: Vec<usize> = [0.0, 1.5, -1.5, 123.456, -0.00001, -999.99999]
+            .iter()
+            .map(|&v| format_table_value(v).chars().count())
+            .collect();
+        assert!(widths.iter().all(|&w| w == widths[0]));
+        assert!(format_table_value(0.5).starts_with(' '));
+        assert!(format_table_value(0.5).contains('+'));
+
+        let errors: Vec<String> = [3.2e-5, 1.0e-10, 7.5e-1]
+            .iter()
+            .map(|&v| format_table_error(v))
+            .collect();
+        assert_eq!(errors[0], "3.20e-05");
+        assert_eq!(errors[1], "1.00e-10");
+        assert!(errors.iter().all(|e| e.len() == errors[0].len()));
+        assert_eq!(format_table_error(0.0), "0.00e+00");
+        assert_eq!(format_table_error(9.999e-7), "1.00e-06");
+    }
+
+    #[test]
+    fn test_realtime_ratio() {
+        assert_eq!(realtime_ratio(0.8, 1.0), Some(0.8));
+        assert_eq!(realtime_ratio(3.0, 1.5), Some(2.0));
+        assert_eq!(realtime_ratio(1.0, 0.0), None);
+    }
+
+    #[test]
+    fn test_accumulate_steps_carries_remainder() {
+        // 0.5× 速度下每帧 0.0025s 模拟时间：2 步，余 0.0005s
+        let (steps, remainder) = accumulate_steps(0.0, 0.005, 0.5, 0.001);
+        assert_eq!(steps, 2);
+        assert!((remainder - 0.0005).abs() < 1e-12);
+
+        // 低速时不足一步的时间会累积到后续帧
+        let mut accumulator = 0.0;
+        let mut total = 0;
+        for _ in 0..100 {
+            let (steps, remainder) = accumulate_steps(accumulator, 0.001, 0.1, 0.001);
+            accumulator = remainder;
+            total += steps;
+        }
+        assert!((9..=10).contains(&total), "took {} steps", total);
+
+        // 长时间卡顿后的补算有上限
+        assert_eq!(accumulate_steps(0.0, 10.0, 5.0, 1e-5).0, MAX_STEPS_PER_FRAME);
+    }
+
+    #[test]
+    fn test_simulation_speed_scales_simulated_time() {
+        let advance = |speed: f32| {
+            let mut app = ChaosPendulumApp {
+                simulation_speed: speed,
+                is_running: true,
+                ..Default::default()
+            };
+            for _ in 0..60 {
+                app.update_physics(FRAME_TIME);
+            }
+            real_to_f64(app.pendulum.time)
+        };
+
+        let normal = advance(1.0);
+        let double = advance(2.0);
+        assert!((normal - 1.0).abs() < 0.01, "1× advanced {}s", normal);
+        assert!((double / normal - 2.0).abs() < 0.02, "2× advanced {}s", double);
+    }
+
+    #[test]
+    fn test_format_health() {
+        let mut app = ChaosPendulumApp::default();
+        let (text, color) = format_health(&app);
+        assert_eq!(text, "⏸ Paused | dt 0.001 s | RK4+corr | err 0.0e0 | no auto-pause");
+        assert_eq!(color, egui::Color32::GREEN);
+
+        app.is_running = true;
+        app.steps_per_second = 1000.4;
+        app.stop_after = Some(12.5);
+        app.energy_error = 2e-5;
+        app.physics_engine.set_error_correction(false);
+        let (text, color) = format_health(&app);
+        assert_eq!(
+            text,
+            "▶ Running 1000 steps/s | dt 0.001 s | RK4 | err 2.0e-5 | auto-pause @ 12.50s"
+        );
+        assert_eq!(color, egui::Color32::RED);
+    }
+
+    #[test]
+    fn test_error_level_hysteresis() {
+        // 不带滞后时每次越过阈值都会切换
+        assert_eq!(ErrorLevel::of(0.9e-6), ErrorLevel::Warning);
+        assert_eq!(ErrorLevel::of(1.1e-6), ErrorLevel::Bad);
+        assert_eq!(ErrorLevel::of(f64::NAN), ErrorLevel::Bad);
+
+        // 在 1e-6 附近来回波动时保持黄色
+        let mut level = ErrorLevel::Warning;
+        for error in [0.9e-6, 1.1e-6, 0.8e-6, 1.5e-6, 0.95e-6, 1.9e-6] {
+            level = level.next(error);
+            assert_eq!(level, ErrorLevel::Warning, "error {}", error);
+        }
+        // 明显越过阈值后才变红，并在回落到阈值附近时保持红色
+        level = level.next(2.5e-6);
+        assert_eq!(level, ErrorLevel::Bad);
+        for error in [0.9e-6, 0.6e-6, 1.2e-6] {
+            level = level.next(error);
+            assert_eq!(level, ErrorLevel::Bad, "error {}", error);
+        }
+        // 明显低于阈值后才恢复
+        level = level.next(0.4e-6);
+        assert_eq!(level, ErrorLevel::Warning);
+
+        // 一次大幅变化可以跨越多个等级
+        assert_eq!(ErrorLevel::Good.next(1e-3), ErrorLevel::Bad);
+        assert_eq!(ErrorLevel::Bad.next(1e-12), ErrorLevel::Good);
+        // 从好变差时只升到越过滞后阈值的等级
+        assert_eq!(ErrorLevel::Good.next(1.5e-6), ErrorLevel::Warning);
+    }
+
+    #[test]
+    fn test_chaos_verdict() {
+        assert!(chaos_verdict(-25.0, -10.0, None, None).contains("regular"));
+        assert!(chaos_verdict(5.0, -10.0, Some(0.05), None).contains("chaotic"));
+        assert!(chaos_verdict(5.0, -10.0, Some(0.0), None).contains("no divergence"));
+        assert!(chaos_verdict(5.0, -10.0, Some(0.05), Some(120)).contains("periodic"));
+    }
+
+    #[test]
+    fn test_scenario_pauses_at_trigger_time() {
+        let scenario = Scenario::from_json(
+            r#"{ "steps": [
+                { "time": 0.05, "action": "set_damping", "value": 0.2 },
+                { "time": 0.1, "action": "pause" }
+            ] }"#,
+        )
+        .unwrap();
+        let mut app = ChaosPendulumApp::default();
+        app.start_scenario(scenario);
+        for _ in 0..30 {
+            app.update_physics(FRAME_TIME);
+        }
+
+        assert!(!app.is_running);
+        assert!(app.scenario.is_none());
+        assert!((app.pendulum.params.damping - 0.2).abs() < 1e-6);
+        assert!((real_to_f64(app.pendulum.time) - 0.1).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_scenario_rejects_invalid_damping() {
+        let scenario = Scenario::from_json(
+            r#"{ "steps": [
+                { "time": 0.0, "action": "set_damping", "value": -1.0 },
+                { "time": 1.0, "action": "pause" }
+            ] }"#,
+        )
+        .unwrap();
+        let mut app = ChaosPendulumApp::default();
+        let damping = app.pendulum.params.damping;
+        app.start_scenario(scenario);
+
+        assert_eq!(app.pendulum.params.damping, damping);
+        assert_eq!(app.temp_params.damping, damping);
+        let status = app.ui_state.status_message().cloned().unwrap_or_default();
+        assert!(status.contains("invalid damping"), "{}", status);
+    }
+
+    #[test]
+    fn test_smooth_readout() {
+        // 系数为 0 时直接取真实值；60 FPS 的一帧保留 factor 的差距
+        let frame = 1.0 / 60.0;
+        assert_eq!(smooth_readout(1.0, 3.0, 0.0, frame), 3.0);
+        assert!((smooth_readout(1.0, 3.0, 0.5, frame) - 2.0).abs() < 1e-6);
+        assert!((smooth_readout(1.0, 3.0, 0.9, frame) - 1.2).abs() < 1e-6);
+
+        // 144 FPS 下经过同样的墙钟时间，显示值与 60 FPS 时一致
+        let mut fast = 1.0;
+        for _ in 0..12 {
+            fast = smooth_readout(fast, 3.0, 0.9, 1.0 / 144.0);
+        }
+        let mut slow = 1.0;
+        for _ in 0..5 {
+            slow = smooth_readout(slow, 3.0, 0.9, frame);
+        }
+        assert!((fast - slow).abs() < 1e-4, "{} vs {}", fast, slow);
+
+        // 反复更新会收敛到真实值
+        let mut shown = 0.0;
+        for _ in 0..200 {
+            shown = smooth_readout(shown, 1.0, 0.9, frame);
+        }
+        assert!((shown - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_freeze_trail_stops_trajectory_recording() {
+        let mut app = ChaosPendulumApp::default();
+        for _ in 0..50 {
+            app.step_once();
+        }
+        let frozen_len = app.statistics.get_trajectory_history().len();
+        let energy_len = app.statistics.get_history_length();
+        let time = app.pendulum.time;
+
+        app.record_trajectory = false;
+        for _ in 0..50 {
+            app.step_once();
+        }
+        assert_eq!(app.statistics.get_trajectory_history().len(), frozen_len);
+        assert!(app.statistics.get_history_length() > energy_len);
+        assert!(app.pendulum.time > time);
+    }
+
+    #[test]
+    fn test_trajectory_export_timestamps() {
+        let mut app = ChaosPendulumApp::default();
+        for _ in 0..100 {
+            app.step_once();
+        }
+
+        let mut buffer = Vec::new();
+        export::write_trajectory_points(&mut buffer, &app.statistics).unwrap();
+        let text = String::from_utf8(buffer).unwrap();
+        let times: Vec<f64> = text
+            .lines()
+            .skip(1)
+            .map(|line| line.split(',').next().unwrap().parse().unwrap())
+            .collect();
+
+        // 初始记录 + 每 record_interval 步一条
+        let interval = app.trajectory_record_interval as usize;
+        assert_eq!(times.len(), 1 + 100 / interval);
+
+        // 时间列单调递增，间隔为 dt * record_interval
+        let expected = real_to_f64(app.time_step) * interval as f64;
+        for pair in times.windows(2) {
+            assert!((pair[1] - pair[0] - expected).abs() < 1e-4);
+        }
+    }
+}
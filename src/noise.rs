@@ -0,0 +1,180 @@
+/// 噪声注入模块
+/// 每步积分后向状态加入可复现的高斯噪声，模拟测量和驱动误差
+use crate::pendulum::{PendulumState, Real};
+
+/// 默认随机种子
+pub const DEFAULT_NOISE_SEED: u64 = 42;
+
+/// SplitMix64 伪随机数生成器，相同种子产生相同序列
+#[derive(Clone, Debug)]
+pub(crate) struct NoiseRng {
+    state: u64,
+}
+
+impl NoiseRng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// (0, 1] 区间内的均匀分布
+    pub(crate) fn next_unit(&mut self) -> f64 {
+        ((self.next_u64() >> 11) + 1) as f64 / (1u64 << 53) as f64
+    }
+
+    /// 标准正态分布（Box–Muller 变换）
+    fn next_gaussian(&mut self) -> f64 {
+        let radius = (-2.0 * self.next_unit().ln()).sqrt();
+        let angle = 2.0 * std::f64::consts::PI * self.next_unit();
+        radius * angle.cos()
+    }
+}
+
+/// 状态噪声注入器，标准差为0时不改变状态
+#[derive(Clone, Debug)]
+pub struct NoiseInjector {
+    /// 角度噪声的标准差（rad）
+    angle_std: Real,
+    /// 角速度噪声的标准差（rad/s）
+    velocity_std: Real,
+    /// 随机种子
+    seed: u64,
+    rng: NoiseRng,
+}
+
+impl Default for NoiseInjector {
+    fn default() -> Self {
+        Self::new(DEFAULT_NOISE_SEED)
+    }
+}
+
+impl NoiseInjector {
+    /// 创建关闭状态的噪声注入器
+    pub fn new(seed: u64) -> Self {
+        Self {
+            angle_std: 0.0,
+            velocity_std: 0.0,
+            seed,
+            rng: NoiseRng::new(seed),
+        }
+    }
+
+    /// 是否会向状态加入噪声
+    pub fn is_enabled(&self) -> bool {
+        self.angle_std > 0.0 || self.velocity_std > 0.0
+    }
+
+    /// 角度噪声的标准差
+    pub fn angle_std(&self) -> Real {
+        self.angle_std
+    }
+
+    /// 设置角度噪声的标准差（负值按0处理）
+    pub fn set_angle_std(&mut self, std: Real) {
+        self.angle_std = std.max(0.0);
+    }
+
+    /// 角速度噪声的标准差
+    pub fn velocity_std(&self) -> Real {
+        self.velocity_std
+    }
+
+    /// 设置角速度噪声的标准差（负值按0处理）
+    pub fn set_velocity_std(&mut self, std: Real) {
+        self.velocity_std = std.max(0.0);
+    }
+
+    /// 随机种子
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// 设置随机种子并从头开始随机序列
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.restart();
+    }
+
+    /// 从种子重新开始随机序列，使下一次运行可以复现
+    pub fn restart(&mut self) {
+        self.rng = NoiseRng::new(self.seed);
+    }
+
+    /// 向状态加入噪声，关闭时不消耗随机数
+    pub fn apply(&mut self, state: &mut PendulumState) {
+        if !self.is_enabled() {
+            return;
+        }
+        state.theta1 += self.angle_std * self.rng.next_gaussian() as Real;
+        state.theta2 += self.angle_std * self.rng.next_gaussian() as Real;
+        state.omega1 += self.velocity_std * self.rng.next_gaussian() as Real;
+        state.omega2 += self.velocity_std * self.rng.next_gaussian() as Real;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pendulum::PendulumParams;
+    use crate::physics::PhysicsEngine;
+
+    fn run(noise: &mut NoiseInjector, steps: usize) -> Vec<PendulumState> {
+        let engine = PhysicsEngine::new(0.01);
+        let params = PendulumParams::default();
+        let mut state = PendulumState::new(2.0, 1.5, 0.0, 0.0);
+        (0..steps)
+            .map(|_| {
+                state = engine.step(&state, &params).0;
+                noise.apply(&mut state);
+                state
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_same_seed_reproduces_noisy_trajectory() {
+        let mut first = NoiseInjector::new(7);
+        first.set_angle_std(1e-4);
+        first.set_velocity_std(1e-3);
+        let mut second = first.clone();
+
+        let a = run(&mut first, 500);
+        assert_eq!(a, run(&mut second, 500));
+
+        // 重新开始后再次得到相同的轨迹，不同种子则不同
+        first.restart();
+        assert_eq!(a, run(&mut first, 500));
+        first.set_seed(8);
+        assert_ne!(a, run(&mut first, 500));
+    }
+
+    #[test]
+    fn test_zero_noise_matches_deterministic_baseline() {
+        let mut noise = NoiseInjector::new(7);
+        assert!(!noise.is_enabled());
+
+        let engine = PhysicsEngine::new(0.01);
+        let params = PendulumParams::default();
+        let initial = PendulumState::new(2.0, 1.5, 0.0, 0.0);
+        let baseline = engine.advance(&initial, &params, 500);
+        assert_eq!(*run(&mut noise, 500).last().unwrap(), baseline);
+    }
+
+    #[test]
+    fn test_gaussian_moments() {
+        let mut rng = NoiseRng::new(1);
+        let samples: Vec<f64> = (0..20000).map(|_| rng.next_gaussian()).collect();
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        let variance =
+            samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / samples.len() as f64;
+        assert!(mean.abs() < 0.03);
+        assert!((variance - 1.0).abs() < 0.05);
+    }
+}
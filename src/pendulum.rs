@@ -2,22 +2,53 @@
 /// 定义双摆的状态、参数和基本物理计算
 use serde::{Deserialize, Serialize};
 
+/// 物理计算使用的浮点类型（启用 `f32-physics` 特性时为单精度）
+#[cfg(not(feature = "f32-physics"))]
+pub type Real = f64;
+/// 物理计算使用的浮点类型（启用 `f32-physics` 特性时为单精度）
+#[cfg(feature = "f32-physics")]
+pub type Real = f32;
+
+/// 与 `Real` 对应的数学常量
+#[cfg(not(feature = "f32-physics"))]
+pub use std::f64::consts;
+/// 与 `Real` 对应的数学常量
+#[cfg(feature = "f32-physics")]
+pub use std::f32::consts;
+
+/// 将物理浮点数转换为f64（用于统计记录和绘图）
+#[inline]
+#[allow(clippy::unnecessary_cast)]
+pub fn real_to_f64(value: Real) -> f64 {
+    value as f64
+}
+
+/// 将物理浮点数转换为f32（用于屏幕坐标绘制）
+#[inline]
+#[allow(clippy::unnecessary_cast)]
+pub fn real_to_f32(value: Real) -> f32 {
+    value as f32
+}
+
+/// 由坐标反推角度时，允许摆杆长度与给定长度的最大偏差（米）
+pub const POSITION_TOLERANCE: Real = 1e-3;
+
 /// 双摆的瞬时状态
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct PendulumState {
     /// 上摆的角度（弧度）
-    pub theta1: f64,
+    pub theta1: Real,
     /// 下摆的角度（弧度）
-    pub theta2: f64,
+    pub theta2: Real,
     /// 上摆的角速度（弧度/秒）
-    pub omega1: f64,
+    pub omega1: Real,
     /// 下摆的角速度（弧度/秒）
-    pub omega2: f64,
+    pub omega2: Real,
 }
 
 impl PendulumState {
     /// 创建新的摆状态
-    pub fn new(theta1: f64, theta2: f64, omega1: f64, omega2: f64) -> Self {
+    pub fn new(theta1: Real, theta2: Real, omega1: Real, omega2: Real) -> Self {
         Self {
             theta1,
             theta2,
@@ -27,22 +58,40 @@ impl PendulumState {
     }
 
     /// 获取上摆质点的笛卡尔坐标
-    pub fn get_mass1_position(&self, l1: f64) -> (f64, f64) {
+    pub fn get_mass1_position(&self, l1: Real) -> (Real, Real) {
         let x1 = l1 * self.theta1.sin();
         let y1 = -l1 * self.theta1.cos();
         (x1, y1)
     }
 
     /// 获取下摆质点的笛卡尔坐标
-    pub fn get_mass2_position(&self, l1: f64, l2: f64) -> (f64, f64) {
+    pub fn get_mass2_position(&self, l1: Real, l2: Real) -> (Real, Real) {
         let (x1, y1) = self.get_mass1_position(l1);
         let x2 = x1 + l2 * self.theta2.sin();
         let y2 = y1 - l2 * self.theta2.cos();
         (x2, y2)
     }
 
+    /// 下摆质点的速率
+    pub fn mass2_speed(&self, l1: Real, l2: Real) -> Real {
+        let vx = l1 * self.omega1 * self.theta1.cos() + l2 * self.omega2 * self.theta2.cos();
+        let vy = l1 * self.omega1 * self.theta1.sin() + l2 * self.omega2 * self.theta2.sin();
+        vx.hypot(vy)
+    }
+
+    /// 获取系统质心的笛卡尔坐标
+    pub fn center_of_mass(&self, params: &PendulumParams) -> (Real, Real) {
+        let (x1, y1) = self.get_mass1_position(params.l1);
+        let (x2, y2) = self.get_mass2_position(params.l1, params.l2);
+        let total_mass = params.m1 + params.m2;
+        (
+            (params.m1 * x1 + params.m2 * x2) / total_mass,
+            (params.m1 * y1 + params.m2 * y2) / total_mass,
+        )
+    }
+
     /// 计算系统的动能
-    pub fn kinetic_energy(&self, params: &PendulumParams) -> f64 {
+    pub fn kinetic_energy(&self, params: &PendulumParams) -> Real {
         let m1 = params.m1;
         let m2 = params.m2;
         let l1 = params.l1;
@@ -64,12 +113,12 @@ impl PendulumState {
     }
 
     /// 计算系统的势能
-    pub fn potential_energy(&self, params: &PendulumParams) -> f64 {
+    pub fn potential_energy(&self, params: &PendulumParams) -> Real {
         let m1 = params.m1;
         let m2 = params.m2;
         let l1 = params.l1;
         let l2 = params.l2;
-        let g = params.g;
+        let g = params.effective_g();
 
         // 势能参考点为摆的悬挂点
         let y1 = -l1 * self.theta1.cos();
@@ -79,10 +128,48 @@ impl PendulumState {
     }
 
     /// 计算系统的总能量
-    pub fn total_energy(&self, params: &PendulumParams) -> f64 {
+    pub fn total_energy(&self, params: &PendulumParams) -> Real {
         self.kinetic_energy(params) + self.potential_energy(params)
     }
 
+    /// 计算随悬挂点加速运动的参考系中的总能量
+    /// 坐标和速度本就相对悬挂点，只需加入惯性力 -m·a 的势能 m·(a·r)
+    /// 悬挂点静止（加速度为0）时等于 total_energy
+    pub fn pivot_frame_energy(
+        &self,
+        params: &PendulumParams,
+        pivot_acceleration: (Real, Real),
+    ) -> Real {
+        let (ax, ay) = pivot_acceleration;
+        let (x1, y1) = self.get_mass1_position(params.l1);
+        let (x2, y2) = self.get_mass2_position(params.l1, params.l2);
+        let fictitious = params.m1 * (ax * x1 + ay * y1) + params.m2 * (ax * x2 + ay * y2);
+        self.total_energy(params) + fictitious
+    }
+
+    /// 计算拉格朗日量 L = T - V
+    pub fn lagrangian(&self, params: &PendulumParams) -> Real {
+        self.kinetic_energy(params) - self.potential_energy(params)
+    }
+
+    /// 计算阻尼和摩擦耗散的瞬时功率 P = -τ_d(ω₁)ω₁ - τ_d(ω₂)ω₂ + μ(|ω₁| + |ω₂|)
+    /// 线性阻尼时 -τ_d(ω)ω = bω²，二次阻尼时为 c|ω|³
+    pub fn dissipated_power(&self, params: &PendulumParams) -> Real {
+        let drag = |omega: Real| -params.drag_model.torque(params.damping, omega) * omega;
+        drag(self.omega1)
+            + drag(self.omega2)
+            + params.coulomb_friction * (self.omega1.abs() + self.omega2.abs())
+    }
+
+    /// 计算两个质点绕悬挂点的总角动量
+    /// L = (m₁+m₂)l₁²ω₁ + m₂l₂²ω₂ + m₂l₁l₂(ω₁+ω₂)cos(θ₁-θ₂)，无重力且无阻尼时守恒
+    pub fn angular_momentum(&self, params: &PendulumParams) -> Real {
+        let (m1, m2, l1, l2) = (params.m1, params.m2, params.l1, params.l2);
+        (m1 + m2) * l1 * l1 * self.omega1
+            + m2 * l2 * l2 * self.omega2
+            + m2 * l1 * l2 * (self.omega1 + self.omega2) * (self.theta1 - self.theta2).cos()
+    }
+
     /// 标准化角度到 [-π, π] 范围
     pub fn normalize_angles(&mut self) {
         self.theta1 = normalize_angle(self.theta1);
@@ -91,31 +178,108 @@ impl PendulumState {
 
     /// 创建静止状态（角速度为0）
     #[allow(dead_code)]
-    pub fn at_rest(theta1: f64, theta2: f64) -> Self {
+    pub fn at_rest(theta1: Real, theta2: Real) -> Self {
         Self::new(theta1, theta2, 0.0, 0.0)
     }
+
+    /// 沿第 mode 个简正模（0 为慢模，1 为快模）偏离平衡位置的静止状态
+    /// 较大的摆角等于 amplitude，小角度下随后的运动是单一频率的振动
+    pub fn from_normal_mode(params: &PendulumParams, mode: usize, amplitude: f64) -> Self {
+        let (_, [v1, v2]) = params.normal_modes()[mode];
+        let scale = amplitude / v1.abs().max(v2.abs());
+        let equilibrium = params.equilibrium_angle();
+        Self::at_rest(
+            equilibrium + (scale * v1) as Real,
+            equilibrium + (scale * v2) as Real,
+        )
+    }
+
+    /// 由两个质点的笛卡尔坐标创建静止状态（get_mass1_position 等的逆运算）
+    /// 坐标与摆长不一致（超过 POSITION_TOLERANCE）时返回错误
+    pub fn from_positions(
+        pos1: (Real, Real),
+        pos2: (Real, Real),
+        l1: Real,
+        l2: Real,
+    ) -> Result<Self, String> {
+        let (x1, y1) = pos1;
+        let (dx, dy) = (pos2.0 - x1, pos2.1 - y1);
+
+        let r1 = (x1 * x1 + y1 * y1).sqrt();
+        if (r1 - l1).abs() > POSITION_TOLERANCE {
+            return Err(format!("上摆球到悬挂点的距离 {:.4} 与摆长 {:.4} 不符", r1, l1));
+        }
+        let r2 = (dx * dx + dy * dy).sqrt();
+        if (r2 - l2).abs() > POSITION_TOLERANCE {
+            return Err(format!("两摆球之间的距离 {:.4} 与摆长 {:.4} 不符", r2, l2));
+        }
+
+        // x = l·sinθ, y = -l·cosθ
+        Ok(Self::at_rest(x1.atan2(-y1), dx.atan2(-dy)))
+    }
+
+    /// 把指定摆臂（1 为上摆，2 为下摆）的角度微调 delta 弧度，角速度不变
+    pub fn nudge(&mut self, arm: u8, delta: Real) {
+        match arm {
+            1 => self.theta1 += delta,
+            2 => self.theta2 += delta,
+            _ => {}
+        }
+    }
+
+    /// 插值：t=0 返回 self，t=1 返回 other
+    /// 角度沿最短弧插值，跨越 ±π 时不会绕经下方；角速度逐分量线性插值
+    pub fn lerp(&self, other: &PendulumState, t: Real) -> Self {
+        Self::new(
+            normalize_angle(self.theta1 + normalize_angle(other.theta1 - self.theta1) * t),
+            normalize_angle(self.theta2 + normalize_angle(other.theta2 - self.theta2) * t),
+            self.omega1 + (other.omega1 - self.omega1) * t,
+            self.omega2 + (other.omega2 - self.omega2) * t,
+        )
+    }
 }
 
 /// 双摆的物理参数
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct PendulumParams {
     /// 上摆质量（kg）
-    pub m1: f64,
+    pub m1: Real,
     /// 下摆质量（kg）
-    pub m2: f64,
+    pub m2: Real,
     /// 上摆长度（m）
-    pub l1: f64,
+    pub l1: Real,
     /// 下摆长度（m）
-    pub l2: f64,
+    pub l2: Real,
     /// 重力加速度（m/s²）
-    pub g: f64,
-    /// 阻尼系数
-    pub damping: f64,
+    pub g: Real,
+    /// 阻尼系数（线性阻尼为 N·m·s，二次阻尼为 N·m·s²）
+    pub damping: Real,
+    /// 阻尼力矩与角速度的关系
+    #[serde(default)]
+    pub drag_model: DragModel,
+    /// 库仑摩擦力矩（N·m），与角速度大小无关，方向与转动相反
+    #[serde(default)]
+    pub coulomb_friction: Real,
+    /// 上关节正弦驱动力矩的振幅（N·m），0 表示无驱动
+    #[serde(default)]
+    pub drive_amplitude: Real,
+    /// 驱动力矩的角频率（rad/s）
+    #[serde(default)]
+    pub drive_frequency: Real,
+    /// 悬挂点竖直振动的振幅（m），0 表示悬挂点固定
+    #[serde(default)]
+    pub pivot_amplitude: Real,
+    /// 悬挂点振动的角频率（rad/s）
+    #[serde(default)]
+    pub pivot_frequency: Real,
+    /// 反摆模式：重力指向上方，竖直向上成为稳定平衡位置
+    #[serde(default)]
+    pub inverted_gravity: bool,
 }
 
 impl PendulumParams {
     /// 创建新的摆参数
-    pub fn new(m1: f64, m2: f64, l1: f64, l2: f64, g: f64, damping: f64) -> Self {
+    pub fn new(m1: Real, m2: Real, l1: Real, l2: Real, g: Real, damping: Real) -> Self {
         Self {
             m1,
             m2,
@@ -123,6 +287,58 @@ impl PendulumParams {
             l2,
             g,
             damping,
+            drag_model: DragModel::Linear,
+            coulomb_friction: 0.0,
+            drive_amplitude: 0.0,
+            drive_frequency: 0.0,
+            pivot_amplitude: 0.0,
+            pivot_frequency: 0.0,
+            inverted_gravity: false,
+        }
+    }
+
+    /// t 时刻作用在上关节的驱动力矩 A·sin(ω_d·t)
+    pub fn drive_torque(&self, time: Real) -> Real {
+        if self.drive_amplitude == 0.0 {
+            return 0.0;
+        }
+        self.drive_amplitude * (self.drive_frequency * time).sin()
+    }
+
+    /// t 时刻悬挂点相对静止位置的竖直位移 A·sin(Ω·t)（向上为正）
+    pub fn pivot_offset(&self, time: Real) -> Real {
+        if self.pivot_amplitude == 0.0 {
+            return 0.0;
+        }
+        self.pivot_amplitude * (self.pivot_frequency * time).sin()
+    }
+
+    /// t 时刻悬挂点的竖直加速度 -A·Ω²·sin(Ω·t)（向上为正）
+    pub fn pivot_acceleration(&self, time: Real) -> Real {
+        -self.pivot_frequency * self.pivot_frequency * self.pivot_offset(time)
+    }
+
+    /// 随悬挂点一起运动的参考系中 t 时刻的等效重力加速度
+    /// 悬挂点向上加速时惯性力与重力同向，等效重力增大
+    pub fn gravity_at(&self, time: Real) -> Real {
+        self.effective_g() + self.pivot_acceleration(time)
+    }
+
+    /// 运动方程中使用的重力加速度，反摆模式下取负值
+    pub fn effective_g(&self) -> Real {
+        if self.inverted_gravity {
+            -self.g
+        } else {
+            self.g
+        }
+    }
+
+    /// 稳定平衡位置的摆角：正常为 0（竖直向下），反摆模式为 π（竖直向上）
+    pub fn equilibrium_angle(&self) -> Real {
+        if self.inverted_gravity {
+            consts::PI
+        } else {
+            0.0
         }
     }
 
@@ -140,14 +356,104 @@ impl PendulumParams {
         if self.l2 <= 0.0 {
             return Err("下摆长度必须为正数".to_string());
         }
-        if self.g <= 0.0 {
-            return Err("重力加速度必须为正数".to_string());
+        // g = 0 时运动可积，用于检验额外的守恒量
+        if self.g < 0.0 {
+            return Err("重力加速度不能为负数".to_string());
         }
         if self.damping < 0.0 {
             return Err("阻尼系数不能为负数".to_string());
         }
+        if self.coulomb_friction < 0.0 {
+            return Err("库仑摩擦不能为负数".to_string());
+        }
+        if !self.drive_amplitude.is_finite() {
+            return Err("驱动振幅必须为有限值".to_string());
+        }
+        if !self.drive_frequency.is_finite() || self.drive_frequency < 0.0 {
+            return Err("驱动频率不能为负数".to_string());
+        }
+        if !self.pivot_amplitude.is_finite() || self.pivot_amplitude < 0.0 {
+            return Err("悬挂点振幅不能为负数".to_string());
+        }
+        if !self.pivot_frequency.is_finite() || self.pivot_frequency < 0.0 {
+            return Err("悬挂点振动频率不能为负数".to_string());
+        }
         Ok(())
     }
+
+    /// 势能的最小值（两摆都竖直下垂）：-(m₁ + m₂)gl₁ - m₂gl₂
+    /// 以此为零点时势能非负；反摆模式下对应两摆竖直向上，数值相同
+    pub fn minimum_potential_energy(&self) -> Real {
+        -(self.m1 + self.m2) * self.g * self.l1 - self.m2 * self.g * self.l2
+    }
+
+    /// 下摆能越过顶部（θ₂ = π）所需的最低总能量
+    /// 即 θ₁ = 0、θ₂ = π 且静止时的势能：m₂gl₂ - (m₁ + m₂)gl₁
+    /// 反摆模式下为下摆越过底部所需的能量，数值相同
+    pub fn flip_energy_threshold(&self) -> Real {
+        self.m2 * self.g * self.l2 - (self.m1 + self.m2) * self.g * self.l1
+    }
+
+    /// 小角度线性化系统的质量矩阵 M 和刚度矩阵 K 的特征值 λ = ω²（从小到大）
+    /// M = [[(m₁+m₂)l₁², m₂l₁l₂], [m₂l₁l₂, m₂l₂²]]，K = diag((m₁+m₂)gl₁, m₂gl₂)
+    fn normal_mode_eigenvalues(&self) -> (f64, f64) {
+        let (m1, m2) = (real_to_f64(self.m1), real_to_f64(self.m2));
+        let (l1, l2, g) = (real_to_f64(self.l1), real_to_f64(self.l2), real_to_f64(self.g));
+        let m11 = (m1 + m2) * l1 * l1;
+        let m12 = m2 * l1 * l2;
+        let m22 = m2 * l2 * l2;
+        let k11 = (m1 + m2) * g * l1;
+        let k22 = m2 * g * l2;
+
+        // det(K - λM) = 0：a·λ² - b·λ + c = 0
+        let a = m11 * m22 - m12 * m12;
+        let b = k11 * m22 + k22 * m11;
+        let c = k11 * k22;
+        let root = (b * b - 4.0 * a * c).max(0.0).sqrt();
+        ((b - root) / (2.0 * a), (b + root) / (2.0 * a))
+    }
+
+    /// 两个小角度简正模的角频率（rad/s），先慢后快
+    pub fn normal_mode_frequencies(&self) -> (f64, f64) {
+        let (slow, fast) = self.normal_mode_eigenvalues();
+        (slow.max(0.0).sqrt(), fast.max(0.0).sqrt())
+    }
+
+    /// 两个简正模的角频率和振型向量 (θ₁, θ₂)，先慢后快
+    /// 振型向量的 θ₁ 分量取 1，θ₂ 分量为振幅比
+    pub fn normal_modes(&self) -> [(f64, [f64; 2]); 2] {
+        let (slow, fast) = self.normal_mode_frequencies();
+        let (slow_shape, fast_shape) = self.normal_mode_shapes();
+        [(slow, [1.0, slow_shape]), (fast, [1.0, fast_shape])]
+    }
+
+    /// 刚性估计：系统最快的固有角频率（rad/s），即快简正模的频率
+    /// 摆长很短、下摆很重或重力很大时该值变大，需要更小的步长
+    pub fn stiffness_estimate(&self) -> f64 {
+        self.normal_mode_frequencies().1
+    }
+
+    /// 按刚性估计建议的最大时间步长：ω·dt ≤ STIFFNESS_DT_FACTOR，向下取到一位有效数字
+    /// 没有有限的刚性估计时返回 None
+    pub fn suggested_max_dt(&self) -> Option<f64> {
+        let stiffness = self.stiffness_estimate();
+        if !stiffness.is_finite() || stiffness <= 0.0 {
+            return None;
+        }
+        Some(floor_to_one_significant_digit(STIFFNESS_DT_FACTOR / stiffness))
+    }
+
+    /// 两个简正模的振型，以 θ₂/θ₁ 的振幅比表示（同相为正，反相为负）
+    pub fn normal_mode_shapes(&self) -> (f64, f64) {
+        let (slow, fast) = self.normal_mode_eigenvalues();
+        let (m1, m2) = (real_to_f64(self.m1), real_to_f64(self.m2));
+        let (l1, l2, g) = (real_to_f64(self.l1), real_to_f64(self.l2), real_to_f64(self.g));
+        // 由第一行 (k11 - λm11)θ₁ = λm12·θ₂ 得到振幅比
+        let ratio = |lambda: f64| {
+            ((m1 + m2) * g * l1 - lambda * (m1 + m2) * l1 * l1) / (lambda * m2 * l1 * l2)
+        };
+        (ratio(slow), ratio(fast))
+    }
 }
 
 impl Default for PendulumParams {
@@ -163,17 +469,234 @@ impl Default for PendulumParams {
     }
 }
 
+/// 建议步长与最快固有周期的关系：ω·dt 不超过该值
+const STIFFNESS_DT_FACTOR: f64 = 0.02;
+
+/// 把正数向下取到一位有效数字，例如 0.00086 → 0.0008
+fn floor_to_one_significant_digit(value: f64) -> f64 {
+    let exponent = value.log10().floor() as i32;
+    // 用除法得到与十进制写法最接近的浮点数
+    let scale = 10f64.powi(-exponent);
+    (value * scale).floor() / scale
+}
+
+/// 线性化解析解适用的最大摆角（弧度）
+pub const SMALL_ANGLE_LIMIT: f64 = 0.3;
+
+/// 小角度线性化系统的解析解：两个简正模的叠加
+/// θ(t) = Σₖ vₖ(Aₖcos ωₖt + Bₖsin ωₖt)，vₖ = (1, rₖ) 为振型
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NormalModeSolution {
+    /// 两个简正模的角频率
+    frequencies: (f64, f64),
+    /// 两个简正模的振型 θ₂/θ₁
+    shapes: (f64, f64),
+    /// 每个模的余弦、正弦系数 (Aₖ, Bₖ)
+    coefficients: [(f64, f64); 2],
+    /// 展开所围绕的平衡角（反摆模式下为 π）
+    equilibrium: Real,
+}
+
+impl NormalModeSolution {
+    /// 按初始状态把运动分解到两个简正模上
+    pub fn new(initial: &PendulumState, params: &PendulumParams) -> Self {
+        let frequencies = params.normal_mode_frequencies();
+        let shapes = params.normal_mode_shapes();
+        let (r1, r2) = shapes;
+        // 解 x₁ + x₂ = first，r₁x₁ + r₂x₂ = second
+        let split = |first: Real, second: Real| {
+            let (first, second) = (real_to_f64(first), real_to_f64(second));
+            let x2 = (second - r1 * first) / (r2 - r1);
+            (first - x2, x2)
+        };
+        // 反摆与正常双摆在角度平移 π 后完全等价，频率和振型不变
+        let equilibrium = params.equilibrium_angle();
+        let (a1, a2) = split(
+            normalize_angle(initial.theta1 - equilibrium),
+            normalize_angle(initial.theta2 - equilibrium),
+        );
+        let (c1, c2) = split(initial.omega1, initial.omega2);
+        Self {
+            frequencies,
+            shapes,
+            coefficients: [(a1, c1 / frequencies.0), (a2, c2 / frequencies.1)],
+            equilibrium,
+        }
+    }
+
+    /// 初始时刻之后 t 秒的状态
+    pub fn state_at(&self, t: f64) -> PendulumState {
+        let modes = [
+            (self.frequencies.0, self.shapes.0, self.coefficients[0]),
+            (self.frequencies.1, self.shapes.1, self.coefficients[1]),
+        ];
+        let mut state = [0.0; 4];
+        for (omega, shape, (a, b)) in modes {
+            let (sin, cos) = (omega * t).sin_cos();
+            let angle = a * cos + b * sin;
+            let rate = omega * (b * cos - a * sin);
+            state[0] += angle;
+            state[1] += shape * angle;
+            state[2] += rate;
+            state[3] += shape * rate;
+        }
+        PendulumState::new(
+            state[0] as Real + self.equilibrium,
+            state[1] as Real + self.equilibrium,
+            state[2] as Real,
+            state[3] as Real,
+        )
+    }
+
+    /// 两个摆角可能达到的最大幅度（上界），用于判断线性化是否适用
+    pub fn max_amplitude(&self) -> f64 {
+        let modes = [
+            (self.shapes.0, self.coefficients[0]),
+            (self.shapes.1, self.coefficients[1]),
+        ];
+        modes
+            .iter()
+            .map(|(shape, (a, b))| a.hypot(*b) * shape.abs().max(1.0))
+            .sum()
+    }
+}
+
 /// 标准化角度到 [-π, π] 范围
-pub fn normalize_angle(angle: f64) -> f64 {
-    let mut normalized = angle % (2.0 * std::f64::consts::PI);
-    if normalized > std::f64::consts::PI {
-        normalized -= 2.0 * std::f64::consts::PI;
-    } else if normalized < -std::f64::consts::PI {
-        normalized += 2.0 * std::f64::consts::PI;
+pub fn normalize_angle(angle: Real) -> Real {
+    let mut normalized = angle % (2.0 * consts::PI);
+    if normalized > consts::PI {
+        normalized -= 2.0 * consts::PI;
+    } else if normalized < -consts::PI {
+        normalized += 2.0 * consts::PI;
     }
     normalized
 }
 
+/// 阻尼模型
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DragModel {
+    /// 线性粘性阻尼 -bω
+    #[default]
+    Linear,
+    /// 二次空气阻力 -cω|ω|，大幅高速运动时更接近实际
+    Quadratic,
+}
+
+impl DragModel {
+    /// 所有阻尼模型
+    pub const ALL: [DragModel; 2] = [DragModel::Linear, DragModel::Quadratic];
+
+    /// 显示名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            DragModel::Linear => "Linear",
+            DragModel::Quadratic => "Quadratic",
+        }
+    }
+
+    /// 系数为 coefficient、角速度为 omega 时的阻尼力矩
+    pub fn torque(self, coefficient: Real, omega: Real) -> Real {
+        match self {
+            DragModel::Linear => -coefficient * omega,
+            DragModel::Quadratic => -coefficient * omega * omega.abs(),
+        }
+    }
+}
+
+/// 保存文件中角度的约定
+/// 内部表示：两个角度都从竖直向下方向起算，逆时针为正（摆球在右侧时为正）
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AngleConvention {
+    /// 内部约定：从竖直向下起算的绝对角度
+    #[default]
+    AbsoluteFromDown,
+    /// θ₂、ω₂ 相对上摆测量
+    RelativeToUpperArm,
+    /// 从竖直向上起算的绝对角度
+    AbsoluteFromUp,
+}
+
+impl AngleConvention {
+    /// 把按该约定存储的状态转换为内部表示
+    pub fn to_internal(self, state: PendulumState) -> PendulumState {
+        match self {
+            AngleConvention::AbsoluteFromDown => state,
+            AngleConvention::RelativeToUpperArm => PendulumState::new(
+                state.theta1,
+                state.theta1 + state.theta2,
+                state.omega1,
+                state.omega1 + state.omega2,
+            ),
+            AngleConvention::AbsoluteFromUp => PendulumState::new(
+                normalize_angle(state.theta1 + consts::PI),
+                normalize_angle(state.theta2 + consts::PI),
+                state.omega1,
+                state.omega2,
+            ),
+        }
+    }
+
+    /// 把内部表示的状态转换为该约定（to_internal 的逆运算）
+    /// 保存时总是使用默认约定，只在测试中验证往返转换
+    #[cfg(test)]
+    pub fn to_stored(self, state: PendulumState) -> PendulumState {
+        match self {
+            AngleConvention::AbsoluteFromDown => state,
+            AngleConvention::RelativeToUpperArm => PendulumState::new(
+                state.theta1,
+                state.theta2 - state.theta1,
+                state.omega1,
+                state.omega2 - state.omega1,
+            ),
+            AngleConvention::AbsoluteFromUp => PendulumState::new(
+                normalize_angle(state.theta1 - consts::PI),
+                normalize_angle(state.theta2 - consts::PI),
+                state.omega1,
+                state.omega2,
+            ),
+        }
+    }
+}
+
+/// 按整数步数计时的时钟：时间 = 起点 + 步数 × dt，避免逐步累加 dt 的舍入漂移
+/// 步长改变时以当前时间为新起点重新计数
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StepClock {
+    /// 当前步长开始使用时的时间
+    origin: f64,
+    /// 以当前步长走过的步数
+    steps: u64,
+    /// 当前步长
+    dt: f64,
+}
+
+impl StepClock {
+    /// 从指定时间开始计时
+    pub fn new(origin: f64) -> Self {
+        Self {
+            origin,
+            ..Self::default()
+        }
+    }
+
+    /// 当前时间
+    pub fn time(&self) -> f64 {
+        self.origin + self.steps as f64 * self.dt
+    }
+
+    /// 前进一步并返回新的时间
+    pub fn advance(&mut self, dt: f64) -> f64 {
+        if dt != self.dt {
+            *self = Self::new(self.time());
+            self.dt = dt;
+        }
+        self.steps += 1;
+        self.time()
+    }
+}
+
 /// 双摆系统的完整状态和参数组合
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DoublePendulum {
@@ -181,8 +704,11 @@ pub struct DoublePendulum {
     pub state: PendulumState,
     /// 物理参数
     pub params: PendulumParams,
-    /// 模拟时间
-    pub time: f64,
+    /// 模拟时间（由 clock 按步数计算）
+    pub time: Real,
+    /// 模拟时间的整数步时钟
+    #[serde(skip)]
+    clock: StepClock,
 }
 
 impl DoublePendulum {
@@ -192,6 +718,7 @@ impl DoublePendulum {
             state,
             params,
             time: 0.0,
+            clock: StepClock::default(),
         }
     }
 
@@ -199,36 +726,95 @@ impl DoublePendulum {
     pub fn reset(&mut self, new_state: PendulumState) {
         self.state = new_state;
         self.time = 0.0;
+        self.clock = StepClock::default();
     }
 
-    /// 更新模拟时间
-    pub fn advance_time(&mut self, dt: f64) {
-        self.time += dt;
+    /// 前进一步 dt 更新模拟时间
+    pub fn advance_time(&mut self, dt: Real) {
+        // time 被直接修改过时（如跳转到记录的状态）从新的时间重新计数
+        if self.clock.time() as Real != self.time {
+            self.clock = StepClock::new(real_to_f64(self.time));
+        }
+        self.time = self.clock.advance(real_to_f64(dt)) as Real;
     }
 
     /// 获取当前总能量
-    pub fn total_energy(&self) -> f64 {
+    pub fn total_energy(&self) -> Real {
         self.state.total_energy(&self.params)
     }
 
+    /// 获取随悬挂点运动的参考系中的当前总能量
+    pub fn pivot_frame_energy(&self) -> Real {
+        let acceleration = self.params.pivot_acceleration(self.time);
+        self.state.pivot_frame_energy(&self.params, (0.0, acceleration))
+    }
+
     /// 获取当前动能
-    pub fn kinetic_energy(&self) -> f64 {
+    pub fn kinetic_energy(&self) -> Real {
         self.state.kinetic_energy(&self.params)
     }
 
     /// 获取当前势能
-    pub fn potential_energy(&self) -> f64 {
+    pub fn potential_energy(&self) -> Real {
         self.state.potential_energy(&self.params)
     }
 
+    /// 获取当前拉格朗日量 L = T - V
+    pub fn lagrangian(&self) -> Real {
+        self.state.lagrangian(&self.params)
+    }
+
+    /// 获取当前阻尼耗散功率
+    pub fn dissipated_power(&self) -> Real {
+        self.state.dissipated_power(&self.params)
+    }
+
+    /// 获取当前哈密顿量 H = T + V（实验室参考系的总能量）
+    /// 只有在无阻尼、无库仑摩擦、无驱动力矩且悬挂点固定时才守恒
+    pub fn hamiltonian(&self) -> Real {
+        self.total_energy()
+    }
+
     /// 获取两个质点的当前位置
-    pub fn get_positions(&self) -> ((f64, f64), (f64, f64)) {
+    pub fn get_positions(&self) -> ((Real, Real), (Real, Real)) {
         let pos1 = self.state.get_mass1_position(self.params.l1);
         let pos2 = self
             .state
             .get_mass2_position(self.params.l1, self.params.l2);
         (pos1, pos2)
     }
+
+    /// 序列化为 JSON（状态、参数和模拟时间），状态按内部约定保存并注明
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&SavedPendulum {
+            state: self.state,
+            params: self.params,
+            time: self.time,
+            convention: AngleConvention::default(),
+        })
+    }
+
+    /// 从 JSON 恢复，状态转换为内部角度约定，时钟从保存的模拟时间继续计数
+    pub fn from_json(text: &str) -> serde_json::Result<Self> {
+        let saved: SavedPendulum = serde_json::from_str(text)?;
+        Ok(Self {
+            state: saved.convention.to_internal(saved.state),
+            params: saved.params,
+            time: saved.time,
+            clock: StepClock::new(real_to_f64(saved.time)),
+        })
+    }
+}
+
+/// 状态文件的格式：在双摆的状态、参数和时间之外注明角度约定
+#[derive(Serialize, Deserialize)]
+struct SavedPendulum {
+    state: PendulumState,
+    params: PendulumParams,
+    time: Real,
+    /// 状态使用的角度约定（缺省为内部约定）
+    #[serde(default)]
+    convention: AngleConvention,
 }
 
 #[cfg(test)]
@@ -246,7 +832,7 @@ mod tests {
 
     #[test]
     fn test_at_rest_state() {
-        let state = PendulumState::at_rest(std::f64::consts::PI / 4.0, std::f64::consts::PI / 6.0);
+        let state = PendulumState::at_rest(consts::PI / 4.0, consts::PI / 6.0);
         assert_eq!(state.omega1, 0.0);
         assert_eq!(state.omega2, 0.0);
     }
@@ -263,10 +849,90 @@ mod tests {
         assert!((y2 - (-2.0)).abs() < 1e-10);
     }
 
+    #[test]
+    fn test_from_positions_round_trip() {
+        let (l1, l2) = (1.0, 1.5);
+        for (theta1, theta2) in [(0.0, 0.0), (0.7, -2.1), (-3.0, 3.0), (consts::PI / 2.0, 1.0)] {
+            let state = PendulumState::at_rest(theta1, theta2);
+            let pos1 = state.get_mass1_position(l1);
+            let pos2 = state.get_mass2_position(l1, l2);
+
+            let restored = PendulumState::from_positions(pos1, pos2, l1, l2).unwrap();
+            let (x1, y1) = restored.get_mass1_position(l1);
+            let (x2, y2) = restored.get_mass2_position(l1, l2);
+            assert!((x1 - pos1.0).abs() < 1e-5 && (y1 - pos1.1).abs() < 1e-5);
+            assert!((x2 - pos2.0).abs() < 1e-5 && (y2 - pos2.1).abs() < 1e-5);
+            assert_eq!(restored.omega1, 0.0);
+            assert_eq!(restored.omega2, 0.0);
+        }
+    }
+
+    #[test]
+    fn test_from_positions_rejects_inconsistent_lengths() {
+        assert!(PendulumState::from_positions((0.0, -1.2), (0.0, -2.2), 1.0, 1.0).is_err());
+        assert!(PendulumState::from_positions((0.0, -1.0), (0.5, -1.0), 1.0, 1.0).is_err());
+        assert!(PendulumState::from_positions((0.0, -1.0), (1.0, -1.0), 1.0, 1.0).is_ok());
+    }
+
+    #[test]
+    fn test_nudge() {
+        let mut state = PendulumState::new(0.5, -0.5, 1.0, 2.0);
+        state.nudge(1, 0.001);
+        assert!((state.theta1 - 0.501).abs() < 1e-6);
+        state.nudge(2, -0.001);
+        assert!((state.theta2 + 0.501).abs() < 1e-6);
+        assert_eq!((state.omega1, state.omega2), (1.0, 2.0));
+
+        // 无效的摆臂编号不改变状态
+        let before = state;
+        state.nudge(3, 1.0);
+        assert_eq!(state, before);
+    }
+
+    #[test]
+    fn test_state_lerp() {
+        let a = PendulumState::new(0.0, 1.0, -2.0, 4.0);
+        let b = PendulumState::new(1.0, -1.0, 2.0, 8.0);
+
+        assert_eq!(a.lerp(&b, 0.0), a);
+        assert_eq!(a.lerp(&b, 1.0), b);
+
+        let mid = a.lerp(&b, 0.5);
+        assert_eq!(mid, PendulumState::new(0.5, 0.0, 0.0, 6.0));
+
+        let quarter = a.lerp(&b, 0.25);
+        assert!((quarter.theta1 - 0.25).abs() < 1e-10);
+        assert!((quarter.theta2 - 0.5).abs() < 1e-10);
+        assert!((quarter.omega1 - (-1.0)).abs() < 1e-10);
+        assert!((quarter.omega2 - 5.0).abs() < 1e-10);
+
+        // 两个状态分处 ±π 两侧时沿最短弧经过顶部，而不是绕经底部
+        let left = PendulumState::new(consts::PI - 0.1, -consts::PI + 0.2, 0.0, 0.0);
+        let right = PendulumState::new(-consts::PI + 0.1, consts::PI - 0.2, 0.0, 0.0);
+        let mid = left.lerp(&right, 0.5);
+        assert!((mid.theta1.abs() - consts::PI).abs() < 1e-6);
+        assert!((mid.theta2.abs() - consts::PI).abs() < 1e-6);
+        let quarter = left.lerp(&right, 0.25);
+        assert!((quarter.theta1 - (consts::PI - 0.05)).abs() < 1e-6);
+        assert!((quarter.theta2 - (-consts::PI + 0.1)).abs() < 1e-6);
+        assert_eq!(left.lerp(&right, 1.0).theta1, right.theta1);
+    }
+
+    #[test]
+    fn test_center_of_mass() {
+        let state = PendulumState::at_rest(0.0, 0.0);
+        let params = PendulumParams::new(1.0, 3.0, 1.0, 1.0, 9.81, 0.0);
+        let (x, y) = state.center_of_mass(&params);
+
+        // 质点位于 y=-1 和 y=-2，质量比 1:3
+        assert!(x.abs() < 1e-10);
+        assert!((y - (-1.75)).abs() < 1e-10);
+    }
+
     #[test]
     fn test_energy_conservation() {
         let params = PendulumParams::default();
-        let state = PendulumState::at_rest(std::f64::consts::PI / 4.0, std::f64::consts::PI / 4.0);
+        let state = PendulumState::at_rest(consts::PI / 4.0, consts::PI / 4.0);
 
         let ke = state.kinetic_energy(&params);
         let pe = state.potential_energy(&params);
@@ -277,6 +943,51 @@ mod tests {
         assert!((total - (ke + pe)).abs() < 1e-10); // 总能量 = 动能 + 势能
     }
 
+    #[test]
+    fn test_lagrangian_hamiltonian_consistency() {
+        let states = [
+            PendulumState::at_rest(consts::PI / 4.0, consts::PI / 6.0),
+            PendulumState::new(1.0, -0.5, 2.0, -1.5),
+            PendulumState::new(-2.5, 3.0, -0.3, 4.0),
+            PendulumState::new(0.0, 0.0, 0.0, 0.0),
+        ];
+
+        for state in states {
+            let pendulum = DoublePendulum::new(state, PendulumParams::default());
+            let l = pendulum.lagrangian();
+            let h = pendulum.hamiltonian();
+            let v = pendulum.potential_energy();
+
+            // L = T - V, H = T + V => L + 2V = H
+            assert!((l + 2.0 * v - h).abs() < 1e-4);
+            assert!((h - pendulum.total_energy()).abs() < 1e-10);
+        }
+    }
+
+    #[test]
+    fn test_minimum_potential_energy() {
+        let params = PendulumParams::new(2.0, 1.0, 1.0, 1.5, 10.0, 0.0);
+        let hanging = PendulumState::at_rest(0.0, 0.0);
+        let minimum = params.minimum_potential_energy();
+        assert!((hanging.potential_energy(&params) - minimum).abs() < 1e-4);
+        assert!(PendulumState::at_rest(0.4, -1.0).potential_energy(&params) > minimum);
+    }
+
+    #[test]
+    fn test_flip_energy_threshold() {
+        let params = PendulumParams::new(2.0, 1.0, 1.0, 1.5, 10.0, 0.0);
+        assert!((params.flip_energy_threshold() - (15.0 - 30.0)).abs() < 1e-9);
+
+        // 阈值等于下摆竖直向上静止时的最低能量
+        let flipped = PendulumState::at_rest(0.0, consts::PI);
+        let energy = flipped.total_energy(&params);
+        assert!((energy - params.flip_energy_threshold()).abs() < 1e-4);
+        for theta1 in [-0.5, 0.3, 1.0] {
+            let state = PendulumState::at_rest(theta1, consts::PI);
+            assert!(state.total_energy(&params) > params.flip_energy_threshold());
+        }
+    }
+
     #[test]
     fn test_parameter_validation() {
         let valid_params = PendulumParams::default();
@@ -284,18 +995,76 @@ mod tests {
 
         let invalid_params = PendulumParams::new(-1.0, 1.0, 1.0, 1.0, 9.81, 0.0);
         assert!(invalid_params.validate().is_err());
+
+        // 允许无重力，但重力不能为负（倒置重力用 inverted_gravity 表示）
+        assert!(PendulumParams::new(1.0, 1.0, 1.0, 1.0, 0.0, 0.0).validate().is_ok());
+        assert!(PendulumParams::new(1.0, 1.0, 1.0, 1.0, -9.81, 0.0).validate().is_err());
     }
 
     #[test]
     fn test_angle_normalization() {
         assert!((normalize_angle(0.0) - 0.0).abs() < 1e-10);
-        assert!((normalize_angle(std::f64::consts::PI) - std::f64::consts::PI).abs() < 1e-10);
-        assert!((normalize_angle(-std::f64::consts::PI) - (-std::f64::consts::PI)).abs() < 1e-10);
+        assert!((normalize_angle(consts::PI) - consts::PI).abs() < 1e-10);
+        assert!((normalize_angle(-consts::PI) - (-consts::PI)).abs() < 1e-10);
 
         // 测试大角度
-        let big_angle = 3.0 * std::f64::consts::PI;
+        let big_angle = 3.0 * consts::PI;
         let normalized = normalize_angle(big_angle);
-        assert!(normalized > -std::f64::consts::PI && normalized <= std::f64::consts::PI);
+        assert!(normalized > -consts::PI && normalized <= consts::PI);
+    }
+
+    #[test]
+    fn test_pivot_frame_energy() {
+        let params = PendulumParams::default();
+        let state = PendulumState::new(0.8, -1.3, 0.4, 2.0);
+
+        // 静止悬挂点时与实验室系能量一致
+        let lab = state.total_energy(&params);
+        assert!((state.pivot_frame_energy(&params, (0.0, 0.0)) - lab).abs() < 1e-6);
+
+        // 悬挂点向上加速 a 等效于重力变为 g + a
+        let a = 2.0;
+        let heavier = PendulumParams { g: params.g + a, ..params };
+        let expected = state.total_energy(&heavier);
+        assert!((state.pivot_frame_energy(&params, (0.0, a)) - expected).abs() < 1e-4);
+
+        // 整体摆按当前时刻的悬挂点加速度计算
+        let mut pendulum = DoublePendulum::new(state, params);
+        pendulum.params.pivot_amplitude = 0.05;
+        pendulum.params.pivot_frequency = 40.0;
+        pendulum.time = 0.3;
+        let acceleration = pendulum.params.pivot_acceleration(0.3);
+        assert!(acceleration.abs() > 1.0);
+        let expected = state.pivot_frame_energy(&pendulum.params, (0.0, acceleration));
+        assert_eq!(pendulum.pivot_frame_energy(), expected);
+    }
+
+    #[test]
+    fn test_dissipated_power() {
+        let state = PendulumState::new(0.3, -0.2, 1.5, -2.0);
+        let undamped = DoublePendulum::new(state, PendulumParams::default());
+        assert_eq!(undamped.dissipated_power(), 0.0);
+
+        let params = PendulumParams::new(1.0, 1.0, 1.0, 1.0, 9.81, 0.2);
+        let damped = DoublePendulum::new(state, params);
+        assert!((damped.dissipated_power() - 0.2 * 6.25).abs() < 1e-6);
+        let at_rest = DoublePendulum::new(PendulumState::at_rest(0.3, -0.2), params);
+        assert_eq!(at_rest.dissipated_power(), 0.0);
+    }
+
+    #[test]
+    fn test_angular_momentum_of_rigid_rotation() {
+        // 两臂伸直成一线整体转动时，L = Iω，I = m₁l₁² + m₂(l₁+l₂)²
+        let params = PendulumParams::new(1.5, 0.5, 1.0, 2.0, 9.81, 0.0);
+        let omega = 0.8;
+        let state = PendulumState::new(0.4, 0.4, omega, omega);
+        let inertia = 1.5 * 1.0 + 0.5 * 3.0 * 3.0;
+        assert!((state.angular_momentum(&params) - inertia * omega).abs() < 1e-5);
+
+        // 方向相反时角动量变号，静止时为零
+        let reversed = PendulumState::new(0.4, 0.4, -omega, -omega);
+        assert!((reversed.angular_momentum(&params) + inertia * omega).abs() < 1e-5);
+        assert_eq!(PendulumState::at_rest(0.4, -1.0).angular_momentum(&params), 0.0);
     }
 
     #[test]
@@ -312,4 +1081,142 @@ mod tests {
         let energy = pendulum.total_energy();
         assert!(energy < 0.0); // 由于位于参考点下方
     }
+
+    #[test]
+    fn test_angle_convention_round_trip() {
+        let state = PendulumState::new(0.4, -1.1, 0.3, -0.7);
+        let conventions = [
+            AngleConvention::AbsoluteFromDown,
+            AngleConvention::RelativeToUpperArm,
+            AngleConvention::AbsoluteFromUp,
+        ];
+        for convention in conventions {
+            let back = convention.to_internal(convention.to_stored(state));
+            assert!((back.theta1 - state.theta1).abs() < 1e-6);
+            assert!((back.theta2 - state.theta2).abs() < 1e-6);
+            assert!((back.omega1 - state.omega1).abs() < 1e-6);
+            assert!((back.omega2 - state.omega2).abs() < 1e-6);
+        }
+
+        // 两种约定下描述的是同一个几何位置
+        let relative = AngleConvention::RelativeToUpperArm.to_stored(state);
+        assert!((relative.theta2 - (-1.5)).abs() < 1e-6);
+        let from_up = AngleConvention::AbsoluteFromUp.to_internal(PendulumState::at_rest(0.0, 0.5));
+        let (x, y) = from_up.get_mass1_position(1.0);
+        assert!(x.abs() < 1e-6 && (y - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_normal_modes_equal_masses_and_lengths() {
+        // 等质量等摆长时 ω² = (g/l)(2 ∓ √2)，振型 θ₂/θ₁ = ±√2
+        let params = PendulumParams::new(1.0, 1.0, 1.5, 1.5, 9.81, 0.0);
+        let (slow, fast) = params.normal_mode_frequencies();
+        let base = 9.81 / 1.5;
+        let sqrt2 = std::f64::consts::SQRT_2;
+        assert!((slow - (base * (2.0 - sqrt2)).sqrt()).abs() < 1e-4);
+        assert!((fast - (base * (2.0 + sqrt2)).sqrt()).abs() < 1e-4);
+
+        let (slow_shape, fast_shape) = params.normal_mode_shapes();
+        assert!((slow_shape - sqrt2).abs() < 1e-4);
+        assert!((fast_shape + sqrt2).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_stiffness_estimate_and_suggested_dt() {
+        // 默认参数：快模频率 √((g/l)(2 + √2))
+        let params = PendulumParams::default();
+        let expected = (9.81 * (2.0 + std::f64::consts::SQRT_2)).sqrt();
+        assert!((params.stiffness_estimate() - expected).abs() < 1e-4);
+        assert_eq!(params.suggested_max_dt(), Some(0.003));
+
+        // 短而重的下摆配合大重力：刚性大得多，建议步长更小
+        let stiff = PendulumParams::new(0.1, 5.0, 1.0, 0.1, 20.0, 0.0);
+        assert!(stiff.stiffness_estimate() > 10.0 * params.stiffness_estimate());
+        let suggested = stiff.suggested_max_dt().unwrap();
+        assert!(suggested < 0.001);
+        assert!(suggested * stiff.stiffness_estimate() <= STIFFNESS_DT_FACTOR);
+
+        // 没有重力时没有固有频率，不给建议
+        let weightless = PendulumParams::new(1.0, 1.0, 1.0, 1.0, 0.0, 0.0);
+        assert_eq!(weightless.suggested_max_dt(), None);
+    }
+
+    #[test]
+    fn test_floor_to_one_significant_digit() {
+        assert_eq!(floor_to_one_significant_digit(0.00086), 0.0008);
+        assert_eq!(floor_to_one_significant_digit(0.0034), 0.003);
+        assert_eq!(floor_to_one_significant_digit(0.02), 0.02);
+        assert_eq!(floor_to_one_significant_digit(7.9), 7.0);
+    }
+
+    #[test]
+    fn test_json_round_trip_restores_state_params_and_time() {
+        let params = PendulumParams {
+            m2: 2.5,
+            coulomb_friction: 0.1,
+            ..PendulumParams::default()
+        };
+        let mut pendulum = DoublePendulum::new(PendulumState::new(1.0, -2.0, 0.3, 4.0), params);
+        for _ in 0..1234 {
+            pendulum.advance_time(0.001);
+        }
+
+        let mut restored = DoublePendulum::from_json(&pendulum.to_json().unwrap()).unwrap();
+        assert_eq!(restored.state, pendulum.state);
+        assert_eq!(restored.params, pendulum.params);
+        assert_eq!(restored.time, pendulum.time);
+
+        // 恢复后从保存的时间继续计时
+        pendulum.advance_time(0.001);
+        restored.advance_time(0.001);
+        assert!((restored.time - pendulum.time).abs() < 1e-6);
+
+        assert!(DoublePendulum::from_json("{ \"state\": 1 }").is_err());
+    }
+
+    #[test]
+    fn test_json_state_converts_convention() {
+        let text = r#"{
+            "state": { "theta1": 0.5, "theta2": 0.25, "omega1": 1.0, "omega2": -2.0 },
+            "params": { "m1": 1.0, "m2": 1.0, "l1": 1.0, "l2": 1.0, "g": 9.81, "damping": 0.0 },
+            "time": 2.0,
+            "convention": "relative_to_upper_arm"
+        }"#;
+        let pendulum = DoublePendulum::from_json(text).unwrap();
+        assert!((pendulum.state.theta1 - 0.5).abs() < 1e-6);
+        assert!((pendulum.state.theta2 - 0.75).abs() < 1e-6);
+        assert!((pendulum.state.omega2 - (-1.0)).abs() < 1e-6);
+        assert_eq!(pendulum.time, 2.0);
+
+        // 未注明约定的旧文件按内部约定读取，保存时注明内部约定
+        let legacy = text.replace(r#""convention": "relative_to_upper_arm""#, r#""note": "old""#);
+        let pendulum = DoublePendulum::from_json(&legacy).unwrap();
+        assert!((pendulum.state.theta2 - 0.25).abs() < 1e-6);
+        assert!(pendulum.to_json().unwrap().contains("absolute_from_down"));
+    }
+
+    #[test]
+    fn test_advance_time_is_exact_after_many_steps() {
+        let dt: Real = 0.001;
+        let steps = 1_000_000u64;
+        let mut pendulum =
+            DoublePendulum::new(PendulumState::at_rest(0.0, 0.0), PendulumParams::default());
+        let mut naive: Real = 0.0;
+        for _ in 0..steps {
+            pendulum.advance_time(dt);
+            naive += dt;
+        }
+
+        let exact = (steps as f64 * real_to_f64(dt)) as Real;
+        assert_eq!(pendulum.time, exact);
+        // 逐步累加会产生舍入漂移
+        assert_ne!(naive, exact);
+
+        // 直接设置时间后从新的时间继续计数，改变步长也不丢失已走过的时间
+        pendulum.time = 2.0;
+        pendulum.advance_time(0.5);
+        assert_eq!(pendulum.time, 2.5);
+        pendulum.advance_time(0.25);
+        assert_eq!(pendulum.time, 2.75);
+    }
 }
@@ -1,18 +1,59 @@
 /// 物理引擎模块
 /// 实现双摆的动力学方程和数值积分
-use crate::pendulum::{PendulumParams, PendulumState};
+use crate::pendulum::{normalize_angle, real_to_f64, PendulumParams, PendulumState, Real};
+
+/// 允许手动输入的最小时间步长
+pub const MIN_TIME_STEP: Real = 1e-5;
+
+/// 允许手动输入的最大时间步长
+pub const MAX_TIME_STEP: Real = 0.05;
+
+/// 自适应积分允许的单个子步局部误差（状态分量的最大差值）
+pub const ADAPTIVE_TOLERANCE: Real = 1e-5;
+
+/// 界面上允许设置的自适应容差范围
+pub const MIN_ADAPTIVE_TOLERANCE: Real = 1e-9;
+pub const MAX_ADAPTIVE_TOLERANCE: Real = 1e-2;
+
+/// 自适应积分最多把步长减半的次数
+const MAX_ADAPTIVE_HALVINGS: u32 = 10;
+
+/// 库仑摩擦的最小静止死区（rad/s）：角速度低于死区时按静摩擦判断是否保持静止
+const MIN_COULOMB_DEADBAND: Real = 1e-3;
+
+/// 自适应积分一步的结果，用于在界面上显示步长调整情况
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AdaptiveStep {
+    /// 积分完整个时间步长后的状态
+    pub state: PendulumState,
+    /// 最终采用的子步长
+    pub accepted_dt: Real,
+    /// 采用该子步长时的局部误差估计（一个整步与两个半步之差）
+    pub error_estimate: Real,
+    /// 尝试过的子步长个数（包括被拒绝的）
+    pub iterations: u32,
+}
+
+/// RK4 一步中的四个中间阶段，用于诊断积分器
+#[derive(Clone, Copy, Debug)]
+pub struct Rk4Stages {
+    /// 各阶段求导时使用的状态（第一个即起始状态）
+    pub states: [PendulumState; 4],
+    /// 各阶段的导数 k1..k4
+    pub derivatives: [StateDerivative; 4],
+}
 
 /// 双摆的动力学方程导数
 #[derive(Clone, Copy, Debug)]
 pub struct StateDerivative {
-    pub dtheta1: f64,
-    pub dtheta2: f64,
-    pub domega1: f64,
-    pub domega2: f64,
+    pub dtheta1: Real,
+    pub dtheta2: Real,
+    pub domega1: Real,
+    pub domega2: Real,
 }
 
 impl StateDerivative {
-    pub fn new(dtheta1: f64, dtheta2: f64, domega1: f64, domega2: f64) -> Self {
+    pub fn new(dtheta1: Real, dtheta2: Real, domega1: Real, domega2: Real) -> Self {
         Self {
             dtheta1,
             dtheta2,
@@ -22,7 +63,7 @@ impl StateDerivative {
     }
 
     /// 将导数与标量相乘
-    pub fn mul_scalar(&self, scalar: f64) -> Self {
+    pub fn mul_scalar(&self, scalar: Real) -> Self {
         Self {
             dtheta1: self.dtheta1 * scalar,
             dtheta2: self.dtheta2 * scalar,
@@ -42,26 +83,142 @@ impl StateDerivative {
     }
 }
 
+/// 数值积分方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntegratorKind {
+    /// 半隐式欧拉，一阶
+    Euler,
+    /// 固定步长四阶Runge-Kutta（可选能量误差修正）
+    Rk4,
+    /// 按局部误差细分步长的RK4
+    Adaptive,
+}
+
+impl IntegratorKind {
+    /// 所有积分方式
+    pub const ALL: [IntegratorKind; 3] =
+        [IntegratorKind::Euler, IntegratorKind::Rk4, IntegratorKind::Adaptive];
+
+    /// 显示名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            IntegratorKind::Euler => "Euler",
+            IntegratorKind::Rk4 => "RK4",
+            IntegratorKind::Adaptive => "RK4 adaptive",
+        }
+    }
+}
+
 /// 物理引擎
+#[derive(Clone, Debug)]
 pub struct PhysicsEngine {
     /// 时间步长
-    dt: f64,
+    dt: Real,
+    /// 能量误差过大时是否用半步长重新积分
+    error_correction: bool,
+    /// 每步后是否把角度标准化到 [-π, π]（关闭时保留连续的展开角度）
+    normalize_after_step: bool,
+    /// 积分方式
+    integrator: IntegratorKind,
+    /// 自适应积分允许的子步局部误差
+    adaptive_tolerance: Real,
+    /// 下一步开始时的模拟时间，用于计算驱动力矩
+    time: Real,
 }
 
 impl PhysicsEngine {
     /// 创建新的物理引擎
-    pub fn new(dt: f64) -> Self {
-        Self { dt }
+    pub fn new(dt: Real) -> Self {
+        Self {
+            dt,
+            error_correction: true,
+            normalize_after_step: true,
+            integrator: IntegratorKind::Rk4,
+            adaptive_tolerance: ADAPTIVE_TOLERANCE,
+            time: 0.0,
+        }
     }
 
     /// 设置时间步长
-    pub fn set_dt(&mut self, dt: f64) {
-        self.dt = dt.max(1e-12); // 防止时间步长过小
+    pub fn set_dt(&mut self, dt: Real) {
+        self.dt = dt.max(1e-6); // 防止时间步长过小
+    }
+
+    /// 获取当前生效的时间步长
+    pub fn dt(&self) -> Real {
+        self.dt
     }
 
-    /// 高级步进函数 - 自适应积分器并验证能量守恒
-    pub fn step(&self, state: &PendulumState, params: &PendulumParams) -> (PendulumState, f64) {
-        let initial_energy = state.total_energy(params);
+    /// 是否启用能量误差修正
+    pub fn error_correction(&self) -> bool {
+        self.error_correction
+    }
+
+    /// 设置是否启用能量误差修正
+    pub fn set_error_correction(&mut self, enabled: bool) {
+        self.error_correction = enabled;
+    }
+
+    /// 每步后是否标准化角度
+    pub fn normalize_after_step(&self) -> bool {
+        self.normalize_after_step
+    }
+
+    /// 设置每步后是否标准化角度
+    pub fn set_normalize_after_step(&mut self, enabled: bool) {
+        self.normalize_after_step = enabled;
+    }
+
+    /// 设置下一步开始时的模拟时间（驱动力矩的相位由它决定）
+    pub fn set_time(&mut self, time: Real) {
+        self.time = time;
+    }
+
+    /// 步长为 dt、起始时间为 time 的同设置引擎，用于细分步长
+    fn sub_engine(&self, dt: Real, time: Real) -> PhysicsEngine {
+        let mut engine = PhysicsEngine::new(dt);
+        engine.set_normalize_after_step(self.normalize_after_step);
+        engine.set_time(time);
+        engine
+    }
+
+    /// 当前积分方式
+    pub fn integrator(&self) -> IntegratorKind {
+        self.integrator
+    }
+
+    /// 设置积分方式
+    pub fn set_integrator(&mut self, integrator: IntegratorKind) {
+        self.integrator = integrator;
+    }
+
+    /// 是否使用自适应步长
+    pub fn adaptive(&self) -> bool {
+        self.integrator == IntegratorKind::Adaptive
+    }
+
+    /// 自适应积分的局部误差容差
+    pub fn adaptive_tolerance(&self) -> Real {
+        self.adaptive_tolerance
+    }
+
+    /// 设置自适应积分的容差（限制在允许范围内）
+    pub fn set_adaptive_tolerance(&mut self, tolerance: Real) {
+        self.adaptive_tolerance = tolerance.clamp(MIN_ADAPTIVE_TOLERANCE, MAX_ADAPTIVE_TOLERANCE);
+    }
+
+    /// 高级步进函数 - 按所选积分方式推进一步并验证能量守恒
+    pub fn step(&self, state: &PendulumState, params: &PendulumParams) -> (PendulumState, Real) {
+        let initial_energy = self.step_energy(state, params);
+
+        match self.integrator {
+            IntegratorKind::Adaptive => {
+                let (result, energy_error) = self.step_adaptive(state, params);
+                return (result.state, energy_error);
+            }
+            IntegratorKind::Euler => return self.step_euler(state, params),
+            IntegratorKind::Rk4 => {}
+        }
 
         // 检查状态是否有效（防止NaN或无穷大）
         if !self.is_state_valid(state) {
@@ -70,38 +227,188 @@ impl PhysicsEngine {
 
         // 使用改进的RK4积分
         let new_state = self.integrate_rk4_robust(state, params);
-        let final_energy = new_state.total_energy(params);
+        let final_energy = self.step_energy(&new_state, params);
 
         // 计算能量误差（用于监控数值精度）
-        let energy_error = if initial_energy.abs() > 1e-12 {
-            (final_energy - initial_energy).abs() / initial_energy.abs()
-        } else {
-            (final_energy - initial_energy).abs()
-        };
+        let energy_error = relative_energy_error(initial_energy, final_energy);
 
         // 如果能量误差过大，尝试使用更小的步长
-        if energy_error > 1e-3 {
-            let smaller_engine = PhysicsEngine::new(self.dt * 0.5);
-            let intermediate_state = smaller_engine.integrate_rk4_robust(state, params);
-            let final_state = smaller_engine.integrate_rk4_robust(&intermediate_state, params);
-            let corrected_energy = final_state.total_energy(params);
-            let corrected_error = if initial_energy.abs() > 1e-12 {
-                (corrected_energy - initial_energy).abs() / initial_energy.abs()
-            } else {
-                (corrected_energy - initial_energy).abs()
-            };
-            (final_state, corrected_error)
+        if self.error_correction && energy_error > 1e-3 {
+            let half_dt = self.dt * 0.5;
+            let intermediate_state =
+                self.sub_engine(half_dt, self.time).integrate_rk4_robust(state, params);
+            let mut final_state = self
+                .sub_engine(half_dt, self.time + half_dt)
+                .integrate_rk4_robust(&intermediate_state, params);
+            self.settle_stuck_joints(&mut final_state, params);
+            let corrected_energy = self.step_energy(&final_state, params);
+            (final_state, relative_energy_error(initial_energy, corrected_energy))
         } else {
+            let mut new_state = new_state;
+            self.settle_stuck_joints(&mut new_state, params);
             (new_state, energy_error)
         }
     }
 
+    /// 欧拉步进，返回新状态和能量误差（不做误差修正）
+    fn step_euler(&self, state: &PendulumState, params: &PendulumParams) -> (PendulumState, Real) {
+        if !self.is_state_valid(state) {
+            return (*state, 1.0);
+        }
+        let mut new_state = self.integrate_euler(state, params);
+        self.settle_stuck_joints(&mut new_state, params);
+        let energy_error = relative_energy_error(
+            self.step_energy(state, params),
+            self.step_energy(&new_state, params),
+        );
+        (new_state, energy_error)
+    }
+
+    /// 衡量一步能量误差所用的能量
+    /// 悬挂点振动时实验室系能量不守恒，改用随悬挂点运动参考系中的能量，
+    /// 两端都取步长中点的悬挂点加速度，使显式时间依赖带来的变化降到 O(dt³)
+    fn step_energy(&self, state: &PendulumState, params: &PendulumParams) -> Real {
+        if params.pivot_amplitude == 0.0 {
+            return state.total_energy(params);
+        }
+        let acceleration = params.pivot_acceleration(self.time + self.dt * 0.5);
+        state.pivot_frame_energy(params, (0.0, acceleration))
+    }
+
+    /// 库仑摩擦下被静摩擦卡住的关节（死区内且角加速度为0）角速度直接置0，使摆在有限时间内停止
+    fn settle_stuck_joints(&self, state: &mut PendulumState, params: &PendulumParams) {
+        if params.coulomb_friction <= 0.0 {
+            return;
+        }
+        let derivative = self.derivatives_at(state, params, self.time + self.dt);
+        let deadband = self.coulomb_deadband(params);
+        if state.omega1.abs() < deadband[0] && derivative.domega1 == 0.0 {
+            state.omega1 = 0.0;
+        }
+        if state.omega2.abs() < deadband[1] && derivative.domega2 == 0.0 {
+            state.omega2 = 0.0;
+        }
+    }
+
+    /// 两个关节的库仑摩擦死区：至少能容纳摩擦在一步内造成的角速度变化，避免在0附近来回抖动
+    fn coulomb_deadband(&self, params: &PendulumParams) -> [Real; 2] {
+        let inertia1 = (params.m1 + params.m2) * params.l1 * params.l1;
+        let inertia2 = params.m2 * params.l2 * params.l2;
+        let per_step = 2.0 * params.coulomb_friction * self.dt;
+        [
+            (per_step / inertia1).max(MIN_COULOMB_DEADBAND),
+            (per_step / inertia2).max(MIN_COULOMB_DEADBAND),
+        ]
+    }
+
+    /// 自适应步进，返回积分结果和能量误差
+    pub fn step_adaptive(
+        &self,
+        state: &PendulumState,
+        params: &PendulumParams,
+    ) -> (AdaptiveStep, Real) {
+        // 无效状态不积分，返回原状态和高误差
+        if !self.is_state_valid(state) {
+            let result = AdaptiveStep {
+                state: *state,
+                accepted_dt: self.dt,
+                error_estimate: Real::INFINITY,
+                iterations: 0,
+            };
+            return (result, 1.0);
+        }
+
+        let mut result = self.integrate_adaptive(state, params, self.adaptive_tolerance);
+        self.settle_stuck_joints(&mut result.state, params);
+        let energy_error = relative_energy_error(
+            self.step_energy(state, params),
+            self.step_energy(&result.state, params),
+        );
+        (result, energy_error)
+    }
+
+    /// 用 Benettin 算法估计最大李雅普诺夫指数（1/s）
+    /// 与主轨迹并行积分一条初始偏离极小的影子轨迹，每 renorm_interval 步把两者的相空间距离
+    /// 缩放回初始值，指数为距离增长倍数的对数之和除以经过的时间
+    pub fn lyapunov_benettin(
+        &self,
+        initial: &PendulumState,
+        params: &PendulumParams,
+        steps: usize,
+        renorm_interval: usize,
+    ) -> f64 {
+        // 取浮点精度的平方根，既远小于运动尺度又不被舍入误差淹没
+        let separation = Real::EPSILON.sqrt();
+        let renorm_interval = renorm_interval.max(1);
+        let mut engine = self.clone();
+        let mut state = *initial;
+        let mut shadow = *initial;
+        shadow.theta1 += separation;
+        let mut log_growth = 0.0;
+        let mut renormalized_steps = 0;
+
+        for step in 0..steps {
+            engine.set_time(self.time + step as Real * self.dt);
+            state = engine.step(&state, params).0;
+            shadow = engine.step(&shadow, params).0;
+            if !(step + 1).is_multiple_of(renorm_interval) {
+                continue;
+            }
+            // 角度差取最短方向，避免 ±π 处的跳变被当作分离
+            let offset = [
+                normalize_angle(shadow.theta1 - state.theta1),
+                normalize_angle(shadow.theta2 - state.theta2),
+                shadow.omega1 - state.omega1,
+                shadow.omega2 - state.omega2,
+            ];
+            let distance = offset.iter().map(|d| d * d).sum::<Real>().sqrt();
+            if !distance.is_finite() || distance == 0.0 {
+                break;
+            }
+            log_growth += real_to_f64(distance / separation).ln();
+            renormalized_steps = step + 1;
+            let scale = separation / distance;
+            shadow = PendulumState::new(
+                state.theta1 + offset[0] * scale,
+                state.theta2 + offset[1] * scale,
+                state.omega1 + offset[2] * scale,
+                state.omega2 + offset[3] * scale,
+            );
+        }
+
+        if renormalized_steps == 0 {
+            return 0.0;
+        }
+        log_growth / (renormalized_steps as f64 * real_to_f64(self.dt))
+    }
+
+    /// 从给定状态连续步进指定步数，返回最终状态
+    pub fn advance(&self, state: &PendulumState, params: &PendulumParams, steps: usize) -> PendulumState {
+        let mut engine = self.clone();
+        let mut current = *state;
+        for step in 0..steps {
+            engine.set_time(self.time + step as Real * self.dt);
+            current = engine.step(&current, params).0;
+        }
+        current
+    }
+
     /// 计算双摆系统的导数（动力学方程）
     /// 使用标准的Lagrange方程推导
     pub fn compute_derivatives(
         &self,
         state: &PendulumState,
         params: &PendulumParams,
+    ) -> StateDerivative {
+        self.derivatives_at(state, params, self.time)
+    }
+
+    /// 计算 time 时刻的状态导数（驱动力矩随时间变化）
+    fn derivatives_at(
+        &self,
+        state: &PendulumState,
+        params: &PendulumParams,
+        time: Real,
     ) -> StateDerivative {
         let theta1 = state.theta1;
         let theta2 = state.theta2;
@@ -112,7 +419,8 @@ impl PhysicsEngine {
         let m2 = params.m2;
         let l1 = params.l1;
         let l2 = params.l2;
-        let g = params.g;
+        // 悬挂点振动时在其参考系中表现为随时间变化的重力
+        let g = params.gravity_at(time);
         let damping = params.damping;
 
         // 角度差
@@ -126,18 +434,17 @@ impl PhysicsEngine {
         let m12 = m2 * l1 * l2 * cos_delta;
         let m22 = m2 * l2 * l2;
 
-        // 科里奥利和离心力项 - 修正计算
-        let c1 = -m2 * l1 * l2 * omega2 * omega2 * sin_delta
-            - m2 * l1 * l2 * omega2 * omega1 * sin_delta;
+        // 离心力项（Lagrange方程中不含 omega1*omega2 交叉项）
+        let c1 = -m2 * l1 * l2 * omega2 * omega2 * sin_delta;
         let c2 = m2 * l1 * l2 * omega1 * omega1 * sin_delta;
 
-        // 重力项（theta=0为垂直向下，重力提供回复力矩）
-        let g1 = -(m1 + m2) * g * l1 * theta1.sin();
+        // 重力项（theta=0为垂直向下，重力提供回复力矩），上关节另加驱动力矩
+        let g1 = -(m1 + m2) * g * l1 * theta1.sin() + params.drive_torque(time);
         let g2 = -m2 * g * l2 * theta2.sin();
 
         // 阻尼项
-        let d1 = -damping * omega1;
-        let d2 = -damping * omega2;
+        let d1 = params.drag_model.torque(damping, omega1);
+        let d2 = params.drag_model.torque(damping, omega2);
 
         // 右侧项
         let rhs1 = c1 + g1 + d1;
@@ -157,9 +464,23 @@ impl PhysicsEngine {
             det
         };
 
-        // 求解角加速度 (逆矩阵乘法)
-        let alpha1 = (m22 * rhs1 - m12 * rhs2) / det;
-        let alpha2 = (m11 * rhs2 - m12 * rhs1) / det;
+        // 求解角加速度 (逆矩阵乘法)，有库仑摩擦时按静摩擦/滑动摩擦分别处理
+        let (alpha1, alpha2) = if params.coulomb_friction > 0.0 {
+            let [alpha1, alpha2] = coulomb_accelerations(
+                [[m11, m12], [m12, m22]],
+                det,
+                [rhs1, rhs2],
+                [omega1, omega2],
+                params.coulomb_friction,
+                self.coulomb_deadband(params),
+            );
+            (alpha1, alpha2)
+        } else {
+            (
+                (m22 * rhs1 - m12 * rhs2) / det,
+                (m11 * rhs2 - m12 * rhs1) / det,
+            )
+        };
 
         // 检查结果是否有效
         if !alpha1.is_finite() || !alpha2.is_finite() {
@@ -169,6 +490,18 @@ impl PhysicsEngine {
         }
     }
 
+    /// 计算当前状态下悬挂点提供的约束力
+    pub fn pivot_force(&self, state: &PendulumState, params: &PendulumParams) -> (Real, Real) {
+        let derivative = self.compute_derivatives(state, params);
+        pivot_force(state, params, &derivative, params.gravity_at(self.time))
+    }
+
+    /// 计算当前状态下两根摆杆的张力
+    pub fn rod_tensions(&self, state: &PendulumState, params: &PendulumParams) -> (Real, Real) {
+        let derivative = self.compute_derivatives(state, params);
+        rod_tensions(state, params, &derivative, params.gravity_at(self.time))
+    }
+
     /// 检查状态是否有效
     fn is_state_valid(&self, state: &PendulumState) -> bool {
         state.theta1.is_finite() && state.theta2.is_finite() 
@@ -176,20 +509,57 @@ impl PhysicsEngine {
             && state.omega1.abs() < 1000.0 && state.omega2.abs() < 1000.0 // 防止角速度过大
     }
 
-    /// 使用欧拉方法进行数值积分（简单但精度较低）
-    #[allow(dead_code)]
+    /// 使用半隐式（辛）欧拉方法进行数值积分（简单但精度较低）
+    /// 先更新角速度，再用新的角速度更新角度
     pub fn integrate_euler(&self, state: &PendulumState, params: &PendulumParams) -> PendulumState {
         let dt = self.dt;
         let derivative = self.compute_derivatives(state, params);
 
-        let mut new_state = self.add_scaled_derivative(state, &derivative, dt);
-        new_state.normalize_angles();
+        let omega1 = state.omega1 + derivative.domega1 * dt;
+        let omega2 = state.omega2 + derivative.domega2 * dt;
+        let mut new_state = PendulumState::new(
+            state.theta1 + omega1 * dt,
+            state.theta2 + omega2 * dt,
+            omega1,
+            omega2,
+        );
+        if self.normalize_after_step {
+            new_state.normalize_angles();
+        }
 
         new_state
     }
 
     /// 使用Runge-Kutta 4阶方法进行数值积分
     pub fn integrate_rk4(&self, state: &PendulumState, params: &PendulumParams) -> PendulumState {
+        let [k1, k2, k3, k4] = self.rk4_stages(state, params).derivatives;
+
+        // y_{n+1} = y_n + dt/6 * (k1 + 2*k2 + 2*k3 + k4)
+        let k_combined = k1
+            .add(&k2.mul_scalar(2.0))
+            .add(&k3.mul_scalar(2.0))
+            .add(&k4);
+
+        let mut new_state = self.add_scaled_derivative(state, &k_combined, self.dt / 6.0);
+
+        // 标准化角度到 [-π, π] 范围
+        if self.normalize_after_step {
+            new_state.normalize_angles();
+        }
+
+        new_state
+    }
+
+    /// 当前积分方式的简短名称
+    pub fn integrator_label(&self) -> &'static str {
+        match self.integrator {
+            IntegratorKind::Rk4 if self.error_correction => "RK4+corr",
+            kind => kind.label(),
+        }
+    }
+
+    /// 计算 RK4 一步的四个中间状态和导数，不组合结果
+    pub fn rk4_stages(&self, state: &PendulumState, params: &PendulumParams) -> Rk4Stages {
         let dt = self.dt;
 
         // k1 = f(t, y)
@@ -197,28 +567,20 @@ impl PhysicsEngine {
 
         // k2 = f(t + dt/2, y + dt/2 * k1)
         let state2 = self.add_scaled_derivative(state, &k1, dt / 2.0);
-        let k2 = self.compute_derivatives(&state2, params);
+        let k2 = self.derivatives_at(&state2, params, self.time + dt / 2.0);
 
         // k3 = f(t + dt/2, y + dt/2 * k2)
         let state3 = self.add_scaled_derivative(state, &k2, dt / 2.0);
-        let k3 = self.compute_derivatives(&state3, params);
+        let k3 = self.derivatives_at(&state3, params, self.time + dt / 2.0);
 
         // k4 = f(t + dt, y + dt * k3)
         let state4 = self.add_scaled_derivative(state, &k3, dt);
-        let k4 = self.compute_derivatives(&state4, params);
-
-        // y_{n+1} = y_n + dt/6 * (k1 + 2*k2 + 2*k3 + k4)
-        let k_combined = k1
-            .add(&k2.mul_scalar(2.0))
-            .add(&k3.mul_scalar(2.0))
-            .add(&k4);
+        let k4 = self.derivatives_at(&state4, params, self.time + dt);
 
-        let mut new_state = self.add_scaled_derivative(state, &k_combined, dt / 6.0);
-
-        // 标准化角度到 [-π, π] 范围
-        new_state.normalize_angles();
-
-        new_state
+        Rk4Stages {
+            states: [*state, state2, state3, state4],
+            derivatives: [k1, k2, k3, k4],
+        }
     }
 
     /// 使用改进的Runge-Kutta 4阶方法进行数值积分（更鲁棒）
@@ -230,15 +592,15 @@ impl PhysicsEngine {
 
         // k2 = f(t + dt/2, y + dt/2 * k1)
         let state2 = self.add_scaled_derivative_safe(state, &k1, dt / 2.0);
-        let k2 = self.compute_derivatives(&state2, params);
+        let k2 = self.derivatives_at(&state2, params, self.time + dt / 2.0);
 
         // k3 = f(t + dt/2, y + dt/2 * k2)
         let state3 = self.add_scaled_derivative_safe(state, &k2, dt / 2.0);
-        let k3 = self.compute_derivatives(&state3, params);
+        let k3 = self.derivatives_at(&state3, params, self.time + dt / 2.0);
 
         // k4 = f(t + dt, y + dt * k3)
         let state4 = self.add_scaled_derivative_safe(state, &k3, dt);
-        let k4 = self.compute_derivatives(&state4, params);
+        let k4 = self.derivatives_at(&state4, params, self.time + dt);
 
         // y_{n+1} = y_n + dt/6 * (k1 + 2*k2 + 2*k3 + k4)
         let k_combined = k1
@@ -249,7 +611,9 @@ impl PhysicsEngine {
         let mut new_state = self.add_scaled_derivative_safe(state, &k_combined, dt / 6.0);
 
         // 标准化角度到 [-π, π] 范围
-        new_state.normalize_angles();
+        if self.normalize_after_step {
+            new_state.normalize_angles();
+        }
 
         // 应用角速度限制以提高稳定性
         new_state.omega1 = new_state.omega1.clamp(-100.0, 100.0);
@@ -263,7 +627,7 @@ impl PhysicsEngine {
         &self,
         state: &PendulumState,
         derivative: &StateDerivative,
-        scale: f64,
+        scale: Real,
     ) -> PendulumState {
         PendulumState::new(
             state.theta1 + derivative.dtheta1 * scale,
@@ -278,7 +642,7 @@ impl PhysicsEngine {
         &self,
         state: &PendulumState,
         derivative: &StateDerivative,
-        scale: f64,
+        scale: Real,
     ) -> PendulumState {
         let new_theta1 = state.theta1 + derivative.dtheta1 * scale;
         let new_theta2 = state.theta2 + derivative.dtheta2 * scale;
@@ -296,51 +660,58 @@ impl PhysicsEngine {
         }
     }
 
-    /// 自适应步长的Runge-Kutta方法（可选的高级功能）
-    #[allow(dead_code)]
+    /// 自适应步长的Runge-Kutta方法，积分一个完整的时间步长
+    /// 用步长加倍法估计局部误差：不断把子步长减半，直到一个子步与两个半子步的结果足够接近，
+    /// 再用该子步长积分完整个 dt，使模拟时间仍按固定步长推进
     pub fn integrate_adaptive(
         &self,
         state: &PendulumState,
         params: &PendulumParams,
-        tolerance: f64,
-    ) -> (PendulumState, f64) {
-        let mut current_dt = self.dt;
-        let min_dt = 1e-8;
-        let max_dt = 1e-2;
-
-        loop {
-            // 使用当前步长计算一步
-            let engine_full = PhysicsEngine::new(current_dt);
+        tolerance: Real,
+    ) -> AdaptiveStep {
+        let mut substeps: u32 = 1;
+        let mut iterations = 0;
+        let (mut engine, error_estimate) = loop {
+            iterations += 1;
+            // 估计误差时保留展开角度，避免 ±π 处的跳变被当作误差
+            let mut engine_full = self.sub_engine(self.dt / substeps as Real, self.time);
+            engine_full.set_normalize_after_step(false);
             let result_full = engine_full.integrate_rk4(state, params);
 
             // 使用两个半步长计算
-            let engine_half = PhysicsEngine::new(current_dt / 2.0);
+            let half_dt = engine_full.dt / 2.0;
+            let mut engine_half = engine_full.sub_engine(half_dt, self.time);
             let result_half1 = engine_half.integrate_rk4(state, params);
+            engine_half.set_time(self.time + half_dt);
             let result_half2 = engine_half.integrate_rk4(&result_half1, params);
 
-            // 估算误差
             let error = self.estimate_error(&result_full, &result_half2);
-
-            if error < tolerance {
-                // 误差足够小，接受结果
-                if error < tolerance / 10.0 && current_dt < max_dt {
-                    // 误差很小，可以增大步长
-                    current_dt = (current_dt * 1.5).min(max_dt);
-                }
-                return (result_half2, current_dt);
-            } else {
-                // 误差太大，减小步长
-                current_dt = (current_dt * 0.5).max(min_dt);
-                if current_dt <= min_dt {
-                    // 达到最小步长，强制接受结果
-                    return (result_full, current_dt);
-                }
+            if error < tolerance || iterations > MAX_ADAPTIVE_HALVINGS {
+                break (engine_full, error);
             }
+            // 误差太大，减小步长
+            substeps *= 2;
+        };
+
+        let mut new_state = *state;
+        for substep in 0..substeps {
+            engine.set_time(self.time + substep as Real * engine.dt);
+            new_state = engine.integrate_rk4(&new_state, params);
+        }
+        if self.normalize_after_step {
+            new_state.normalize_angles();
+        }
+
+        AdaptiveStep {
+            state: new_state,
+            accepted_dt: engine.dt,
+            error_estimate,
+            iterations,
         }
     }
 
     /// 估算数值误差
-    fn estimate_error(&self, full_step: &PendulumState, half_steps: &PendulumState) -> f64 {
+    fn estimate_error(&self, full_step: &PendulumState, half_steps: &PendulumState) -> Real {
         let error_theta1 = (full_step.theta1 - half_steps.theta1).abs();
         let error_theta2 = (full_step.theta2 - half_steps.theta2).abs();
         let error_omega1 = (full_step.omega1 - half_steps.omega1).abs();
@@ -353,6 +724,191 @@ impl PhysicsEngine {
     }
 }
 
+/// 时间步长精度扫描
+/// 对每个时间步长从相同初始条件积分固定的模拟时长，返回 (dt, 相对能量漂移)
+/// 关闭能量误差修正，测量的是固定步长RK4本身的收敛阶
+pub fn sweep_dt_accuracy(
+    state: &PendulumState,
+    params: &PendulumParams,
+    dts: &[Real],
+    duration: Real,
+) -> Vec<(Real, Real)> {
+    let initial_energy = state.total_energy(params);
+
+    dts.iter()
+        .map(|&dt| {
+            let mut engine = PhysicsEngine::new(dt);
+            engine.set_error_correction(false);
+            let steps = (duration / dt).round() as usize;
+            let final_state = engine.advance(state, params, steps);
+            let final_energy = final_state.total_energy(params);
+
+            let drift = if initial_energy.abs() > 1e-12 {
+                (final_energy - initial_energy).abs() / initial_energy.abs()
+            } else {
+                (final_energy - initial_energy).abs()
+            };
+            (dt, drift)
+        })
+        .collect()
+}
+
+/// 生成对数均匀分布的时间步长序列（包含两端点）
+pub fn log_spaced_dts(min_dt: Real, max_dt: Real, count: usize) -> Vec<Real> {
+    if count < 2 {
+        return vec![min_dt];
+    }
+
+    let log_min = min_dt.log10();
+    let log_max = max_dt.log10();
+    (0..count)
+        .map(|i| {
+            let t = i as Real / (count - 1) as Real;
+            Real::powf(10.0, log_min + (log_max - log_min) * t)
+        })
+        .collect()
+}
+
+/// 两个质点的加速度 ((a1x, a1y), (a2x, a2y))：切向项 + 向心项
+fn bob_accelerations(
+    state: &PendulumState,
+    params: &PendulumParams,
+    derivative: &StateDerivative,
+) -> ((Real, Real), (Real, Real)) {
+    let (sin1, cos1) = state.theta1.sin_cos();
+    let (sin2, cos2) = state.theta2.sin_cos();
+    let omega1_sq = state.omega1 * state.omega1;
+    let omega2_sq = state.omega2 * state.omega2;
+
+    let a1x = params.l1 * (derivative.domega1 * cos1 - omega1_sq * sin1);
+    let a1y = params.l1 * (derivative.domega1 * sin1 + omega1_sq * cos1);
+    let a2x = a1x + params.l2 * (derivative.domega2 * cos2 - omega2_sq * sin2);
+    let a2y = a1y + params.l2 * (derivative.domega2 * sin2 + omega2_sq * cos2);
+    ((a1x, a1y), (a2x, a2y))
+}
+
+/// 计算悬挂点必须提供的约束力 (Fx, Fy)
+/// 由牛顿第二定律：F = m1·a1 + m2·a2 + (m1 + m2)·g（竖直向上支撑重力）
+/// gravity 为悬挂点参考系中的等效重力，悬挂点振动时含其加速度
+pub fn pivot_force(
+    state: &PendulumState,
+    params: &PendulumParams,
+    derivative: &StateDerivative,
+    gravity: Real,
+) -> (Real, Real) {
+    let ((a1x, a1y), (a2x, a2y)) = bob_accelerations(state, params, derivative);
+    let total_mass = params.m1 + params.m2;
+    (
+        params.m1 * a1x + params.m2 * a2x,
+        params.m1 * a1y + params.m2 * a2y + total_mass * gravity,
+    )
+}
+
+/// 计算两根摆杆沿杆方向的张力 (T1, T2)，正值为拉伸，负值为压缩
+/// 下杆对下摆球的力为 m2·(a2 - g)；上杆传递的力即悬挂点约束力
+pub fn rod_tensions(
+    state: &PendulumState,
+    params: &PendulumParams,
+    derivative: &StateDerivative,
+    gravity: Real,
+) -> (Real, Real) {
+    let (_, (a2x, a2y)) = bob_accelerations(state, params, derivative);
+    let (fx, fy) = pivot_force(state, params, derivative, gravity);
+    let (sin1, cos1) = state.theta1.sin_cos();
+    let (sin2, cos2) = state.theta2.sin_cos();
+
+    // 投影到由质点指向杆上端的方向
+    let tension1 = -fx * sin1 + fy * cos1;
+    let tension2 = params.m2 * (-a2x * sin2 + (a2y + gravity) * cos2);
+    (tension1, tension2)
+}
+
+/// 带库仑摩擦时求解角加速度（Karnopp 模型）
+/// 角速度在死区内的关节若所需静摩擦力矩不超过 μ 则保持静止（角加速度恰为0），
+/// 否则以 ±μ 的滑动摩擦开始转动；死区外的关节受 -μ·sign(ω) 的滑动摩擦
+fn coulomb_accelerations(
+    mass: [[Real; 2]; 2],
+    det: Real,
+    rhs: [Real; 2],
+    omega: [Real; 2],
+    mu: Real,
+    deadband: [Real; 2],
+) -> [Real; 2] {
+    let mut stuck = [omega[0].abs() < deadband[0], omega[1].abs() < deadband[1]];
+    let mut friction = [0, 1].map(|i| if stuck[i] { 0.0 } else { -mu * omega[i].signum() });
+
+    // 每轮最多有一个关节由静止转为滑动，循环至多三次
+    loop {
+        // 静止关节的角加速度为0，holding 为保持静止所需的摩擦力矩
+        let (alpha, holding) = match stuck {
+            [true, true] => ([0.0, 0.0], [-rhs[0], -rhs[1]]),
+            [true, false] => {
+                let alpha2 = (rhs[1] + friction[1]) / mass[1][1];
+                ([0.0, alpha2], [mass[0][1] * alpha2 - rhs[0], 0.0])
+            }
+            [false, true] => {
+                let alpha1 = (rhs[0] + friction[0]) / mass[0][0];
+                ([alpha1, 0.0], [0.0, mass[1][0] * alpha1 - rhs[1]])
+            }
+            [false, false] => {
+                let (r1, r2) = (rhs[0] + friction[0], rhs[1] + friction[1]);
+                return [
+                    (mass[1][1] * r1 - mass[0][1] * r2) / det,
+                    (mass[0][0] * r2 - mass[1][0] * r1) / det,
+                ];
+            }
+        };
+        match (0..2).find(|&i| stuck[i] && holding[i].abs() > mu) {
+            Some(i) => {
+                stuck[i] = false;
+                friction[i] = mu * holding[i].signum();
+            }
+            None => return alpha,
+        }
+    }
+}
+
+/// 两个能量之间的相对误差，初始能量接近0时使用绝对误差
+fn relative_energy_error(initial: Real, last: Real) -> Real {
+    if initial.abs() > 1e-12 {
+        (last - initial).abs() / initial.abs()
+    } else {
+        (last - initial).abs()
+    }
+}
+
+/// 无重力时两个守恒量（总能量、绕悬挂点的角动量）相对初始状态的漂移
+pub fn zero_gravity_invariant_drift(
+    initial: &PendulumState,
+    current: &PendulumState,
+    params: &PendulumParams,
+) -> (Real, Real) {
+    (
+        relative_energy_error(initial.total_energy(params), current.total_energy(params)),
+        relative_energy_error(
+            initial.angular_momentum(params),
+            current.angular_momentum(params),
+        ),
+    )
+}
+
+/// 验证手动输入的时间步长：必须为有限正数且在允许范围内
+pub fn validate_time_step(dt: Real) -> Result<Real, String> {
+    if !dt.is_finite() {
+        return Err("时间步长必须为有限值".to_string());
+    }
+    if dt <= 0.0 {
+        return Err("时间步长必须为正数".to_string());
+    }
+    if !(MIN_TIME_STEP..=MAX_TIME_STEP).contains(&dt) {
+        return Err(format!(
+            "时间步长必须在 {} 到 {} 之间",
+            MIN_TIME_STEP, MAX_TIME_STEP
+        ));
+    }
+    Ok(dt)
+}
+
 impl Default for PhysicsEngine {
     fn default() -> Self {
         Self::new(0.0005) // 默认0.5ms时间步长，提高精度
@@ -362,7 +918,9 @@ impl Default for PhysicsEngine {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::pendulum::PendulumParams;
+    use crate::pendulum::{
+        consts, normalize_angle, DragModel, NormalModeSolution, PendulumParams, SMALL_ANGLE_LIMIT,
+    };
 
     #[test]
     fn test_physics_engine_creation() {
@@ -370,6 +928,39 @@ mod tests {
         assert_eq!(engine.dt, 0.01);
     }
 
+    /// 无重力守恒量测试的相对容差（随浮点精度而定）
+    #[cfg(not(feature = "f32-physics"))]
+    const INVARIANT_TOLERANCE: Real = 1e-8;
+    #[cfg(feature = "f32-physics")]
+    const INVARIANT_TOLERANCE: Real = 1e-3;
+
+    #[test]
+    fn test_zero_gravity_conserves_angular_momentum() {
+        let engine = PhysicsEngine::new(0.001);
+        let params = PendulumParams::new(1.0, 2.0, 1.0, 0.7, 0.0, 0.0);
+        let initial = PendulumState::new(0.3, 2.0, 1.5, -4.0);
+
+        let mut state = initial;
+        for _ in 0..20 {
+            state = engine.advance(&state, &params, 500);
+            let (energy_drift, momentum_drift) =
+                zero_gravity_invariant_drift(&initial, &state, &params);
+            assert!(energy_drift < INVARIANT_TOLERANCE, "energy drift {}", energy_drift);
+            assert!(
+                momentum_drift < INVARIANT_TOLERANCE,
+                "angular momentum drift {}",
+                momentum_drift
+            );
+        }
+        // 运动并非静止：两摆之间确实发生了相对转动
+        assert!((state.theta2 - state.theta1 - (initial.theta2 - initial.theta1)).abs() > 0.1);
+
+        // 有重力时角动量不守恒
+        let gravity = PendulumParams::new(1.0, 2.0, 1.0, 0.7, 9.81, 0.0);
+        let state = engine.advance(&initial, &gravity, 500);
+        assert!(zero_gravity_invariant_drift(&initial, &state, &gravity).1 > 1e-3);
+    }
+
     #[test]
     fn test_set_dt() {
         let mut engine = PhysicsEngine::new(0.01);
@@ -381,6 +972,116 @@ mod tests {
         assert!(engine.dt >= 1e-6);
     }
 
+    #[test]
+    fn test_pivot_force_at_rest() {
+        let engine = PhysicsEngine::default();
+        let params = PendulumParams::new(1.0, 2.0, 1.0, 1.0, 9.81, 0.0);
+        let state = PendulumState::at_rest(0.0, 0.0);
+
+        // 静止下垂时悬挂点只支撑总重量
+        let (fx, fy) = engine.pivot_force(&state, &params);
+        assert!(fx.abs() < 1e-6);
+        assert!((fy - 3.0 * 9.81).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_normal_mode_solution_matches_small_oscillation() {
+        let engine = PhysicsEngine::new(0.001);
+        let params = PendulumParams::new(1.0, 1.5, 1.2, 0.8, 9.81, 0.0);
+        let initial = PendulumState::new(0.01, -0.005, 0.0, 0.02);
+        let solution = NormalModeSolution::new(&initial, &params);
+        assert!(solution.max_amplitude() < SMALL_ANGLE_LIMIT);
+
+        let mut state = initial;
+        for step in 1..=3000 {
+            state = engine.step(&state, &params).0;
+            if step % 500 == 0 {
+                let expected = solution.state_at(step as f64 * 0.001);
+                assert!((state.theta1 - expected.theta1).abs() < 1e-4);
+                assert!((state.theta2 - expected.theta2).abs() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rod_tensions() {
+        let engine = PhysicsEngine::default();
+        let params = PendulumParams::new(1.0, 2.0, 1.0, 1.0, 9.81, 0.0);
+
+        // 静止下垂时上杆承受总重量，下杆只承受下摆球重量
+        let (t1, t2) = engine.rod_tensions(&PendulumState::at_rest(0.0, 0.0), &params);
+        assert!((t1 - 3.0 * 9.81).abs() < 1e-4);
+        assert!((t2 - 2.0 * 9.81).abs() < 1e-4);
+
+        // 两摆一起匀速转动时下杆还要提供向心力 m2·ω²·l2（加上自身重量）
+        let spinning = PendulumState::new(0.0, 0.0, 0.0, 3.0);
+        let (_, t2) = engine.rod_tensions(&spinning, &params);
+        assert!(t2 > 2.0 * 9.81 + 2.0 * 9.0 * 0.5);
+    }
+
+    #[test]
+    fn test_pivot_force_matches_com_acceleration() {
+        let h = 0.01;
+        let engine = PhysicsEngine::new(h);
+        let params = PendulumParams::default();
+        let total_mass = params.m1 + params.m2;
+        let start = PendulumState::new(0.8, -0.4, 1.5, -2.0);
+
+        // 质心加速度的二阶中心差分
+        let mid = engine.advance(&start, &params, 1);
+        let end = engine.advance(&mid, &params, 1);
+        let (x0, y0) = start.center_of_mass(&params);
+        let (x1, y1) = mid.center_of_mass(&params);
+        let (x2, y2) = end.center_of_mass(&params);
+        let ax = (x2 - 2.0 * x1 + x0) / (h * h);
+        let ay = (y2 - 2.0 * y1 + y0) / (h * h);
+
+        // F = M·a_com + M·g
+        let (fx, fy) = engine.pivot_force(&mid, &params);
+        assert!((fx - total_mass * ax).abs() < 0.05, "fx = {}, M·ax = {}", fx, total_mass * ax);
+        assert!(
+            (fy - total_mass * (ay + params.g)).abs() < 0.05,
+            "fy = {}, M·(ay + g) = {}",
+            fy,
+            total_mass * (ay + params.g)
+        );
+    }
+
+    #[test]
+    fn test_pivot_force_follows_oscillating_pivot() {
+        let mut params = PendulumParams::new(1.0, 2.0, 1.0, 1.0, 9.81, 0.0);
+        params.pivot_amplitude = 0.05;
+        params.pivot_frequency = 40.0;
+        let state = PendulumState::at_rest(0.0, 0.0);
+
+        // 悬挂点加速时约束力随等效重力变化，而不是静止时的 M·g
+        let time = 0.3;
+        let mut engine = PhysicsEngine::default();
+        engine.set_time(time);
+        let (_, fy) = engine.pivot_force(&state, &params);
+        let expected = 3.0 * params.gravity_at(time);
+        assert!((expected - 3.0 * 9.81).abs() > 1.0);
+        assert!((fy - expected).abs() < 1e-3, "fy = {}, expected {}", fy, expected);
+
+        let (t1, t2) = engine.rod_tensions(&state, &params);
+        assert!((t1 - expected).abs() < 1e-3);
+        assert!((t2 - 2.0 * params.gravity_at(time)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_validate_time_step() {
+        assert_eq!(validate_time_step(0.0025), Ok(0.0025));
+        assert!(validate_time_step(MIN_TIME_STEP).is_ok());
+        assert!(validate_time_step(MAX_TIME_STEP).is_ok());
+
+        assert!(validate_time_step(0.0).is_err());
+        assert!(validate_time_step(-0.001).is_err());
+        assert!(validate_time_step(Real::NAN).is_err());
+        assert!(validate_time_step(Real::INFINITY).is_err());
+        assert!(validate_time_step(MIN_TIME_STEP / 2.0).is_err());
+        assert!(validate_time_step(MAX_TIME_STEP * 2.0).is_err());
+    }
+
     #[test]
     fn test_state_derivative_operations() {
         let d1 = StateDerivative::new(1.0, 2.0, 3.0, 4.0);
@@ -395,6 +1096,32 @@ mod tests {
         assert_eq!(sum.dtheta2, 3.0);
     }
 
+    #[test]
+    fn test_rk4_stages_recombine_to_integrate_rk4() {
+        let mut engine = PhysicsEngine::new(0.01);
+        engine.set_normalize_after_step(false);
+        let params = PendulumParams::default();
+        let state = PendulumState::new(2.0, -1.0, 0.5, 3.0);
+
+        let stages = engine.rk4_stages(&state, &params);
+        assert_eq!(stages.states[0], state);
+
+        // 按 RK4 权重 (1, 2, 2, 1)/6 组合各阶段导数
+        let weights = [1.0, 2.0, 2.0, 1.0];
+        let mut combined = StateDerivative::new(0.0, 0.0, 0.0, 0.0);
+        for (k, weight) in stages.derivatives.iter().zip(weights) {
+            combined = combined.add(&k.mul_scalar(weight));
+        }
+        let dt = engine.dt();
+        let recombined = PendulumState::new(
+            state.theta1 + combined.dtheta1 * (dt / 6.0),
+            state.theta2 + combined.dtheta2 * (dt / 6.0),
+            state.omega1 + combined.domega1 * (dt / 6.0),
+            state.omega2 + combined.domega2 * (dt / 6.0),
+        );
+        assert_eq!(recombined, engine.integrate_rk4(&state, &params));
+    }
+
     #[test]
     fn test_compute_derivatives() {
         let engine = PhysicsEngine::new(0.001);
@@ -411,6 +1138,50 @@ mod tests {
         assert!((derivatives.domega2).abs() < 1e-10);
     }
 
+    #[test]
+    fn test_derivatives_satisfy_euler_lagrange() {
+        // 由笛卡尔坐标写出拉格朗日量 L = T - V，用有限差分检验 d/dt(∂L/∂ω) = ∂L/∂θ
+        let (m1, m2, l1, l2, g) = (1.3, 0.7, 1.1, 0.9, 9.81);
+        let lagrangian = |q: [f64; 4]| {
+            let [theta1, theta2, omega1, omega2] = q;
+            let (vx1, vy1) = (l1 * theta1.cos() * omega1, l1 * theta1.sin() * omega1);
+            let vx2 = vx1 + l2 * theta2.cos() * omega2;
+            let vy2 = vy1 + l2 * theta2.sin() * omega2;
+            let y1 = -l1 * theta1.cos();
+            let y2 = y1 - l2 * theta2.cos();
+            let kinetic = 0.5 * m1 * (vx1 * vx1 + vy1 * vy1) + 0.5 * m2 * (vx2 * vx2 + vy2 * vy2);
+            kinetic - (m1 * g * y1 + m2 * g * y2)
+        };
+        // 中心差分求偏导数，q 依次为 (θ₁, θ₂, ω₁, ω₂)
+        let h = 1e-4;
+        let shifted = |mut q: [f64; 4], i: usize, d: f64| {
+            q[i] += d;
+            q
+        };
+        let partial = |q: [f64; 4], i: usize| {
+            (lagrangian(shifted(q, i, h)) - lagrangian(shifted(q, i, -h))) / (2.0 * h)
+        };
+        let mixed = |q: [f64; 4], i: usize, j: usize| {
+            (partial(shifted(q, j, h), i) - partial(shifted(q, j, -h), i)) / (2.0 * h)
+        };
+
+        let engine = PhysicsEngine::new(0.001);
+        let params =
+            PendulumParams::new(m1 as Real, m2 as Real, l1 as Real, l2 as Real, g as Real, 0.0);
+        let q: [f64; 4] = [0.7, -1.9, 1.3, -2.2];
+        let state = PendulumState::new(q[0] as Real, q[1] as Real, q[2] as Real, q[3] as Real);
+        let derivative = engine.compute_derivatives(&state, &params);
+        let accel = [derivative.domega1, derivative.domega2].map(crate::pendulum::real_to_f64);
+        for i in 0..2 {
+            // d/dt(∂L/∂ωᵢ) = Σⱼ ∂²L/∂ωᵢ∂θⱼ·ωⱼ + ∂²L/∂ωᵢ∂ωⱼ·αⱼ
+            let rate: f64 = (0..2)
+                .map(|j| mixed(q, i + 2, j) * q[j + 2] + mixed(q, i + 2, j + 2) * accel[j])
+                .sum();
+            let residual = rate - partial(q, i);
+            assert!(residual.abs() < 1e-5, "equation {} residual {}", i + 1, residual);
+        }
+    }
+
     #[test]
     fn test_euler_integration() {
         let engine = PhysicsEngine::new(0.001);
@@ -458,6 +1229,104 @@ mod tests {
         assert!(energy_error < 0.01, "Energy error: {}", energy_error);
     }
 
+    #[test]
+    fn test_coulomb_friction_stops_in_finite_time() {
+        let engine = PhysicsEngine::new(0.001);
+        let initial = PendulumState::new(0.5, 0.3, 0.0, 0.0);
+
+        // 纯粘性阻尼只会渐近地接近静止
+        let viscous = PendulumParams::new(1.0, 1.0, 1.0, 1.0, 9.81, 0.5);
+        let state = engine.advance(&initial, &viscous, 30_000);
+        assert!(state.omega1 != 0.0 || state.omega2 != 0.0);
+
+        // 库仑摩擦使摆在有限时间内完全停止，并且停止后保持不动
+        let friction = PendulumParams {
+            coulomb_friction: 0.5,
+            ..PendulumParams::default()
+        };
+        let mut state = initial;
+        let mut stopped_at = None;
+        for step in 1..=30_000 {
+            state = engine.step(&state, &friction).0;
+            if state.omega1 == 0.0 && state.omega2 == 0.0 {
+                stopped_at = Some(step);
+                break;
+            }
+        }
+        let stopped_at = stopped_at.expect("Coulomb friction should stop the pendulum");
+        assert!(stopped_at < 30_000);
+        let rest = state;
+        assert_eq!(engine.advance(&rest, &friction, 1000), rest);
+        // 停止前摩擦已耗散部分能量
+        assert!(rest.total_energy(&friction) < initial.total_energy(&friction));
+    }
+
+    #[test]
+    fn test_coulomb_friction_cannot_hold_large_torque() {
+        let engine = PhysicsEngine::new(0.001);
+        let params = PendulumParams {
+            coulomb_friction: 0.1,
+            ..PendulumParams::default()
+        };
+        // 静止在水平位置时重力矩远大于静摩擦，摆开始下落
+        let state = PendulumState::new(consts::FRAC_PI_2, consts::FRAC_PI_2, 0.0, 0.0);
+        let derivatives = engine.compute_derivatives(&state, &params);
+        assert!(derivatives.domega1 < 0.0);
+
+        // 接近竖直时静摩擦可以保持静止
+        let state = PendulumState::new(0.001, 0.001, 0.0, 0.0);
+        let derivatives = engine.compute_derivatives(&state, &params);
+        assert_eq!((derivatives.domega1, derivatives.domega2), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_dissipated_power_matches_energy_loss() {
+        let dt = 0.001;
+        let engine = PhysicsEngine::new(dt);
+        let params = PendulumParams::new(1.0, 1.0, 1.0, 1.0, 9.81, 0.3);
+        let mut state = PendulumState::new(1.0, 0.5, 0.0, 0.0);
+        let initial_energy = state.total_energy(&params);
+
+        // 梯形法积分耗散功率
+        let mut dissipated = 0.0;
+        for _ in 0..2000 {
+            let next = engine.integrate_rk4(&state, &params);
+            let power = state.dissipated_power(&params) + next.dissipated_power(&params);
+            dissipated += 0.5 * dt * power;
+            state = next;
+        }
+
+        let energy_loss = initial_energy - state.total_energy(&params);
+        assert!(energy_loss > 0.0);
+        assert!(
+            (energy_loss - dissipated).abs() < 1e-3 * energy_loss,
+            "loss {} vs dissipated {}",
+            energy_loss,
+            dissipated
+        );
+    }
+
+    #[test]
+    fn test_unwrapped_angles_grow_past_pi() {
+        let mut engine = PhysicsEngine::new(0.001);
+        engine.set_normalize_after_step(false);
+        // 无重力时下摆以恒定角速度持续转动
+        let params = PendulumParams::new(1.0, 1.0, 1.0, 1.0, 0.0, 0.0);
+        let mut state = PendulumState::new(0.0, 3.0, 0.0, 5.0);
+
+        for _ in 0..2000 {
+            let next = engine.step(&state, &params).0;
+            assert!(next.theta2 > state.theta2);
+            state = next;
+        }
+        assert!(state.theta2 > 2.0 * consts::PI, "theta2 = {}", state.theta2);
+
+        // 默认会标准化
+        engine.set_normalize_after_step(true);
+        let wrapped = engine.advance(&state, &params, 1);
+        assert!(wrapped.theta2.abs() <= consts::PI);
+    }
+
     #[test]
     fn test_angle_normalization() {
         let engine = PhysicsEngine::new(0.001);
@@ -465,8 +1334,8 @@ mod tests {
 
         // 创建一个角度超出范围的状态
         let state = PendulumState::new(
-            4.0 * std::f64::consts::PI,
-            -3.0 * std::f64::consts::PI,
+            4.0 * consts::PI,
+            -3.0 * consts::PI,
             1.0,
             -1.0,
         );
@@ -474,10 +1343,146 @@ mod tests {
         let new_state = engine.integrate_rk4(&state, &params);
 
         // 积分后角度应该被标准化
-        assert!(new_state.theta1 >= -std::f64::consts::PI);
-        assert!(new_state.theta1 <= std::f64::consts::PI);
-        assert!(new_state.theta2 >= -std::f64::consts::PI);
-        assert!(new_state.theta2 <= std::f64::consts::PI);
+        assert!(new_state.theta1 >= -consts::PI);
+        assert!(new_state.theta1 <= consts::PI);
+        assert!(new_state.theta2 >= -consts::PI);
+        assert!(new_state.theta2 <= consts::PI);
+    }
+
+    // 单精度下舍入误差在这些步长已占主导，漂移不再随dt单调下降
+    #[cfg(not(feature = "f32-physics"))]
+    #[test]
+    fn test_dt_sweep_drift_decreases_with_dt() {
+        let params = PendulumParams::default();
+        let state = PendulumState::new(
+            -consts::PI / 2.0,
+            -consts::PI / 3.0,
+            0.0,
+            0.0,
+        );
+
+        let dts = [0.01, 0.005, 0.0025];
+        let results = sweep_dt_accuracy(&state, &params, &dts, 1.0);
+        assert_eq!(results.len(), dts.len());
+
+        for pair in results.windows(2) {
+            let (dt_large, drift_large) = pair[0];
+            let (dt_small, drift_small) = pair[1];
+            assert!(
+                drift_small < drift_large,
+                "dt={} drift={} should be below dt={} drift={}",
+                dt_small,
+                drift_small,
+                dt_large,
+                drift_large
+            );
+        }
+
+        // 误差修正不参与扫描：大步长快速运动时结果仍与逐步调用 integrate_rk4_robust 一致
+        let fast = PendulumState::new(2.0, 2.5, 10.0, -15.0);
+        let mut engine = PhysicsEngine::new(0.05);
+        let mut current = fast;
+        for step in 0..20 {
+            engine.set_time(step as Real * 0.05);
+            current = engine.integrate_rk4_robust(&current, &params);
+        }
+        let initial_energy = fast.total_energy(&params);
+        let drift = (current.total_energy(&params) - initial_energy).abs() / initial_energy.abs();
+        assert_eq!(sweep_dt_accuracy(&fast, &params, &[0.05], 1.0)[0].1, drift);
+    }
+
+    /// 小角度周期测试的相对容差（随浮点精度而定）
+    #[cfg(not(feature = "f32-physics"))]
+    const PERIOD_TOLERANCE: Real = 1e-4;
+    #[cfg(feature = "f32-physics")]
+    const PERIOD_TOLERANCE: Real = 1e-2;
+
+    #[test]
+    fn test_small_angle_period() {
+        // 等质量等长度时，慢模态 ω² = (2 - √2) g / l，振型 θ₂/θ₁ = √2
+        let params = PendulumParams::default();
+        let amplitude: Real = 0.01;
+        let sqrt2 = consts::SQRT_2;
+        let mut state = PendulumState::at_rest(amplitude, amplitude * sqrt2);
+
+        let omega = ((2.0 - sqrt2) * params.g / params.l1).sqrt();
+        let expected_period = 2.0 * consts::PI / omega;
+
+        let dt: Real = 0.001;
+        let engine = PhysicsEngine::new(dt);
+        let mut time: Real = 0.0;
+        let mut crossings = Vec::new();
+
+        while crossings.len() < 3 && time < 3.0 * expected_period {
+            let next = engine.integrate_rk4(&state, &params);
+            // 线性插值求θ₁过零时刻
+            if (state.theta1 > 0.0) != (next.theta1 > 0.0) {
+                let fraction = state.theta1 / (state.theta1 - next.theta1);
+                crossings.push(time + fraction * dt);
+            }
+            state = next;
+            time += dt;
+        }
+
+        assert_eq!(crossings.len(), 3);
+        let period = crossings[2] - crossings[0];
+        let relative_error = (period - expected_period).abs() / expected_period;
+        assert!(
+            relative_error < PERIOD_TOLERANCE,
+            "period {} vs expected {}",
+            period,
+            expected_period
+        );
+    }
+
+    #[test]
+    fn test_excited_normal_mode_oscillates_at_its_frequency() {
+        let params = PendulumParams::new(1.0, 1.5, 1.2, 0.8, 9.81, 0.0);
+        let [(omega, [v1, v2]), _] = params.normal_modes();
+        let expected_ratio = (v2 / v1) as Real;
+        let expected_period = (2.0 * std::f64::consts::PI / omega) as Real;
+
+        let amplitude = 0.01;
+        let mut state = PendulumState::from_normal_mode(&params, 0, amplitude);
+        assert!((state.theta1.abs().max(state.theta2.abs()) - amplitude as Real).abs() < 1e-9);
+
+        let dt: Real = 0.001;
+        let engine = PhysicsEngine::new(dt);
+        let mut time: Real = 0.0;
+        let mut crossings = Vec::new();
+        while crossings.len() < 3 && time < 3.0 * expected_period {
+            let next = engine.integrate_rk4(&state, &params);
+            if (state.theta1 > 0.0) != (next.theta1 > 0.0) {
+                let fraction = state.theta1 / (state.theta1 - next.theta1);
+                crossings.push(time + fraction * dt);
+            }
+            // 纯简正模运动中两个摆角始终保持固定比例
+            if state.theta1.abs() > 0.3 * amplitude as Real {
+                let ratio = state.theta2 / state.theta1;
+                assert!(
+                    (ratio - expected_ratio).abs() < 1e-2 * expected_ratio.abs(),
+                    "ratio {} vs {}",
+                    ratio,
+                    expected_ratio
+                );
+            }
+            state = next;
+            time += dt;
+        }
+
+        assert_eq!(crossings.len(), 3);
+        let period = crossings[2] - crossings[0];
+        let relative_error = (period - expected_period).abs() / expected_period;
+        assert!(relative_error < PERIOD_TOLERANCE, "period {} vs {}", period, expected_period);
+    }
+
+    #[test]
+    fn test_log_spaced_dts() {
+        let dts = log_spaced_dts(1e-4, 1e-2, 5);
+        assert_eq!(dts.len(), 5);
+        assert!((dts[0] - 1e-4).abs() < 1e-12);
+        assert!((dts[2] - 1e-3).abs() < 1e-12);
+        assert!((dts[4] - 1e-2).abs() < 1e-12);
     }
 
     #[test]
@@ -505,4 +1510,252 @@ mod tests {
             "上摆向左偏移时，应该产生向右的角加速度"
         );
     }
+
+    #[test]
+    fn test_inverted_gravity_restores_toward_up() {
+        let engine = PhysicsEngine::new(0.001);
+        let params = PendulumParams {
+            inverted_gravity: true,
+            ..PendulumParams::default()
+        };
+
+        // 反摆模式下竖直向下是不稳定平衡：稍微偏右时继续向右加速，远离向下的位置
+        let state = PendulumState::new(0.1, 0.0, 0.0, 0.0);
+        let derivatives = engine.compute_derivatives(&state, &params);
+        assert!(derivatives.domega1 > 0.0);
+
+        // 在竖直向上附近，两侧的偏离都受到指向 θ = π 的回复力矩
+        for offset in [0.1, -0.1] {
+            let state = PendulumState::new(consts::PI - offset, consts::PI, 0.0, 0.0);
+            let derivatives = engine.compute_derivatives(&state, &params);
+            assert!(derivatives.domega1 * offset > 0.0);
+
+            let state = PendulumState::new(consts::PI, consts::PI - offset, 0.0, 0.0);
+            let derivatives = engine.compute_derivatives(&state, &params);
+            assert!(derivatives.domega2 * offset > 0.0);
+        }
+
+        // 竖直向上是能量最低的状态
+        let up = PendulumState::at_rest(consts::PI, consts::PI);
+        let down = PendulumState::at_rest(0.0, 0.0);
+        assert!(up.potential_energy(&params) < down.potential_energy(&params));
+        assert!((up.potential_energy(&params) - params.minimum_potential_energy()).abs() < 1e-4);
+
+        // 从向上附近小幅释放后保持在向上附近振荡
+        let mut state = PendulumState::new(consts::PI - 0.1, consts::PI - 0.1, 0.0, 0.0);
+        for _ in 0..5000 {
+            state = engine.step(&state, &params).0;
+            assert!(normalize_angle(state.theta1 - consts::PI).abs() < 0.5);
+        }
+    }
+
+    #[test]
+    fn test_adaptive_step_refines_in_stiff_region() {
+        let engine = PhysicsEngine::new(0.01);
+        let params = PendulumParams::default();
+
+        // 小幅摆动：一次就接受完整步长
+        let smooth = PendulumState::new(0.1, 0.05, 0.0, 0.0);
+        let smooth_step = engine.integrate_adaptive(&smooth, &params, ADAPTIVE_TOLERANCE);
+        assert_eq!(smooth_step.iterations, 1);
+        assert_eq!(smooth_step.accepted_dt, engine.dt());
+        assert!(smooth_step.error_estimate < ADAPTIVE_TOLERANCE);
+
+        // 下摆高速甩动：需要更小的子步长和更多次尝试
+        let stiff = PendulumState::new(2.5, -2.0, 8.0, -25.0);
+        let stiff_step = engine.integrate_adaptive(&stiff, &params, ADAPTIVE_TOLERANCE);
+        assert!(stiff_step.accepted_dt < smooth_step.accepted_dt);
+        assert!(stiff_step.iterations > smooth_step.iterations);
+        assert!(stiff_step.error_estimate < ADAPTIVE_TOLERANCE);
+
+        // 子步长细分后积分的仍是完整的 dt，结果与细步长的固定步长积分一致
+        let substeps = (engine.dt() / stiff_step.accepted_dt).round() as usize;
+        let fine = PhysicsEngine::new(stiff_step.accepted_dt);
+        let mut expected = stiff;
+        for _ in 0..substeps {
+            expected = fine.integrate_rk4(&expected, &params);
+        }
+        assert!((expected.omega2 - stiff_step.state.omega2).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_step_dispatches_on_integrator_kind() {
+        let mut engine = PhysicsEngine::new(0.01);
+        let params = PendulumParams::default();
+        let state = PendulumState::new(2.5, -2.0, 8.0, -25.0);
+        assert_eq!(engine.integrator(), IntegratorKind::Rk4);
+
+        engine.set_integrator(IntegratorKind::Euler);
+        assert_eq!(engine.step(&state, &params).0, engine.integrate_euler(&state, &params));
+        assert_eq!(engine.integrator_label(), "Euler");
+
+        // 自适应积分使用引擎上的容差，容差越小子步长越小
+        engine.set_integrator(IntegratorKind::Adaptive);
+        engine.set_adaptive_tolerance(1e-3);
+        let loose = engine.step_adaptive(&state, &params).0;
+        assert_eq!(engine.step(&state, &params).0, loose.state);
+        engine.set_adaptive_tolerance(1e-8);
+        let tight = engine.step_adaptive(&state, &params).0;
+        assert!(tight.accepted_dt < loose.accepted_dt);
+
+        // 超出范围的容差被限制
+        engine.set_adaptive_tolerance(0.0);
+        assert_eq!(engine.adaptive_tolerance(), MIN_ADAPTIVE_TOLERANCE);
+    }
+
+    #[test]
+    fn test_loose_tolerance_takes_larger_adaptive_steps() {
+        let preset = crate::presets::get_preset_by_name("Classic Chaos").unwrap();
+        // 沿经典混沌轨迹运行，返回采用过的最小子步长
+        let smallest_dt = |tolerance: Real| {
+            let mut engine = PhysicsEngine::new(0.01);
+            engine.set_integrator(IntegratorKind::Adaptive);
+            engine.set_adaptive_tolerance(tolerance);
+            let mut state = preset.initial_state;
+            let mut smallest = engine.dt();
+            for _ in 0..300 {
+                let (result, _) = engine.step_adaptive(&state, &preset.params);
+                smallest = smallest.min(result.accepted_dt);
+                state = result.state;
+            }
+            smallest
+        };
+        assert!(smallest_dt(1e-3) > smallest_dt(1e-7));
+    }
+
+    #[test]
+    fn test_drive_torque_follows_engine_time() {
+        let params = PendulumParams {
+            drive_amplitude: 2.0,
+            drive_frequency: 1.0,
+            ..PendulumParams::default()
+        };
+        let at_rest = PendulumState::new(0.0, 0.0, 0.0, 0.0);
+        let mut engine = PhysicsEngine::new(0.01);
+        // t = 0 时没有力矩，t = π/2 时力矩最大且推动上摆
+        assert_eq!(engine.compute_derivatives(&at_rest, &params).domega1, 0.0);
+        engine.set_time(consts::FRAC_PI_2);
+        assert!(engine.compute_derivatives(&at_rest, &params).domega1 > 0.0);
+
+        // advance 按步推进驱动相位，与逐步设置时间的结果一致
+        engine.set_time(0.0);
+        let mut state = at_rest;
+        for step in 0..200 {
+            engine.set_time(step as Real * engine.dt());
+            state = engine.step(&state, &params).0;
+        }
+        engine.set_time(0.0);
+        assert_eq!(engine.advance(&at_rest, &params, 200), state);
+    }
+
+    #[test]
+    fn test_vibrating_pivot_stabilizes_inverted_pendulum() {
+        let preset = crate::presets::get_preset_by_name("Kapitza Inverted").unwrap();
+        // 悬挂点在最低点时向上加速最大，等效重力为 g + AΩ²
+        let params = preset.params;
+        let bottom = 3.0 * consts::FRAC_PI_2 / params.pivot_frequency;
+        let boost = params.pivot_amplitude * params.pivot_frequency * params.pivot_frequency;
+        assert!((params.gravity_at(bottom) - (params.g + boost)).abs() < 1e-3 * boost);
+        assert_eq!(params.gravity_at(0.0), params.g);
+
+        // 20 s 内与竖直向上的最大偏离，角度按 2π 回绕
+        let max_tilt = |params: &PendulumParams| {
+            let mut engine = PhysicsEngine::new(0.001);
+            let mut state = preset.initial_state;
+            let mut tilt: Real = 0.0;
+            for step in 0..20_000 {
+                engine.set_time(step as Real * engine.dt());
+                state = engine.step(&state, params).0;
+                for theta in [state.theta1, state.theta2] {
+                    tilt = tilt.max((theta.rem_euclid(2.0 * consts::PI) - consts::PI).abs());
+                }
+            }
+            tilt
+        };
+        assert!(max_tilt(&params) < 0.5);
+        let fixed_pivot = PendulumParams {
+            pivot_amplitude: 0.0,
+            ..params
+        };
+        assert!(max_tilt(&fixed_pivot) > 2.0);
+    }
+
+    #[test]
+    fn test_vibrating_pivot_rarely_triggers_error_correction() {
+        let preset = crate::presets::get_preset_by_name("Kapitza Inverted").unwrap();
+        let params = preset.params;
+        let mut engine = PhysicsEngine::new(preset.recommended_dt.unwrap());
+        let mut state = preset.initial_state;
+        let mut corrected_steps = 0;
+        for step in 0..4000 {
+            engine.set_time(step as Real * engine.dt());
+            let next = engine.step(&state, &params).0;
+            // 误差修正会改用两个半步，结果与单步 RK4 不同
+            if next != engine.integrate_rk4_robust(&state, &params) {
+                corrected_steps += 1;
+            }
+            state = next;
+        }
+        // 实验室系能量下几乎每步都会触发修正；只有参考系能量经过0附近的少数步例外
+        assert!(corrected_steps < 40, "correction fired on {} steps", corrected_steps);
+    }
+
+    #[test]
+    fn test_drive_at_slow_mode_resonates() {
+        let engine = PhysicsEngine::new(0.01);
+        let at_rest = PendulumState::new(0.0, 0.0, 0.0, 0.0);
+        let slow_frequency = PendulumParams::default().normal_modes()[0].0 as Real;
+        // 以给定频率驱动 20 s，返回上摆的最大摆角
+        let peak_angle = |frequency: Real| {
+            let params = PendulumParams {
+                damping: 0.05,
+                drive_amplitude: 0.2,
+                drive_frequency: frequency,
+                ..PendulumParams::default()
+            };
+            let mut engine = engine.clone();
+            let mut state = at_rest;
+            let mut peak: Real = 0.0;
+            for step in 0..2000 {
+                engine.set_time(step as Real * engine.dt());
+                state = engine.step(&state, &params).0;
+                peak = peak.max(state.theta1.abs());
+            }
+            peak
+        };
+        assert!(peak_angle(slow_frequency) > 3.0 * peak_angle(slow_frequency * 2.0));
+    }
+
+    #[test]
+    fn test_quadratic_drag_relative_to_linear() {
+        let engine = PhysicsEngine::new(0.001);
+        // 以给定角速度从底部出发 0.1 s 内损失的能量
+        let energy_loss = |drag_model: DragModel, omega: Real| {
+            let params = PendulumParams {
+                damping: 0.2,
+                drag_model,
+                ..PendulumParams::default()
+            };
+            let initial = PendulumState::new(0.0, 0.0, omega, omega);
+            let state = engine.advance(&initial, &params, 100);
+            initial.total_energy(&params) - state.total_energy(&params)
+        };
+        // |ω| > 1 时二次阻尼更强，|ω| < 1 时更弱
+        assert!(energy_loss(DragModel::Quadratic, 6.0) > energy_loss(DragModel::Linear, 6.0));
+        assert!(energy_loss(DragModel::Quadratic, 0.3) < energy_loss(DragModel::Linear, 0.3));
+        assert!(energy_loss(DragModel::Quadratic, 0.3) > 0.0);
+    }
+
+    #[test]
+    fn test_benettin_lyapunov_separates_chaos_from_regular_motion() {
+        let engine = PhysicsEngine::new(0.01);
+        let exponent = |name: &str| {
+            let preset = crate::presets::get_preset_by_name(name).unwrap();
+            engine.lyapunov_benettin(&preset.initial_state, &preset.params, 20_000, 10)
+        };
+        let chaotic = exponent("Classic Chaos");
+        let regular = exponent("Small Angle");
+        assert!(chaotic > 0.3, "chaotic exponent = {}", chaotic);
+        assert!(regular.abs() < 0.05, "regular exponent = {}", regular);
+    }
 }
@@ -1,9 +1,8 @@
 /// 预设配置模块
 /// 提供经典的混沌摆初始条件和参数组合
-
 #[allow(dead_code)]
-
-use crate::pendulum::{PendulumState, PendulumParams};
+use crate::pendulum::consts::PI;
+use crate::pendulum::{AngleConvention, PendulumState, PendulumParams, Real};
 use serde::{Deserialize, Serialize};
 
 /// 预设配置结构体
@@ -17,6 +16,12 @@ pub struct PendulumPreset {
     pub initial_state: PendulumState,
     /// 物理参数
     pub params: PendulumParams,
+    /// 推荐的时间步长（高能量预设使用更小的步长）
+    #[serde(default)]
+    pub recommended_dt: Option<Real>,
+    /// 初始状态使用的角度约定（缺省为内部约定）
+    #[serde(default)]
+    pub convention: AngleConvention,
 }
 
 impl PendulumPreset {
@@ -32,7 +37,37 @@ impl PendulumPreset {
             description,
             initial_state,
             params,
+            recommended_dt: None,
+            convention: AngleConvention::default(),
+        }
+    }
+
+    /// 设置推荐的时间步长
+    pub fn with_recommended_dt(mut self, dt: Real) -> Self {
+        self.recommended_dt = Some(dt);
+        self
+    }
+
+    /// 从 JSON 解析预设，并把初始状态转换为内部角度约定
+    pub fn from_json(text: &str) -> serde_json::Result<Self> {
+        let mut preset: PendulumPreset = serde_json::from_str(text)?;
+        preset.initial_state = preset.convention.to_internal(preset.initial_state);
+        preset.convention = AngleConvention::default();
+        Ok(preset)
+    }
+
+    /// 检查物理参数和推荐步长，手工编辑或分享的预设文件可能含有无效值
+    pub fn validate(&self) -> Result<(), String> {
+        self.params.validate()?;
+        if let Some(dt) = self.recommended_dt {
+            crate::physics::validate_time_step(dt)?;
         }
+        Ok(())
+    }
+
+    /// 序列化为 JSON，初始状态按内部约定保存并注明
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
     }
 }
 
@@ -52,8 +87,8 @@ pub fn get_all_presets() -> Vec<PendulumPreset> {
             "Classic Chaos".to_string(),
             "Classic chaotic motion with equal masses".to_string(),
             PendulumState::new(
-                -std::f64::consts::PI / 2.0, // 向上90度
-                -std::f64::consts::PI / 3.0, // 向上60度
+                -PI / 2.0, // 向上90度
+                -PI / 3.0, // 向上60度
                 0.0,
                 0.0,
             ),
@@ -65,8 +100,8 @@ pub fn get_all_presets() -> Vec<PendulumPreset> {
             "High Energy".to_string(),
             "High energy chaotic motion - complex trajectories".to_string(),
             PendulumState::new(
-                -std::f64::consts::PI * 0.7, // 向上126度
-                -std::f64::consts::PI * 0.8, // 向上144度
+                -PI * 0.7, // 向上126度
+                -PI * 0.8, // 向上144度
                 1.0,   // 减少初始角速度避免过快
                 -0.5,
             ),
@@ -78,8 +113,8 @@ pub fn get_all_presets() -> Vec<PendulumPreset> {
             "Unequal Masses".to_string(),
             "Heavy bottom mass creates interesting dynamics".to_string(),
             PendulumState::new(
-                -std::f64::consts::PI / 3.0, // 向上60度
-                -std::f64::consts::PI / 4.0, // 向上45度
+                -PI / 3.0, // 向上60度
+                -PI / 4.0, // 向上45度
                 0.0,
                 0.0,
             ),
@@ -91,8 +126,8 @@ pub fn get_all_presets() -> Vec<PendulumPreset> {
             "Unequal Lengths".to_string(),
             "Different arm lengths create asymmetric motion".to_string(),
             PendulumState::new(
-                -std::f64::consts::PI / 4.0, // 向上45度
-                -std::f64::consts::PI / 3.0, // 向上60度
+                -PI / 4.0, // 向上45度
+                -PI / 3.0, // 向上60度
                 0.0,
                 0.0,
             ),
@@ -104,8 +139,8 @@ pub fn get_all_presets() -> Vec<PendulumPreset> {
             "Damped System".to_string(),
             "Damped motion shows energy dissipation".to_string(),
             PendulumState::new(
-                -std::f64::consts::PI / 2.0, // 向上90度
-                -std::f64::consts::PI / 4.0, // 向上45度
+                -PI / 2.0, // 向上90度
+                -PI / 4.0, // 向上45度
                 0.0,
                 0.0,
             ),
@@ -117,8 +152,8 @@ pub fn get_all_presets() -> Vec<PendulumPreset> {
             "Low Gravity".to_string(),
             "Moon-like gravity creates slower, extended motion".to_string(),
             PendulumState::new(
-                -std::f64::consts::PI / 3.0, // 向上60度
-                -std::f64::consts::PI / 2.0, // 向上90度
+                -PI / 3.0, // 向上60度
+                -PI / 2.0, // 向上90度
                 0.0,
                 0.0,
             ),
@@ -132,11 +167,122 @@ pub fn get_all_presets() -> Vec<PendulumPreset> {
             PendulumState::new(0.0, 0.0, 3.0, 4.0),
             PendulumParams::default(),
         ),
+
+        // 双臂水平释放
+        PendulumPreset::new(
+            "Horizontal Release".to_string(),
+            "Both arms held horizontal and released from rest".to_string(),
+            PendulumState::new(
+                -PI / 2.0, // 水平
+                -PI / 2.0, // 水平
+                0.0,
+                0.0,
+            ),
+            PendulumParams::default(),
+        )
+        .with_recommended_dt(0.0005),
+
+        // 上臂竖直向上，下臂下垂
+        PendulumPreset::new(
+            "Upper Up, Lower Down".to_string(),
+            "Upper arm balanced upright with the lower arm hanging - tips over into chaos"
+                .to_string(),
+            PendulumState::new(
+                -(PI - 0.01), // 接近竖直向上，略微偏离不稳定平衡
+                0.0,          // 竖直向下
+                0.0,
+                0.0,
+            ),
+            PendulumParams::default(),
+        )
+        .with_recommended_dt(0.0005),
+
+        // 以慢简正模频率驱动上关节
+        PendulumPreset::new(
+            "Driven Resonance".to_string(),
+            "Upper joint driven near the slow normal mode - the swing builds up".to_string(),
+            PendulumState::new(0.0, 0.0, 0.0, 0.0),
+            PendulumParams {
+                damping: 0.05,
+                drive_amplitude: 1.0,
+                drive_frequency: 2.4, // 约等于慢模角频率 √((2-√2)g/l)
+                ..PendulumParams::default()
+            },
+        ),
+
+        // 悬挂点高频竖直振动使倒立的双摆保持稳定（卡皮查摆）
+        PendulumPreset::new(
+            "Kapitza Inverted".to_string(),
+            "Pivot shaken vertically at high frequency - the upside-down pendulum stays up"
+                .to_string(),
+            PendulumState::new(PI + 0.1, PI - 0.05, 0.0, 0.0),
+            PendulumParams {
+                damping: 0.2,
+                pivot_amplitude: 0.1,
+                pivot_frequency: 80.0,
+                ..PendulumParams::default()
+            },
+        )
+        .with_recommended_dt(0.0005),
     ]
 }
 
+/// 自定义预设列表在 eframe 存储中的键
+pub const CUSTOM_PRESETS_KEY: &str = "custom_presets";
+
+/// 用户保存的自定义预设，与内置预设一起显示
+#[derive(Clone, Debug, Default)]
+pub struct CustomPresets {
+    presets: Vec<PendulumPreset>,
+}
+
+impl CustomPresets {
+    /// 从 JSON 数组解析，初始状态按各自注明的角度约定转换
+    /// 参数或推荐步长无效的条目（存储损坏或手工编辑）被丢弃
+    pub fn from_json(text: &str) -> serde_json::Result<Self> {
+        let mut presets: Vec<PendulumPreset> = serde_json::from_str(text)?;
+        presets.retain(|preset| preset.validate().is_ok());
+        for preset in &mut presets {
+            preset.initial_state = preset.convention.to_internal(preset.initial_state);
+            preset.convention = AngleConvention::default();
+        }
+        Ok(Self { presets })
+    }
+
+    /// 序列化为 JSON 数组
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&self.presets)
+    }
+
+    /// 所有自定义预设
+    pub fn presets(&self) -> &[PendulumPreset] {
+        &self.presets
+    }
+
+    /// 添加预设，同名的自定义预设被替换；名称为空或与内置预设重名时拒绝
+    pub fn add(&mut self, preset: PendulumPreset) -> Result<(), String> {
+        if preset.name.trim().is_empty() {
+            return Err("预设名称不能为空".to_string());
+        }
+        if get_preset_by_name(&preset.name).is_some() {
+            return Err(format!("不能覆盖内置预设 {}", preset.name));
+        }
+        match self.presets.iter_mut().find(|existing| existing.name == preset.name) {
+            Some(existing) => *existing = preset,
+            None => self.presets.push(preset),
+        }
+        Ok(())
+    }
+
+    /// 按名称删除预设，返回是否存在
+    pub fn remove(&mut self, name: &str) -> bool {
+        let before = self.presets.len();
+        self.presets.retain(|preset| preset.name != name);
+        self.presets.len() != before
+    }
+}
+
 /// 根据名称获取预设
-#[allow(dead_code)]
 pub fn get_preset_by_name(name: &str) -> Option<PendulumPreset> {
     get_all_presets().into_iter().find(|preset| preset.name == name)
 }
@@ -165,16 +311,20 @@ pub fn get_presets_by_category(category: PresetCategory) -> Vec<PendulumPreset>
             all_presets[1].clone(), // Classic Chaos
             all_presets[2].clone(), // High Energy
             all_presets[7].clone(), // Near Circular
+            all_presets[8].clone(), // Horizontal Release
         ],
         PresetCategory::Physical => vec![
             all_presets[3].clone(), // Unequal Masses
             all_presets[4].clone(), // Unequal Lengths
             all_presets[5].clone(), // Damped System
+            all_presets[10].clone(), // Driven Resonance
         ],
         PresetCategory::Extreme => vec![
             all_presets[2].clone(), // High Energy
             all_presets[6].clone(), // Low Gravity
             all_presets[7].clone(), // Near Circular
+            all_presets[9].clone(), // Upper Up, Lower Down
+            all_presets[11].clone(), // Kapitza Inverted
         ],
     }
 }
@@ -204,12 +354,27 @@ mod tests {
     fn test_preset_validation() {
         let presets = get_all_presets();
         for preset in presets {
-            assert!(preset.params.validate().is_ok());
+            assert!(preset.validate().is_ok(), "{}", preset.name);
             assert!(!preset.name.is_empty());
             assert!(!preset.description.is_empty());
         }
     }
 
+    #[test]
+    fn test_horizontal_release_preset() {
+        let preset = get_preset_by_name("Horizontal Release").unwrap();
+        assert!((preset.initial_state.theta1 - (-PI / 2.0)).abs() < 1e-6);
+        assert!((preset.initial_state.theta2 - (-PI / 2.0)).abs() < 1e-6);
+        assert_eq!(preset.initial_state.omega1, 0.0);
+        assert_eq!(preset.initial_state.omega2, 0.0);
+        assert!(preset.recommended_dt.is_some());
+
+        let upper_up = get_preset_by_name("Upper Up, Lower Down").unwrap();
+        assert!(upper_up.initial_state.theta1.abs() > PI * 0.99);
+        assert_eq!(upper_up.initial_state.theta2, 0.0);
+        assert!(upper_up.recommended_dt.is_some());
+    }
+
     #[test]
     fn test_categories() {
         let basic = get_presets_by_category(PresetCategory::Basic);
@@ -222,4 +387,82 @@ mod tests {
         assert!(!physical.is_empty());
         assert!(!extreme.is_empty());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_custom_presets_add_remove_and_round_trip() {
+        let mut custom = CustomPresets::default();
+        let preset = |name: &str, theta1: Real| {
+            PendulumPreset::new(
+                name.to_string(),
+                "Mine".to_string(),
+                PendulumState::new(theta1, 0.5, 0.0, 0.0),
+                PendulumParams::default(),
+            )
+        };
+
+        // 不能覆盖内置预设，也不能没有名称
+        assert!(custom.add(preset("Classic Chaos", 1.0)).is_err());
+        assert!(custom.add(preset("  ", 1.0)).is_err());
+        assert!(custom.presets().is_empty());
+
+        custom.add(preset("Mine", 1.0)).unwrap();
+        custom.add(preset("Other", 2.0)).unwrap();
+        // 同名的自定义预设被替换
+        custom.add(preset("Mine", 3.0)).unwrap();
+        assert_eq!(custom.presets().len(), 2);
+        assert_eq!(custom.presets()[0].initial_state.theta1, 3.0);
+
+        let reloaded = CustomPresets::from_json(&custom.to_json().unwrap()).unwrap();
+        assert_eq!(reloaded.presets().len(), 2);
+        assert_eq!(reloaded.presets()[1].name, "Other");
+        assert_eq!(reloaded.presets()[1].initial_state, custom.presets()[1].initial_state);
+
+        assert!(custom.remove("Mine"));
+        assert!(!custom.remove("Mine"));
+        assert_eq!(custom.presets().len(), 1);
+        assert!(CustomPresets::from_json("{ \"name\": 1 }").is_err());
+
+        // 无效的条目被丢弃，其余条目照常恢复
+        let mut entries: Vec<serde_json::Value> =
+            serde_json::from_str(&reloaded.to_json().unwrap()).unwrap();
+        let mut broken = entries[0].clone();
+        broken["name"] = "Broken".into();
+        broken["params"]["m2"] = (-1.0).into();
+        entries.push(broken);
+        let mut coarse = entries[0].clone();
+        coarse["name"] = "Coarse".into();
+        coarse["recommended_dt"] = 1.0.into();
+        entries.push(coarse);
+        let text = serde_json::to_string(&entries).unwrap();
+        let filtered = CustomPresets::from_json(&text).unwrap();
+        let names: Vec<&str> = filtered.presets().iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["Mine", "Other"]);
+    }
+
+    #[test]
+    fn test_preset_json_converts_convention() {
+        let text = r#"{
+            "name": "Shared",
+            "description": "Saved with relative angles",
+            "initial_state": { "theta1": 0.5, "theta2": 0.25, "omega1": 1.0, "omega2": -2.0 },
+            "params": { "m1": 1.0, "m2": 1.0, "l1": 1.0, "l2": 1.0, "g": 9.81, "damping": 0.0 },
+            "convention": "relative_to_upper_arm"
+        }"#;
+        let preset = PendulumPreset::from_json(text).unwrap();
+        assert_eq!(preset.convention, AngleConvention::AbsoluteFromDown);
+        assert!((preset.initial_state.theta1 - 0.5).abs() < 1e-6);
+        assert!((preset.initial_state.theta2 - 0.75).abs() < 1e-6);
+        assert!((preset.initial_state.omega2 - (-1.0)).abs() < 1e-6);
+
+        // 未注明约定的旧文件按内部约定读取
+        let legacy = text.replace(r#""convention": "relative_to_upper_arm""#, r#""note": "old""#);
+        let preset = PendulumPreset::from_json(&legacy).unwrap();
+        assert!((preset.initial_state.theta2 - 0.25).abs() < 1e-6);
+
+        // 保存后再读取保持不变
+        let original = get_preset_by_name("Classic Chaos").unwrap();
+        let reloaded = PendulumPreset::from_json(&original.to_json().unwrap()).unwrap();
+        assert_eq!(reloaded.initial_state, original.initial_state);
+        assert!(reloaded.to_json().unwrap().contains("absolute_from_down"));
+    }
+}
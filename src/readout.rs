@@ -0,0 +1,109 @@
+//! 读数格式化模块
+//! 按数值类别和全局显示精度统一格式化界面上的物理量
+
+/// 默认显示精度（能量的小数位数）
+pub const DEFAULT_PRECISION: usize = 3;
+
+/// 最大显示精度
+pub const MAX_PRECISION: usize = 8;
+
+/// 读数的数值类别
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValueKind {
+    /// 角度，输入为弧度，以度显示
+    Angle,
+    /// 角速度（rad/s）
+    AngularVelocity,
+    /// 能量（J）
+    Energy,
+    /// 功率（W）
+    Power,
+    /// 时间（s）
+    Time,
+    /// 频率（Hz）
+    Frequency,
+    /// 角动量（kg·m²/s）
+    AngularMomentum,
+    /// 无量纲数值
+    Number,
+    /// 相对误差，科学计数法
+    Error,
+}
+
+impl ValueKind {
+    /// 相对全局精度的小数位偏移，使默认精度下与原有显示一致
+    fn decimal_offset(self) -> isize {
+        match self {
+            ValueKind::Angle => -2,
+            ValueKind::AngularVelocity | ValueKind::Time | ValueKind::Error => -1,
+            ValueKind::Energy
+            | ValueKind::Power
+            | ValueKind::Frequency
+            | ValueKind::AngularMomentum
+            | ValueKind::Number => 0,
+        }
+    }
+
+    /// 单位后缀
+    fn unit(self) -> &'static str {
+        match self {
+            ValueKind::Angle => "°",
+            ValueKind::AngularVelocity => " rad/s",
+            ValueKind::Energy => " J",
+            ValueKind::Power => " W",
+            ValueKind::Time => " s",
+            ValueKind::Frequency => " Hz",
+            ValueKind::AngularMomentum => " kg·m²/s",
+            ValueKind::Error | ValueKind::Number => "",
+        }
+    }
+}
+
+/// 按类别和全局精度格式化数值（带单位）
+/// precision 为能量的小数位数，其他类别按 decimal_offset 增减，最少0位
+pub fn fmt_value(value: f64, kind: ValueKind, precision: usize) -> String {
+    let decimals = (precision.min(MAX_PRECISION) as isize + kind.decimal_offset()).max(0) as usize;
+    match kind {
+        ValueKind::Angle => format!("{:.*}{}", decimals, value.to_degrees(), kind.unit()),
+        ValueKind::Error => format!("{:.*e}", decimals, value),
+        _ => format!("{:.*}{}", decimals, value, kind.unit()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fmt_value_default_precision() {
+        let p = DEFAULT_PRECISION;
+        assert_eq!(fmt_value(std::f64::consts::FRAC_PI_2, ValueKind::Angle, p), "90.0°");
+        assert_eq!(fmt_value(1.23456, ValueKind::AngularVelocity, p), "1.23 rad/s");
+        assert_eq!(fmt_value(-12.34567, ValueKind::Energy, p), "-12.346 J");
+        assert_eq!(fmt_value(0.5, ValueKind::Power, p), "0.500 W");
+        assert_eq!(fmt_value(4.126, ValueKind::Time, p), "4.13 s");
+        assert_eq!(fmt_value(3.2e-5, ValueKind::Error, p), "3.20e-5");
+        assert_eq!(fmt_value(1.5, ValueKind::Frequency, p), "1.500 Hz");
+        assert_eq!(fmt_value(0.25, ValueKind::AngularMomentum, p), "0.250 kg·m²/s");
+        assert_eq!(fmt_value(-0.12345, ValueKind::Number, p), "-0.123");
+    }
+
+    #[test]
+    fn test_fmt_value_precision_settings() {
+        // 提高精度时所有类别同步增加位数
+        assert_eq!(fmt_value(1.0, ValueKind::Angle, 6), "57.2958°");
+        assert_eq!(fmt_value(-12.34567, ValueKind::Energy, 5), "-12.34567 J");
+        assert_eq!(fmt_value(3.2e-5, ValueKind::Error, 5), "3.2000e-5");
+
+        // 降低精度时不会出现负的小数位数
+        assert_eq!(fmt_value(1.0, ValueKind::Angle, 0), "57°");
+        assert_eq!(fmt_value(1.26, ValueKind::AngularVelocity, 1), "1 rad/s");
+        assert_eq!(fmt_value(3.2e-5, ValueKind::Error, 0), "3e-5");
+
+        // 超出上限的精度按上限处理
+        assert_eq!(
+            fmt_value(1.0, ValueKind::Energy, 20),
+            fmt_value(1.0, ValueKind::Energy, MAX_PRECISION)
+        );
+    }
+}
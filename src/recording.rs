@@ -0,0 +1,132 @@
+/// 会话录制模块
+/// 运行时记录每个物理步的状态和时间，之后可以拖动时间轴回放，也可以保存为 JSON 文件
+use crate::pendulum::{PendulumParams, PendulumState};
+use serde::{Deserialize, Serialize};
+
+/// 录制保留的最大帧数，超过后丢弃一半的帧并把记录间隔加倍
+pub const MAX_RECORDING_FRAMES: usize = 50_000;
+
+/// 一次录制的会话：参数取自开始录制时
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Recording {
+    /// 回放时绘制摆形使用的参数
+    pub params: PendulumParams,
+    /// 每隔多少个物理步记录一帧
+    stride: usize,
+    /// 录制的 (模拟时间, 状态)
+    frames: Vec<(f64, PendulumState)>,
+    /// 距上次记录经过的步数
+    #[serde(skip)]
+    pending_steps: usize,
+}
+
+impl Recording {
+    /// 以当前时刻的状态作为第一帧开始录制
+    pub fn start(time: f64, state: PendulumState, params: PendulumParams) -> Self {
+        Self {
+            params,
+            stride: 1,
+            frames: vec![(time, state)],
+            pending_steps: 0,
+        }
+    }
+
+    /// 记录一个物理步之后的状态，超过帧数上限时降采样
+    /// 超过上限时帧数为奇数，隔帧保留同时留下第一帧和最新一帧，之后的间隔保持均匀
+    pub fn observe(&mut self, time: f64, state: PendulumState) {
+        self.pending_steps += 1;
+        if self.pending_steps < self.stride {
+            return;
+        }
+        self.pending_steps = 0;
+        self.frames.push((time, state));
+        if self.frames.len() > MAX_RECORDING_FRAMES {
+            let mut index = 0;
+            self.frames.retain(|_| {
+                index += 1;
+                index % 2 == 1
+            });
+            self.stride *= 2;
+        }
+    }
+
+    /// 录制的帧数
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// 是否没有任何帧
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// 第 index 帧的 (时间, 状态)
+    pub fn frame(&self, index: usize) -> Option<(f64, PendulumState)> {
+        self.frames.get(index).copied()
+    }
+
+    /// 当前的记录间隔（物理步）
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// 第一帧到最后一帧经过的模拟时间
+    pub fn duration(&self) -> f64 {
+        match (self.frames.first(), self.frames.last()) {
+            (Some(first), Some(last)) => last.0 - first.0,
+            _ => 0.0,
+        }
+    }
+
+    /// 序列化为 JSON
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    /// 从 JSON 解析录制
+    pub fn from_json(text: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_observe_downsamples_at_cap() {
+        let state = PendulumState::new(1.0, 0.5, 0.0, 0.0);
+        let mut recording = Recording::start(0.0, state, PendulumParams::default());
+        for step in 1..MAX_RECORDING_FRAMES {
+            recording.observe(step as f64 * 0.01, state);
+        }
+        assert_eq!(recording.len(), MAX_RECORDING_FRAMES);
+        recording.observe(500.0, state);
+
+        // 超过上限后保留偶数帧（包括最新一帧），时间间隔加倍
+        assert_eq!(recording.len(), MAX_RECORDING_FRAMES / 2 + 1);
+        assert_eq!(recording.stride(), 2);
+        assert_eq!(recording.frame(1).unwrap().0, 0.02);
+        assert_eq!(recording.frame(recording.len() - 1).unwrap().0, 500.0);
+
+        // 之后每两步才记录一帧，与降采样前最新一帧的间隔同样是两步
+        let before = recording.len();
+        recording.observe(500.01, state);
+        assert_eq!(recording.len(), before);
+        recording.observe(500.02, state);
+        assert_eq!(recording.len(), before + 1);
+        assert!((recording.duration() - 500.02).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_recording_json_round_trip() {
+        let params = PendulumParams::new(1.0, 2.0, 1.0, 0.5, 9.81, 0.1);
+        let mut recording = Recording::start(1.5, PendulumState::new(0.3, 0.2, 0.0, 0.0), params);
+        recording.observe(1.6, PendulumState::new(0.31, 0.19, 0.1, -0.1));
+
+        let restored = Recording::from_json(&recording.to_json().unwrap()).unwrap();
+        assert_eq!(restored, recording);
+        assert_eq!(restored.frame(1), recording.frame(1));
+        assert!(Recording::from_json("{\"frames\": 3}").is_err());
+    }
+}
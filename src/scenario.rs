@@ -0,0 +1,174 @@
+/// 场景脚本模块
+/// 按模拟时间依次执行预先编排的操作，用于自动演示
+use crate::pendulum::StepClock;
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::Path;
+
+/// 场景中的单个操作
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ScenarioAction {
+    /// 按名称加载预设
+    LoadPreset { name: String },
+    /// 设置阻尼系数
+    SetDamping { value: f64 },
+    /// 设置模拟速度倍率
+    SetSpeed { value: f64 },
+    /// 暂停模拟
+    Pause,
+    /// 重置模拟
+    Reset,
+}
+
+/// 在指定时间触发的操作
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScenarioStep {
+    /// 触发时间（场景开始后的模拟时间，秒）
+    pub time: f64,
+    #[serde(flatten)]
+    pub action: ScenarioAction,
+}
+
+/// 场景脚本：按触发时间排序的操作列表
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Scenario {
+    pub steps: Vec<ScenarioStep>,
+}
+
+impl Scenario {
+    /// 创建场景，按触发时间稳定排序（同一时间的操作保持原有顺序）
+    pub fn new(mut steps: Vec<ScenarioStep>) -> Self {
+        steps.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Self { steps }
+    }
+
+    /// 从 JSON 文本解析场景
+    pub fn from_json(text: &str) -> serde_json::Result<Self> {
+        let scenario: Scenario = serde_json::from_str(text)?;
+        Ok(Self::new(scenario.steps))
+    }
+
+    /// 从 JSON 文件加载场景
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Self::from_json(&text).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
+
+/// 场景执行器，使用自己的时钟，不受重置和加载预设影响
+#[derive(Clone, Debug)]
+pub struct ScenarioRunner {
+    scenario: Scenario,
+    /// 场景开始后经过的模拟时间（按步数计算，不累积舍入误差）
+    clock: StepClock,
+    /// 下一个待执行操作的索引
+    next: usize,
+}
+
+impl ScenarioRunner {
+    /// 从头开始执行场景
+    pub fn new(scenario: Scenario) -> Self {
+        Self {
+            scenario,
+            clock: StepClock::default(),
+            next: 0,
+        }
+    }
+
+    /// 取出当前时刻已到期的操作（包括 t=0 的操作）
+    pub fn due(&mut self) -> Vec<ScenarioAction> {
+        // 允许触发时间本身的少量浮点误差
+        let limit = self.clock.time() + 1e-9;
+        let start = self.next;
+        while self.next < self.scenario.steps.len() && self.scenario.steps[self.next].time <= limit
+        {
+            self.next += 1;
+        }
+        self.scenario.steps[start..self.next]
+            .iter()
+            .map(|step| step.action.clone())
+            .collect()
+    }
+
+    /// 推进场景时钟并返回到期的操作
+    pub fn advance(&mut self, dt: f64) -> Vec<ScenarioAction> {
+        self.clock.advance(dt);
+        self.due()
+    }
+
+    /// 场景开始后经过的模拟时间
+    pub fn elapsed(&self) -> f64 {
+        self.clock.time()
+    }
+
+    /// 已执行的操作数和总操作数
+    pub fn progress(&self) -> (usize, usize) {
+        (self.next, self.scenario.steps.len())
+    }
+
+    /// 所有操作是否都已执行
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.scenario.steps.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCRIPT: &str = r#"{
+        "steps": [
+            { "time": 10.0, "action": "pause" },
+            { "time": 0.0, "action": "load_preset", "name": "Classic Chaos" },
+            { "time": 5.0, "action": "set_damping", "value": 0.2 },
+            { "time": 5.0, "action": "set_speed", "value": 2.0 }
+        ]
+    }"#;
+
+    #[test]
+    fn test_actions_fire_in_order_at_trigger_times() {
+        let scenario = Scenario::from_json(SCRIPT).unwrap();
+        let mut runner = ScenarioRunner::new(scenario);
+
+        assert_eq!(
+            runner.due(),
+            vec![ScenarioAction::LoadPreset {
+                name: "Classic Chaos".to_string()
+            }]
+        );
+
+        // 以 0.01s 步长推进，记录每个操作触发时的场景时间
+        let mut fired = Vec::new();
+        for _ in 0..1200 {
+            for action in runner.advance(0.01) {
+                fired.push((runner.elapsed(), action));
+            }
+        }
+
+        assert_eq!(fired.len(), 3);
+        // 同一时间触发的操作保持脚本中的顺序
+        assert_eq!(fired[0].1, ScenarioAction::SetDamping { value: 0.2 });
+        assert_eq!(fired[1].1, ScenarioAction::SetSpeed { value: 2.0 });
+        assert_eq!(fired[2].1, ScenarioAction::Pause);
+        assert!((fired[0].0 - 5.0).abs() < 1e-6);
+        assert_eq!(fired[0].0, fired[1].0);
+        assert!((fired[2].0 - 10.0).abs() < 1e-6);
+        assert!(runner.is_finished());
+        assert_eq!(runner.progress(), (4, 4));
+    }
+
+    #[test]
+    fn test_large_step_fires_all_due_actions() {
+        let mut runner = ScenarioRunner::new(Scenario::from_json(SCRIPT).unwrap());
+        assert_eq!(runner.advance(7.0).len(), 3);
+        assert!(runner.advance(1.0).is_empty());
+        assert_eq!(runner.advance(2.0), vec![ScenarioAction::Pause]);
+    }
+
+    #[test]
+    fn test_invalid_action_is_rejected() {
+        let text = r#"{ "steps": [ { "time": 1.0, "action": "explode" } ] }"#;
+        assert!(Scenario::from_json(text).is_err());
+    }
+}
@@ -1,21 +1,90 @@
 /// 物理统计模块
 /// 负责跟踪和分析双摆的运动统计数据
+use crate::pendulum::{normalize_angle, real_to_f64, PendulumParams, PendulumState, Real};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::path::Path;
 
-#[allow(dead_code)]
+/// 估计李雅普诺夫指数时使用的窗口（样本数）
+pub const LYAPUNOV_WINDOW: usize = 50;
+
+/// 周期检测的相空间距离容差
+pub const PERIOD_TOLERANCE: f64 = 1e-2;
+
+/// 周期检测的最短周期（样本数）
+pub const MIN_PERIOD: usize = 20;
 
+/// 频谱分析中相邻样本间隔视为相同的相对容差
+const SPACING_TOLERANCE: f64 = 1e-3;
+
+#[allow(dead_code)]
 /// 物理统计数据结构
 #[derive(Clone, Debug)]
 pub struct PhysicsStatistics {
     /// 能量历史记录（总能量、动能、势能）
     energy_history: Vec<(f64, f64, f64)>,
+    /// 每个能量样本对应的模拟时间
+    energy_time_history: Vec<f64>,
     /// 能量误差历史记录
     energy_error_history: Vec<f64>,
+    /// 绕悬挂点的总角动量历史记录，与能量样本一同记录
+    angular_momentum_history: Vec<f64>,
     /// 轨迹点历史记录 (x1, y1, x2, y2)
     trajectory_history: Vec<(f64, f64, f64, f64)>,
+    /// 轨迹点对应的模拟时间
+    trajectory_time_history: Vec<f64>,
     /// 相空间点历史记录 (theta1, omega1, theta2, omega2)
     phase_space_history: Vec<(f64, f64, f64, f64)>,
     /// 历史记录的最大长度
     max_history_length: usize,
+    /// 频闪快照（定期保存的完整摆状态）
+    strobe_snapshots: Vec<PendulumState>,
+    /// 频闪快照间隔（每N次记录保存一次）
+    strobe_interval: usize,
+    /// 频闪记录计数器
+    strobe_counter: usize,
+    /// 最多保留的频闪快照数量
+    max_strobe_snapshots: usize,
+    /// 庞加莱截面穿越点 (θ₂, ω₂)，从旧到新
+    poincare_crossings: VecDeque<(f64, f64)>,
+    /// 最多保留的庞加莱截面穿越点数量，超出时丢弃最旧的
+    max_poincare_crossings: usize,
+    /// 数据版本，每次添加或清除数据时递增，用于判断缓存是否过期
+    revision: u64,
+    /// 周期检测结果缓存（键为容差和最短周期）
+    periodicity_cache: AnalysisCache<(f64, usize), Option<usize>>,
+    /// 李雅普诺夫指数估计缓存（键为窗口大小）
+    lyapunov_cache: AnalysisCache<usize, Option<f64>>,
+}
+
+/// 派生分析结果的缓存：数据版本和参数都未变化时直接返回上次的结果
+/// 渲染路径只持有不可变引用，因此使用内部可变性
+#[derive(Clone, Debug)]
+struct AnalysisCache<K, V> {
+    entry: RefCell<Option<(u64, K, V)>>,
+}
+
+impl<K, V> Default for AnalysisCache<K, V> {
+    fn default() -> Self {
+        Self {
+            entry: RefCell::new(None),
+        }
+    }
+}
+
+impl<K: Copy + PartialEq, V: Clone> AnalysisCache<K, V> {
+    /// 返回缓存结果，版本或参数不同时调用 compute 重新计算
+    fn get_or_compute(&self, revision: u64, key: K, compute: impl FnOnce() -> V) -> V {
+        if let Some((cached_revision, cached_key, value)) = self.entry.borrow().as_ref() {
+            if *cached_revision == revision && *cached_key == key {
+                return value.clone();
+            }
+        }
+        let value = compute();
+        *self.entry.borrow_mut() = Some((revision, key, value.clone()));
+        value
+    }
 }
 
 #[allow(dead_code)]
@@ -24,26 +93,52 @@ impl PhysicsStatistics {
     pub fn new(max_history_length: usize) -> Self {
         Self {
             energy_history: Vec::new(),
+            energy_time_history: Vec::new(),
             energy_error_history: Vec::new(),
+            angular_momentum_history: Vec::new(),
             trajectory_history: Vec::new(),
+            trajectory_time_history: Vec::new(),
             phase_space_history: Vec::new(),
             max_history_length,
+            strobe_snapshots: Vec::new(),
+            strobe_interval: 20,
+            strobe_counter: 0,
+            max_strobe_snapshots: 12,
+            poincare_crossings: VecDeque::new(),
+            max_poincare_crossings: 2000,
+            revision: 0,
+            periodicity_cache: AnalysisCache::default(),
+            lyapunov_cache: AnalysisCache::default(),
         }
     }
 
-    /// 添加新的能量数据点
+    /// 添加新的能量数据点（附带采样时的模拟时间）
     pub fn add_energy_data(
         &mut self,
+        time: f64,
         total_energy: f64,
         kinetic_energy: f64,
         potential_energy: f64,
     ) {
         self.energy_history
             .push((total_energy, kinetic_energy, potential_energy));
+        self.energy_time_history.push(time);
+        self.revision += 1;
 
         // 保持历史记录在指定长度内
         if self.energy_history.len() > self.max_history_length {
             self.energy_history.remove(0);
+            self.energy_time_history.remove(0);
+        }
+    }
+
+    /// 添加新的角动量数据点（kg·m²/s）
+    pub fn add_angular_momentum(&mut self, angular_momentum: f64) {
+        self.angular_momentum_history.push(angular_momentum);
+        self.revision += 1;
+
+        if self.angular_momentum_history.len() > self.max_history_length {
+            self.angular_momentum_history.remove(0);
         }
     }
 
@@ -57,6 +152,7 @@ impl PhysicsStatistics {
         };
         
         self.energy_error_history.push(log_error);
+        self.revision += 1;
 
         // 保持历史记录在指定长度内
         if self.energy_error_history.len() > self.max_history_length {
@@ -64,13 +160,16 @@ impl PhysicsStatistics {
         }
     }
 
-    /// 添加新的轨迹数据点
-    pub fn add_trajectory_point(&mut self, x1: f64, y1: f64, x2: f64, y2: f64) {
+    /// 添加新的轨迹数据点（附带采样时的模拟时间）
+    pub fn add_trajectory_point(&mut self, time: f64, x1: f64, y1: f64, x2: f64, y2: f64) {
         self.trajectory_history.push((x1, y1, x2, y2));
+        self.trajectory_time_history.push(time);
+        self.revision += 1;
 
         // 保持历史记录在指定长度内
         if self.trajectory_history.len() > self.max_history_length {
             self.trajectory_history.remove(0);
+            self.trajectory_time_history.remove(0);
         }
     }
 
@@ -78,6 +177,7 @@ impl PhysicsStatistics {
     pub fn add_phase_space_point(&mut self, theta1: f64, omega1: f64, theta2: f64, omega2: f64) {
         self.phase_space_history
             .push((theta1, omega1, theta2, omega2));
+        self.revision += 1;
 
         // 保持历史记录在指定长度内
         if self.phase_space_history.len() > self.max_history_length {
@@ -85,12 +185,84 @@ impl PhysicsStatistics {
         }
     }
 
+    /// 记录一次频闪采样，每 strobe_interval 次保存一个快照
+    /// 返回本次是否保存了快照
+    pub fn add_strobe_sample(&mut self, state: PendulumState) -> bool {
+        let capture = self.strobe_counter == 0;
+        self.strobe_counter = (self.strobe_counter + 1) % self.strobe_interval;
+        self.revision += 1;
+
+        if capture {
+            self.strobe_snapshots.push(state);
+            if self.strobe_snapshots.len() > self.max_strobe_snapshots {
+                self.strobe_snapshots.remove(0);
+            }
+        }
+        capture
+    }
+
+    /// 添加一个庞加莱截面穿越点
+    pub fn add_poincare_crossing(&mut self, theta2: f64, omega2: f64) {
+        self.poincare_crossings.push_back((theta2, omega2));
+        while self.poincare_crossings.len() > self.max_poincare_crossings {
+            self.poincare_crossings.pop_front();
+        }
+        self.revision += 1;
+    }
+
+    /// 获取庞加莱截面穿越点（从旧到新）
+    pub fn get_poincare_crossings(&self) -> &VecDeque<(f64, f64)> {
+        &self.poincare_crossings
+    }
+
+    /// 获取庞加莱截面穿越点的保留上限
+    pub fn max_poincare_crossings(&self) -> usize {
+        self.max_poincare_crossings
+    }
+
+    /// 设置庞加莱截面穿越点的保留上限（至少为1），立即丢弃超出的旧点
+    pub fn set_max_poincare_crossings(&mut self, max: usize) {
+        self.max_poincare_crossings = max.max(1);
+        let excess = self.poincare_crossings.len().saturating_sub(self.max_poincare_crossings);
+        if excess > 0 {
+            self.poincare_crossings.drain(..excess);
+            self.revision += 1;
+        }
+    }
+
+    /// 获取频闪快照（从旧到新）
+    pub fn get_strobe_snapshots(&self) -> &Vec<PendulumState> {
+        &self.strobe_snapshots
+    }
+
+    /// 获取频闪快照间隔
+    pub fn strobe_interval(&self) -> usize {
+        self.strobe_interval
+    }
+
+    /// 设置频闪快照间隔（至少为1）
+    pub fn set_strobe_interval(&mut self, interval: usize) {
+        self.strobe_interval = interval.max(1);
+    }
+
     /// 清除所有统计历史
     pub fn clear_history(&mut self) {
         self.energy_history.clear();
+        self.energy_time_history.clear();
         self.energy_error_history.clear();
+        self.angular_momentum_history.clear();
         self.trajectory_history.clear();
+        self.trajectory_time_history.clear();
         self.phase_space_history.clear();
+        self.strobe_snapshots.clear();
+        self.strobe_counter = 0;
+        self.poincare_crossings.clear();
+        self.revision += 1;
+    }
+
+    /// 获取角动量历史记录的引用
+    pub fn get_angular_momentum_history(&self) -> &Vec<f64> {
+        &self.angular_momentum_history
     }
 
     /// 获取能量历史记录的引用
@@ -98,16 +270,184 @@ impl PhysicsStatistics {
         &self.energy_history
     }
 
+    /// 获取能量样本对应的模拟时间
+    pub fn get_energy_time_history(&self) -> &Vec<f64> {
+        &self.energy_time_history
+    }
+
     /// 获取轨迹历史记录的引用
     pub fn get_trajectory_history(&self) -> &Vec<(f64, f64, f64, f64)> {
         &self.trajectory_history
     }
 
+    /// 获取轨迹点采样时间的引用
+    pub fn get_trajectory_time_history(&self) -> &Vec<f64> {
+        &self.trajectory_time_history
+    }
+
     /// 获取相空间历史记录的引用
     pub fn get_phase_space_history(&self) -> &Vec<(f64, f64, f64, f64)> {
         &self.phase_space_history
     }
 
+    /// θ₂ 的回归映射：相空间历史中相邻两个 θ₂ 极大值组成的 (当前极大值, 下一个极大值)
+    /// ω₂ 由正变为非正的相邻样本视为一个极大值，取两者中较大的 θ₂
+    pub fn theta2_return_map(&self) -> Vec<(f64, f64)> {
+        let maxima: Vec<f64> = self
+            .phase_space_history
+            .windows(2)
+            .filter(|pair| pair[0].3 > 0.0 && pair[1].3 <= 0.0)
+            .map(|pair| pair[0].2.max(pair[1].2))
+            .collect();
+        maxima.windows(2).map(|pair| (pair[0], pair[1])).collect()
+    }
+
+    /// θ₂ 时间序列的频谱，返回从 0 到奈奎斯特频率的 (频率 Hz, 幅值)
+    /// 只取最近一段采样间隔不变的样本，间隔由记录的采样时间得出；
+    /// θ₂ 按相邻差值展开后去掉均值，补零到 2 的幂长度做 FFT，幅值按样本数归一化
+    pub fn power_spectrum(&self) -> Vec<(f64, f64)> {
+        let count = self.phase_space_history.len().min(self.energy_time_history.len());
+        if count < 2 {
+            return Vec::new();
+        }
+        let times = &self.energy_time_history[..count];
+        let sample_dt = times[count - 1] - times[count - 2];
+        if sample_dt <= 0.0 || !sample_dt.is_finite() {
+            return Vec::new();
+        }
+
+        // 步长或记录间隔中途改变时，之前的样本不能与之后的共用一个频率轴
+        let start = times
+            .windows(2)
+            .rposition(|pair| {
+                ((pair[1] - pair[0]) - sample_dt).abs() > sample_dt * SPACING_TOLERANCE
+            })
+            .map_or(0, |index| index + 1);
+        let phase = &self.phase_space_history[start..count];
+        let samples = phase.len();
+
+        // 展开 θ₂，避免翻转时 ±π 处的跳变在频谱中产生宽带假峰
+        let mut unwrapped = Vec::with_capacity(samples);
+        let mut theta2 = phase[0].2;
+        unwrapped.push(theta2);
+        for pair in phase.windows(2) {
+            theta2 += real_to_f64(normalize_angle((pair[1].2 - pair[0].2) as Real));
+            unwrapped.push(theta2);
+        }
+
+        let mean = unwrapped.iter().sum::<f64>() / samples as f64;
+        let size = samples.next_power_of_two();
+        let mut re: Vec<f64> = unwrapped.iter().map(|theta| theta - mean).collect();
+        re.resize(size, 0.0);
+        let mut im = vec![0.0; size];
+        fft_in_place(&mut re, &mut im);
+
+        let resolution = 1.0 / (size as f64 * sample_dt);
+        (0..=size / 2)
+            .map(|k| (k as f64 * resolution, re[k].hypot(im[k]) / samples as f64))
+            .collect()
+    }
+
+    /// 完整历史导出的行数（能量和相空间历史中较短的长度）和较长历史的长度
+    pub fn history_row_counts(&self) -> (usize, usize) {
+        let lengths = [self.energy_history.len(), self.phase_space_history.len()];
+        (
+            lengths.into_iter().min().unwrap_or(0),
+            lengths.into_iter().max().unwrap_or(0),
+        )
+    }
+
+    /// 把相空间和能量历史逐行对齐写成 CSV，只写到较短历史的长度
+    /// 两者在同一次记录中添加；摆球坐标由每行的角度和参数算出，
+    /// 不依赖可能被冻结或单独裁剪的轨迹历史
+    pub fn write_csv<W: Write>(&self, writer: &mut W, params: &PendulumParams) -> io::Result<()> {
+        writeln!(
+            writer,
+            "index,time,x1,y1,x2,y2,theta1,omega1,theta2,omega2,total_energy,kinetic,potential"
+        )?;
+        let rows = self
+            .phase_space_history
+            .iter()
+            .zip(self.energy_history.iter().zip(&self.energy_time_history))
+            .enumerate();
+        for (index, (phase, (energy, time))) in rows {
+            let (theta1, omega1, theta2, omega2) = phase;
+            let (total, kinetic, potential) = energy;
+            let state = PendulumState::at_rest(*theta1 as Real, *theta2 as Real);
+            let (x1, y1) = state.get_mass1_position(params.l1);
+            let (x2, y2) = state.get_mass2_position(params.l1, params.l2);
+            let (x1, y1, x2, y2) =
+                (real_to_f64(x1), real_to_f64(y1), real_to_f64(x2), real_to_f64(y2));
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                index, time, x1, y1, x2, y2, theta1, omega1, theta2, omega2, total, kinetic,
+                potential
+            )?;
+        }
+        Ok(())
+    }
+
+    /// 把完整历史导出为 CSV 文件
+    pub fn export_csv(&self, path: &Path, params: &PendulumParams) -> io::Result<()> {
+        let mut writer = io::BufWriter::new(std::fs::File::create(path)?);
+        self.write_csv(&mut writer, params)?;
+        writer.flush()
+    }
+
+    /// 第 index 个相空间样本对应的模拟时间和完整状态
+    pub fn recorded_state(&self, index: usize) -> Option<(f64, PendulumState)> {
+        let &(theta1, omega1, theta2, omega2) = self.phase_space_history.get(index)?;
+        // 能量和相空间样本在同一次记录中添加，索引一一对应
+        let time = *self.energy_time_history.get(index)?;
+        let state = PendulumState::new(
+            theta1 as Real,
+            theta2 as Real,
+            omega1 as Real,
+            omega2 as Real,
+        );
+        Some((time, state))
+    }
+
+    /// 按记录的样本插值出任意时刻的状态，超出记录范围时返回 None
+    /// 角度用以角速度为斜率的三次 Hermite 插值（跨越 ±π 时按最短方向），角速度线性插值
+    pub fn state_at_time(&self, time: f64) -> Option<PendulumState> {
+        let count = self.phase_space_history.len().min(self.energy_time_history.len());
+        let times = &self.energy_time_history[..count];
+        let (&first, &last) = (times.first()?, times.last()?);
+        if !(first..=last).contains(&time) {
+            return None;
+        }
+
+        // 第一个时间不小于 time 的样本
+        let upper = times.partition_point(|&sample| sample < time);
+        let (t1, after) = self.recorded_state(upper)?;
+        if upper == 0 || t1 == time {
+            return Some(after);
+        }
+        let (t0, before) = self.recorded_state(upper - 1)?;
+
+        let h = t1 - t0;
+        let s = (time - t0) / h;
+        let angle = |a: Real, b: Real, rate_a: Real, rate_b: Real| {
+            let b = real_to_f64(a + normalize_angle(b - a));
+            let (a, rate_a, rate_b) = (real_to_f64(a), real_to_f64(rate_a), real_to_f64(rate_b));
+            let (s2, s3) = (s * s, s * s * s);
+            let value = (2.0 * s3 - 3.0 * s2 + 1.0) * a
+                + (s3 - 2.0 * s2 + s) * h * rate_a
+                + (-2.0 * s3 + 3.0 * s2) * b
+                + (s3 - s2) * h * rate_b;
+            value as Real
+        };
+        let linear = |a: Real, b: Real| a + (b - a) * s as Real;
+        Some(PendulumState::new(
+            angle(before.theta1, after.theta1, before.omega1, after.omega1),
+            angle(before.theta2, after.theta2, before.omega2, after.omega2),
+            linear(before.omega1, after.omega1),
+            linear(before.omega2, after.omega2),
+        ))
+    }
+
     /// 获取能量误差历史记录的引用
     pub fn get_energy_error_history(&self) -> &Vec<f64> {
         &self.energy_error_history
@@ -178,9 +518,52 @@ impl PhysicsStatistics {
         Some(variance.sqrt())
     }
 
-    /// 检测系统是否处于周期性运动
-    /// 通过分析相空间轨迹的回归性来判断
+    /// 计算最近 n 个样本的能量守恒度（总能量的标准差）
+    /// 与 get_energy_conservation 不同，早期的瞬态不会影响结果
+    pub fn energy_conservation_window(&self, n: usize) -> Option<f64> {
+        let start = self.energy_history.len().saturating_sub(n);
+        let energies: Vec<f64> = self.energy_history[start..].iter().map(|e| e.0).collect();
+        if energies.len() < 2 {
+            return None;
+        }
+
+        let mean = energies.iter().sum::<f64>() / energies.len() as f64;
+        let variance =
+            energies.iter().map(|e| (e - mean).powi(2)).sum::<f64>() / energies.len() as f64;
+
+        Some(variance.sqrt())
+    }
+
+    /// 用最近 window 个总能量样本的最小二乘斜率估计能量漂移率（J/s）
+    /// sample_dt 为相邻样本间的模拟时间间隔
+    pub fn energy_drift_rate(&self, window: usize, sample_dt: f64) -> Option<f64> {
+        let start = self.energy_history.len().saturating_sub(window);
+        let samples = &self.energy_history[start..];
+        if samples.len() < 2 || sample_dt <= 0.0 {
+            return None;
+        }
+
+        let n = samples.len() as f64;
+        let mean_t = (n - 1.0) / 2.0;
+        let mean_e = samples.iter().map(|e| e.0).sum::<f64>() / n;
+        let (mut covariance, mut variance) = (0.0, 0.0);
+        for (i, e) in samples.iter().enumerate() {
+            let dt = i as f64 - mean_t;
+            covariance += dt * (e.0 - mean_e);
+            variance += dt * dt;
+        }
+        Some(covariance / variance / sample_dt)
+    }
+
+    /// 检测系统是否处于周期性运动（结果在数据变化前缓存）
     pub fn detect_periodicity(&self, tolerance: f64, min_period: usize) -> Option<usize> {
+        self.periodicity_cache.get_or_compute(self.revision, (tolerance, min_period), || {
+            self.compute_periodicity(tolerance, min_period)
+        })
+    }
+
+    /// 通过分析相空间轨迹的回归性来判断周期
+    fn compute_periodicity(&self, tolerance: f64, min_period: usize) -> Option<usize> {
         if self.phase_space_history.len() < min_period * 2 {
             return None;
         }
@@ -225,9 +608,15 @@ impl PhysicsStatistics {
         None
     }
 
-    /// 计算李雅普诺夫指数的近似值
-    /// 通过观察相近初始条件的发散来估算
+    /// 计算李雅普诺夫指数的近似值（结果在数据变化前缓存）
     pub fn estimate_lyapunov_exponent(&self, window_size: usize) -> Option<f64> {
+        self.lyapunov_cache.get_or_compute(self.revision, window_size, || {
+            self.compute_lyapunov_exponent(window_size)
+        })
+    }
+
+    /// 通过观察相近初始条件的发散来估算李雅普诺夫指数
+    fn compute_lyapunov_exponent(&self, window_size: usize) -> Option<f64> {
         if self.phase_space_history.len() < window_size + 100 {
             return None;
         }
@@ -248,8 +637,7 @@ impl PhysicsStatistics {
             let mut min_distance = f64::INFINITY;
             let mut closest_idx = 0;
 
-            for j in (i + 10)..(i + 50).min(len) {
-                let test_point = &history[j];
+            for (j, test_point) in history.iter().enumerate().take((i + 50).min(len)).skip(i + 10) {
                 let distance = ((ref_point.0 - test_point.0).powi(2)
                     + (ref_point.1 - test_point.1).powi(2)
                     + (ref_point.2 - test_point.2).powi(2)
@@ -295,9 +683,207 @@ impl Default for PhysicsStatistics {
     }
 }
 
+/// 轨迹采样方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrajectorySampling {
+    /// 每N个物理步记录一次
+    StepInterval,
+    /// 下摆球移动超过一定弧长后记录一次
+    ArcLength,
+}
+
+impl TrajectorySampling {
+    /// 所有采样方式
+    pub const ALL: [TrajectorySampling; 2] =
+        [TrajectorySampling::StepInterval, TrajectorySampling::ArcLength];
+
+    /// 界面显示名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            TrajectorySampling::StepInterval => "By Step Interval",
+            TrajectorySampling::ArcLength => "By Distance",
+        }
+    }
+}
+
+/// 判断自上次记录以来是否移动了超过阈值的距离
+pub fn moved_beyond_threshold(last: (f64, f64), current: (f64, f64), threshold: f64) -> bool {
+    let dx = current.0 - last.0;
+    let dy = current.1 - last.1;
+    dx * dx + dy * dy >= threshold * threshold
+}
+
+/// 检测一步内是否穿过庞加莱截面 θ₁ = 0（ω₁ > 0 方向）
+/// 穿过时按线性插值返回截面上的 (θ₂, ω₂)；θ₁ 在 ±π 处回绕不算穿越
+pub fn poincare_crossing(previous: &PendulumState, next: &PendulumState) -> Option<(f64, f64)> {
+    let (before, after) = (real_to_f64(previous.theta1), real_to_f64(next.theta1));
+    if !(before < 0.0 && after >= 0.0) || next.omega1 <= 0.0 {
+        return None;
+    }
+    if after - before >= std::f64::consts::PI {
+        return None;
+    }
+    let t = -before / (after - before);
+    let lerp = |a: Real, b: Real| real_to_f64(a) + (real_to_f64(b) - real_to_f64(a)) * t;
+    Some((lerp(previous.theta2, next.theta2), lerp(previous.omega2, next.omega2)))
+}
+
+/// 相邻相空间样本之间的距离，第一个样本为0
+/// 角度差按最短方向计算，跨越 ±π 的回绕不算作跳变
+pub fn phase_space_rates(history: &[(f64, f64, f64, f64)]) -> Vec<f64> {
+    let angle = |b: f64, a: f64| real_to_f64(normalize_angle((b - a) as Real));
+    std::iter::once(0.0)
+        .chain(history.windows(2).map(|pair| {
+            let (a, b) = (pair[0], pair[1]);
+            (angle(b.0, a.0).powi(2)
+                + (b.1 - a.1).powi(2)
+                + angle(b.2, a.2).powi(2)
+                + (b.3 - a.3).powi(2))
+            .sqrt()
+        }))
+        .take(history.len())
+        .collect()
+}
+
+/// 位形空间路径 (θ₁, θ₂)，取自相空间历史
+/// unwrap 为真时把相邻样本间超过 π 的跳变视为 ±π 处的回绕并累加，使整圈转动不会折回
+pub fn configuration_path(history: &[(f64, f64, f64, f64)], unwrap: bool) -> Vec<[f64; 2]> {
+    let mut path: Vec<[f64; 2]> = Vec::with_capacity(history.len());
+    for &(theta1, _, theta2, _) in history {
+        let point = match path.last() {
+            Some(&[last1, last2]) if unwrap => [
+                last1 + real_to_f64(normalize_angle((theta1 - last1) as Real)),
+                last2 + real_to_f64(normalize_angle((theta2 - last2) as Real)),
+            ],
+            _ => [theta1, theta2],
+        };
+        path.push(point);
+    }
+    path
+}
+
+/// 原地迭代基 2 FFT，长度必须是 2 的幂
+fn fft_in_place(re: &mut [f64], im: &mut [f64]) {
+    let n = re.len();
+    debug_assert!(n.is_power_of_two() && im.len() == n);
+
+    // 按位反转的顺序重排
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * std::f64::consts::PI / len as f64;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (sin, cos) = (angle * k as f64).sin_cos();
+                let (a, b) = (start + k, start + k + len / 2);
+                let (tr, ti) = (re[b] * cos - im[b] * sin, re[b] * sin + im[b] * cos);
+                re[b] = re[a] - tr;
+                im[b] = im[a] - ti;
+                re[a] += tr;
+                im[a] += ti;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+/// 最大值所在的索引，忽略非有限值；相等时取最早的
+pub fn extremum_index(values: impl IntoIterator<Item = f64>) -> Option<usize> {
+    values
+        .into_iter()
+        .enumerate()
+        .filter(|(_, value)| value.is_finite())
+        .fold(None, |best: Option<(usize, f64)>, (index, value)| match best {
+            Some((_, max)) if max >= value => best,
+            _ => Some((index, value)),
+        })
+        .map(|(index, _)| index)
+}
+
+/// 运行中的最大能量误差及其出现位置
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MaxErrorTracker {
+    /// 已观察的步数
+    steps: u64,
+    /// 最大误差、出现时的步序号和模拟时间
+    peak: Option<(f64, u64, f64)>,
+}
+
+impl MaxErrorTracker {
+    /// 记录一步的误差，超过当前最大值时更新
+    pub fn observe(&mut self, time: f64, error: f64) {
+        let index = self.steps;
+        self.steps += 1;
+        let error = error.abs();
+        if !error.is_finite() {
+            return;
+        }
+        if self.peak.is_none_or(|(max, _, _)| error > max) {
+            self.peak = Some((error, index, time));
+        }
+    }
+
+    /// 最大误差、步序号（从0开始）和模拟时间
+    pub fn peak(&self) -> Option<(f64, u64, f64)> {
+        self.peak
+    }
+
+    /// 清空记录
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::pendulum::Real;
+
+    #[test]
+    fn test_csv_rows_line_up_across_frozen_trail() {
+        let params = PendulumParams::default();
+        let mut stats = PhysicsStatistics::new(100);
+        let mut expected = Vec::new();
+        for i in 0..4 {
+            let t = i as f64;
+            let state = PendulumState::new(0.3 * t as Real, -0.5 * t as Real, 0.1, 0.2);
+            stats.add_energy_data(t, -1.0 + t, t, -1.0);
+            stats.add_phase_space_point(t * 0.3, 0.1, -t * 0.5, 0.2);
+            // 冻结轨迹期间（第1、2个样本）不记录轨迹点
+            if !(1..=2).contains(&i) {
+                stats.add_trajectory_point(t, 9.0, 9.0, 9.0, 9.0);
+            }
+            expected.push(state.get_mass2_position(params.l1, params.l2));
+        }
+        assert_eq!(stats.history_row_counts(), (4, 4));
+
+        let mut buffer = Vec::new();
+        stats.write_csv(&mut buffer, &params).unwrap();
+        let text = String::from_utf8(buffer).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[0].starts_with("index,time,x1,y1,x2,y2,theta1"));
+        for (line, (x2, y2)) in lines[1..].iter().zip(expected) {
+            let fields: Vec<f64> = line.split(',').map(|f| f.parse().unwrap()).collect();
+            assert!((fields[4] - real_to_f64(x2)).abs() < 1e-6, "{}", line);
+            assert!((fields[5] - real_to_f64(y2)).abs() < 1e-6, "{}", line);
+        }
+        assert!(lines[3].starts_with("2,2,"));
+        assert!(lines[3].ends_with(",0.6,0.1,-1,0.2,1,2,-1"));
+    }
 
     #[test]
     fn test_physics_statistics_creation() {
@@ -309,9 +895,9 @@ mod tests {
     #[test]
     fn test_add_energy_data() {
         let mut stats = PhysicsStatistics::new(3);
-        stats.add_energy_data(100.0, 60.0, 40.0);
-        stats.add_energy_data(102.0, 65.0, 37.0);
-        stats.add_energy_data(99.0, 55.0, 44.0);
+        stats.add_energy_data(0.0, 100.0, 60.0, 40.0);
+        stats.add_energy_data(0.0, 102.0, 65.0, 37.0);
+        stats.add_energy_data(0.0, 99.0, 55.0, 44.0);
 
         assert_eq!(stats.get_history_length(), 3);
         assert!((stats.get_current_total_energy().unwrap() - 99.0).abs() < 1e-10);
@@ -322,24 +908,110 @@ mod tests {
     #[test]
     fn test_history_length_limit() {
         let mut stats = PhysicsStatistics::new(2);
-        stats.add_energy_data(100.0, 60.0, 40.0);
-        stats.add_energy_data(102.0, 65.0, 37.0);
-        stats.add_energy_data(99.0, 55.0, 44.0);
+        stats.add_energy_data(0.0, 100.0, 60.0, 40.0);
+        stats.add_energy_data(0.0, 102.0, 65.0, 37.0);
+        stats.add_energy_data(0.0, 99.0, 55.0, 44.0);
 
         assert_eq!(stats.get_history_length(), 2);
         assert_eq!(stats.get_energy_history().len(), 2);
+
+        for momentum in [1.0, 2.0, 3.0] {
+            stats.add_angular_momentum(momentum);
+        }
+        assert_eq!(stats.get_angular_momentum_history(), &vec![2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_energy_conservation_window_ignores_early_jump() {
+        let mut stats = PhysicsStatistics::new(1000);
+        assert!(stats.energy_conservation_window(10).is_none());
+
+        // 早期有一次较大的能量跳变，之后保持稳定
+        stats.add_energy_data(0.0, 10.0, 5.0, 5.0);
+        stats.add_energy_data(0.0, 15.0, 10.0, 5.0);
+        for i in 0..100 {
+            let energy = 12.0 + if i % 2 == 0 { 1e-6 } else { -1e-6 };
+            stats.add_energy_data(0.0, energy, 6.0, energy - 6.0);
+        }
+
+        let recent = stats.energy_conservation_window(50).unwrap();
+        let whole = stats.get_energy_conservation().unwrap();
+        assert!(recent < 2e-6, "recent = {}", recent);
+        assert!(whole > 0.1, "whole = {}", whole);
+    }
+
+    #[test]
+    fn test_energy_drift_rate() {
+        let mut stats = PhysicsStatistics::new(1000);
+        assert!(stats.energy_drift_rate(50, 0.01).is_none());
+
+        // 先有一段恒定能量，再以 0.5 J/s 线性衰减
+        for _ in 0..20 {
+            stats.add_energy_data(0.0, 10.0, 5.0, 5.0);
+        }
+        for i in 0..100 {
+            let energy = 10.0 - 0.5 * i as f64 * 0.01;
+            stats.add_energy_data(0.0, energy, 5.0, energy - 5.0);
+        }
+
+        let rate = stats.energy_drift_rate(50, 0.01).unwrap();
+        assert!((rate + 0.5).abs() < 1e-9);
+        assert!(stats.energy_drift_rate(50, 0.0).is_none());
     }
 
     #[test]
     fn test_clear_history() {
         let mut stats = PhysicsStatistics::new(10);
-        stats.add_energy_data(100.0, 60.0, 40.0);
-        stats.add_trajectory_point(1.0, 2.0, 3.0, 4.0);
+        stats.add_energy_data(0.0, 100.0, 60.0, 40.0);
+        stats.add_trajectory_point(0.0, 1.0, 2.0, 3.0, 4.0);
+        stats.add_angular_momentum(1.5);
 
         assert!(stats.has_data());
         stats.clear_history();
         assert!(!stats.has_data());
         assert!(stats.get_trajectory_history().is_empty());
+        assert!(stats.get_angular_momentum_history().is_empty());
+    }
+
+    #[test]
+    fn test_strobe_snapshot_interval() {
+        let mut stats = PhysicsStatistics::new(100);
+        stats.set_strobe_interval(3);
+
+        let captured: Vec<bool> = (0..7)
+            .map(|i| stats.add_strobe_sample(PendulumState::at_rest(i as Real * 0.1, 0.0)))
+            .collect();
+
+        // 第0、3、6次采样保存快照
+        assert_eq!(captured, vec![true, false, false, true, false, false, true]);
+        assert_eq!(stats.get_strobe_snapshots().len(), 3);
+        assert!((stats.get_strobe_snapshots()[1].theta1 - 0.3).abs() < 1e-12);
+
+        stats.clear_history();
+        assert!(stats.get_strobe_snapshots().is_empty());
+        assert!(stats.add_strobe_sample(PendulumState::at_rest(0.0, 0.0)));
+    }
+
+    #[test]
+    fn test_strobe_snapshot_cap() {
+        let mut stats = PhysicsStatistics::new(100);
+        stats.set_strobe_interval(1);
+
+        for i in 0..50 {
+            stats.add_strobe_sample(PendulumState::at_rest(i as Real, 0.0));
+        }
+
+        let snapshots = stats.get_strobe_snapshots();
+        assert_eq!(snapshots.len(), stats.max_strobe_snapshots);
+        assert_eq!(snapshots.last().unwrap().theta1, 49.0);
+    }
+
+    #[test]
+    fn test_moved_beyond_threshold() {
+        assert!(!moved_beyond_threshold((0.0, 0.0), (0.03, 0.04), 0.1));
+        assert!(moved_beyond_threshold((0.0, 0.0), (0.06, 0.09), 0.1));
+        assert!(moved_beyond_threshold((1.0, -1.0), (1.0, -1.2), 0.1));
+        assert!(!moved_beyond_threshold((1.0, -1.0), (1.0, -1.0), 0.1));
     }
 
     #[test]
@@ -347,15 +1019,329 @@ mod tests {
         let mut stats = PhysicsStatistics::new(10);
         // 添加能量守恒的数据
         for _ in 0..5 {
-            stats.add_energy_data(100.0, 60.0, 40.0);
+            stats.add_energy_data(0.0, 100.0, 60.0, 40.0);
         }
 
         let conservation = stats.get_energy_conservation().unwrap();
         assert!(conservation < 1e-10); // 应该非常小
 
         // 添加能量不守恒的数据
-        stats.add_energy_data(200.0, 120.0, 80.0);
+        stats.add_energy_data(0.0, 200.0, 120.0, 80.0);
         let conservation2 = stats.get_energy_conservation().unwrap();
         assert!(conservation2 > 10.0); // 应该较大
     }
+
+    #[test]
+    fn test_max_error_tracker() {
+        let mut tracker = MaxErrorTracker::default();
+        assert_eq!(tracker.peak(), None);
+
+        for (i, error) in [1e-7, -3e-5, 2e-6, f64::NAN, 3e-5, 1e-9].iter().enumerate() {
+            tracker.observe(i as f64 * 0.5, *error);
+        }
+        // 取绝对值最大的一个，相等时保留最早的
+        assert_eq!(tracker.peak(), Some((3e-5, 1, 0.5)));
+
+        tracker.reset();
+        assert_eq!(tracker.peak(), None);
+        tracker.observe(2.0, 1e-8);
+        assert_eq!(tracker.peak(), Some((1e-8, 0, 2.0)));
+    }
+
+    #[test]
+    fn test_extremum_index() {
+        assert_eq!(extremum_index([1.0, 5.0, f64::NAN, 3.0, 5.0]), Some(1));
+        assert_eq!(extremum_index([f64::INFINITY, 2.0]), Some(1));
+        assert_eq!(extremum_index(Vec::new()), None);
+
+        // 相空间变化最快的位置
+        let history = [
+            (0.0, 0.0, 0.0, 0.0),
+            (0.1, 0.0, 0.0, 0.0),
+            (0.1, 2.0, 0.0, 0.0),
+            (0.2, 2.0, 0.0, 0.1),
+        ];
+        let rates = phase_space_rates(&history);
+        assert_eq!(rates.len(), 4);
+        assert_eq!(rates[0], 0.0);
+        assert_eq!(extremum_index(rates), Some(2));
+        assert!(phase_space_rates(&[]).is_empty());
+
+        // θ₂ 跨越 ±π 的回绕只是一小步，最快的是之后角速度的跳变
+        let pi = std::f64::consts::PI;
+        let history = [
+            (0.0, 0.0, pi - 0.05, 1.0),
+            (0.0, 0.0, -pi + 0.05, 1.0),
+            (0.0, 0.0, -pi + 0.15, 1.5),
+        ];
+        let rates = phase_space_rates(&history);
+        assert!((rates[1] - 0.1).abs() < 1e-6, "{:?}", rates);
+        assert_eq!(extremum_index(rates), Some(2));
+    }
+
+    /// 展开路径的容差：f32 物理下角度差按单精度折算
+    fn unwrap_tolerance(f64_tolerance: f64) -> f64 {
+        if cfg!(feature = "f32-physics") {
+            1e-4
+        } else {
+            f64_tolerance
+        }
+    }
+
+    #[test]
+    fn test_configuration_path() {
+        // 锁相关系 θ₂ = 2θ₁：所有点都落在斜率为2的直线上
+        let history: Vec<_> = (0..50)
+            .map(|i| {
+                let theta1 = 0.5 * (i as f64 * 0.2).sin();
+                (theta1, 0.0, 2.0 * theta1, 0.0)
+            })
+            .collect();
+        let path = configuration_path(&history, false);
+        assert_eq!(path.len(), history.len());
+        assert!(path.iter().all(|&[x, y]| (y - 2.0 * x).abs() < 1e-12));
+        // 没有回绕时展开不改变路径
+        let tolerance = unwrap_tolerance(1e-12);
+        for (a, b) in configuration_path(&history, true).iter().zip(&path) {
+            assert!((a[0] - b[0]).abs() < tolerance && (a[1] - b[1]).abs() < tolerance);
+        }
+
+        // 下摆整圈转动：标准化后的角度在 ±π 处折回，展开后持续增长
+        let rotating: Vec<_> = (0..40)
+            .map(|i| {
+                let theta2 = i as f64 * 0.5;
+                let wrapped = (theta2 + std::f64::consts::PI)
+                    .rem_euclid(2.0 * std::f64::consts::PI)
+                    - std::f64::consts::PI;
+                (0.1, 0.0, wrapped, 0.0)
+            })
+            .collect();
+        let unwrapped = configuration_path(&rotating, true);
+        for (i, point) in unwrapped.iter().enumerate() {
+            assert!((point[1] - i as f64 * 0.5).abs() < unwrap_tolerance(1e-9));
+            assert_eq!(point[0], 0.1);
+        }
+        let aliased = configuration_path(&rotating, false);
+        assert!(aliased.iter().all(|point| point[1].abs() <= std::f64::consts::PI));
+    }
+
+    /// 按固定角速度记录若干样本：θ₁ = 0.5t，θ₂ = -t
+    fn uniform_rotation_statistics() -> PhysicsStatistics {
+        let mut statistics = PhysicsStatistics::new(100);
+        for i in 0..=10 {
+            let t = 1.0 + i as f64 * 0.1;
+            statistics.add_energy_data(t, 0.0, 0.0, 0.0);
+            statistics.add_phase_space_point(0.5 * t, 0.5, -t, -1.0);
+        }
+        statistics
+    }
+
+    #[test]
+    fn test_state_at_time_exact_sample() {
+        let statistics = uniform_rotation_statistics();
+        let (time, recorded) = statistics.recorded_state(3).unwrap();
+        assert_eq!(statistics.state_at_time(time), Some(recorded));
+        let (first, state) = statistics.recorded_state(0).unwrap();
+        assert_eq!(statistics.state_at_time(first), Some(state));
+        let (last, state) = statistics.recorded_state(10).unwrap();
+        assert_eq!(statistics.state_at_time(last), Some(state));
+    }
+
+    #[test]
+    fn test_state_at_time_interpolates_between_samples() {
+        let statistics = uniform_rotation_statistics();
+        let state = statistics.state_at_time(1.25).unwrap();
+        assert!((real_to_f64(state.theta1) - 0.625).abs() < 1e-6);
+        assert!((real_to_f64(state.theta2) + 1.25).abs() < 1e-6);
+        assert!((real_to_f64(state.omega1) - 0.5).abs() < 1e-6);
+        assert!((real_to_f64(state.omega2) + 1.0).abs() < 1e-6);
+
+        // 角度在两个样本之间跨越 π 时按最短方向插值
+        let mut statistics = PhysicsStatistics::new(10);
+        statistics.add_energy_data(0.0, 0.0, 0.0, 0.0);
+        statistics.add_phase_space_point(3.1, 1.0, 0.0, 0.0);
+        statistics.add_energy_data(0.1, 0.0, 0.0, 0.0);
+        statistics.add_phase_space_point(-3.1, 1.0, 0.0, 0.0);
+        let state = statistics.state_at_time(0.05).unwrap();
+        assert!(real_to_f64(state.theta1).abs() > 3.1);
+    }
+
+    #[test]
+    fn test_state_at_time_out_of_range() {
+        let statistics = uniform_rotation_statistics();
+        assert_eq!(statistics.state_at_time(0.99), None);
+        assert_eq!(statistics.state_at_time(2.01), None);
+        assert_eq!(statistics.state_at_time(f64::NAN), None);
+        assert_eq!(PhysicsStatistics::new(10).state_at_time(0.0), None);
+    }
+
+    #[test]
+    fn test_recorded_state() {
+        let mut stats = PhysicsStatistics::new(10);
+        for i in 0..3 {
+            let t = i as f64 * 0.5;
+            stats.add_energy_data(t, 0.0, 0.0, 0.0);
+            stats.add_phase_space_point(t, 1.0, 2.0, 3.0);
+        }
+
+        let (time, state) = stats.recorded_state(2).unwrap();
+        assert_eq!(time, 1.0);
+        assert_eq!(state, PendulumState::new(1.0, 2.0, 1.0, 3.0));
+        assert!(stats.recorded_state(3).is_none());
+    }
+
+    #[test]
+    fn test_analysis_cache_recomputes_only_after_new_data() {
+        let mut stats = PhysicsStatistics::new(500);
+        for i in 0..200 {
+            let t = i as f64 * 0.1;
+            stats.add_phase_space_point(t.sin(), t.cos(), (2.0 * t).sin(), t);
+        }
+
+        let computations = std::cell::Cell::new(0);
+        let lyapunov = |stats: &PhysicsStatistics| {
+            stats.lyapunov_cache.get_or_compute(stats.revision, 50, || {
+                computations.set(computations.get() + 1);
+                stats.compute_lyapunov_exponent(50)
+            })
+        };
+
+        let first = lyapunov(&stats);
+        assert_eq!(lyapunov(&stats), first);
+        assert_eq!(computations.get(), 1);
+        // 公开接口与缓存结果一致
+        assert_eq!(stats.estimate_lyapunov_exponent(50), first);
+
+        stats.add_phase_space_point(0.5, 0.5, 0.5, 0.5);
+        lyapunov(&stats);
+        assert_eq!(computations.get(), 2);
+        lyapunov(&stats);
+        assert_eq!(computations.get(), 2);
+
+        // 周期检测同样写入自己的缓存
+        stats.detect_periodicity(1e-2, 20);
+        assert!(stats.periodicity_cache.entry.borrow().is_some());
+    }
+
+    #[test]
+    fn test_poincare_crossings_respect_cap() {
+        let mut stats = PhysicsStatistics::new(100);
+        stats.set_max_poincare_crossings(5);
+        for i in 0..12 {
+            stats.add_poincare_crossing(i as f64, 0.0);
+        }
+        let crossings = stats.get_poincare_crossings();
+        assert_eq!(crossings.len(), 5);
+        // 保留最新的点
+        assert_eq!(crossings.front(), Some(&(7.0, 0.0)));
+        assert_eq!(crossings.back(), Some(&(11.0, 0.0)));
+
+        // 缩小上限时立即丢弃旧点
+        stats.set_max_poincare_crossings(2);
+        assert_eq!(stats.get_poincare_crossings().len(), 2);
+        assert_eq!(stats.get_poincare_crossings().front(), Some(&(10.0, 0.0)));
+
+        stats.clear_history();
+        assert!(stats.get_poincare_crossings().is_empty());
+    }
+
+    #[test]
+    fn test_poincare_crossing_detection() {
+        let before = PendulumState::new(-0.1, 0.0, 1.0, 2.0);
+        let after = PendulumState::new(0.1, 1.0, 1.0, 4.0);
+        let (theta2, omega2) = poincare_crossing(&before, &after).unwrap();
+        assert!((theta2 - 0.5).abs() < 1e-6);
+        assert!((omega2 - 3.0).abs() < 1e-6);
+
+        // 反方向穿越和 ±π 回绕都不记录
+        let backwards = PendulumState::new(0.1, 0.0, -1.0, 0.0);
+        assert!(poincare_crossing(&backwards, &before).is_none());
+        let wrap_before = PendulumState::new(-3.1, 0.0, 1.0, 0.0);
+        let wrap_after = PendulumState::new(3.1, 0.0, 1.0, 0.0);
+        assert!(poincare_crossing(&wrap_before, &wrap_after).is_none());
+    }
+
+    #[test]
+    fn test_power_spectrum_peak() {
+        let mut stats = PhysicsStatistics::new(5000);
+        assert!(stats.power_spectrum().is_empty());
+
+        // 1000 个样本补零到 1024，2 Hz 的正弦叠加常数偏移
+        for i in 0..1000 {
+            let t = i as f64 * 0.01;
+            let theta2 = 0.5 + 0.3 * (2.0 * std::f64::consts::PI * 2.0 * t).sin();
+            stats.add_energy_data(t, 0.0, 0.0, 0.0);
+            stats.add_phase_space_point(0.0, 0.0, theta2, 0.0);
+        }
+        let spectrum = stats.power_spectrum();
+        assert_eq!(spectrum.len(), 513);
+        assert!((spectrum[512].0 - 50.0).abs() < 1e-9);
+        assert!(spectrum[0].1 < 1e-3);
+
+        let peak = extremum_index(spectrum.iter().map(|p| p.1)).unwrap();
+        let (peak_frequency, peak_magnitude) = spectrum[peak];
+        assert!((peak_frequency - 2.0).abs() < 0.1);
+        assert!(peak_magnitude > 0.05 && peak_magnitude <= 0.15);
+    }
+
+    #[test]
+    fn test_power_spectrum_unwraps_theta2() {
+        // 绕 π 的 2 Hz 摆动记录下来在 ±π 之间来回跳变；展开后应与绕 0 的同样摆动频谱一致
+        let mut wrapped_stats = PhysicsStatistics::new(5000);
+        let mut plain_stats = PhysicsStatistics::new(5000);
+        for i in 0..1000 {
+            let t = i as f64 * 0.01;
+            let swing = 0.5 * (2.0 * std::f64::consts::PI * 2.0 * t).sin();
+            let wrapped = real_to_f64(normalize_angle((std::f64::consts::PI + swing) as Real));
+            wrapped_stats.add_energy_data(t, 0.0, 0.0, 0.0);
+            wrapped_stats.add_phase_space_point(0.0, 0.0, wrapped, 0.0);
+            plain_stats.add_energy_data(t, 0.0, 0.0, 0.0);
+            plain_stats.add_phase_space_point(0.0, 0.0, swing, 0.0);
+        }
+        let wrapped = wrapped_stats.power_spectrum();
+        let plain = plain_stats.power_spectrum();
+        assert_eq!(wrapped.len(), plain.len());
+        for (a, b) in wrapped.iter().zip(&plain) {
+            assert!((a.1 - b.1).abs() < 1e-4, "{} Hz: {} vs {}", a.0, a.1, b.1);
+        }
+    }
+
+    #[test]
+    fn test_power_spectrum_uses_latest_spacing() {
+        // 采样间隔从 0.01 改为 0.02 后只用新间隔的样本，频率轴不被旧间隔缩放
+        let mut stats = PhysicsStatistics::new(5000);
+        let mut t = 0.0;
+        for i in 0..1500 {
+            let dt = if i < 500 { 0.01 } else { 0.02 };
+            let theta2 = 0.3 * (2.0 * std::f64::consts::PI * 2.0 * t).sin();
+            stats.add_energy_data(t, 0.0, 0.0, 0.0);
+            stats.add_phase_space_point(0.0, 0.0, theta2, 0.0);
+            t += dt;
+        }
+        let spectrum = stats.power_spectrum();
+        assert_eq!(spectrum.len(), 513);
+        assert!((spectrum[512].0 - 25.0).abs() < 1e-6);
+        let peak = extremum_index(spectrum.iter().map(|p| p.1)).unwrap();
+        assert!((spectrum[peak].0 - 2.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_theta2_return_map() {
+        let mut stats = PhysicsStatistics::new(5000);
+        assert!(stats.theta2_return_map().is_empty());
+
+        // 振幅逐周期衰减的 θ₂ = A(t) cos t，极大值出现在 t = 2πk 附近
+        for i in 0..=2000 {
+            let t = i as f64 * 0.01;
+            let amplitude = 1.0 - 0.02 * t;
+            let (theta2, omega2) = (amplitude * t.cos(), -amplitude * t.sin() - 0.02 * t.cos());
+            stats.add_phase_space_point(0.0, 0.0, theta2, omega2);
+        }
+        let pairs = stats.theta2_return_map();
+        assert_eq!(pairs.len(), 2);
+        for (current, next) in pairs {
+            assert!(next < current);
+            assert!((current - next - 0.02 * 2.0 * std::f64::consts::PI).abs() < 0.01);
+        }
+    }
 }
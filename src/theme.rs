@@ -1,12 +1,84 @@
 /// 主题管理模块
 /// 负责颜色主题切换和动画效果
 use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+/// 颜色主题在 eframe 存储中的键
+pub const THEME_KEY: &str = "color_theme";
 
 /// 颜色主题枚举
-#[derive(Clone, Copy, PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
 pub enum ColorTheme {
     Light,
     Dark,
+    /// 黑底高对比度，摆球和轨迹使用色盲友好的 Okabe–Ito 配色
+    HighContrast,
+}
+
+/// 摆杆、质点、轨迹和背景网格的颜色
+type PendulumColors = (egui::Color32, egui::Color32, egui::Color32, egui::Color32);
+
+impl ColorTheme {
+    /// 所有主题
+    pub const ALL: [ColorTheme; 3] =
+        [ColorTheme::Light, ColorTheme::Dark, ColorTheme::HighContrast];
+
+    /// 显示名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            ColorTheme::Light => "Light",
+            ColorTheme::Dark => "Dark",
+            ColorTheme::HighContrast => "High Contrast",
+        }
+    }
+
+    /// 按 ALL 的顺序循环到下一个主题
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&theme| theme == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// 该主题下摆系统的颜色
+    fn pendulum_colors(self) -> PendulumColors {
+        match self {
+            ColorTheme::Light => (
+                egui::Color32::BLACK,                      // 摆杆颜色
+                egui::Color32::RED,                        // 质点颜色
+                egui::Color32::BLUE,                       // 轨迹颜色
+                egui::Color32::from_rgb(180, 180, 180),    // 背景网格 - 深灰色，在白色背景上可见
+            ),
+            ColorTheme::Dark => (
+                egui::Color32::WHITE,                   // 摆杆颜色
+                egui::Color32::from_rgb(255, 100, 100), // 质点颜色
+                egui::Color32::from_rgb(100, 150, 255), // 轨迹颜色
+                egui::Color32::from_rgb(80, 80, 80),    // 背景网格 - 深灰色，在黑色背景上可见
+            ),
+            ColorTheme::HighContrast => (
+                egui::Color32::WHITE,                   // 摆杆颜色
+                egui::Color32::from_rgb(230, 159, 0),   // 质点颜色 - 橙色
+                egui::Color32::from_rgb(86, 180, 233),  // 轨迹颜色 - 天蓝色
+                egui::Color32::from_rgb(110, 110, 110), // 背景网格
+            ),
+        }
+    }
+
+    /// 该主题的界面样式
+    fn visuals(self) -> egui::Visuals {
+        match self {
+            ColorTheme::Light => egui::Visuals::light(),
+            ColorTheme::Dark => egui::Visuals::dark(),
+            ColorTheme::HighContrast => {
+                let mut visuals = egui::Visuals::dark();
+                visuals.override_text_color = Some(egui::Color32::WHITE);
+                visuals.panel_fill = egui::Color32::BLACK;
+                visuals.window_fill = egui::Color32::BLACK;
+                visuals.extreme_bg_color = egui::Color32::BLACK;
+                visuals.widgets.noninteractive.bg_stroke.color = egui::Color32::from_gray(200);
+                visuals.selection.bg_fill = egui::Color32::from_rgb(0, 114, 178);
+                visuals
+            }
+        }
+    }
 }
 
 /// 主题管理器结构体
@@ -35,7 +107,7 @@ impl ThemeManager {
 
     /// 开始主题切换动画
     pub fn start_theme_transition(&mut self, new_theme: ColorTheme) {
-        if new_theme != self.current_theme {
+        if new_theme != self.target_theme {
             self.target_theme = new_theme;
             self.transition_start = Some(std::time::Instant::now());
             self.transition_progress = 0.0;
@@ -87,28 +159,11 @@ impl ThemeManager {
     }
 
     /// 获取当前主题的颜色配置（用于摆杆和轨迹渲染）
-    pub fn get_pendulum_colors(
-        &self,
-    ) -> (egui::Color32, egui::Color32, egui::Color32, egui::Color32) {
-        let light_colors = (
-            egui::Color32::BLACK,                      // 摆杆颜色
-            egui::Color32::RED,                        // 质点颜色
-            egui::Color32::BLUE,                       // 轨迹颜色
-            egui::Color32::from_rgb(180, 180, 180),    // 背景网格 - 深灰色，在白色背景上可见
-        );
-        let dark_colors = (
-            egui::Color32::WHITE,                   // 摆杆颜色
-            egui::Color32::from_rgb(255, 100, 100), // 质点颜色
-            egui::Color32::from_rgb(100, 150, 255), // 轨迹颜色
-            egui::Color32::from_rgb(80, 80, 80),    // 背景网格 - 深灰色，在黑色背景上可见
-        );
-
-        // 如果正在进行主题切换动画
+    pub fn get_pendulum_colors(&self) -> PendulumColors {
+        // 如果正在进行主题切换动画，从当前主题的颜色过渡到目标主题
         if self.transition_progress < 1.0 {
-            let (from_colors, to_colors) = match self.target_theme {
-                ColorTheme::Dark => (light_colors, dark_colors),
-                ColorTheme::Light => (dark_colors, light_colors),
-            };
+            let from_colors = self.current_theme.pendulum_colors();
+            let to_colors = self.target_theme.pendulum_colors();
 
             (
                 Self::lerp_color(from_colors.0, to_colors.0, self.transition_progress),
@@ -117,10 +172,7 @@ impl ThemeManager {
                 Self::lerp_color(from_colors.3, to_colors.3, self.transition_progress),
             )
         } else {
-            match self.current_theme {
-                ColorTheme::Light => light_colors,
-                ColorTheme::Dark => dark_colors,
-            }
+            self.current_theme.pendulum_colors()
         }
     }
 
@@ -136,6 +188,22 @@ impl ThemeManager {
                 egui::Color32::from_rgb(100, 150, 255), // 平均线：亮蓝色
                 egui::Color32::from_rgb(100, 200, 150), // 方差线：亮绿色
             ),
+            ColorTheme::HighContrast => (
+                egui::Color32::from_rgb(86, 180, 233),  // 平均线：天蓝色
+                egui::Color32::from_rgb(240, 228, 66),  // 方差线：黄色
+            ),
+        }
+    }
+
+    /// 获取表示运行状态的强调色：运行时为绿色，暂停时为灰色
+    pub fn get_state_color(&self, is_running: bool) -> egui::Color32 {
+        match (self.current_theme, is_running) {
+            (ColorTheme::Light, true) => egui::Color32::from_rgb(40, 160, 70),
+            (ColorTheme::Light, false) => egui::Color32::from_rgb(150, 150, 150),
+            (ColorTheme::Dark, true) => egui::Color32::from_rgb(80, 200, 110),
+            (ColorTheme::Dark, false) => egui::Color32::from_rgb(100, 100, 100),
+            (ColorTheme::HighContrast, true) => egui::Color32::from_rgb(0, 158, 115),
+            (ColorTheme::HighContrast, false) => egui::Color32::from_rgb(170, 170, 170),
         }
     }
 
@@ -154,23 +222,17 @@ impl ThemeManager {
             self.current_theme
         };
 
-        match ui_theme {
-            ColorTheme::Light => {
-                ctx.set_visuals(egui::Visuals::light());
-            }
-            ColorTheme::Dark => {
-                ctx.set_visuals(egui::Visuals::dark());
-            }
-        }
+        ctx.set_visuals(ui_theme.visuals());
+    }
+
+    /// 所选主题（切换动画进行中时为目标主题）
+    pub fn theme(&self) -> ColorTheme {
+        self.target_theme
     }
 
-    /// 切换主题
+    /// 切换到下一个主题
     pub fn toggle_theme(&mut self) {
-        let new_theme = match self.current_theme {
-            ColorTheme::Light => ColorTheme::Dark,
-            ColorTheme::Dark => ColorTheme::Light,
-        };
-        self.start_theme_transition(new_theme);
+        self.start_theme_transition(self.theme().next());
     }
 }
 
@@ -199,6 +261,22 @@ mod tests {
         // 应该开始过渡到Dark主题
         assert!(theme_manager.is_transitioning());
         assert_eq!(theme_manager.target_theme, ColorTheme::Dark);
+        // 保存设置时记录切换的目标主题
+        assert_eq!(theme_manager.theme(), ColorTheme::Dark);
+    }
+
+    #[test]
+    fn test_state_color() {
+        for theme in ColorTheme::ALL {
+            let theme_manager = ThemeManager::new(theme);
+            let running = theme_manager.get_state_color(true);
+            let paused = theme_manager.get_state_color(false);
+
+            assert_ne!(running, paused);
+            // 运行时偏绿，暂停时为灰色
+            assert!(running.g() > running.r() && running.g() > running.b());
+            assert!(paused.r() == paused.g() && paused.g() == paused.b());
+        }
     }
 
     #[test]
@@ -209,4 +287,26 @@ mod tests {
         let mid_color = ThemeManager::lerp_color(color1, color2, 0.5);
         assert_eq!(mid_color, egui::Color32::from_rgb(127, 127, 127));
     }
+
+    #[test]
+    fn test_theme_cycle_and_transition_colors() {
+        // 依次循环所有主题后回到起点
+        let mut theme = ColorTheme::Light;
+        for expected in [ColorTheme::Dark, ColorTheme::HighContrast, ColorTheme::Light] {
+            theme = theme.next();
+            assert_eq!(theme, expected);
+        }
+
+        // 从高对比度切换到浅色时，动画从高对比度的颜色开始
+        let mut theme_manager = ThemeManager::new(ColorTheme::HighContrast);
+        theme_manager.start_theme_transition(ColorTheme::Light);
+        assert!(theme_manager.is_transitioning());
+        assert_eq!(
+            theme_manager.get_pendulum_colors(),
+            ColorTheme::HighContrast.pendulum_colors()
+        );
+        theme_manager.transition_progress = 0.999_999;
+        let (rod, ..) = theme_manager.get_pendulum_colors();
+        assert_eq!(rod, ColorTheme::Light.pendulum_colors().0);
+    }
 }
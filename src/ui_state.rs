@@ -1,22 +1,54 @@
 /// UI状态管理模块
 /// 管理界面状态，包括显示选项和状态信息
+use crate::readout::DEFAULT_PRECISION;
+use crate::visualization::{TrailColorMode, TrailPaths, TrailSelection, DEFAULT_PLOT_POINT_LIMIT};
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug)]
+/// 显示设置在 eframe 存储中的键
+pub const UI_STATE_KEY: &str = "ui_state";
+
+/// 图表数据点上限的可选范围
+pub const PLOT_POINT_LIMIT_RANGE: std::ops::RangeInclusive<usize> = 500..=100_000;
+
+/// 缺少的字段（旧版本保存的设置）取默认值，状态信息和平移偏移不保存
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct UiStateManager {
     /// 缩放级别
     zoom_level: f32,
     /// 视口平移偏移
+    #[serde(skip)]
     pan_offset: egui::Vec2,
     /// 是否显示网格线
     show_grid_lines: bool,
     /// 状态信息
+    #[serde(skip)]
     status_message: Option<String>,
     /// 状态信息显示的时间戳
+    #[serde(skip)]
     status_timestamp: Option<std::time::Instant>,
     /// 是否显示轨迹
     show_trajectory: bool,
+    /// 绘制哪些轨迹路径
+    trail_paths: TrailPaths,
     /// 轨迹透明度
     trajectory_alpha: f32,
+    /// 轨迹着色方式
+    trail_color_mode: TrailColorMode,
+    /// 是否显示频闪叠影
+    show_strobe: bool,
+    /// 频闪叠影的最大不透明度
+    strobe_opacity: f32,
+    /// 是否显示画布图例
+    show_legend: bool,
+    /// 是否用画布边框颜色提示运行/暂停状态
+    show_state_border: bool,
+    /// 是否显示悬挂点约束力
+    show_pivot_force: bool,
+    /// 读数的显示精度（能量的小数位数）
+    display_precision: usize,
+    /// 图表数据点的安全上限，超过后自动抽稀
+    plot_point_limit: usize,
 }
 
 impl UiStateManager {
@@ -29,7 +61,29 @@ impl UiStateManager {
             status_message: None,
             status_timestamp: None,
             show_trajectory: true,
+            trail_paths: TrailSelection::Lower.paths(),
             trajectory_alpha: 0.7,
+            trail_color_mode: TrailColorMode::Age,
+            show_strobe: false,
+            strobe_opacity: 0.5,
+            show_legend: false,
+            show_state_border: true,
+            show_pivot_force: false,
+            display_precision: DEFAULT_PRECISION,
+            plot_point_limit: DEFAULT_PLOT_POINT_LIMIT,
+        }
+    }
+
+    /// 把从存储恢复的设置限制到各 setter 接受的范围，反序列化不经过 setter
+    pub fn sanitize(&mut self) {
+        let defaults = Self::new();
+        let finite_or = |value: f32, default: f32| if value.is_finite() { value } else { default };
+        self.set_trajectory_alpha(finite_or(self.trajectory_alpha, defaults.trajectory_alpha));
+        self.set_strobe_opacity(finite_or(self.strobe_opacity, defaults.strobe_opacity));
+        self.set_display_precision(self.display_precision);
+        self.set_plot_point_limit(self.plot_point_limit);
+        if !(self.zoom_level.is_finite() && self.zoom_level > 0.0) {
+            self.zoom_level = defaults.zoom_level;
         }
     }
 
@@ -80,6 +134,21 @@ impl UiStateManager {
         self.show_trajectory = show;
     }
 
+    /// 绘制哪些轨迹路径
+    pub fn trail_paths(&self) -> TrailPaths {
+        self.trail_paths
+    }
+
+    /// 当前路径开关对应的轨迹选择
+    pub fn trail_selection(&self) -> Option<TrailSelection> {
+        TrailSelection::from_paths(self.trail_paths)
+    }
+
+    /// 按轨迹选择设置各条路径的开关
+    pub fn set_trail_selection(&mut self, selection: TrailSelection) {
+        self.trail_paths = selection.paths();
+    }
+
     /// 获取轨迹透明度
     pub fn trajectory_alpha(&self) -> f32 {
         self.trajectory_alpha
@@ -89,10 +158,145 @@ impl UiStateManager {
     pub fn set_trajectory_alpha(&mut self, alpha: f32) {
         self.trajectory_alpha = alpha.clamp(0.0, 1.0);
     }
+
+    /// 获取轨迹着色方式
+    pub fn trail_color_mode(&self) -> TrailColorMode {
+        self.trail_color_mode
+    }
+
+    /// 设置轨迹着色方式
+    pub fn set_trail_color_mode(&mut self, mode: TrailColorMode) {
+        self.trail_color_mode = mode;
+    }
+
+    /// 是否显示频闪叠影
+    pub fn show_strobe(&self) -> bool {
+        self.show_strobe
+    }
+
+    /// 设置是否显示频闪叠影
+    pub fn set_show_strobe(&mut self, show: bool) {
+        self.show_strobe = show;
+    }
+
+    /// 获取频闪叠影不透明度
+    pub fn strobe_opacity(&self) -> f32 {
+        self.strobe_opacity
+    }
+
+    /// 设置频闪叠影不透明度
+    pub fn set_strobe_opacity(&mut self, opacity: f32) {
+        self.strobe_opacity = opacity.clamp(0.0, 1.0);
+    }
+
+    /// 是否显示画布图例
+    pub fn show_legend(&self) -> bool {
+        self.show_legend
+    }
+
+    /// 设置是否显示画布图例
+    pub fn set_show_legend(&mut self, show: bool) {
+        self.show_legend = show;
+    }
+
+    /// 是否显示运行状态边框
+    pub fn show_state_border(&self) -> bool {
+        self.show_state_border
+    }
+
+    /// 设置是否显示运行状态边框
+    pub fn set_show_state_border(&mut self, show: bool) {
+        self.show_state_border = show;
+    }
+
+    /// 获取读数的显示精度
+    pub fn display_precision(&self) -> usize {
+        self.display_precision
+    }
+
+    /// 设置读数的显示精度
+    pub fn set_display_precision(&mut self, precision: usize) {
+        self.display_precision = precision.min(crate::readout::MAX_PRECISION);
+    }
+
+    /// 获取图表数据点的安全上限
+    pub fn plot_point_limit(&self) -> usize {
+        self.plot_point_limit
+    }
+
+    /// 设置图表数据点的安全上限
+    pub fn set_plot_point_limit(&mut self, limit: usize) {
+        self.plot_point_limit =
+            limit.clamp(*PLOT_POINT_LIMIT_RANGE.start(), *PLOT_POINT_LIMIT_RANGE.end());
+    }
+
+    /// 是否显示悬挂点约束力
+    pub fn show_pivot_force(&self) -> bool {
+        self.show_pivot_force
+    }
+
+    /// 设置是否显示悬挂点约束力
+    pub fn set_show_pivot_force(&mut self, show: bool) {
+        self.show_pivot_force = show;
+    }
 }
 
 impl Default for UiStateManager {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ui_state_round_trips_through_storage_format() {
+        let mut ui_state = UiStateManager::new();
+        ui_state.set_show_grid_lines(false);
+        ui_state.set_trajectory_alpha(0.3);
+        ui_state.set_trail_color_mode(TrailColorMode::Speed);
+        ui_state.set_status("not persisted".to_string());
+
+        let text = serde_json::to_string(&ui_state).unwrap();
+        let restored: UiStateManager = serde_json::from_str(&text).unwrap();
+        assert!(!restored.show_grid_lines());
+        assert_eq!(restored.trajectory_alpha(), 0.3);
+        assert_eq!(restored.trail_color_mode(), TrailColorMode::Speed);
+        assert!(restored.status_message().is_none());
+
+        // 旧版本保存的设置缺少字段时取默认值
+        let partial: UiStateManager = serde_json::from_str(r#"{"show_grid_lines":false}"#).unwrap();
+        assert!(!partial.show_grid_lines());
+        assert_eq!(partial.trajectory_alpha(), UiStateManager::new().trajectory_alpha());
+    }
+
+    #[test]
+    fn test_sanitize_clamps_restored_values() {
+        let mut restored: UiStateManager = serde_json::from_str(
+            r#"{
+                "trajectory_alpha": 7.0,
+                "strobe_opacity": -1.0,
+                "display_precision": 1000000,
+                "plot_point_limit": 0,
+                "zoom_level": 0.0
+            }"#,
+        )
+        .unwrap();
+        restored.sanitize();
+        assert_eq!(restored.trajectory_alpha(), 1.0);
+        assert_eq!(restored.strobe_opacity(), 0.0);
+        assert_eq!(restored.display_precision(), crate::readout::MAX_PRECISION);
+        assert_eq!(restored.plot_point_limit(), *PLOT_POINT_LIMIT_RANGE.start());
+        assert_eq!(restored.zoom_level, 1.0);
+
+        // 有效的设置保持不变
+        let mut ui_state = UiStateManager::new();
+        ui_state.set_trajectory_alpha(0.3);
+        ui_state.set_display_precision(2);
+        let before = serde_json::to_string(&ui_state).unwrap();
+        ui_state.sanitize();
+        assert_eq!(serde_json::to_string(&ui_state).unwrap(), before);
+    }
+}
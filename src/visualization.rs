@@ -1,23 +1,370 @@
-use crate::pendulum::DoublePendulum;
+use crate::ensemble::Ensemble;
+use crate::pendulum::{
+    real_to_f32, real_to_f64, DoublePendulum, PendulumParams, PendulumState, Real,
+};
+use crate::physics::PhysicsEngine;
 use crate::statistics::PhysicsStatistics;
 use crate::theme::ThemeManager;
 use crate::ui_state::UiStateManager;
 /// 可视化渲染模块
 /// 负责绘制双摆系统的实时状态和轨迹
-
 #[allow(dead_code)]
 use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+/// 速度向量的显示比例（像素 / (m/s)）
+const VELOCITY_ARROW_SCALE: f32 = 10.0;
+
+/// 悬挂点约束力箭头的显示比例（像素 / N）
+const PIVOT_FORCE_ARROW_SCALE: f32 = 3.0;
+
+/// 图例中参考箭头的最大长度（像素）
+const LEGEND_MAX_ARROW_LENGTH: f32 = 80.0;
+
+/// 缩略图中质点的半径（像素），绘制时留出同样宽度的边距
+const THUMBNAIL_BOB_RADIUS: f32 = 3.0;
+
+/// 轨迹线宽（像素）
+const TRAIL_STROKE_WIDTH: f32 = 1.5;
+
+/// 参数变化前的摆形叠影持续时间（秒）
+const PARAMETER_GHOST_DURATION: f32 = 1.5;
+
+/// 参数变化前的摆形叠影的初始不透明度
+const PARAMETER_GHOST_OPACITY: f32 = 0.6;
+
+/// 按负载绘制时摆杆的最小和最大宽度（像素）
+const MIN_ROD_WIDTH: f32 = 0.5;
+const MAX_ROD_WIDTH: f32 = 12.0;
+
+/// 拖动摆球的判定容差在 eframe 存储中的键
+pub const GRAB_TOLERANCE_KEY: &str = "grab_tolerance_px";
+
+/// 视图缩放比例在 eframe 存储中的键
+pub const RENDER_SCALE_KEY: &str = "render_scale";
+
+/// 默认拖动判定容差（物理像素）
+const DEFAULT_GRAB_TOLERANCE_PX: f32 = 5.0;
+
+/// 拖动判定半径的下限（逻辑点），避免小质量摆球难以抓取
+const MIN_GRAB_RADIUS: f32 = 12.0;
+
+/// 平移惯性的指数衰减速率（1/秒）
+const PAN_INERTIA_DECAY: f32 = 5.0;
+
+/// 低于该速度（像素/秒）时平移惯性停止
+const PAN_INERTIA_MIN_SPEED: f32 = 5.0;
+
+/// 运动模糊在最大强度时的摆球残影个数
+const MAX_MOTION_BLUR_COPIES: usize = 8;
+
+/// 运动模糊在最大强度时相邻残影间隔的轨迹采样数
+const MAX_MOTION_BLUR_SPACING: usize = 3;
+
+/// 最新残影的不透明度
+const MOTION_BLUR_OPACITY: f32 = 0.5;
+
+/// 平滑系数所对应的参考帧时长（秒）：系数为 s 时每个参考帧保留 s 的差距
+const SMOOTHING_REFERENCE_FRAME: f32 = 1.0 / 60.0;
+
+/// 画布图例的布局
+#[derive(Clone, Debug, PartialEq)]
+struct LegendLayout {
+    /// 图例背景区域
+    frame: egui::Rect,
+    /// 参考箭头的起点和终点
+    arrow: (egui::Pos2, egui::Pos2),
+    /// 参考箭头标签
+    arrow_label: String,
+    /// 速度色带区域（仅在速度着色时存在）
+    ramp_rect: Option<egui::Rect>,
+    /// 色带刻度（位置, 标签）
+    ramp_ticks: Vec<(egui::Pos2, String)>,
+}
+
+/// 相机模式
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CameraMode {
+    /// 悬挂点固定在屏幕上
+    FixedPivot,
+    /// 跟随下摆质点
+    FollowLowerBob,
+    /// 跟随系统质心
+    FollowCenterOfMass,
+    /// 悬挂点固定，仅自动缩放以容纳整条轨迹
+    AutoZoomFixedPivot,
+}
+
+impl CameraMode {
+    /// 所有相机模式
+    pub const ALL: [CameraMode; 4] = [
+        CameraMode::FixedPivot,
+        CameraMode::FollowLowerBob,
+        CameraMode::FollowCenterOfMass,
+        CameraMode::AutoZoomFixedPivot,
+    ];
+
+    /// 显示名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            CameraMode::FixedPivot => "Fixed Pivot",
+            CameraMode::FollowLowerBob => "Follow Lower Bob",
+            CameraMode::FollowCenterOfMass => "Follow Center of Mass",
+            CameraMode::AutoZoomFixedPivot => "Auto-Zoom, Fixed Pivot",
+        }
+    }
+}
+
+/// 画布宽高比约束
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AspectRatio {
+    /// 使用整个画布
+    Free,
+    /// 1:1
+    Square,
+    /// 4:3
+    Standard,
+    /// 16:9
+    Widescreen,
+}
+
+impl AspectRatio {
+    /// 所有宽高比选项
+    pub const ALL: [AspectRatio; 4] = [
+        AspectRatio::Free,
+        AspectRatio::Square,
+        AspectRatio::Standard,
+        AspectRatio::Widescreen,
+    ];
+
+    /// 显示名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            AspectRatio::Free => "Free",
+            AspectRatio::Square => "1:1",
+            AspectRatio::Standard => "4:3",
+            AspectRatio::Widescreen => "16:9",
+        }
+    }
+
+    /// 宽高比数值（宽/高），Free 返回None
+    pub fn ratio(&self) -> Option<f32> {
+        match self {
+            AspectRatio::Free => None,
+            AspectRatio::Square => Some(1.0),
+            AspectRatio::Standard => Some(4.0 / 3.0),
+            AspectRatio::Widescreen => Some(16.0 / 9.0),
+        }
+    }
+}
+
+/// 轨迹着色方式
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TrailColorMode {
+    /// 主题颜色，越旧越透明
+    Age,
+    /// 按下摆球速度着色（蓝慢红快），透明度一致
+    Speed,
+    /// 色相取自速度，透明度取自新旧程度
+    SpeedWithAgeFade,
+}
+
+impl TrailColorMode {
+    /// 所有着色方式
+    pub const ALL: [TrailColorMode; 3] = [
+        TrailColorMode::Age,
+        TrailColorMode::Speed,
+        TrailColorMode::SpeedWithAgeFade,
+    ];
+
+    /// 显示名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            TrailColorMode::Age => "Age Fade",
+            TrailColorMode::Speed => "Speed",
+            TrailColorMode::SpeedWithAgeFade => "Speed + Age Fade",
+        }
+    }
+
+    /// 是否按速度着色
+    pub fn uses_speed(&self) -> bool {
+        !matches!(self, TrailColorMode::Age)
+    }
+}
+
+/// 各条轨迹路径是否绘制
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrailPaths {
+    /// 下摆球
+    pub lower: bool,
+    /// 上摆球
+    pub upper: bool,
+    /// 系统质心
+    pub center_of_mass: bool,
+}
+
+/// 轨迹选择：一次设置要绘制的轨迹路径
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrailSelection {
+    Lower,
+    Upper,
+    Both,
+    CenterOfMass,
+}
+
+impl TrailSelection {
+    /// 所有选项
+    pub const ALL: [TrailSelection; 4] = [
+        TrailSelection::Lower,
+        TrailSelection::Upper,
+        TrailSelection::Both,
+        TrailSelection::CenterOfMass,
+    ];
+
+    /// 显示名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            TrailSelection::Lower => "Lower",
+            TrailSelection::Upper => "Upper",
+            TrailSelection::Both => "Both",
+            TrailSelection::CenterOfMass => "COM",
+        }
+    }
+
+    /// 该选项对应的轨迹路径开关
+    pub fn paths(&self) -> TrailPaths {
+        let (lower, upper, center_of_mass) = match self {
+            TrailSelection::Lower => (true, false, false),
+            TrailSelection::Upper => (false, true, false),
+            TrailSelection::Both => (true, true, false),
+            TrailSelection::CenterOfMass => (false, false, true),
+        };
+        TrailPaths {
+            lower,
+            upper,
+            center_of_mass,
+        }
+    }
+
+    /// 与路径开关完全对应的选项
+    pub fn from_paths(paths: TrailPaths) -> Option<Self> {
+        Self::ALL.into_iter().find(|selection| selection.paths() == paths)
+    }
+}
+
+/// 单条轨迹路径
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum TrailPath {
+    Lower,
+    Upper,
+    CenterOfMass,
+}
+
+impl TrailPath {
+    /// 从轨迹记录 (x₁, y₁, x₂, y₂) 中取出该路径的位置
+    fn point(self, sample: (f64, f64, f64, f64), params: &PendulumParams) -> (f64, f64) {
+        let (x1, y1, x2, y2) = sample;
+        match self {
+            TrailPath::Lower => (x2, y2),
+            TrailPath::Upper => (x1, y1),
+            TrailPath::CenterOfMass => {
+                let (m1, m2) = (real_to_f64(params.m1), real_to_f64(params.m2));
+                let total = m1 + m2;
+                ((m1 * x1 + m2 * x2) / total, (m1 * y1 + m2 * y2) / total)
+            }
+        }
+    }
+
+    /// 轨迹的基础颜色：上摆球使用质点颜色，与下摆球的轨迹区分
+    fn color(self, trajectory_color: egui::Color32, mass_color: egui::Color32) -> egui::Color32 {
+        match self {
+            TrailPath::Upper => mass_color,
+            TrailPath::Lower | TrailPath::CenterOfMass => trajectory_color,
+        }
+    }
+}
+
+/// 摆杆宽度模式
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RodWidthMode {
+    /// 固定宽度
+    Fixed,
+    /// 按摆杆张力缩放
+    ByLoad,
+}
+
+impl RodWidthMode {
+    /// 所有宽度模式
+    pub const ALL: [RodWidthMode; 2] = [RodWidthMode::Fixed, RodWidthMode::ByLoad];
+
+    /// 显示名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            RodWidthMode::Fixed => "Fixed Width",
+            RodWidthMode::ByLoad => "Width by Load",
+        }
+    }
+}
+
+/// 画布右键菜单中需要由应用处理的操作
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CanvasAction {
+    /// 重置模拟
+    Reset,
+    /// 切换运行/暂停
+    TogglePause,
+    /// 重置视图
+    ResetView,
+    /// 清除轨迹
+    ClearTrail,
+    /// 保存画布截图
+    SaveImage,
+}
 
 /// 可视化渲染器
 pub struct PendulumRenderer {
     /// 画布中心点
     center: egui::Pos2,
+    /// 本帧振动悬挂点相对画布中心的屏幕偏移，只在绘制摆形期间非零
+    pivot_shift: egui::Vec2,
     /// 缩放比例（像素/米）
     scale: f32,
     /// 当前正在拖动的摆球（None, Some(1), Some(2)）
     dragging_mass: Option<u8>,
     /// 拖动起始位置
     drag_start_pos: Option<egui::Pos2>,
+    /// 相机模式
+    camera_mode: CameraMode,
+    /// 相机跟随平滑系数（0为不平滑）
+    camera_smoothing: f32,
+    /// 是否启用拖动平移惯性
+    pan_inertia: bool,
+    /// 当前平移速度（像素/秒）
+    pan_velocity: egui::Vec2,
+    /// 画布宽高比约束
+    aspect_ratio: AspectRatio,
+    /// 应用参数前的摆形叠影（状态、旧参数、开始时间）
+    parameter_ghost: Option<(PendulumState, PendulumParams, std::time::Instant)>,
+    /// 摆杆基准宽度（像素）
+    rod_width: f32,
+    /// 摆杆宽度模式
+    rod_width_mode: RodWidthMode,
+    /// 拖动摆球时在可见半径外额外允许的距离（物理像素）
+    grab_tolerance_px: f32,
+    /// 线性化解析解的参考摆形
+    reference_overlay: Option<PendulumState>,
+    /// 对比用参考运行的摆形及其参数
+    comparison_overlay: Option<(PendulumState, PendulumParams)>,
+    /// 固定显示的下摆球轨迹快照（世界坐标），清除轨迹后仍保留
+    pinned_trail: Option<Vec<(f64, f64)>>,
+    /// 右键菜单中选择、等待应用处理的操作
+    canvas_action: Option<CanvasAction>,
+    /// 最近一次绘制的画布区域
+    canvas_rect: egui::Rect,
+    /// 是否沿摆球最近的路径绘制运动模糊残影
+    bob_motion_blur: bool,
+    /// 运动模糊强度（0..=1），决定残影个数和拖尾长度
+    motion_blur_intensity: f32,
 }
 
 #[allow(dead_code)]
@@ -26,24 +373,58 @@ impl PendulumRenderer {
     pub fn new() -> Self {
         Self {
             center: egui::Pos2::ZERO,
+            pivot_shift: egui::Vec2::ZERO,
             scale: 100.0, // 默认100像素/米
             dragging_mass: None,
             drag_start_pos: None,
+            camera_mode: CameraMode::FixedPivot,
+            camera_smoothing: 0.8,
+            pan_inertia: true,
+            pan_velocity: egui::Vec2::ZERO,
+            aspect_ratio: AspectRatio::Free,
+            parameter_ghost: None,
+            rod_width: 3.0,
+            rod_width_mode: RodWidthMode::Fixed,
+            grab_tolerance_px: DEFAULT_GRAB_TOLERANCE_PX,
+            reference_overlay: None,
+            comparison_overlay: None,
+            pinned_trail: None,
+            canvas_action: None,
+            canvas_rect: egui::Rect::NOTHING,
+            bob_motion_blur: false,
+            motion_blur_intensity: 0.5,
         }
     }
 
     /// 在给定的UI区域内渲染摆系统
     /// 返回是否进行了拖动操作以及新的摆状态
+    /// ensemble 为系综模式下的扰动成员，随主摆一起绘制
+    #[allow(clippy::too_many_arguments)]
     pub fn render(
         &mut self,
         ui: &mut egui::Ui,
         pendulum: &DoublePendulum,
+        ensemble: Option<&Ensemble>,
         statistics: &PhysicsStatistics,
         theme_manager: &ThemeManager,
         ui_state: &UiStateManager,
         is_paused: bool,
     ) -> Option<crate::pendulum::PendulumState> {
-        let available_rect = ui.available_rect_before_wrap();
+        let full_rect = ui.available_rect_before_wrap();
+
+        // 按宽高比约束裁剪出居中的绘制区域，两侧留黑边
+        let available_rect = match self.aspect_ratio.ratio() {
+            Some(ratio) => {
+                let canvas_rect = letterbox_rect(full_rect, ratio);
+                ui.painter().rect_filled(full_rect, 0.0, egui::Color32::from_black_alpha(160));
+                ui.painter().rect_filled(canvas_rect, 0.0, ui.visuals().extreme_bg_color);
+                ui.set_clip_rect(canvas_rect.intersect(ui.clip_rect()));
+                canvas_rect
+            }
+            None => full_rect,
+        };
+
+        self.canvas_rect = available_rect;
 
         // 更新画布中心点（只在第一次或重置时更新）
         if self.center == egui::Pos2::ZERO {
@@ -53,6 +434,31 @@ impl PendulumRenderer {
         // 先处理滚轮缩放
         self.handle_zoom(ui, available_rect);
 
+        // 按本帧时长换算平滑比例，跟随的快慢与帧率无关
+        let frame_dt = ui.ctx().input(|i| i.stable_dt);
+        let camera_weight = smoothing_weight(self.camera_smoothing, frame_dt);
+
+        // 跟随模式下移动悬挂点，使被跟踪的点保持在画布中心
+        if let Some(target) = camera_target_center(
+            self.camera_mode,
+            pendulum,
+            available_rect.center(),
+            self.scale,
+        ) {
+            self.center = self.center.lerp(target, camera_weight);
+        }
+
+        // 自动缩放模式下只调整缩放比例，悬挂点保持不动
+        if self.camera_mode == CameraMode::AutoZoomFixedPivot {
+            let half_size = egui::Vec2::new(
+                (self.center.x - available_rect.left()).min(available_rect.right() - self.center.x),
+                (self.center.y - available_rect.top()).min(available_rect.bottom() - self.center.y),
+            );
+            let target = auto_zoom_scale(trajectory_extent(pendulum, statistics), half_size);
+            let scale = egui::lerp(self.scale..=target, camera_weight);
+            self.set_scale(scale);
+        }
+
         // 获取主题颜色
         let (rod_color, mass_color, trajectory_color, grid_color) =
             theme_manager.get_pendulum_colors();
@@ -62,24 +468,117 @@ impl PendulumRenderer {
             self.draw_grid(ui, available_rect, grid_color);
         }
 
-        // 绘制轨迹历史
+        // 绘制轨迹历史，图例使用第一条绘制路径的速度范围
+        let mut speed_range = None;
         if ui_state.show_trajectory() {
-            self.draw_trajectory(
+            let paths = ui_state.trail_paths();
+            for (enabled, path) in [
+                (paths.lower, TrailPath::Lower),
+                (paths.upper, TrailPath::Upper),
+                (paths.center_of_mass, TrailPath::CenterOfMass),
+            ] {
+                if !enabled {
+                    continue;
+                }
+                let range = self.draw_trajectory(
+                    ui,
+                    statistics,
+                    (path, &pendulum.params),
+                    path.color(trajectory_color, mass_color),
+                    ui_state.trajectory_alpha(),
+                    ui_state.trail_color_mode(),
+                );
+                speed_range = speed_range.or(range);
+            }
+        }
+
+        // 绘制固定的轨迹快照
+        if let Some(pinned) = &self.pinned_trail {
+            let points: Vec<egui::Pos2> = pinned
+                .iter()
+                .map(|&(x, y)| self.world_to_screen(x as Real, y as Real))
+                .collect();
+            let color = trajectory_color.gamma_multiply(0.35);
+            ui.painter()
+                .add(egui::Shape::line(points, egui::Stroke::new(TRAIL_STROKE_WIDTH, color)));
+        }
+
+        // 绘制频闪叠影
+        if ui_state.show_strobe() {
+            self.draw_strobe(
                 ui,
-                statistics,
-                trajectory_color,
-                ui_state.trajectory_alpha(),
+                statistics.get_strobe_snapshots(),
+                &pendulum.params,
+                rod_color,
+                mass_color,
+                ui_state.strobe_opacity(),
             );
         }
 
+        // 绘制参数变化前的淡出叠影
+        if let Some((state, params, start)) = self.parameter_ghost {
+            let alpha = ghost_alpha(start.elapsed().as_secs_f32());
+            if alpha > 0.0 {
+                self.draw_faded_pendulum(ui, &state, &params, rod_color, mass_color, alpha);
+                ui.ctx().request_repaint();
+            } else {
+                self.parameter_ghost = None;
+            }
+        }
+
+        // 绘制线性化解析解的参考摆形
+        if let Some(reference) = self.reference_overlay {
+            let color = egui::Color32::from_rgb(0, 200, 255);
+            self.draw_faded_pendulum(ui, &reference, &pendulum.params, color, color, 0.5);
+        }
+
+        // 绘制对比参考运行的叠影
+        if let Some((state, params)) = self.comparison_overlay {
+            let color = egui::Color32::from_rgb(255, 120, 200);
+            self.draw_faded_pendulum(ui, &state, &params, color, color, 0.5);
+        }
+
+        // 绘制摆球的运动模糊残影
+        if self.bob_motion_blur {
+            self.draw_motion_blur(ui, statistics, &pendulum.params, mass_color);
+        }
+
+        // 振动的悬挂点带着摆形上下移动，轨迹仍相对悬挂点的静止位置绘制
+        let pivot_offset = real_to_f64(pendulum.params.pivot_offset(pendulum.time)) as f32;
+        self.pivot_shift = egui::vec2(0.0, -pivot_offset * self.scale);
+
+        // 绘制系综成员及其淡化轨迹，与主摆共用振动后的悬挂点
+        if let Some(ensemble) = ensemble {
+            self.draw_ensemble(ui, ensemble, &pendulum.params);
+        }
+
         // 绘制悬挂点
-        self.draw_suspension_point(ui, rod_color);
+        self.draw_suspension_point(ui, rod_color, pendulum.params.inverted_gravity);
 
         // 绘制摆杆和质点
         self.draw_pendulum(ui, pendulum, rod_color, mass_color);
 
+        // 绘制悬挂点约束力
+        if ui_state.show_pivot_force() {
+            self.draw_pivot_force(ui, pendulum);
+        }
+
+        // 绘制图例
+        if ui_state.show_legend() {
+            self.draw_legend(ui, available_rect, speed_range, rod_color);
+        }
+
+        // 用边框颜色提示运行/暂停状态
+        if ui_state.show_state_border() {
+            ui.painter().rect_stroke(
+                available_rect.shrink(1.0),
+                0.0,
+                egui::Stroke::new(2.0, theme_manager.get_state_color(!is_paused)),
+            );
+        }
+
         // 处理鼠标交互（包括拖动）
-        let new_state = if is_paused {
+        let pendulum_state = if is_paused {
             // 在暂停状态下显示拖动提示
             self.draw_drag_hint(ui, pendulum);
 
@@ -88,17 +587,85 @@ impl PendulumRenderer {
 
             // 如果没有正在拖动摆球，则允许拖动画布
             if self.dragging_mass.is_none() {
-                self.handle_canvas_pan(ui);
+                let response = self.handle_canvas_pan(ui);
+                self.show_canvas_menu(&response, pendulum, statistics, is_paused);
             }
 
             pendulum_state
         } else {
             // 运行时允许拖动画布平移
-            self.handle_canvas_pan(ui);
+            let response = self.handle_canvas_pan(ui);
+            self.show_canvas_menu(&response, pendulum, statistics, is_paused);
             None
         };
+        self.pivot_shift = egui::Vec2::ZERO;
+        pendulum_state
+    }
+
+    /// 画布右键菜单：常用操作，运行状态决定显示"暂停"还是"运行"
+    /// 视图类操作直接在渲染器中完成，其余操作留给应用通过 take_canvas_action 处理
+    fn show_canvas_menu(
+        &mut self,
+        response: &egui::Response,
+        pendulum: &DoublePendulum,
+        statistics: &PhysicsStatistics,
+        is_paused: bool,
+    ) {
+        response.context_menu(|ui| {
+            let play_text = if is_paused { "▶ Play" } else { "⏸ Pause" };
+            if ui.button(play_text).clicked() {
+                self.canvas_action = Some(CanvasAction::TogglePause);
+                ui.close_menu();
+            }
+            if ui.button("🔄 Reset").clicked() {
+                self.canvas_action = Some(CanvasAction::Reset);
+                ui.close_menu();
+            }
+            ui.separator();
+            if ui.button("Reset View").clicked() {
+                self.canvas_action = Some(CanvasAction::ResetView);
+                ui.close_menu();
+            }
+            if ui.button("Auto-fit").clicked() {
+                self.fit_view(pendulum, statistics);
+                ui.close_menu();
+            }
+            ui.separator();
+            let pin_text = if self.pinned_trail.is_some() {
+                "Unpin Trajectory"
+            } else {
+                "📌 Pin Trajectory"
+            };
+            if ui.button(pin_text).clicked() {
+                self.pinned_trail = match self.pinned_trail {
+                    Some(_) => None,
+                    None => Some(
+                        statistics
+                            .get_trajectory_history()
+                            .iter()
+                            .map(|&(_, _, x2, y2)| (x2, y2))
+                            .collect(),
+                    ),
+                };
+                ui.close_menu();
+            }
+            if ui.button("🗑 Clear Trail").clicked() {
+                self.canvas_action = Some(CanvasAction::ClearTrail);
+                ui.close_menu();
+            }
+            if ui.button("📷 Save Image...").clicked() {
+                self.canvas_action = Some(CanvasAction::SaveImage);
+                ui.close_menu();
+            }
+        });
+    }
 
-        new_state
+    /// 把悬挂点移回画布中心，并缩放到能完整显示摆和轨迹
+    fn fit_view(&mut self, pendulum: &DoublePendulum, statistics: &PhysicsStatistics) {
+        self.center = self.canvas_rect.center();
+        self.pan_velocity = egui::Vec2::ZERO;
+        let half_size = self.canvas_rect.size() * 0.5;
+        self.set_scale(auto_zoom_scale(trajectory_extent(pendulum, statistics), half_size));
     }
 
     /// 绘制背景网格
@@ -161,8 +728,8 @@ impl PendulumRenderer {
         let screen_pos2 = self.world_to_screen(pos2.0, pos2.1);
 
         // 计算摆球半径
-        let mass1_radius = (pendulum.params.m1 * 8.0 + 4.0) as f32;
-        let mass2_radius = (pendulum.params.m2 * 8.0 + 4.0) as f32;
+        let mass1_radius = real_to_f32(pendulum.params.m1 * 8.0 + 4.0);
+        let mass2_radius = real_to_f32(pendulum.params.m2 * 8.0 + 4.0);
 
         // 在摆球周围绘制虚线圆圈提示可以拖动
         let hint_color = egui::Color32::from_rgba_unmultiplied(255, 255, 255, 100);
@@ -196,73 +763,90 @@ impl PendulumRenderer {
         }
     }
 
-    /// 绘制轨迹历史
+    /// 绘制轨迹历史，按速度着色时返回用于图例的速度范围 (最小, 最大)
     fn draw_trajectory(
         &self,
         ui: &mut egui::Ui,
         statistics: &PhysicsStatistics,
+        (path, params): (TrailPath, &PendulumParams),
         color: egui::Color32,
         alpha: f32,
-    ) {
+        mode: TrailColorMode,
+    ) -> Option<(f32, f32)> {
         let painter = ui.painter();
         let trajectory_history = statistics.get_trajectory_history();
 
         if trajectory_history.len() < 2 {
-            return;
+            return None;
         }
 
-        // 创建带透明度的颜色
-        let trajectory_color = egui::Color32::from_rgba_premultiplied(
-            color.r(),
-            color.g(),
-            color.b(),
-            (255.0 * alpha) as u8,
-        );
-
-        // 绘制第二个质点的轨迹
-        let mut points = Vec::new();
-        for (_, _, x2, y2) in trajectory_history {
-            let screen_pos = self.world_to_screen(*x2, *y2);
-            points.push(screen_pos);
-        }
+        // 绘制所选路径的轨迹
+        let path_points: Vec<(f64, f64)> = trajectory_history
+            .iter()
+            .map(|&sample| path.point(sample, params))
+            .collect();
+        let points: Vec<egui::Pos2> = path_points
+            .iter()
+            .map(|&(x, y)| self.world_to_screen(x as Real, y as Real))
+            .collect();
+
+        let speeds = if mode.uses_speed() {
+            trail_segment_speeds(&path_points, statistics.get_trajectory_time_history())
+        } else {
+            Vec::new()
+        };
+        let speed_range = speed_range(&speeds);
 
-        // 绘制轨迹线段
+        // 绘制轨迹线段，跳过完全位于可见区域之外的线段
+        let visible = painter.clip_rect();
         for i in 1..points.len() {
-            let alpha_factor = i as f32 / points.len() as f32; // 渐变效果
-            let line_color = egui::Color32::from_rgba_premultiplied(
-                trajectory_color.r(),
-                trajectory_color.g(),
-                trajectory_color.b(),
-                (trajectory_color.a() as f32 * alpha_factor) as u8,
-            );
+            if !segment_visible(visible, points[i - 1], points[i], TRAIL_STROKE_WIDTH) {
+                continue;
+            }
+            let age = i as f32 / points.len() as f32; // 渐变效果
+            let speed = normalized_speed(&speeds, speed_range, i - 1);
+            let line_color = trail_segment_color(color, mode, speed, age, alpha);
 
             painter.line_segment(
                 [points[i - 1], points[i]],
-                egui::Stroke::new(1.5, line_color),
+                egui::Stroke::new(TRAIL_STROKE_WIDTH, line_color),
             );
         }
+
+        speed_range
     }
 
     /// 绘制悬挂点
-    fn draw_suspension_point(&self, ui: &mut egui::Ui, color: egui::Color32) {
+    /// 反摆模式下支架画在悬挂点下方，并标出向上的重力方向
+    fn draw_suspension_point(&self, ui: &mut egui::Ui, color: egui::Color32, inverted: bool) {
         let painter = ui.painter();
+        let origin = self.origin();
 
         // 绘制悬挂点
-        painter.circle_filled(self.center, 4.0, color);
+        painter.circle_filled(origin, 4.0, color);
 
         // 绘制悬挂支架
-        let support_height = 20.0;
+        let support_height = if inverted { -20.0 } else { 20.0 };
+        if inverted {
+            painter.text(
+                origin + egui::Vec2::new(20.0, 20.0),
+                egui::Align2::LEFT_CENTER,
+                "g ↑",
+                egui::FontId::proportional(12.0),
+                color,
+            );
+        }
         painter.line_segment(
             [
-                egui::Pos2::new(self.center.x - 15.0, self.center.y - support_height),
-                egui::Pos2::new(self.center.x + 15.0, self.center.y - support_height),
+                egui::Pos2::new(origin.x - 15.0, origin.y - support_height),
+                egui::Pos2::new(origin.x + 15.0, origin.y - support_height),
             ],
             egui::Stroke::new(3.0, color),
         );
         painter.line_segment(
             [
-                self.center - egui::Vec2::new(0.0, support_height),
-                self.center,
+                origin - egui::Vec2::new(0.0, support_height),
+                origin,
             ],
             egui::Stroke::new(2.0, color),
         );
@@ -276,47 +860,164 @@ impl PendulumRenderer {
         rod_color: egui::Color32,
         mass_color: egui::Color32,
     ) {
+        // 绘制质点（拖动时使用不同颜色）
+        let mass1_color = if self.dragging_mass == Some(1) {
+            egui::Color32::YELLOW // 拖动时高亮显示
+        } else {
+            mass_color
+        };
+        let mass2_color = if self.dragging_mass == Some(2) {
+            egui::Color32::YELLOW // 拖动时高亮显示
+        } else {
+            mass_color
+        };
+
+        let rod_widths = match self.rod_width_mode {
+            RodWidthMode::Fixed => (self.rod_width, self.rod_width),
+            RodWidthMode::ByLoad => {
+                let (tension1, tension2) =
+                    engine_at(pendulum.time).rod_tensions(&pendulum.state, &pendulum.params);
+                // 以静止下垂时上杆承受的总重量为基准
+                let params = &pendulum.params;
+                let reference = (params.m1 + params.m2) * params.g;
+                (
+                    rod_width_for_load(tension1, reference, self.rod_width),
+                    rod_width_for_load(tension2, reference, self.rod_width),
+                )
+            }
+        };
+
+        let (screen_pos1, screen_pos2) = self.draw_pendulum_state(
+            ui,
+            &pendulum.state,
+            &pendulum.params,
+            rod_color,
+            (mass1_color, mass2_color),
+            rod_widths,
+        );
+
+        // 绘制速度向量（可选）
+        self.draw_velocity_vectors(ui, pendulum, screen_pos1, screen_pos2, rod_color);
+    }
+
+    /// 按给定状态和参数绘制摆杆和质点，返回两个质点的屏幕坐标
+    fn draw_pendulum_state(
+        &self,
+        ui: &mut egui::Ui,
+        state: &PendulumState,
+        params: &PendulumParams,
+        rod_color: egui::Color32,
+        mass_colors: (egui::Color32, egui::Color32),
+        rod_widths: (f32, f32),
+    ) -> (egui::Pos2, egui::Pos2) {
         let painter = ui.painter();
 
         // 获取质点位置
-        let (pos1, pos2) = pendulum.get_positions();
+        let pos1 = state.get_mass1_position(params.l1);
+        let pos2 = state.get_mass2_position(params.l1, params.l2);
         let screen_pos1 = self.world_to_screen(pos1.0, pos1.1);
         let screen_pos2 = self.world_to_screen(pos2.0, pos2.1);
 
         // 绘制摆杆
         painter.line_segment(
-            [self.center, screen_pos1],
-            egui::Stroke::new(3.0, rod_color),
+            [self.origin(), screen_pos1],
+            egui::Stroke::new(rod_widths.0, rod_color),
         );
         painter.line_segment(
             [screen_pos1, screen_pos2],
-            egui::Stroke::new(3.0, rod_color),
+            egui::Stroke::new(rod_widths.1, rod_color),
         );
 
         // 计算质点大小（基于质量）
-        let mass1_radius = (pendulum.params.m1 * 8.0 + 4.0) as f32;
-        let mass2_radius = (pendulum.params.m2 * 8.0 + 4.0) as f32;
-
-        // 绘制质点（拖动时使用不同颜色）
-        let mass1_color = if self.dragging_mass == Some(1) {
-            egui::Color32::YELLOW // 拖动时高亮显示
-        } else {
-            mass_color
-        };
-        let mass2_color = if self.dragging_mass == Some(2) {
-            egui::Color32::YELLOW // 拖动时高亮显示
-        } else {
-            mass_color
-        };
+        let mass1_radius = real_to_f32(params.m1 * 8.0 + 4.0);
+        let mass2_radius = real_to_f32(params.m2 * 8.0 + 4.0);
 
-        painter.circle_filled(screen_pos1, mass1_radius, mass1_color);
+        painter.circle_filled(screen_pos1, mass1_radius, mass_colors.0);
         painter.circle_stroke(screen_pos1, mass1_radius, egui::Stroke::new(1.0, rod_color));
 
-        painter.circle_filled(screen_pos2, mass2_radius, mass2_color);
+        painter.circle_filled(screen_pos2, mass2_radius, mass_colors.1);
         painter.circle_stroke(screen_pos2, mass2_radius, egui::Stroke::new(1.0, rod_color));
 
-        // 绘制速度向量（可选）
-        self.draw_velocity_vectors(ui, pendulum, screen_pos1, screen_pos2, rod_color);
+        (screen_pos1, screen_pos2)
+    }
+
+    /// 以指定不透明度绘制某一状态的淡化摆形
+    fn draw_faded_pendulum(
+        &self,
+        ui: &mut egui::Ui,
+        state: &PendulumState,
+        params: &PendulumParams,
+        rod_color: egui::Color32,
+        mass_color: egui::Color32,
+        alpha: f32,
+    ) {
+        let faded_mass = mass_color.gamma_multiply(alpha);
+        self.draw_pendulum_state(
+            ui,
+            state,
+            params,
+            rod_color.gamma_multiply(alpha),
+            (faded_mass, faded_mass),
+            (self.rod_width, self.rod_width),
+        );
+    }
+
+    /// 按蓝到红的渐变绘制系综成员，轨迹越旧越淡
+    fn draw_ensemble(&self, ui: &mut egui::Ui, ensemble: &Ensemble, params: &PendulumParams) {
+        let last = ensemble.members().len().saturating_sub(1).max(1) as f32;
+        let members = ensemble.members().iter().zip(ensemble.trails());
+        for (index, (member, trail)) in members.enumerate() {
+            let color = speed_color(index as f32 / last);
+            let points: Vec<egui::Pos2> =
+                trail.iter().map(|&(x, y)| self.world_to_screen(x, y)).collect();
+            for (i, segment) in points.windows(2).enumerate() {
+                let age = (i + 1) as f32 / points.len() as f32;
+                ui.painter().line_segment(
+                    [segment[0], segment[1]],
+                    egui::Stroke::new(TRAIL_STROKE_WIDTH, color.gamma_multiply(0.3 * age)),
+                );
+            }
+            self.draw_faded_pendulum(ui, &member.state, params, color, color, 0.6);
+        }
+    }
+
+    /// 沿最近记录的轨迹位置绘制两个摆球的淡化残影
+    fn draw_motion_blur(
+        &self,
+        ui: &mut egui::Ui,
+        statistics: &PhysicsStatistics,
+        params: &PendulumParams,
+        mass_color: egui::Color32,
+    ) {
+        let history = statistics.get_trajectory_history();
+        let mass1_radius = real_to_f32(params.m1 * 8.0 + 4.0);
+        let mass2_radius = real_to_f32(params.m2 * 8.0 + 4.0);
+        let painter = ui.painter();
+        for (index, alpha) in motion_blur_samples(history.len(), self.motion_blur_intensity) {
+            let (x1, y1, x2, y2) = history[index];
+            let color = mass_color.gamma_multiply(alpha);
+            let pos1 = self.world_to_screen(x1 as Real, y1 as Real);
+            let pos2 = self.world_to_screen(x2 as Real, y2 as Real);
+            painter.circle_filled(pos1, mass1_radius, color);
+            painter.circle_filled(pos2, mass2_radius, color);
+        }
+    }
+
+    /// 绘制频闪叠影：越早的快照越透明
+    fn draw_strobe(
+        &self,
+        ui: &mut egui::Ui,
+        snapshots: &[PendulumState],
+        params: &PendulumParams,
+        rod_color: egui::Color32,
+        mass_color: egui::Color32,
+        opacity: f32,
+    ) {
+        let count = snapshots.len();
+        for (i, snapshot) in snapshots.iter().enumerate() {
+            let alpha = strobe_alpha(i, count, opacity);
+            self.draw_faded_pendulum(ui, snapshot, params, rod_color, mass_color, alpha);
+        }
     }
 
     /// 绘制速度向量
@@ -347,7 +1048,7 @@ impl PendulumRenderer {
         let v2y = l1 * omega1 * theta1.sin() + l2 * omega2 * theta2.sin();
 
         // 缩放速度向量以便显示
-        let velocity_scale = 10.0;
+        let velocity_scale = VELOCITY_ARROW_SCALE;
 
         // 创建半透明颜色
         let velocity_color =
@@ -355,18 +1056,102 @@ impl PendulumRenderer {
 
         // 绘制速度向量（注意Y轴翻转）
         if omega1.abs() > 0.01 {
-            let v1_end =
-                pos1 + egui::Vec2::new(v1x as f32 * velocity_scale, -v1y as f32 * velocity_scale);
+            let v1_end = pos1
+                + egui::Vec2::new(
+                    real_to_f32(v1x) * velocity_scale,
+                    real_to_f32(-v1y) * velocity_scale,
+                );
             painter.arrow(pos1, v1_end - pos1, egui::Stroke::new(1.5, velocity_color));
         }
 
         if omega2.abs() > 0.01 {
-            let v2_end =
-                pos2 + egui::Vec2::new(v2x as f32 * velocity_scale, -v2y as f32 * velocity_scale);
+            let v2_end = pos2
+                + egui::Vec2::new(
+                    real_to_f32(v2x) * velocity_scale,
+                    real_to_f32(-v2y) * velocity_scale,
+                );
             painter.arrow(pos2, v2_end - pos2, egui::Stroke::new(1.5, velocity_color));
         }
     }
 
+    /// 在悬挂点绘制约束力箭头及其大小
+    fn draw_pivot_force(&self, ui: &mut egui::Ui, pendulum: &DoublePendulum) {
+        let (fx, fy) = engine_at(pendulum.time).pivot_force(&pendulum.state, &pendulum.params);
+        let force = egui::Vec2::new(real_to_f32(fx), real_to_f32(-fy)); // 翻转Y轴
+        let color = egui::Color32::from_rgb(255, 165, 0);
+
+        let painter = ui.painter();
+        painter.arrow(
+            self.origin(),
+            force * PIVOT_FORCE_ARROW_SCALE,
+            egui::Stroke::new(2.0, color),
+        );
+        painter.text(
+            self.origin() + egui::Vec2::new(8.0, 8.0),
+            egui::Align2::LEFT_TOP,
+            format!("F = {:.1} N", force.length()),
+            egui::FontId::proportional(12.0),
+            color,
+        );
+    }
+
+    /// 在画布右下角绘制图例：速度向量参考箭头，以及速度着色时的色带
+    fn draw_legend(
+        &self,
+        ui: &mut egui::Ui,
+        rect: egui::Rect,
+        speed_range: Option<(f32, f32)>,
+        color: egui::Color32,
+    ) {
+        let painter = ui.painter();
+        let layout = compute_legend_layout(rect, speed_range);
+
+        painter.rect_filled(
+            layout.frame,
+            4.0,
+            ui.visuals().extreme_bg_color.gamma_multiply(0.8),
+        );
+
+        let (arrow_start, arrow_end) = layout.arrow;
+        painter.arrow(arrow_start, arrow_end - arrow_start, egui::Stroke::new(1.5, color));
+        painter.text(
+            arrow_start - egui::Vec2::new(0.0, 4.0),
+            egui::Align2::LEFT_BOTTOM,
+            &layout.arrow_label,
+            egui::FontId::proportional(11.0),
+            color,
+        );
+
+        if let Some(ramp_rect) = layout.ramp_rect {
+            // 用细竖条近似连续色带
+            let steps = 32;
+            let step_width = ramp_rect.width() / steps as f32;
+            for i in 0..steps {
+                let t = (i as f32 + 0.5) / steps as f32;
+                let x = ramp_rect.min.x + i as f32 * step_width;
+                painter.rect_filled(
+                    egui::Rect::from_min_size(
+                        egui::Pos2::new(x, ramp_rect.min.y),
+                        egui::Vec2::new(step_width + 0.5, ramp_rect.height()),
+                    ),
+                    0.0,
+                    speed_color(t),
+                );
+            }
+            painter.rect_stroke(ramp_rect, 0.0, egui::Stroke::new(1.0, color));
+
+            for (pos, label) in &layout.ramp_ticks {
+                painter.text(
+                    *pos,
+                    egui::Align2::CENTER_TOP,
+                    label,
+                    egui::FontId::proportional(10.0),
+                    color,
+                );
+            }
+        }
+    }
+
     /// 处理摆球拖动交互（仅在暂停状态下）
     fn handle_pendulum_dragging(
         &mut self,
@@ -378,9 +1163,14 @@ impl PendulumRenderer {
         let screen_pos1 = self.world_to_screen(pos1.0, pos1.1);
         let screen_pos2 = self.world_to_screen(pos2.0, pos2.1);
 
-        // 计算摆球半径（用于检测点击）
-        let mass1_radius = (pendulum.params.m1 * 8.0 + 4.0) as f32;
-        let mass2_radius = (pendulum.params.m2 * 8.0 + 4.0) as f32;
+        // 计算摆球的判定半径（可见半径加容差）
+        let pixels_per_point = ui.ctx().pixels_per_point();
+        let grab_radius = |mass: Real| {
+            let visual_radius = real_to_f32(mass * 8.0 + 4.0);
+            effective_grab_radius(visual_radius, self.grab_tolerance_px, pixels_per_point)
+        };
+        let mass1_radius = grab_radius(pendulum.params.m1);
+        let mass2_radius = grab_radius(pendulum.params.m2);
 
         // 获取指针位置
         let pointer_pos = ui.ctx().pointer_interact_pos();
@@ -392,10 +1182,10 @@ impl PendulumRenderer {
                 let dist1 = pos.distance(screen_pos1);
                 let dist2 = pos.distance(screen_pos2);
 
-                if dist1 <= mass1_radius + 5.0 {
+                if dist1 <= mass1_radius {
                     self.dragging_mass = Some(1);
                     self.drag_start_pos = Some(pos);
-                } else if dist2 <= mass2_radius + 5.0 {
+                } else if dist2 <= mass2_radius {
                     self.dragging_mass = Some(2);
                     self.drag_start_pos = Some(pos);
                 }
@@ -404,6 +1194,7 @@ impl PendulumRenderer {
             // 处理拖动过程
             if self.dragging_mass.is_some() && ui.ctx().input(|i| i.pointer.primary_down()) {
                 let world_pos = self.screen_to_world(pos);
+                self.draw_drag_constraint(ui, pendulum, pos, world_pos);
                 return self.calculate_new_pendulum_state(pendulum, world_pos);
             }
         }
@@ -417,11 +1208,53 @@ impl PendulumRenderer {
         None
     }
 
+    /// 拖动时绘制摆杆长度约束：可达圆周，以及指针到实际落点的连线和落点虚影
+    fn draw_drag_constraint(
+        &self,
+        ui: &mut egui::Ui,
+        pendulum: &DoublePendulum,
+        pointer: egui::Pos2,
+        target: (Real, Real),
+    ) {
+        let (anchor, length, radius) = match self.dragging_mass {
+            Some(1) => ((0.0, 0.0), pendulum.params.l1, pendulum.params.m1 * 8.0 + 4.0),
+            Some(2) => (
+                pendulum.state.get_mass1_position(pendulum.params.l1),
+                pendulum.params.l2,
+                pendulum.params.m2 * 8.0 + 4.0,
+            ),
+            _ => return,
+        };
+
+        let landing = project_onto_rod(anchor, target, length);
+        let screen_anchor = self.world_to_screen(anchor.0, anchor.1);
+        let screen_landing = self.world_to_screen(landing.0, landing.1);
+        let ghost_color = egui::Color32::from_rgba_unmultiplied(255, 255, 0, 90);
+
+        let painter = ui.painter();
+        painter.circle_stroke(
+            screen_anchor,
+            real_to_f32(length) * self.scale,
+            egui::Stroke::new(1.0, ghost_color),
+        );
+        painter.add(egui::Shape::dashed_line(
+            &[pointer, screen_landing],
+            egui::Stroke::new(1.0, ghost_color),
+            4.0,
+            4.0,
+        ));
+        painter.circle_stroke(
+            screen_landing,
+            real_to_f32(radius),
+            egui::Stroke::new(1.5, ghost_color),
+        );
+    }
+
     /// 根据拖动位置计算新的摆状态
     fn calculate_new_pendulum_state(
         &self,
         pendulum: &crate::pendulum::DoublePendulum,
-        target_pos: (f64, f64),
+        target_pos: (Real, Real),
     ) -> Option<crate::pendulum::PendulumState> {
         let l1 = pendulum.params.l1;
         let _l2 = pendulum.params.l2;
@@ -484,39 +1317,199 @@ impl PendulumRenderer {
     }
 
     /// 处理画布平移（拖动）
-    fn handle_canvas_pan(&mut self, ui: &mut egui::Ui) {
+    /// 只有主键拖动会平移画布，右键留给上下文菜单
+    fn handle_canvas_pan(&mut self, ui: &mut egui::Ui) -> egui::Response {
         let response = ui.interact(
             ui.available_rect_before_wrap(),
             ui.id().with("canvas_pan"),
             egui::Sense::click_and_drag(),
         );
 
-        // 处理拖拽平移
-        if response.dragged() {
-            self.center += response.drag_delta();
+        let dt = ui.ctx().input(|i| i.stable_dt);
+
+        // 处理拖拽平移，并记录最近的拖动速度
+        if response.dragged_by(egui::PointerButton::Primary) {
+            let delta = response.drag_delta();
+            self.center += delta;
+            self.pan_velocity = if dt > 0.0 {
+                delta / dt
+            } else {
+                egui::Vec2::ZERO
+            };
+        } else if self.pan_inertia && self.pan_velocity != egui::Vec2::ZERO {
+            // 松开后继续平移并逐渐减速
+            let (offset, velocity) = decay_pan_velocity(self.pan_velocity, dt);
+            self.center += offset;
+            self.pan_velocity = velocity;
+            ui.ctx().request_repaint();
+        } else {
+            self.pan_velocity = egui::Vec2::ZERO;
         }
+
+        response
+    }
+
+    /// 悬挂点在屏幕上的位置：画布中心加上振动悬挂点的偏移
+    fn origin(&self) -> egui::Pos2 {
+        self.center + self.pivot_shift
     }
 
     /// 世界坐标到屏幕坐标的转换
-    fn world_to_screen(&self, world_x: f64, world_y: f64) -> egui::Pos2 {
+    fn world_to_screen(&self, world_x: Real, world_y: Real) -> egui::Pos2 {
+        let origin = self.origin();
         egui::Pos2::new(
-            self.center.x + world_x as f32 * self.scale,
-            self.center.y - world_y as f32 * self.scale, // 翻转Y轴：物理坐标Y向上，屏幕坐标Y向下
+            origin.x + real_to_f32(world_x) * self.scale,
+            origin.y - real_to_f32(world_y) * self.scale, // 翻转Y轴：物理坐标Y向上，屏幕坐标Y向下
         )
     }
 
     /// 屏幕坐标到世界坐标的转换
-    fn screen_to_world(&self, screen_pos: egui::Pos2) -> (f64, f64) {
+    fn screen_to_world(&self, screen_pos: egui::Pos2) -> (Real, Real) {
+        let origin = self.origin();
         (
-            ((screen_pos.x - self.center.x) / self.scale) as f64,
-            ((self.center.y - screen_pos.y) / self.scale) as f64, // 翻转Y轴
+            ((screen_pos.x - origin.x) / self.scale) as Real,
+            ((origin.y - screen_pos.y) / self.scale) as Real, // 翻转Y轴
         )
     }
 
+    /// 设置线性化解析解的参考摆形（None 为不显示）
+    pub fn set_reference_overlay(&mut self, state: Option<PendulumState>) {
+        self.reference_overlay = state;
+    }
+
+    /// 设置对比参考运行的叠影（None 为不显示）
+    pub fn set_comparison_overlay(&mut self, overlay: Option<(PendulumState, PendulumParams)>) {
+        self.comparison_overlay = overlay;
+    }
+
+    /// 取出右键菜单中选择的操作
+    pub fn take_canvas_action(&mut self) -> Option<CanvasAction> {
+        self.canvas_action.take()
+    }
+
+    /// 最近一次绘制的画布区域（逻辑点）
+    pub fn canvas_rect(&self) -> egui::Rect {
+        self.canvas_rect
+    }
+
+    /// 显示参数变化前的摆形叠影，随后在短时间内淡出
+    pub fn show_parameter_ghost(&mut self, state: PendulumState, params: PendulumParams) {
+        self.parameter_ghost = Some((state, params, std::time::Instant::now()));
+    }
+
     /// 重置视图
     pub fn reset_view(&mut self) {
         self.center = egui::Pos2::ZERO; // 标记为需要重置
         self.scale = 100.0;
+        self.pan_velocity = egui::Vec2::ZERO;
+    }
+
+    /// 把悬挂点放到 rect 中心，并缩放到能完整容纳摆的最大可达范围（l1 + l2）
+    /// rect 无效（如尚未绘制过画布）时退回到重置视图
+    pub fn fit_reach(&mut self, rect: egui::Rect, params: &PendulumParams) {
+        if !rect.is_positive() {
+            self.reset_view();
+            return;
+        }
+        let reach = real_to_f32(params.l1 + params.l2);
+        // 振动的悬挂点会让摆形上下额外移动一个振幅
+        let vertical_reach = reach + real_to_f32(params.pivot_amplitude.abs());
+        self.center = rect.center();
+        self.pan_velocity = egui::Vec2::ZERO;
+        self.set_scale(auto_zoom_scale((reach, vertical_reach), rect.size() * 0.5));
+    }
+
+    /// 是否启用平移惯性
+    pub fn pan_inertia(&self) -> bool {
+        self.pan_inertia
+    }
+
+    /// 设置是否启用平移惯性
+    pub fn set_pan_inertia(&mut self, enabled: bool) {
+        self.pan_inertia = enabled;
+        if !enabled {
+            self.pan_velocity = egui::Vec2::ZERO;
+        }
+    }
+
+    /// 获取相机模式
+    pub fn camera_mode(&self) -> CameraMode {
+        self.camera_mode
+    }
+
+    /// 设置相机模式
+    pub fn set_camera_mode(&mut self, mode: CameraMode) {
+        self.camera_mode = mode;
+    }
+
+    /// 获取相机平滑系数
+    pub fn camera_smoothing(&self) -> f32 {
+        self.camera_smoothing
+    }
+
+    /// 设置相机平滑系数
+    pub fn set_camera_smoothing(&mut self, smoothing: f32) {
+        self.camera_smoothing = smoothing.clamp(0.0, 0.95);
+    }
+
+    /// 获取画布宽高比约束
+    pub fn aspect_ratio(&self) -> AspectRatio {
+        self.aspect_ratio
+    }
+
+    /// 设置画布宽高比约束
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: AspectRatio) {
+        self.aspect_ratio = aspect_ratio;
+    }
+
+    /// 获取摆杆基准宽度
+    pub fn rod_width(&self) -> f32 {
+        self.rod_width
+    }
+
+    /// 是否绘制摆球运动模糊
+    pub fn bob_motion_blur(&self) -> bool {
+        self.bob_motion_blur
+    }
+
+    /// 设置是否绘制摆球运动模糊
+    pub fn set_bob_motion_blur(&mut self, enabled: bool) {
+        self.bob_motion_blur = enabled;
+    }
+
+    /// 获取运动模糊强度
+    pub fn motion_blur_intensity(&self) -> f32 {
+        self.motion_blur_intensity
+    }
+
+    /// 设置运动模糊强度（限制在 0..=1）
+    pub fn set_motion_blur_intensity(&mut self, intensity: f32) {
+        self.motion_blur_intensity = intensity.clamp(0.0, 1.0);
+    }
+
+    /// 设置摆杆基准宽度
+    pub fn set_rod_width(&mut self, width: f32) {
+        self.rod_width = width.clamp(MIN_ROD_WIDTH, MAX_ROD_WIDTH);
+    }
+
+    /// 获取摆杆宽度模式
+    pub fn rod_width_mode(&self) -> RodWidthMode {
+        self.rod_width_mode
+    }
+
+    /// 设置摆杆宽度模式
+    pub fn set_rod_width_mode(&mut self, mode: RodWidthMode) {
+        self.rod_width_mode = mode;
+    }
+
+    /// 获取拖动判定容差（物理像素）
+    pub fn grab_tolerance_px(&self) -> f32 {
+        self.grab_tolerance_px
+    }
+
+    /// 设置拖动判定容差（物理像素）
+    pub fn set_grab_tolerance_px(&mut self, tolerance: f32) {
+        self.grab_tolerance_px = tolerance.clamp(0.0, 50.0);
     }
 
     /// 获取当前缩放比例
@@ -530,6 +1523,623 @@ impl PendulumRenderer {
     }
 }
 
+/// 计算跟随模式下悬挂点应处的屏幕位置，使被跟踪的点位于画布中心
+/// 固定悬挂点模式返回None
+fn camera_target_center(
+    mode: CameraMode,
+    pendulum: &DoublePendulum,
+    canvas_center: egui::Pos2,
+    scale: f32,
+) -> Option<egui::Pos2> {
+    let (tracked_x, tracked_y) = match mode {
+        CameraMode::FixedPivot | CameraMode::AutoZoomFixedPivot => return None,
+        CameraMode::FollowLowerBob => pendulum.get_positions().1,
+        CameraMode::FollowCenterOfMass => pendulum.state.center_of_mass(&pendulum.params),
+    };
+
+    // 屏幕Y轴向下，与物理坐标相反
+    Some(egui::Pos2::new(
+        canvas_center.x - real_to_f32(tracked_x) * scale,
+        canvas_center.y + real_to_f32(tracked_y) * scale,
+    ))
+}
+
+/// 按指数衰减推进平移惯性，返回本帧位移和衰减后的速度
+/// 速度低于阈值时归零
+fn decay_pan_velocity(velocity: egui::Vec2, dt: f32) -> (egui::Vec2, egui::Vec2) {
+    let factor = (-PAN_INERTIA_DECAY * dt).exp();
+    // v(t) = v0·e^(-kt) 在 [0, dt] 上的积分
+    let offset = velocity * (1.0 - factor) / PAN_INERTIA_DECAY;
+    let new_velocity = velocity * factor;
+
+    if new_velocity.length() < PAN_INERTIA_MIN_SPEED {
+        (offset, egui::Vec2::ZERO)
+    } else {
+        (offset, new_velocity)
+    }
+}
+
+/// 指数平滑在时长为 dt 的一帧内向目标靠近的比例 1 - exp(-dt/τ)
+/// smoothing 为参考帧内保留的差距比例，换算成时间常数 τ = -参考帧时长 / ln(smoothing)
+pub fn smoothing_weight(smoothing: f32, dt: f32) -> f32 {
+    if smoothing <= 0.0 {
+        return 1.0;
+    }
+    if smoothing >= 1.0 || dt <= 0.0 || !dt.is_finite() {
+        return 0.0;
+    }
+    let tau = -SMOOTHING_REFERENCE_FRAME / smoothing.ln();
+    1.0 - (-dt / tau).exp()
+}
+
+/// 轨迹和当前摆球相对悬挂点的最大水平、竖直距离（米）
+fn trajectory_extent(pendulum: &DoublePendulum, statistics: &PhysicsStatistics) -> (f32, f32) {
+    let (pos1, pos2) = pendulum.get_positions();
+    let current = [pos1, pos2].into_iter().map(|(x, y)| (real_to_f32(x), real_to_f32(y)));
+    let history = statistics
+        .get_trajectory_history()
+        .iter()
+        .flat_map(|&(x1, y1, x2, y2)| [(x1 as f32, y1 as f32), (x2 as f32, y2 as f32)]);
+
+    current
+        .chain(history)
+        .fold((0.0, 0.0), |(max_x, max_y): (f32, f32), (x, y)| {
+            (max_x.max(x.abs()), max_y.max(y.abs()))
+        })
+}
+
+/// 计算使给定范围（悬挂点两侧的最大距离）恰好容纳在画布内的缩放比例
+/// half_size 为悬挂点到画布边缘的最小距离，留出10%边距
+fn auto_zoom_scale(extent: (f32, f32), half_size: egui::Vec2) -> f32 {
+    const MARGIN: f32 = 0.9;
+    let fit = |half: f32, reach: f32| {
+        if reach > 1e-6 {
+            half.max(0.0) * MARGIN / reach
+        } else {
+            f32::INFINITY
+        }
+    };
+    fit(half_size.x, extent.0)
+        .min(fit(half_size.y, extent.1))
+        .clamp(20.0, 500.0)
+}
+
+/// 在 rect 中计算居中且宽高比为 ratio（宽/高）的最大子矩形
+fn letterbox_rect(rect: egui::Rect, ratio: f32) -> egui::Rect {
+    let size = if rect.width() > rect.height() * ratio {
+        egui::Vec2::new(rect.height() * ratio, rect.height())
+    } else {
+        egui::Vec2::new(rect.width(), rect.width() / ratio)
+    };
+    egui::Rect::from_center_size(rect.center(), size)
+}
+
+/// 把归一化速度（0..1）映射为颜色：蓝（慢）到红（快）
+fn speed_color(t: f32) -> egui::Color32 {
+    let t = t.clamp(0.0, 1.0);
+    egui::ecolor::Hsva::new(0.66 * (1.0 - t), 0.9, 1.0, 1.0).into()
+}
+
+/// 线段是否可能与可见区域相交（按端点包围盒判断，保守地保留部分可见的线段）
+fn segment_visible(rect: egui::Rect, a: egui::Pos2, b: egui::Pos2, stroke_width: f32) -> bool {
+    egui::Rect::from_two_pos(a, b)
+        .expand(stroke_width * 0.5)
+        .intersects(rect)
+}
+
+/// 计算下摆球轨迹每一段的平均速度（m/s），时间间隔无效的段记为0
+fn trail_segment_speeds(points: &[(f64, f64)], times: &[f64]) -> Vec<f32> {
+    points
+        .windows(2)
+        .zip(times.windows(2))
+        .map(|(p, t)| {
+            let dt = t[1] - t[0];
+            if dt > 0.0 {
+                let distance = ((p[1].0 - p[0].0).powi(2) + (p[1].1 - p[0].1).powi(2)).sqrt();
+                (distance / dt) as f32
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+/// 线段速度的 (最小值, 最大值)，没有线段时为 None
+fn speed_range(speeds: &[f32]) -> Option<(f32, f32)> {
+    speeds
+        .iter()
+        .fold(None, |range: Option<(f32, f32)>, &v| match range {
+            Some((lo, hi)) => Some((lo.min(v), hi.max(v))),
+            None => Some((v, v)),
+        })
+}
+
+/// 第 index 段速度按速度范围归一化到 0..1，范围退化时为 0
+fn normalized_speed(speeds: &[f32], range: Option<(f32, f32)>, index: usize) -> f32 {
+    match (speeds.get(index), range) {
+        (Some(&v), Some((lo, hi))) if hi > lo => (v - lo) / (hi - lo),
+        _ => 0.0,
+    }
+}
+
+/// 计算轨迹线段颜色：speed 和 age 均归一化到 0..1（age 越大越新）
+/// 速度只决定色相，新旧只决定透明度；速度模式在非预乘空间合成透明度，淡出时不改变色相
+fn trail_segment_color(
+    base: egui::Color32,
+    mode: TrailColorMode,
+    speed: f32,
+    age: f32,
+    alpha: f32,
+) -> egui::Color32 {
+    let (hue, opacity) = match mode {
+        TrailColorMode::Age => {
+            return egui::Color32::from_rgba_premultiplied(
+                base.r(),
+                base.g(),
+                base.b(),
+                (255.0 * alpha * age) as u8,
+            );
+        }
+        TrailColorMode::Speed => (speed_color(speed), alpha),
+        TrailColorMode::SpeedWithAgeFade => (speed_color(speed), alpha * age),
+    };
+    egui::Color32::from_rgba_unmultiplied(
+        hue.r(),
+        hue.g(),
+        hue.b(),
+        (255.0 * opacity.clamp(0.0, 1.0)) as u8,
+    )
+}
+
+/// 选择不超过 max_value 的 1/2/5×10^k 形式的参考值
+fn nice_reference_value(max_value: f32) -> f32 {
+    if max_value <= 0.0 || !max_value.is_finite() {
+        return 1.0;
+    }
+    let magnitude = 10f32.powf(max_value.log10().floor());
+    let normalized = max_value / magnitude;
+    let nice = if normalized >= 5.0 {
+        5.0
+    } else if normalized >= 2.0 {
+        2.0
+    } else {
+        1.0
+    };
+    nice * magnitude
+}
+
+/// 计算图例布局（位于画布右下角）
+fn compute_legend_layout(rect: egui::Rect, speed_range: Option<(f32, f32)>) -> LegendLayout {
+    let margin = 12.0;
+    let padding = 8.0;
+    let width = 140.0;
+    let height = if speed_range.is_some() { 74.0 } else { 36.0 };
+
+    let frame = egui::Rect::from_min_size(
+        egui::Pos2::new(rect.max.x - margin - width, rect.max.y - margin - height),
+        egui::Vec2::new(width, height),
+    );
+
+    // 参考箭头：长度对应一个整齐的速度值
+    let reference_speed = nice_reference_value(LEGEND_MAX_ARROW_LENGTH / VELOCITY_ARROW_SCALE);
+    let arrow_length = reference_speed * VELOCITY_ARROW_SCALE;
+    let arrow_start = egui::Pos2::new(frame.min.x + padding, frame.min.y + padding + 16.0);
+    let arrow_end = arrow_start + egui::Vec2::new(arrow_length, 0.0);
+    let arrow_label = format!("v = {} m/s", reference_speed);
+
+    let (ramp_rect, ramp_ticks) = match speed_range {
+        Some((min_speed, max_speed)) => {
+            let ramp_rect = egui::Rect::from_min_size(
+                egui::Pos2::new(frame.min.x + padding, arrow_start.y + 12.0),
+                egui::Vec2::new(width - 2.0 * padding, 10.0),
+            );
+            let ticks = (0..3)
+                .map(|i| {
+                    let t = i as f32 / 2.0;
+                    let value = min_speed + (max_speed - min_speed) * t;
+                    let pos = egui::Pos2::new(
+                        ramp_rect.min.x + ramp_rect.width() * t,
+                        ramp_rect.max.y + 2.0,
+                    );
+                    (pos, format!("{:.1}", value))
+                })
+                .collect();
+            (Some(ramp_rect), ticks)
+        }
+        None => (None, Vec::new()),
+    };
+
+    LegendLayout {
+        frame,
+        arrow: (arrow_start, arrow_end),
+        arrow_label,
+        ramp_rect,
+        ramp_ticks,
+    }
+}
+
+/// 把拖动目标投影到以 anchor 为圆心、半径为摆杆长度的圆上，得到摆球的实际落点
+/// 目标与圆心重合时落点为正下方
+fn project_onto_rod(anchor: (Real, Real), target: (Real, Real), length: Real) -> (Real, Real) {
+    let dx = target.0 - anchor.0;
+    let dy = target.1 - anchor.1;
+    let distance = (dx * dx + dy * dy).sqrt();
+    if distance < 1e-9 {
+        return (anchor.0, anchor.1 - length);
+    }
+    (
+        anchor.0 + dx / distance * length,
+        anchor.1 + dy / distance * length,
+    )
+}
+
+/// 计算第 index 个频闪快照的不透明度（共 count 个，从旧到新线性增加）
+fn strobe_alpha(index: usize, count: usize, opacity: f32) -> f32 {
+    if count == 0 {
+        return 0.0;
+    }
+    opacity * (index + 1) as f32 / count as f32
+}
+
+/// 选取运动模糊残影：返回（轨迹索引，不透明度），从新到旧排列
+/// 强度决定残影个数和间隔，越旧的残影越透明；当前位置由摆球本身绘制，不重复
+fn motion_blur_samples(history_len: usize, intensity: f32) -> Vec<(usize, f32)> {
+    let intensity = intensity.clamp(0.0, 1.0);
+    let copies = (intensity * MAX_MOTION_BLUR_COPIES as f32).round() as usize;
+    let spacing = ((intensity * MAX_MOTION_BLUR_SPACING as f32).round() as usize).max(1);
+    (1..=copies)
+        .map_while(|k| {
+            let offset = k * spacing;
+            (offset < history_len).then(|| {
+                let alpha = MOTION_BLUR_OPACITY * (1.0 - k as f32 / (copies + 1) as f32);
+                (history_len - 1 - offset, alpha)
+            })
+        })
+        .collect()
+}
+
+/// 摆球的拖动判定半径（逻辑点）：可见半径加换算后的容差，且不小于 MIN_GRAB_RADIUS
+fn effective_grab_radius(visual_radius: f32, tolerance_px: f32, pixels_per_point: f32) -> f32 {
+    let tolerance = tolerance_px / pixels_per_point.max(f32::EPSILON);
+    (visual_radius + tolerance).max(MIN_GRAB_RADIUS)
+}
+
+/// 求约束力用的引擎，时间设为摆的当前时刻，使驱动力矩和振动悬挂点取当前值
+fn engine_at(time: Real) -> PhysicsEngine {
+    let mut engine = PhysicsEngine::default();
+    engine.set_time(time);
+    engine
+}
+
+/// 按张力计算摆杆宽度：张力等于 reference 时为 base_width，按比例缩放并限制在范围内
+/// 压缩与拉伸同样按大小加粗
+fn rod_width_for_load(tension: Real, reference: Real, base_width: f32) -> f32 {
+    if reference.is_nan() || reference <= 0.0 || !tension.is_finite() {
+        return base_width;
+    }
+    let ratio = real_to_f32(tension.abs() / reference);
+    (base_width * ratio).clamp(MIN_ROD_WIDTH, MAX_ROD_WIDTH)
+}
+
+/// 参数变化叠影在出现 elapsed 秒后的不透明度：前半段保持，后半段线性淡出到0
+fn ghost_alpha(elapsed: f32) -> f32 {
+    let hold = PARAMETER_GHOST_DURATION / 2.0;
+    if elapsed <= hold {
+        PARAMETER_GHOST_OPACITY
+    } else {
+        let t = (elapsed - hold) / (PARAMETER_GHOST_DURATION - hold);
+        PARAMETER_GHOST_OPACITY * (1.0 - t).max(0.0)
+    }
+}
+
+/// 图表数据点的默认安全上限，超过后自动抽稀
+pub const DEFAULT_PLOT_POINT_LIMIT: usize = 5000;
+
+/// 图表数据的自动抽稀：数据点超过上限时按固定步长抽取，始终保留最后一个点
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PlotDecimation {
+    stride: usize,
+}
+
+impl PlotDecimation {
+    /// 按数据点个数和上限决定抽稀步长，上限为0时不抽稀
+    pub fn for_len(len: usize, limit: usize) -> Self {
+        let stride = if limit == 0 || len <= limit {
+            1
+        } else {
+            len.div_ceil(limit)
+        };
+        Self { stride }
+    }
+
+    /// 是否正在抽稀（界面据此显示提示）
+    pub fn is_active(&self) -> bool {
+        self.stride > 1
+    }
+
+    /// 抽稀步长
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// 第 index 个点（共 len 个）是否保留
+    pub fn keep(&self, index: usize, len: usize) -> bool {
+        index.is_multiple_of(self.stride) || index + 1 == len
+    }
+
+    /// 抽取保留的数据点
+    pub fn apply<T: Copy>(&self, points: &[T]) -> Vec<T> {
+        points
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| self.keep(*index, points.len()))
+            .map(|(_, point)| *point)
+            .collect()
+    }
+
+    /// 抽稀时在图表下方显示提示
+    pub fn show_note(&self, ui: &mut egui::Ui) {
+        if self.is_active() {
+            ui.small(format!("(decimated: 1 of every {} points)", self.stride()))
+                .on_hover_text("Too many points to plot smoothly; raise Plot Point Limit");
+        }
+    }
+}
+
+/// 把长度为 len 的折线分成 chunks 段，返回每段的 (起始下标, 结束下标(含), 不透明度)
+/// 相邻段共享端点以保持连续，不透明度从旧到新线性增加
+pub fn fade_chunks(len: usize, chunks: usize) -> Vec<(usize, usize, f32)> {
+    if len < 2 || chunks == 0 {
+        return Vec::new();
+    }
+
+    let segments = len - 1;
+    let chunks = chunks.min(segments);
+    (0..chunks)
+        .map(|k| {
+            let start = k * segments / chunks;
+            let end = (k + 1) * segments / chunks;
+            (start, end, (k + 1) as f32 / chunks as f32)
+        })
+        .collect()
+}
+
+/// 把数值序列缩放进矩形，返回迷你折线图的顶点
+/// 横向均匀铺满，纵向按最小/最大值归一化（值越大越靠上），常数序列画在中线
+pub fn sparkline_points(values: &[f64], rect: egui::Rect) -> Vec<egui::Pos2> {
+    let (min, max) = values
+        .iter()
+        .filter(|v| v.is_finite())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+    let span = max - min;
+    let step = rect.width() / (values.len().max(2) - 1) as f32;
+
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, &v)| {
+            let t = if span > 0.0 && v.is_finite() {
+                ((v - min) / span) as f32
+            } else {
+                0.5
+            };
+            egui::pos2(rect.left() + i as f32 * step, rect.bottom() - t * rect.height())
+        })
+        .collect()
+}
+
+/// 在给定矩形中绘制某一状态的简化摆形缩略图（悬挂点、摆杆和质点）
+pub fn draw_thumbnail(
+    painter: &egui::Painter,
+    rect: egui::Rect,
+    state: &PendulumState,
+    params: &PendulumParams,
+    theme_manager: &ThemeManager,
+) {
+    let (rod_color, mass_color, _, grid_color) = theme_manager.get_pendulum_colors();
+    painter.rect_stroke(rect, 2.0, egui::Stroke::new(1.0, grid_color));
+
+    let (x1, y1) = state.get_mass1_position(params.l1);
+    let (x2, y2) = state.get_mass2_position(params.l1, params.l2);
+    let world = [
+        (0.0, 0.0),
+        (real_to_f32(x1), real_to_f32(y1)),
+        (real_to_f32(x2), real_to_f32(y2)),
+    ];
+    let (origin, scale) = thumbnail_fit(&world, rect.shrink(THUMBNAIL_BOB_RADIUS + 1.0));
+    let [pivot, bob1, bob2] = world.map(|(x, y)| origin + egui::vec2(x * scale, -y * scale));
+
+    let stroke = egui::Stroke::new(1.5, rod_color);
+    painter.line_segment([pivot, bob1], stroke);
+    painter.line_segment([bob1, bob2], stroke);
+    painter.circle_filled(pivot, 1.5, rod_color);
+    painter.circle_filled(bob1, THUMBNAIL_BOB_RADIUS, mass_color);
+    painter.circle_filled(bob2, THUMBNAIL_BOB_RADIUS, mass_color);
+}
+
+/// 计算把世界坐标点（y 向上）完整放入矩形的变换，返回 (世界原点的屏幕位置, 像素/米)
+/// 点集的包围盒在矩形中居中，并按较紧的方向缩放
+pub fn thumbnail_fit(points: &[(f32, f32)], rect: egui::Rect) -> (egui::Pos2, f32) {
+    let (min_x, max_x, min_y, max_y) = points.iter().fold(
+        (f32::INFINITY, f32::NEG_INFINITY, f32::INFINITY, f32::NEG_INFINITY),
+        |(x0, x1, y0, y1), &(x, y)| (x0.min(x), x1.max(x), y0.min(y), y1.max(y)),
+    );
+    if !min_x.is_finite() {
+        return (rect.center(), 1.0);
+    }
+
+    // 退化方向（如竖直下垂时宽度为0）不限制缩放
+    let fit = |extent: f32, available: f32| {
+        if extent > 1e-6 {
+            available / extent
+        } else {
+            f32::INFINITY
+        }
+    };
+    let scale = fit(max_x - min_x, rect.width()).min(fit(max_y - min_y, rect.height()));
+    let scale = if scale.is_finite() { scale } else { 1.0 };
+
+    let center = ((min_x + max_x) / 2.0, (min_y + max_y) / 2.0);
+    let origin = rect.center() + egui::vec2(-center.0 * scale, center.1 * scale);
+    (origin, scale)
+}
+
+/// 离屏渲染帧时使用的颜色和轨迹设置
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FrameStyle {
+    pub background: egui::Color32,
+    pub rod_color: egui::Color32,
+    pub mass_color: egui::Color32,
+    pub trajectory_color: egui::Color32,
+    /// 为 None 时不画轨迹
+    pub trail_paths: Option<TrailPaths>,
+    pub trail_color_mode: TrailColorMode,
+    pub trajectory_alpha: f32,
+}
+
+impl FrameStyle {
+    /// 取当前主题颜色和显示选项中的轨迹设置
+    pub fn new(
+        theme_manager: &ThemeManager,
+        ui_state: &UiStateManager,
+        background: egui::Color32,
+    ) -> Self {
+        let (rod_color, mass_color, trajectory_color, _) = theme_manager.get_pendulum_colors();
+        Self {
+            background,
+            rod_color,
+            mass_color,
+            trajectory_color,
+            trail_paths: ui_state.show_trajectory().then(|| ui_state.trail_paths()),
+            trail_color_mode: ui_state.trail_color_mode(),
+            trajectory_alpha: ui_state.trajectory_alpha(),
+        }
+    }
+}
+
+/// 不经过 egui::Ui，直接把摆形和轨迹光栅化到 size 大小的图像中
+/// 悬挂点位于图像中心，缩放使完全伸直的摆也留在画面内
+/// trail 与统计中的轨迹记录格式相同：(x₁, y₁, x₂, y₂) 及对应时间
+pub fn render_frame_image(
+    size: [usize; 2],
+    pendulum: &DoublePendulum,
+    trail: &[(f64, f64, f64, f64)],
+    trail_times: &[f64],
+    style: &FrameStyle,
+) -> egui::ColorImage {
+    let mut image = egui::ColorImage::new(size, style.background);
+    let params = &pendulum.params;
+    let reach = real_to_f64(params.l1 + params.l2).max(1e-6) as f32;
+    let scale = 0.45 * size[0].min(size[1]) as f32 / reach;
+    let center = egui::pos2(size[0] as f32 / 2.0, size[1] as f32 / 2.0);
+    let to_image = |(x, y): (f64, f64)| center + egui::vec2(x as f32, -y as f32) * scale;
+
+    if let Some(paths) = style.trail_paths.filter(|_| trail.len() >= 2) {
+        for (enabled, path) in [
+            (paths.lower, TrailPath::Lower),
+            (paths.upper, TrailPath::Upper),
+            (paths.center_of_mass, TrailPath::CenterOfMass),
+        ] {
+            if !enabled {
+                continue;
+            }
+            let path_points: Vec<(f64, f64)> =
+                trail.iter().map(|&sample| path.point(sample, params)).collect();
+            let speeds = if style.trail_color_mode.uses_speed() {
+                trail_segment_speeds(&path_points, trail_times)
+            } else {
+                Vec::new()
+            };
+            let range = speed_range(&speeds);
+            let base = path.color(style.trajectory_color, style.mass_color);
+            for i in 1..path_points.len() {
+                let age = i as f32 / path_points.len() as f32;
+                let speed = normalized_speed(&speeds, range, i - 1);
+                let alpha = style.trajectory_alpha;
+                let color = trail_segment_color(base, style.trail_color_mode, speed, age, alpha);
+                let (a, b) = (to_image(path_points[i - 1]), to_image(path_points[i]));
+                raster_line(&mut image, a, b, TRAIL_STROKE_WIDTH, color);
+            }
+        }
+    }
+
+    // 摆形随振动的悬挂点移动
+    let state = &pendulum.state;
+    let pivot_offset = real_to_f64(params.pivot_offset(pendulum.time));
+    let (x1, y1) = state.get_mass1_position(params.l1);
+    let (x2, y2) = state.get_mass2_position(params.l1, params.l2);
+    let pivot = to_image((0.0, pivot_offset));
+    let bob1 = to_image((real_to_f64(x1), real_to_f64(y1) + pivot_offset));
+    let bob2 = to_image((real_to_f64(x2), real_to_f64(y2) + pivot_offset));
+    raster_line(&mut image, pivot, bob1, 2.0, style.rod_color);
+    raster_line(&mut image, bob1, bob2, 2.0, style.rod_color);
+    raster_circle(&mut image, pivot, 4.0, style.rod_color);
+    for (position, mass) in [(bob1, params.m1), (bob2, params.m2)] {
+        let radius = real_to_f32(mass * 8.0 + 4.0);
+        raster_circle(&mut image, position, radius + 1.0, style.rod_color);
+        raster_circle(&mut image, position, radius, style.mass_color);
+    }
+    image
+}
+
+/// 在 bounds 覆盖的像素上按 coverage(像素中心) 混合预乘颜色
+fn raster_coverage(
+    image: &mut egui::ColorImage,
+    bounds: egui::Rect,
+    color: egui::Color32,
+    coverage: impl Fn(egui::Pos2) -> f32,
+) {
+    let [width, height] = image.size;
+    let clamp = |v: f32, max: usize| (v.max(0.0) as usize).min(max);
+    for y in clamp(bounds.top().floor(), height)..clamp(bounds.bottom().ceil(), height) {
+        for x in clamp(bounds.left().floor(), width)..clamp(bounds.right().ceil(), width) {
+            let cover = coverage(egui::pos2(x as f32 + 0.5, y as f32 + 0.5)).clamp(0.0, 1.0);
+            if cover <= 0.0 {
+                continue;
+            }
+            let pixel = &mut image.pixels[y * width + x];
+            let source = color.gamma_multiply(cover);
+            let keep = 1.0 - source.a() as f32 / 255.0;
+            let mix = |src: u8, dst: u8| (src as f32 + dst as f32 * keep).round().min(255.0) as u8;
+            *pixel = egui::Color32::from_rgba_premultiplied(
+                mix(source.r(), pixel.r()),
+                mix(source.g(), pixel.g()),
+                mix(source.b(), pixel.b()),
+                mix(source.a(), pixel.a()),
+            );
+        }
+    }
+}
+
+/// 抗锯齿粗线段
+fn raster_line(
+    image: &mut egui::ColorImage,
+    a: egui::Pos2,
+    b: egui::Pos2,
+    width: f32,
+    color: egui::Color32,
+) {
+    let half = width * 0.5 + 0.5;
+    let bounds = egui::Rect::from_two_pos(a, b).expand(half);
+    let ab = b - a;
+    let length_sq = ab.length_sq();
+    raster_coverage(image, bounds, color, |p| {
+        let t = if length_sq > 0.0 { ((p - a).dot(ab) / length_sq).clamp(0.0, 1.0) } else { 0.0 };
+        half - p.distance(a + ab * t)
+    });
+}
+
+/// 抗锯齿实心圆
+fn raster_circle(
+    image: &mut egui::ColorImage,
+    center: egui::Pos2,
+    radius: f32,
+    color: egui::Color32,
+) {
+    let bounds = egui::Rect::from_center_size(center, egui::Vec2::splat(2.0 * radius + 2.0));
+    raster_coverage(image, bounds, color, |p| radius + 0.5 - p.distance(center));
+}
+
 impl Default for PendulumRenderer {
     fn default() -> Self {
         Self::new()
@@ -558,6 +2168,432 @@ mod tests {
         assert!((back_to_world.1 - world_pos.1).abs() < 0.001);
     }
 
+    #[test]
+    fn test_camera_target_center() {
+        let pendulum = DoublePendulum::new(
+            PendulumState::at_rest(0.0, 0.0),
+            PendulumParams::default(),
+        );
+        let canvas_center = egui::Pos2::new(400.0, 300.0);
+
+        let fixed = camera_target_center(CameraMode::FixedPivot, &pendulum, canvas_center, 100.0);
+        assert!(fixed.is_none());
+
+        // 下摆在 (0, -2)，悬挂点应上移 200 像素
+        let lower =
+            camera_target_center(CameraMode::FollowLowerBob, &pendulum, canvas_center, 100.0)
+                .unwrap();
+        assert_eq!(lower, egui::Pos2::new(400.0, 100.0));
+
+        // 质心在 (0, -1.5)
+        let com =
+            camera_target_center(CameraMode::FollowCenterOfMass, &pendulum, canvas_center, 100.0)
+                .unwrap();
+        assert_eq!(com, egui::Pos2::new(400.0, 150.0));
+
+        // 以该悬挂点渲染时，被跟踪的点正好落在画布中心
+        let mut renderer = PendulumRenderer::new();
+        renderer.center = lower;
+        let (_, pos2) = pendulum.get_positions();
+        assert_eq!(renderer.world_to_screen(pos2.0, pos2.1), canvas_center);
+    }
+
+    #[test]
+    fn test_fit_reach() {
+        let mut renderer = PendulumRenderer::new();
+        let params = PendulumParams::new(1.0, 1.0, 1.5, 1.5, 9.81, 0.0);
+        let rect = egui::Rect::from_min_size(egui::pos2(100.0, 50.0), egui::vec2(800.0, 600.0));
+
+        // 竖直方向受限：300 * 0.9 / 3 = 90
+        renderer.fit_reach(rect, &params);
+        assert_eq!(renderer.center, rect.center());
+        assert!((renderer.scale() - 90.0).abs() < 1e-4);
+
+        // 过小的画布只能缩到下限
+        let tiny = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(4.0, 4.0));
+        renderer.fit_reach(tiny, &params);
+        assert_eq!(renderer.scale(), 20.0);
+
+        // 尚未绘制过画布时退回到重置视图
+        renderer.fit_reach(egui::Rect::NOTHING, &params);
+        assert_eq!(renderer.center, egui::Pos2::ZERO);
+        assert_eq!(renderer.scale(), 100.0);
+    }
+
+    #[test]
+    fn test_auto_zoom_scale() {
+        let half_size = egui::Vec2::new(400.0, 300.0);
+
+        // 竖直方向 2 米更受限：300 * 0.9 / 2 = 135
+        assert!((auto_zoom_scale((1.0, 2.0), half_size) - 135.0).abs() < 1e-4);
+        // 水平方向更受限：400 * 0.9 / 3 = 120
+        assert!((auto_zoom_scale((3.0, 1.0), half_size) - 120.0).abs() < 1e-4);
+
+        // 结果限制在手动缩放的范围内
+        assert_eq!(auto_zoom_scale((0.0, 0.0), half_size), 500.0);
+        assert_eq!(auto_zoom_scale((1000.0, 1000.0), half_size), 20.0);
+
+        // 轨迹范围包含历史点和当前摆球
+        let pendulum = DoublePendulum::new(
+            PendulumState::at_rest(0.0, 0.0),
+            PendulumParams::default(),
+        );
+        let mut statistics = PhysicsStatistics::new(10);
+        statistics.add_trajectory_point(0.0, 0.5, -0.5, 1.5, -1.0);
+        let (max_x, max_y) = trajectory_extent(&pendulum, &statistics);
+        assert!((max_x - 1.5).abs() < 1e-6);
+        assert!((max_y - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_letterbox_rect() {
+        let rect = egui::Rect::from_min_size(egui::Pos2::new(10.0, 20.0), egui::vec2(800.0, 400.0));
+
+        // 宽画布上的正方形：左右留边
+        let square = letterbox_rect(rect, 1.0);
+        assert_eq!(square.size(), egui::vec2(400.0, 400.0));
+        assert_eq!(square.center(), rect.center());
+
+        // 高度不足时按宽度适配：上下留边
+        let tall = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(320.0, 600.0));
+        let wide = letterbox_rect(tall, 16.0 / 9.0);
+        assert!((wide.width() - 320.0).abs() < 1e-3);
+        assert!((wide.height() - 180.0).abs() < 1e-3);
+        assert_eq!(wide.center(), tall.center());
+
+        // 结果始终在原矩形内
+        for ratio in AspectRatio::ALL.iter().filter_map(|a| a.ratio()) {
+            let sub = letterbox_rect(rect, ratio);
+            assert!(rect.expand(1e-3).contains_rect(sub));
+            assert!((sub.width() / sub.height() - ratio).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_nice_reference_value() {
+        assert_eq!(nice_reference_value(8.0), 5.0);
+        assert_eq!(nice_reference_value(3.0), 2.0);
+        assert_eq!(nice_reference_value(1.5), 1.0);
+        assert!((nice_reference_value(0.7) - 0.5).abs() < 1e-6);
+        assert_eq!(nice_reference_value(0.0), 1.0);
+    }
+
+    #[test]
+    fn test_legend_layout() {
+        let rect = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::Vec2::new(800.0, 600.0));
+
+        let layout = compute_legend_layout(rect, None);
+        assert!(rect.contains_rect(layout.frame));
+        assert!(layout.ramp_rect.is_none());
+        assert!(layout.ramp_ticks.is_empty());
+        let arrow_length = layout.arrow.1.x - layout.arrow.0.x;
+        assert!(arrow_length > 0.0 && arrow_length <= LEGEND_MAX_ARROW_LENGTH);
+        assert_eq!(layout.arrow_label, "v = 5 m/s");
+
+        let layout = compute_legend_layout(rect, Some((0.0, 8.0)));
+        let ramp = layout.ramp_rect.unwrap();
+        assert!(layout.frame.contains_rect(ramp));
+        let labels: Vec<&str> = layout.ramp_ticks.iter().map(|(_, l)| l.as_str()).collect();
+        assert_eq!(labels, vec!["0.0", "4.0", "8.0"]);
+        assert_eq!(layout.ramp_ticks[0].0.x, ramp.min.x);
+        assert_eq!(layout.ramp_ticks[2].0.x, ramp.max.x);
+    }
+
+    #[test]
+    fn test_speed_color_ramp() {
+        let slow = speed_color(0.0);
+        let fast = speed_color(1.0);
+        assert!(slow.b() > slow.r());
+        assert!(fast.r() > fast.b());
+    }
+
+    #[test]
+    fn test_trail_selection_paths() {
+        let enabled = |selection: TrailSelection| {
+            let paths = selection.paths();
+            (paths.lower, paths.upper, paths.center_of_mass)
+        };
+        assert_eq!(enabled(TrailSelection::Lower), (true, false, false));
+        assert_eq!(enabled(TrailSelection::Upper), (false, true, false));
+        assert_eq!(enabled(TrailSelection::Both), (true, true, false));
+        assert_eq!(enabled(TrailSelection::CenterOfMass), (false, false, true));
+
+        // 每个选项的路径开关都能反查回同一个选项
+        for selection in TrailSelection::ALL {
+            assert_eq!(TrailSelection::from_paths(selection.paths()), Some(selection));
+        }
+    }
+
+    #[test]
+    fn test_trail_path_points() {
+        let params = PendulumParams::new(1.0, 3.0, 1.0, 1.0, 9.81, 0.0);
+        let sample = (1.0, -1.0, 2.0, -3.0);
+        assert_eq!(TrailPath::Lower.point(sample, &params), (2.0, -3.0));
+        assert_eq!(TrailPath::Upper.point(sample, &params), (1.0, -1.0));
+        assert_eq!(TrailPath::CenterOfMass.point(sample, &params), (1.75, -2.5));
+
+        // 上摆球轨迹与下摆球轨迹颜色不同
+        let (trajectory, mass) = (egui::Color32::BLUE, egui::Color32::RED);
+        assert_eq!(TrailPath::Lower.color(trajectory, mass), trajectory);
+        assert_eq!(TrailPath::Upper.color(trajectory, mass), mass);
+    }
+
+    #[test]
+    fn test_trail_segment_speeds() {
+        let points = [(0.0, 0.0), (0.3, 0.4), (0.3, 0.4)];
+        let speeds = trail_segment_speeds(&points, &[0.0, 0.1, 0.1]);
+        assert_eq!(speeds.len(), 2);
+        assert!((speeds[0] - 5.0).abs() < 1e-5);
+        assert_eq!(speeds[1], 0.0);
+    }
+
+    #[test]
+    fn test_trail_segment_color_composition() {
+        let base = egui::Color32::from_rgb(100, 150, 255);
+
+        // 年龄模式不受速度影响
+        let age_only = trail_segment_color(base, TrailColorMode::Age, 0.0, 0.5, 1.0);
+        assert_eq!(age_only, trail_segment_color(base, TrailColorMode::Age, 1.0, 0.5, 1.0));
+        assert_eq!(age_only.a(), 127);
+
+        // 速度模式不受年龄影响
+        let fast = trail_segment_color(base, TrailColorMode::Speed, 1.0, 0.1, 0.8);
+        assert_eq!(fast, trail_segment_color(base, TrailColorMode::Speed, 1.0, 0.9, 0.8));
+
+        // 组合模式：色相与纯速度模式相同，透明度随年龄变化
+        let mode = TrailColorMode::SpeedWithAgeFade;
+        let old = trail_segment_color(base, mode, 1.0, 0.25, 1.0).to_srgba_unmultiplied();
+        let new = trail_segment_color(base, mode, 1.0, 1.0, 1.0).to_srgba_unmultiplied();
+        let hue = speed_color(1.0);
+        for rgba in [old, new] {
+            for (channel, expected) in rgba[..3].iter().zip([hue.r(), hue.g(), hue.b()]) {
+                assert!(channel.abs_diff(expected) <= 2, "{:?} vs {:?}", rgba, hue);
+            }
+        }
+        assert!(old[3] < new[3]);
+    }
+
+    #[test]
+    fn test_strobe_alpha() {
+        assert_eq!(strobe_alpha(0, 0, 0.5), 0.0);
+        assert!((strobe_alpha(0, 4, 0.8) - 0.2).abs() < 1e-6);
+        assert!((strobe_alpha(3, 4, 0.8) - 0.8).abs() < 1e-6);
+        assert!(strobe_alpha(1, 4, 0.8) < strobe_alpha(2, 4, 0.8));
+    }
+
+    #[test]
+    fn test_motion_blur_samples() {
+        // 强度为0时不绘制残影
+        assert!(motion_blur_samples(100, 0.0).is_empty());
+
+        // 最大强度：8 个残影，每隔 3 个采样一个，从最新往回排列
+        let samples = motion_blur_samples(100, 1.0);
+        let indices: Vec<usize> = samples.iter().map(|&(index, _)| index).collect();
+        assert_eq!(indices, vec![96, 93, 90, 87, 84, 81, 78, 75]);
+        assert!(samples.windows(2).all(|pair| pair[0].1 > pair[1].1));
+        assert!(samples.iter().all(|&(_, alpha)| alpha > 0.0 && alpha < MOTION_BLUR_OPACITY));
+
+        // 较低强度时残影更少、间隔更密
+        let samples = motion_blur_samples(100, 0.5);
+        let indices: Vec<usize> = samples.iter().map(|&(index, _)| index).collect();
+        assert_eq!(indices, vec![97, 95, 93, 91]);
+
+        // 轨迹不够长时只取已有的位置
+        assert_eq!(motion_blur_samples(7, 1.0).len(), 2);
+        assert!(motion_blur_samples(1, 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_ghost_alpha() {
+        assert_eq!(ghost_alpha(0.0), PARAMETER_GHOST_OPACITY);
+        assert_eq!(ghost_alpha(PARAMETER_GHOST_DURATION / 2.0), PARAMETER_GHOST_OPACITY);
+        assert!(ghost_alpha(PARAMETER_GHOST_DURATION * 0.75) < PARAMETER_GHOST_OPACITY);
+        assert!(ghost_alpha(PARAMETER_GHOST_DURATION * 0.75) > 0.0);
+        assert_eq!(ghost_alpha(PARAMETER_GHOST_DURATION), 0.0);
+        assert_eq!(ghost_alpha(10.0), 0.0);
+    }
+
+    #[test]
+    fn test_smoothing_weight_is_frame_rate_independent() {
+        // 参考帧内保留 smoothing 的差距，系数为 0 时直接到达目标
+        assert!((smoothing_weight(0.8, SMOOTHING_REFERENCE_FRAME) - 0.2).abs() < 1e-6);
+        assert_eq!(smoothing_weight(0.0, 0.01), 1.0);
+        assert_eq!(smoothing_weight(0.8, 0.0), 0.0);
+
+        // 120 FPS 下两帧与 60 FPS 下一帧靠近目标的程度相同
+        let half = smoothing_weight(0.8, SMOOTHING_REFERENCE_FRAME / 2.0);
+        let remaining = (1.0 - half) * (1.0 - half);
+        assert!((remaining - 0.8).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_decay_pan_velocity() {
+        let velocity = egui::Vec2::new(300.0, -120.0);
+        let (offset, decayed) = decay_pan_velocity(velocity, 1.0 / 60.0);
+
+        // 速度方向不变，大小衰减
+        assert!(decayed.length() < velocity.length());
+        assert!((decayed.angle() - velocity.angle()).abs() < 1e-5);
+        // 位移介于按初速和末速计算的结果之间
+        assert!(offset.length() < velocity.length() / 60.0);
+        assert!(offset.length() > decayed.length() / 60.0);
+
+        // 总位移收敛到 v0 / k，且最终停止
+        let mut v = velocity;
+        let mut total = egui::Vec2::ZERO;
+        for _ in 0..600 {
+            let (step, next) = decay_pan_velocity(v, 1.0 / 60.0);
+            total += step;
+            v = next;
+        }
+        assert_eq!(v, egui::Vec2::ZERO);
+        assert!(total.length() <= velocity.length() / PAN_INERTIA_DECAY);
+        assert!(total.length() > 0.95 * velocity.length() / PAN_INERTIA_DECAY);
+    }
+
+    #[test]
+    fn test_project_onto_rod() {
+        // 远处的目标被拉回到圆周上，方向不变
+        let (x, y) = project_onto_rod((0.0, 0.0), (3.0, -4.0), 1.0);
+        assert!((x - 0.6).abs() < 1e-6);
+        assert!((y + 0.8).abs() < 1e-6);
+
+        // 圆内的目标被推到圆周上
+        let anchor = (0.5, -1.0);
+        let (x, y) = project_onto_rod(anchor, (0.5, -1.2), 0.8);
+        assert!((x - 0.5).abs() < 1e-6);
+        assert!((y + 1.8).abs() < 1e-6);
+
+        // 落点与拖动计算的角度一致
+        let renderer = PendulumRenderer {
+            dragging_mass: Some(2),
+            ..PendulumRenderer::new()
+        };
+        let pendulum = DoublePendulum::new(
+            PendulumState::at_rest(0.3, 0.0),
+            PendulumParams::default(),
+        );
+        let target = (2.0, 0.5);
+        let state = renderer.calculate_new_pendulum_state(&pendulum, target).unwrap();
+        let anchor = pendulum.state.get_mass1_position(pendulum.params.l1);
+        let landing = project_onto_rod(anchor, target, pendulum.params.l2);
+        let (x2, y2) = state.get_mass2_position(pendulum.params.l1, pendulum.params.l2);
+        assert!((x2 - landing.0).abs() < 1e-5);
+        assert!((y2 - landing.1).abs() < 1e-5);
+
+        // 退化情况：目标与圆心重合
+        assert_eq!(project_onto_rod((0.0, 0.0), (0.0, 0.0), 1.0), (0.0, -1.0));
+    }
+
+    #[test]
+    fn test_render_frame_image() {
+        let params = PendulumParams::default();
+        let pendulum = DoublePendulum::new(PendulumState::new(0.0, 0.0, 0.0, 0.0), params);
+        let style = FrameStyle {
+            background: egui::Color32::BLACK,
+            rod_color: egui::Color32::WHITE,
+            mass_color: egui::Color32::RED,
+            trajectory_color: egui::Color32::BLUE,
+            trail_paths: Some(TrailSelection::Lower.paths()),
+            trail_color_mode: TrailColorMode::Age,
+            trajectory_alpha: 1.0,
+        };
+        // 下摆球轨迹为悬挂点上方的一条水平线，上摆球轨迹不绘制
+        let reach = real_to_f64(params.l1 + params.l2);
+        let trail = [(0.0, 0.0, -reach, 0.5 * reach), (0.0, 0.0, reach, 0.5 * reach)];
+        let image = render_frame_image([100, 100], &pendulum, &trail, &[0.0, 1.0], &style);
+        let pixel = |x: usize, y: usize| image.pixels[y * 100 + x];
+
+        // 竖直下垂时下摆球位于中心正下方 0.45 × 100 像素处
+        assert_eq!(pixel(50, 94), egui::Color32::RED);
+        assert_eq!(pixel(50, 56), egui::Color32::WHITE);
+        assert_eq!(pixel(0, 0), egui::Color32::BLACK);
+        let trail_pixel = pixel(20, 27);
+        assert!(trail_pixel.b() > 0 && trail_pixel.r() == 0);
+    }
+
+    #[test]
+    fn test_thumbnail_fit() {
+        let rect = egui::Rect::from_min_size(egui::pos2(100.0, 50.0), egui::vec2(40.0, 40.0));
+        let to_screen = |(origin, scale): (egui::Pos2, f32), (x, y): (f32, f32)| {
+            origin + egui::vec2(x * scale, -y * scale)
+        };
+
+        // 竖直下垂：高度决定缩放，悬挂点在顶边中点
+        let hanging = [(0.0, 0.0), (0.0, -1.0), (0.0, -2.0)];
+        let fit = thumbnail_fit(&hanging, rect);
+        assert!((fit.1 - 20.0).abs() < 1e-4);
+        let pivot = to_screen(fit, hanging[0]);
+        assert!((pivot - egui::pos2(120.0, 50.0)).length() < 1e-3);
+
+        // 水平伸展：宽度决定缩放，所有点都在矩形内
+        let horizontal = [(0.0, 0.0), (1.0, 0.0), (2.5, -0.5)];
+        let fit = thumbnail_fit(&horizontal, rect);
+        assert!((fit.1 - 16.0).abs() < 1e-4);
+        for point in horizontal {
+            assert!(rect.expand(1e-3).contains(to_screen(fit, point)));
+        }
+    }
+
+    #[test]
+    fn test_sparkline_points() {
+        let rect = egui::Rect::from_min_size(egui::pos2(10.0, 20.0), egui::vec2(100.0, 10.0));
+        let points = sparkline_points(&[1e-6, 3e-6, 2e-6], rect);
+
+        assert_eq!(points.len(), 3);
+        assert_eq!(points[0], egui::pos2(10.0, 30.0));
+        assert_eq!(points[1], egui::pos2(60.0, 20.0));
+        assert!((points[2].x - 110.0).abs() < 1e-4);
+        assert!((points[2].y - 25.0).abs() < 1e-4);
+
+        // 常数序列画在中线上
+        let flat = sparkline_points(&[0.5; 4], rect);
+        assert!(flat.iter().all(|p| (p.y - 25.0).abs() < 1e-4));
+        assert!(sparkline_points(&[], rect).is_empty());
+    }
+
+    #[test]
+    fn test_plot_decimation_threshold() {
+        // 不超过上限时不抽稀，也不显示提示
+        let below = PlotDecimation::for_len(5000, 5000);
+        assert!(!below.is_active());
+        assert_eq!(below.stride(), 1);
+        let points: Vec<usize> = (0..5000).collect();
+        assert_eq!(below.apply(&points), points);
+
+        // 超过上限后自动抽稀，点数不超过上限并保留首尾
+        let above = PlotDecimation::for_len(12_001, 5000);
+        assert!(above.is_active());
+        assert_eq!(above.stride(), 3);
+        let points: Vec<usize> = (0..12_001).collect();
+        let kept = above.apply(&points);
+        assert!(kept.len() <= 5000 + 1);
+        assert_eq!(kept.first(), Some(&0));
+        assert_eq!(kept.last(), Some(&12_000));
+
+        // 上限为0表示关闭保护
+        assert!(!PlotDecimation::for_len(1_000_000, 0).is_active());
+    }
+
+    #[test]
+    fn test_fade_chunks() {
+        let chunks = fade_chunks(101, 10);
+        assert_eq!(chunks.len(), 10);
+        assert_eq!(chunks.first().unwrap().0, 0);
+        assert_eq!(chunks.last().unwrap().1, 100);
+        assert!((chunks.last().unwrap().2 - 1.0).abs() < 1e-6);
+
+        // 相邻段首尾相连，不透明度递增
+        for pair in chunks.windows(2) {
+            assert_eq!(pair[0].1, pair[1].0);
+            assert!(pair[0].2 < pair[1].2);
+        }
+
+        // 点数少于分段数时每段一条线段
+        assert_eq!(fade_chunks(4, 10).len(), 3);
+        assert!(fade_chunks(1, 10).is_empty());
+    }
+
     #[test]
     fn test_scale_limits() {
         let mut renderer = PendulumRenderer::new();
@@ -568,4 +2604,71 @@ mod tests {
         renderer.set_scale(5.0);
         assert_eq!(renderer.scale(), 20.0); // 应该被限制到最小值
     }
+
+    #[test]
+    fn test_rod_width_for_load() {
+        // 基准负载对应基准宽度，按比例缩放
+        assert_eq!(rod_width_for_load(30.0, 30.0, 3.0), 3.0);
+        assert_eq!(rod_width_for_load(60.0, 30.0, 3.0), 6.0);
+        assert_eq!(rod_width_for_load(-15.0, 30.0, 3.0), 1.5);
+
+        // 限制在最小和最大宽度之间
+        assert_eq!(rod_width_for_load(0.0, 30.0, 3.0), MIN_ROD_WIDTH);
+        assert_eq!(rod_width_for_load(1000.0, 30.0, 3.0), MAX_ROD_WIDTH);
+
+        // 无效输入退回基准宽度
+        assert_eq!(rod_width_for_load(10.0, 0.0, 3.0), 3.0);
+        assert_eq!(rod_width_for_load(Real::NAN, 30.0, 3.0), 3.0);
+    }
+
+    #[test]
+    fn test_effective_grab_radius() {
+        // 容差按每点像素数换算为逻辑点
+        assert_eq!(effective_grab_radius(12.0, 6.0, 1.0), 18.0);
+        assert_eq!(effective_grab_radius(12.0, 6.0, 2.0), 15.0);
+
+        // 小摆球且容差为0时使用下限
+        assert_eq!(effective_grab_radius(5.0, 0.0, 1.0), MIN_GRAB_RADIUS);
+        assert_eq!(effective_grab_radius(5.0, 4.0, 2.0), MIN_GRAB_RADIUS);
+    }
+
+    #[test]
+    fn test_segment_visible() {
+        let rect = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(100.0, 100.0));
+        let p = egui::pos2;
+
+        // 完全在内部
+        assert!(segment_visible(rect, p(10.0, 10.0), p(20.0, 20.0), 1.5));
+        // 一端在内部，部分可见
+        assert!(segment_visible(rect, p(50.0, 50.0), p(150.0, 50.0), 1.5));
+        // 两端都在外部但穿过可见区域
+        assert!(segment_visible(rect, p(-50.0, 50.0), p(150.0, 50.0), 1.5));
+        // 线宽使贴着边界外的线段仍然可见
+        assert!(segment_visible(rect, p(-0.5, 10.0), p(-0.5, 20.0), 1.5));
+
+        // 完全在某一侧之外
+        assert!(!segment_visible(rect, p(-30.0, 10.0), p(-10.0, 90.0), 1.5));
+        assert!(!segment_visible(rect, p(110.0, 10.0), p(130.0, 20.0), 1.5));
+        assert!(!segment_visible(rect, p(10.0, -20.0), p(90.0, -5.0), 1.5));
+        assert!(!segment_visible(rect, p(10.0, 120.0), p(90.0, 105.0), 1.5));
+    }
+
+    #[test]
+    fn test_segment_culling_reduces_draw_calls_when_zoomed_in() {
+        // 半径200像素的螺旋轨迹，只看中心附近 40x40 的区域
+        let points: Vec<egui::Pos2> = (0..2000)
+            .map(|i| {
+                let angle = i as f32 * 0.05;
+                let radius = 200.0 * i as f32 / 2000.0;
+                egui::pos2(radius * angle.cos(), radius * angle.sin())
+            })
+            .collect();
+        let rect = egui::Rect::from_center_size(egui::Pos2::ZERO, egui::vec2(40.0, 40.0));
+        let drawn = points
+            .windows(2)
+            .filter(|pair| segment_visible(rect, pair[0], pair[1], TRAIL_STROKE_WIDTH))
+            .count();
+        assert!(drawn > 0);
+        assert!(drawn < points.len() / 5);
+    }
 }
@@ -0,0 +1,164 @@
+/// 窗口几何模块
+/// 负责在两次启动之间保存和恢复窗口的位置与大小
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+/// 窗口几何信息在 eframe 存储中的键
+pub const WINDOW_GEOMETRY_KEY: &str = "window_geometry";
+
+/// 默认窗口大小
+pub const DEFAULT_WINDOW_SIZE: [f32; 2] = [1200.0, 800.0];
+
+/// 窗口外框至少要有该边长的区域落在可能的屏幕内才恢复位置
+const MIN_VISIBLE_SIZE: f32 = 50.0;
+
+/// 比较显示器大小时允许的误差（逻辑像素）
+const MONITOR_SIZE_TOLERANCE: f32 = 1.0;
+
+/// 窗口的外框位置和内部大小
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    /// 外框左上角位置
+    pub position: [f32; 2],
+    /// 内部区域大小
+    pub size: [f32; 2],
+    /// 保存时所在显示器的大小，旧版本保存的几何中没有该字段
+    #[serde(default)]
+    pub monitor_size: Option<[f32; 2]>,
+}
+
+impl WindowGeometry {
+    /// 从视口信息读取当前窗口几何，全屏、最大化或信息不全时返回 None
+    pub fn from_viewport(info: &egui::ViewportInfo) -> Option<Self> {
+        if info.fullscreen == Some(true) || info.maximized == Some(true) {
+            return None;
+        }
+        let outer = info.outer_rect?;
+        let inner = info.inner_rect?;
+        Some(Self {
+            position: [outer.min.x, outer.min.y],
+            size: [inner.width(), inner.height()],
+            monitor_size: info.monitor_size.map(|size| [size.x, size.y]),
+        })
+    }
+
+    /// 生成恢复窗口的视口命令，大小不超过当前显示器
+    /// 显示器大小与保存时不同，或保存的位置不在任何可能的屏幕上时，
+    /// 返回空列表，保持默认位置和 `DEFAULT_WINDOW_SIZE`
+    pub fn restore_commands(&self, monitor_size: egui::Vec2) -> Vec<egui::ViewportCommand> {
+        let position = egui::pos2(self.position[0], self.position[1]);
+        let size = egui::vec2(self.size[0], self.size[1]).min(monitor_size);
+        if !position.is_finite() || !size.is_finite() || size.min_elem() <= 0.0 {
+            return Vec::new();
+        }
+        let same_monitor = self.monitor_size.is_some_and(|saved| {
+            (saved[0] - monitor_size.x).abs() <= MONITOR_SIZE_TOLERANCE
+                && (saved[1] - monitor_size.y).abs() <= MONITOR_SIZE_TOLERANCE
+        });
+        let outer = egui::Rect::from_min_size(position, size);
+        if !same_monitor || !is_on_plausible_screen(outer, monitor_size) {
+            return Vec::new();
+        }
+        vec![
+            egui::ViewportCommand::InnerSize(size),
+            egui::ViewportCommand::OuterPosition(position),
+        ]
+    }
+}
+
+/// 判断窗口外框是否与可能的屏幕区域重叠
+/// 只知道当前显示器的大小而不知道各显示器的排列，因此把主屏及其四周各一块同样大小的屏幕
+/// 视为可能的屏幕区域，副屏上或坐标为负的位置也能恢复
+pub fn is_on_plausible_screen(outer: egui::Rect, monitor_size: egui::Vec2) -> bool {
+    let plausible =
+        egui::Rect::from_min_max((-monitor_size).to_pos2(), (monitor_size * 2.0).to_pos2());
+    let visible = plausible.intersect(outer);
+    visible.width() >= MIN_VISIBLE_SIZE && visible.height() >= MIN_VISIBLE_SIZE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_on_plausible_screen() {
+        let monitor = egui::vec2(1280.0, 720.0);
+        let window =
+            |x: f32, y: f32| egui::Rect::from_min_size(egui::pos2(x, y), egui::vec2(800.0, 600.0));
+
+        assert!(is_on_plausible_screen(window(40.0, 30.0), monitor));
+        // 主屏右侧或左上方的副屏
+        assert!(is_on_plausible_screen(window(2000.0, 100.0), monitor));
+        assert!(is_on_plausible_screen(window(-1500.0, -200.0), monitor));
+        // 远离任何可能的屏幕
+        assert!(!is_on_plausible_screen(window(10000.0, 100.0), monitor));
+        assert!(!is_on_plausible_screen(window(100.0, -5000.0), monitor));
+    }
+
+    #[test]
+    fn test_restore_commands_keep_position_and_clamp_size() {
+        let monitor = egui::vec2(1280.0, 720.0);
+        let saved_monitor = Some([1280.0, 720.0]);
+
+        let on_screen = WindowGeometry {
+            position: [40.0, 30.0],
+            size: [1600.0, 600.0],
+            monitor_size: saved_monitor,
+        };
+        assert_eq!(
+            on_screen.restore_commands(monitor),
+            vec![
+                egui::ViewportCommand::InnerSize(egui::vec2(1280.0, 600.0)),
+                egui::ViewportCommand::OuterPosition(egui::pos2(40.0, 30.0)),
+            ]
+        );
+
+        // 副屏上（包括主屏左侧或上方的负坐标）的位置照常恢复
+        for position in [[2000.0, 100.0], [-1500.0, -200.0]] {
+            let secondary = WindowGeometry {
+                position,
+                size: [1200.0, 700.0],
+                monitor_size: saved_monitor,
+            };
+            assert_eq!(
+                secondary.restore_commands(monitor)[1],
+                egui::ViewportCommand::OuterPosition(egui::pos2(position[0], position[1]))
+            );
+        }
+
+        // 已拔掉的屏幕：位置远在可能的屏幕之外，保持默认位置和大小
+        let unplugged = WindowGeometry {
+            position: [10000.0, 100.0],
+            size: [1200.0, 700.0],
+            monitor_size: saved_monitor,
+        };
+        assert!(unplugged.restore_commands(monitor).is_empty());
+
+        // 显示器大小变化或未记录显示器大小时同样回退到默认值
+        let other_monitor = WindowGeometry {
+            position: [40.0, 30.0],
+            size: [1200.0, 700.0],
+            monitor_size: Some([2560.0, 1440.0]),
+        };
+        assert!(other_monitor.restore_commands(monitor).is_empty());
+        let legacy = WindowGeometry {
+            monitor_size: None,
+            ..other_monitor
+        };
+        assert!(legacy.restore_commands(monitor).is_empty());
+
+        // 损坏的几何不恢复
+        let broken = WindowGeometry {
+            position: [f32::NAN, 0.0],
+            size: [1200.0, 700.0],
+            monitor_size: saved_monitor,
+        };
+        assert!(broken.restore_commands(monitor).is_empty());
+        let empty = WindowGeometry {
+            position: [0.0, 0.0],
+            size: [0.0, 700.0],
+            monitor_size: saved_monitor,
+        };
+        assert!(empty.restore_commands(monitor).is_empty());
+    }
+}